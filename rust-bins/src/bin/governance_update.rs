@@ -0,0 +1,256 @@
+use anyhow::Context;
+use clap::AppSettings;
+use client_server_helpers::*;
+use concordium_base::{
+    base::{UpdateKeyPair, UpdateKeysIndex, UpdateSequenceNumber},
+    common::{to_bytes, types::TransactionTime},
+    transactions::{BlockItem, EncodedPayload},
+    updates::{
+        update::{self, PartiallySignedUpdate},
+        AuthorizationsV0, AuthorizationsV1, UpdateKeysCollection, UpdatePayload,
+    },
+};
+use std::{collections::BTreeMap, path::PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+struct Prepare {
+    #[structopt(long = "payload", help = "File with the JSON encoded update payload.")]
+    payload:        PathBuf,
+    #[structopt(
+        long = "seq-number",
+        help = "Sequence number of the update queue the update is for."
+    )]
+    seq_number:     u64,
+    #[structopt(
+        long = "effective-time",
+        help = "Time, in seconds since the unix epoch, at which the update takes effect. 0 means \
+                immediately.",
+        default_value = "0"
+    )]
+    effective_time: u64,
+    #[structopt(
+        long = "timeout",
+        help = "Time, in seconds since the unix epoch, after which the update can no longer be \
+                enqueued."
+    )]
+    timeout:        u64,
+    #[structopt(long = "out", help = "File to output the unsigned update to.")]
+    out:            PathBuf,
+}
+
+#[derive(StructOpt)]
+struct Sign {
+    #[structopt(long = "update", help = "File with the partially signed update.")]
+    update: PathBuf,
+    #[structopt(
+        long = "keys",
+        help = "File with a JSON object mapping update key indices to the key pairs to sign with."
+    )]
+    keys:   PathBuf,
+    #[structopt(
+        long = "out",
+        help = "File to output the update with the added signatures to."
+    )]
+    out:    PathBuf,
+}
+
+#[derive(StructOpt)]
+struct Merge {
+    #[structopt(
+        long = "update",
+        help = "Files with partially signed versions of the same update."
+    )]
+    updates: Vec<PathBuf>,
+    #[structopt(
+        long = "out",
+        help = "File to output the update with all signatures to."
+    )]
+    out:     PathBuf,
+}
+
+#[derive(StructOpt)]
+struct Verify {
+    #[structopt(long = "update", help = "File with the partially signed update.")]
+    update:      PathBuf,
+    #[structopt(
+        long = "update-keys",
+        help = "File with the JSON encoded update keys collection of the chain."
+    )]
+    update_keys: PathBuf,
+}
+
+#[derive(StructOpt)]
+struct Finalize {
+    #[structopt(long = "update", help = "File with the signed update.")]
+    update:      PathBuf,
+    #[structopt(
+        long = "update-keys",
+        help = "File with the JSON encoded update keys collection of the chain."
+    )]
+    update_keys: PathBuf,
+    #[structopt(
+        long = "out",
+        help = "File to output the serialized block item to. This can be submitted to a node."
+    )]
+    out:         PathBuf,
+}
+
+#[derive(StructOpt)]
+#[structopt(
+    about = "Tool for preparing and signing chain updates by several governance key holders.",
+    author = "Concordium",
+    version = "0.1"
+)]
+enum GovernanceUpdate {
+    #[structopt(name = "prepare", about = "Construct an unsigned update.")]
+    Prepare(Prepare),
+    #[structopt(name = "sign", about = "Add signatures to a partially signed update.")]
+    Sign(Sign),
+    #[structopt(
+        name = "merge",
+        about = "Combine the signatures of several partially signed versions of an update."
+    )]
+    Merge(Merge),
+    #[structopt(
+        name = "verify",
+        about = "Check that the signatures on an update satisfy the access structure for the \
+                 update."
+    )]
+    Verify(Verify),
+    #[structopt(
+        name = "finalize",
+        about = "Check the signatures and output the update instruction for submission."
+    )]
+    Finalize(Finalize),
+}
+
+fn main() {
+    let app = GovernanceUpdate::clap()
+        .setting(AppSettings::ArgRequiredElseHelp)
+        .global_setting(AppSettings::ColoredHelp);
+    let matches = app.get_matches();
+    let cmd = GovernanceUpdate::from_clap(&matches);
+    use GovernanceUpdate::*;
+    let res = match cmd {
+        Prepare(prp) => handle_prepare(prp),
+        Sign(sgn) => handle_sign(sgn),
+        Merge(mrg) => handle_merge(mrg),
+        Verify(vrf) => handle_verify(vrf),
+        Finalize(fin) => handle_finalize(fin),
+    };
+    if let Err(e) = res {
+        eprintln!("{:#}", e);
+        std::process::exit(1);
+    }
+}
+
+/// The update keys of the chain. The level 2 keys are authorizations of
+/// version 1 since protocol version 4, and version 0 before.
+enum UpdateKeys {
+    V0(UpdateKeysCollection<AuthorizationsV0>),
+    V1(UpdateKeysCollection<AuthorizationsV1>),
+}
+
+fn read_update_keys(path: &PathBuf) -> anyhow::Result<UpdateKeys> {
+    let value: serde_json::Value =
+        read_json_from_file(path).context("Could not read the update keys.")?;
+    if let Ok(keys) = serde_json::from_value(value.clone()) {
+        return Ok(UpdateKeys::V1(keys));
+    }
+    let keys = serde_json::from_value(value).context("Could not parse the update keys.")?;
+    Ok(UpdateKeys::V0(keys))
+}
+
+fn read_update(path: &PathBuf) -> anyhow::Result<PartiallySignedUpdate> {
+    read_json_from_file(path)
+        .with_context(|| format!("Could not read the update from {}.", path.display()))
+}
+
+fn verify_update(update: &PartiallySignedUpdate, keys: &UpdateKeys) -> anyhow::Result<()> {
+    match keys {
+        UpdateKeys::V0(keys) => update.verify(keys)?,
+        UpdateKeys::V1(keys) => update.verify(keys)?,
+    }
+    Ok(())
+}
+
+fn handle_prepare(prp: Prepare) -> anyhow::Result<()> {
+    let payload: UpdatePayload =
+        read_json_from_file(&prp.payload).context("Could not read the update payload.")?;
+    let unsigned = update::prepare(
+        UpdateSequenceNumber {
+            number: prp.seq_number,
+        },
+        TransactionTime::from_seconds(prp.effective_time),
+        TransactionTime::from_seconds(prp.timeout),
+        payload,
+    );
+    write_json_to_file(&prp.out, &unsigned).context("Could not write the update.")?;
+    println!(
+        "Wrote unsigned update with hash to sign {} to {}.",
+        unsigned.hash_to_sign(),
+        prp.out.display()
+    );
+    Ok(())
+}
+
+fn handle_sign(sgn: Sign) -> anyhow::Result<()> {
+    let mut update = read_update(&sgn.update)?;
+    let keys: BTreeMap<UpdateKeysIndex, UpdateKeyPair> =
+        read_json_from_file(&sgn.keys).context("Could not read the keys.")?;
+    update.sign(&keys)?;
+    write_json_to_file(&sgn.out, &update).context("Could not write the update.")?;
+    println!(
+        "Added {} signatures. The update now has {} signatures.",
+        keys.len(),
+        update.signatures.len()
+    );
+    Ok(())
+}
+
+fn handle_merge(mrg: Merge) -> anyhow::Result<()> {
+    let mut paths = mrg.updates.iter();
+    let first = paths
+        .next()
+        .context("At least one update must be provided.")?;
+    let mut update = read_update(first)?;
+    for path in paths {
+        update
+            .merge(read_update(path)?)
+            .with_context(|| format!("Could not merge the update from {}.", path.display()))?;
+    }
+    write_json_to_file(&mrg.out, &update).context("Could not write the update.")?;
+    println!(
+        "Wrote update with {} signatures to {}.",
+        update.signatures.len(),
+        mrg.out.display()
+    );
+    Ok(())
+}
+
+fn handle_verify(vrf: Verify) -> anyhow::Result<()> {
+    let update = read_update(&vrf.update)?;
+    let keys = read_update_keys(&vrf.update_keys)?;
+    verify_update(&update, &keys)?;
+    println!("The signatures satisfy the access structure for the update.");
+    Ok(())
+}
+
+fn handle_finalize(fin: Finalize) -> anyhow::Result<()> {
+    let update = read_update(&fin.update)?;
+    let keys = read_update_keys(&fin.update_keys)?;
+    let instruction = match keys {
+        UpdateKeys::V0(keys) => update.finalize(&keys)?,
+        UpdateKeys::V1(keys) => update.finalize(&keys)?,
+    };
+    let block_item = BlockItem::<EncodedPayload>::from(instruction);
+    std::fs::write(&fin.out, to_bytes(&block_item))
+        .context("Could not write the update instruction.")?;
+    println!(
+        "Wrote update instruction with hash {} to {}.",
+        block_item.hash(),
+        fin.out.display()
+    );
+    Ok(())
+}
@@ -1,11 +1,15 @@
 //! Some command line auxiliary utilities.
 //! At the moment we have encryption and decryption in the formats used by other
-//! parts of the Concordium project.
+//! parts of the Concordium project, and generation of account aliases.
 
 use anyhow::Context;
 use clap::AppSettings;
 use client_server_helpers::*;
-use concordium_base::common::encryption;
+use concordium_base::{
+    aliases,
+    common::{encryption, SerdeSerialize},
+    id::types::AccountAddress,
+};
 use std::path::PathBuf;
 use structopt::StructOpt;
 
@@ -28,6 +32,33 @@ struct ConfigDecrypt {
     output: Option<PathBuf>,
 }
 
+#[derive(StructOpt)]
+struct ConfigAliases {
+    #[structopt(long = "address", help = "Address of the account.")]
+    address: AccountAddress,
+    #[structopt(
+        long = "start",
+        help = "Counter of the first alias.",
+        default_value = "0"
+    )]
+    start:   u32,
+    #[structopt(long = "count", help = "Number of aliases to generate.")]
+    count:   u32,
+    #[structopt(
+        long = "out",
+        help = "File to output the aliases to as JSON. Defaults to standard output, one alias per \
+                line."
+    )]
+    output:  Option<PathBuf>,
+}
+
+/// An alias as output by the `aliases` command.
+#[derive(SerdeSerialize)]
+struct Alias {
+    counter: u32,
+    address: AccountAddress,
+}
+
 #[derive(StructOpt)]
 #[structopt(
     about = "Various helper utilities",
@@ -39,6 +70,11 @@ enum Utils {
     Encrypt(ConfigEncrypt),
     #[structopt(name = "decrypt", about = "Decrypt the contents of the supplied file.")]
     Decrypt(ConfigDecrypt),
+    #[structopt(
+        name = "aliases",
+        about = "Generate a range of aliases of an account, e.g., to use as deposit addresses."
+    )]
+    Aliases(ConfigAliases),
 }
 
 fn main() -> anyhow::Result<()> {
@@ -50,6 +86,7 @@ fn main() -> anyhow::Result<()> {
     match utls {
         Utils::Encrypt(cfg) => handle_encrypt(cfg),
         Utils::Decrypt(cfg) => handle_decrypt(cfg),
+        Utils::Aliases(cfg) => handle_aliases(cfg),
     }
 }
 
@@ -82,3 +119,29 @@ fn handle_decrypt(cfg: ConfigDecrypt) -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+fn handle_aliases(cfg: ConfigAliases) -> anyhow::Result<()> {
+    let end = cfg.start.saturating_add(cfg.count);
+    if end > aliases::NUM_ALIASES {
+        anyhow::bail!(
+            "An account only has {} aliases, so the counters must be less than that.",
+            aliases::NUM_ALIASES
+        );
+    }
+    let generated = aliases::aliases(cfg.address, cfg.start..end);
+    match cfg.output {
+        Some(fname) => {
+            let generated = generated
+                .map(|(counter, address)| Alias { counter, address })
+                .collect::<Vec<_>>();
+            eprintln!("Writing output to {}", fname.to_string_lossy());
+            write_json_to_file(&fname, &generated)?;
+        }
+        None => {
+            for (counter, address) in generated {
+                println!("{} {}", counter, address);
+            }
+        }
+    }
+    Ok(())
+}
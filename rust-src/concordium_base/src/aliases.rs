@@ -0,0 +1,323 @@
+//! Utilities for using the aliases of an account, e.g., to give each customer
+//! of an exchange their own deposit address so that deposits can be
+//! attributed without relying on memos.
+//!
+//! An account has 2^24 aliases. All of them identify the same account, and
+//! they only differ in the last 3 bytes, which contain the counter of the
+//! alias. See [`AccountAddress::get_alias`] and
+//! [`AccountAddress::get_alias_counter`].
+use crate::common::types::AccountAddress;
+use serde::{Deserialize as SerdeDeserialize, Serialize as SerdeSerialize};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// The number of aliases of an account. Alias counters are in the range
+/// `0..NUM_ALIASES`.
+pub const NUM_ALIASES: u32 = 1 << 24;
+
+/// The aliases of `base` with counters in the given range, together with their
+/// counters. Counters that are not less than [`NUM_ALIASES`] are skipped.
+pub fn aliases(
+    base: AccountAddress,
+    counters: std::ops::Range<u32>,
+) -> impl Iterator<Item = (u32, AccountAddress)> {
+    let end = std::cmp::min(counters.end, NUM_ALIASES);
+    (counters.start..end).map(move |counter| (counter, base.get_alias_unchecked(counter)))
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AliasError {
+    #[error("All aliases of the account have been allocated.")]
+    Exhausted,
+    #[error("The alias counter {0} is out of range.")]
+    CounterOutOfRange(u32),
+    #[error("The alias with counter {counter} is already allocated to {key}.")]
+    CounterInUse { counter: u32, key: String },
+    #[error("The key {key} is already allocated the alias with counter {counter}.")]
+    KeyInUse { key: String, counter: u32 },
+    #[error("The address {0} is not an alias of the account.")]
+    NotAnAlias(AccountAddress),
+    #[error("The address {address} does not match alias counter {counter}.")]
+    AddressMismatch {
+        counter: u32,
+        address: AccountAddress,
+    },
+}
+
+/// Allocates the aliases of an account to keys, such as customer identifiers,
+/// and resolves addresses back to the key they were allocated to.
+///
+/// Aliases are allocated in order of their counters. Released aliases are not
+/// allocated again by [`allocate`](Self::allocate), since funds might still be
+/// sent to them.
+///
+/// The JSON serialization of the registry lists the allocated aliases, so that
+/// it can be stored and loaded again.
+#[derive(Debug, Clone, PartialEq, Eq, SerdeSerialize, SerdeDeserialize)]
+#[serde(
+    try_from = "alias_registry_json::AliasRegistry",
+    into = "alias_registry_json::AliasRegistry"
+)]
+pub struct AliasRegistry {
+    base:         AccountAddress,
+    /// The counter to try first when allocating the next alias.
+    next_counter: u32,
+    by_counter:   BTreeMap<u32, String>,
+    by_key:       BTreeMap<String, u32>,
+}
+
+impl AliasRegistry {
+    /// A registry of the aliases of the given account, with no aliases
+    /// allocated.
+    pub fn new(base: AccountAddress) -> Self {
+        Self {
+            base,
+            next_counter: 0,
+            by_counter: BTreeMap::new(),
+            by_key: BTreeMap::new(),
+        }
+    }
+
+    /// The address of the account, as given to [`new`](Self::new).
+    pub fn base(&self) -> AccountAddress { self.base }
+
+    /// The number of allocated aliases.
+    pub fn len(&self) -> usize { self.by_counter.len() }
+
+    /// Whether no aliases are allocated.
+    pub fn is_empty(&self) -> bool { self.by_counter.is_empty() }
+
+    /// Allocate the next unused alias to the key. If the key is already
+    /// allocated an alias, that alias is returned.
+    pub fn allocate(&mut self, key: impl Into<String>) -> Result<AccountAddress, AliasError> {
+        let key = key.into();
+        if let Some(address) = self.alias_of(&key) {
+            return Ok(address);
+        }
+        let mut counter = self.next_counter;
+        while self.by_counter.contains_key(&counter) {
+            counter += 1;
+        }
+        if counter >= NUM_ALIASES {
+            return Err(AliasError::Exhausted);
+        }
+        self.insert(key, counter);
+        Ok(self.base.get_alias_unchecked(counter))
+    }
+
+    /// Allocate the alias with the given counter to the key. This is intended
+    /// for recording allocations that were made elsewhere. It fails if the
+    /// alias or the key are already allocated, unless they are allocated to
+    /// each other.
+    pub fn assign(
+        &mut self,
+        key: impl Into<String>,
+        counter: u32,
+    ) -> Result<AccountAddress, AliasError> {
+        let key = key.into();
+        if counter >= NUM_ALIASES {
+            return Err(AliasError::CounterOutOfRange(counter));
+        }
+        match (self.by_counter.get(&counter), self.by_key.get(&key)) {
+            (Some(existing), _) if *existing != key => {
+                return Err(AliasError::CounterInUse {
+                    counter,
+                    key: existing.clone(),
+                })
+            }
+            (_, Some(&existing)) if existing != counter => {
+                return Err(AliasError::KeyInUse {
+                    key,
+                    counter: existing,
+                })
+            }
+            (Some(_), _) => (),
+            (None, _) => {
+                self.insert(key, counter);
+                // Allocations made elsewhere are assumed to be in order, so
+                // the counters below this one are not reused by `allocate`.
+                self.next_counter = std::cmp::max(self.next_counter, counter + 1);
+            }
+        }
+        Ok(self.base.get_alias_unchecked(counter))
+    }
+
+    /// Release the alias allocated to the key, and return it.
+    pub fn release(&mut self, key: &str) -> Option<AccountAddress> {
+        let counter = self.by_key.remove(key)?;
+        self.by_counter.remove(&counter);
+        Some(self.base.get_alias_unchecked(counter))
+    }
+
+    /// The alias allocated to the key, if any.
+    pub fn alias_of(&self, key: &str) -> Option<AccountAddress> {
+        let counter = self.by_key.get(key)?;
+        Some(self.base.get_alias_unchecked(*counter))
+    }
+
+    /// The key the address is allocated to. This fails if the address is not
+    /// an alias of the account, and returns [`None`] if the alias is not
+    /// allocated.
+    pub fn resolve(&self, address: &AccountAddress) -> Result<Option<&str>, AliasError> {
+        let counter = self
+            .base
+            .get_alias_counter(address)
+            .ok_or(AliasError::NotAnAlias(*address))?;
+        Ok(self.by_counter.get(&counter).map(String::as_str))
+    }
+
+    /// The allocated aliases, ordered by their counters.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str, AccountAddress)> {
+        self.by_counter.iter().map(|(counter, key)| {
+            (
+                *counter,
+                key.as_str(),
+                self.base.get_alias_unchecked(*counter),
+            )
+        })
+    }
+
+    fn insert(&mut self, key: String, counter: u32) {
+        self.by_key.insert(key.clone(), counter);
+        self.by_counter.insert(counter, key);
+        if counter == self.next_counter {
+            self.next_counter += 1;
+        }
+    }
+}
+
+mod alias_registry_json {
+    use super::*;
+
+    #[derive(SerdeSerialize, SerdeDeserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Alias {
+        counter: u32,
+        key:     String,
+        address: AccountAddress,
+    }
+
+    #[derive(SerdeSerialize, SerdeDeserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct AliasRegistry {
+        base:         AccountAddress,
+        next_counter: u32,
+        aliases:      Vec<Alias>,
+    }
+
+    impl From<super::AliasRegistry> for AliasRegistry {
+        fn from(value: super::AliasRegistry) -> Self {
+            let aliases = value
+                .iter()
+                .map(|(counter, key, address)| Alias {
+                    counter,
+                    key: key.into(),
+                    address,
+                })
+                .collect();
+            Self {
+                base: value.base,
+                next_counter: value.next_counter,
+                aliases,
+            }
+        }
+    }
+
+    impl TryFrom<AliasRegistry> for super::AliasRegistry {
+        type Error = AliasError;
+
+        fn try_from(value: AliasRegistry) -> Result<Self, Self::Error> {
+            let mut registry = super::AliasRegistry::new(value.base);
+            for alias in value.aliases {
+                let address = registry.assign(alias.key, alias.counter)?;
+                if address != alias.address {
+                    return Err(AliasError::AddressMismatch {
+                        counter: alias.counter,
+                        address: alias.address,
+                    });
+                }
+            }
+            // Assigning advances the counter past the allocated aliases, which
+            // is kept even if the stored counter is lower, so that released
+            // aliases are not allocated again.
+            registry.next_counter = std::cmp::max(
+                registry.next_counter,
+                std::cmp::min(value.next_counter, NUM_ALIASES),
+            );
+            Ok(registry)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::types::ACCOUNT_ADDRESS_SIZE;
+    use rand::Rng;
+
+    #[test]
+    fn test_alias_registry() {
+        let mut rng = rand::thread_rng();
+        let base = AccountAddress(rng.gen::<[u8; ACCOUNT_ADDRESS_SIZE]>());
+        let mut registry = AliasRegistry::new(base);
+        let alice = registry.allocate("alice").expect("Aliases are available.");
+        assert_eq!(alice, base.get_alias_unchecked(0));
+        assert_eq!(registry.allocate("alice"), Ok(alice));
+        assert_eq!(registry.assign("carol", 2), Ok(base.get_alias_unchecked(2)));
+        assert_eq!(
+            registry.assign("dave", 2),
+            Err(AliasError::CounterInUse {
+                counter: 2,
+                key:     "carol".into(),
+            })
+        );
+        let bob = registry.allocate("bob").expect("Aliases are available.");
+        assert_eq!(bob, base.get_alias_unchecked(3));
+        assert_eq!(registry.resolve(&bob), Ok(Some("bob")));
+        assert_eq!(registry.resolve(&base.get_alias_unchecked(1)), Ok(None));
+        let mut other = base;
+        other.0[0] ^= 1;
+        assert_eq!(registry.resolve(&other), Err(AliasError::NotAnAlias(other)));
+
+        assert_eq!(registry.release("alice"), Some(alice));
+        assert_eq!(registry.resolve(&alice), Ok(None));
+        let erin = registry.allocate("erin").expect("Aliases are available.");
+        assert_eq!(erin, base.get_alias_unchecked(4));
+        assert_eq!(registry.len(), 3);
+
+        let json = serde_json::to_string(&registry).expect("Serialization succeeds.");
+        let parsed: AliasRegistry = serde_json::from_str(&json).expect("Deserialization succeeds.");
+        assert_eq!(parsed, registry);
+    }
+
+    #[test]
+    fn test_alias_registry_low_next_counter() {
+        let base = AccountAddress([1u8; ACCOUNT_ADDRESS_SIZE]);
+        let json = serde_json::json!({
+            "base": base,
+            "nextCounter": 0,
+            "aliases": [{
+                "counter": 3,
+                "key": "alice",
+                "address": base.get_alias_unchecked(3),
+            }],
+        });
+        let mut registry: AliasRegistry =
+            serde_json::from_value(json).expect("Deserialization succeeds.");
+        assert_eq!(
+            registry.allocate("bob"),
+            Ok(base.get_alias_unchecked(4)),
+            "Aliases below an allocated one are not allocated."
+        );
+    }
+
+    #[test]
+    fn test_aliases_range() {
+        let base = AccountAddress([1u8; ACCOUNT_ADDRESS_SIZE]);
+        let generated = aliases(base, NUM_ALIASES - 2..NUM_ALIASES + 5).collect::<Vec<_>>();
+        assert_eq!(generated.len(), 2);
+        for (counter, address) in generated {
+            assert_eq!(base.get_alias_counter(&address), Some(counter));
+        }
+    }
+}
@@ -0,0 +1,625 @@
+//! Chain parameters for each chain parameter version, and a model of the
+//! update queues that determine how update instructions change them.
+//!
+//! [`UpdateQueues`] tracks the chain parameters and update keys in effect,
+//! together with the updates that are enqueued but not yet effective. It can
+//! be used to predict the chain parameters at a given time, taking pending
+//! updates into account.
+use crate::{
+    base::*,
+    common::{types::TransactionTime, SerdeDeserialize, SerdeSerialize},
+    updates::*,
+};
+use concordium_contracts_common::{AccountAddress, Amount, Duration};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+#[derive(Debug, Clone, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+/// Parameters that determine the distribution of rewards in chain parameter
+/// version 0.
+pub struct RewardParametersV0 {
+    pub mint_distribution:            MintDistributionV0,
+    pub transaction_fee_distribution: TransactionFeeDistribution,
+    #[serde(rename = "gASRewards")]
+    pub gas_rewards:                  GASRewards,
+}
+
+#[derive(Debug, Clone, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+/// Parameters that determine the distribution of rewards in chain parameter
+/// version 1.
+pub struct RewardParametersV1 {
+    pub mint_distribution:            MintDistributionV1,
+    pub transaction_fee_distribution: TransactionFeeDistribution,
+    #[serde(rename = "gASRewards")]
+    pub gas_rewards:                  GASRewards,
+}
+
+#[derive(Debug, Clone, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+/// Parameters that determine the distribution of rewards in chain parameter
+/// version 2.
+pub struct RewardParametersV2 {
+    pub mint_distribution:            MintDistributionV1,
+    pub transaction_fee_distribution: TransactionFeeDistribution,
+    #[serde(rename = "gASRewards")]
+    pub gas_rewards:                  GASRewardsV1,
+}
+
+#[derive(Debug, Clone, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+/// Chain parameters that apply to protocol versions 1-3.
+pub struct ChainParametersV0 {
+    pub election_difficulty:          ElectionDifficulty,
+    pub euro_per_energy:              ExchangeRate,
+    #[serde(rename = "microGTUPerEuro")]
+    pub micro_ccd_per_euro:           ExchangeRate,
+    /// Number of epochs a baker must wait after removing its stake or
+    /// reducing it.
+    pub baker_cooldown_epochs:        Epoch,
+    /// The maximum number of credential deployments in a block.
+    pub account_creation_limit:       u16,
+    pub reward_parameters:            RewardParametersV0,
+    pub foundation_account:           AccountAddress,
+    /// Minimum amount of CCD that an account must stake to become a baker.
+    pub minimum_threshold_for_baking: Amount,
+}
+
+#[derive(Debug, Clone, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+/// Chain parameters that apply to protocol versions 4-5.
+pub struct ChainParametersV1 {
+    pub election_difficulty:    ElectionDifficulty,
+    pub euro_per_energy:        ExchangeRate,
+    #[serde(rename = "microGTUPerEuro")]
+    pub micro_ccd_per_euro:     ExchangeRate,
+    pub cooldown_parameters:    CooldownParameters,
+    pub time_parameters:        TimeParameters,
+    /// The maximum number of credential deployments in a block.
+    pub account_creation_limit: u16,
+    pub reward_parameters:      RewardParametersV1,
+    pub foundation_account:     AccountAddress,
+    pub pool_parameters:        PoolParameters,
+}
+
+#[derive(Debug, Clone, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+/// Chain parameters that apply to protocol versions 6 and up.
+pub struct ChainParametersV2 {
+    pub timeout_parameters:                TimeoutParameters,
+    /// The minimum time between blocks.
+    pub min_block_time:                    Duration,
+    /// The maximum energy that the transactions in a block may use.
+    pub block_energy_limit:                Energy,
+    pub euro_per_energy:                   ExchangeRate,
+    #[serde(rename = "microGTUPerEuro")]
+    pub micro_ccd_per_euro:                ExchangeRate,
+    pub cooldown_parameters:               CooldownParameters,
+    pub time_parameters:                   TimeParameters,
+    /// The maximum number of credential deployments in a block.
+    pub account_creation_limit:            u16,
+    pub reward_parameters:                 RewardParametersV2,
+    pub foundation_account:                AccountAddress,
+    pub pool_parameters:                   PoolParameters,
+    pub finalization_committee_parameters: FinalizationCommitteeParameters,
+}
+
+/// Trait used to define mapping from a type to a `ChainParameters` type.
+pub trait ChainParametersFamily {
+    type Output;
+}
+
+impl ChainParametersFamily for ChainParameterVersion0 {
+    type Output = ChainParametersV0;
+}
+
+impl ChainParametersFamily for ChainParameterVersion1 {
+    type Output = ChainParametersV1;
+}
+
+impl ChainParametersFamily for ChainParameterVersion2 {
+    type Output = ChainParametersV2;
+}
+
+/// Type family mapping a `ChainParameterVersion` to its corresponding type for
+/// the chain parameters.
+pub type ChainParametersFor<CPV> = <CPV as ChainParametersFamily>::Output;
+
+#[derive(Debug, Error)]
+/// An error that occurs when applying updates to chain parameters.
+pub enum ChainParameterUpdateError {
+    #[error("Updates of type {0:?} do not apply to this chain parameter version.")]
+    UnsupportedUpdate(UpdateType),
+    #[error(
+        "The sequence number {actual} does not match the next sequence number {expected} of the \
+         {update_type:?} queue."
+    )]
+    SequenceNumberMismatch {
+        update_type: UpdateType,
+        expected:    UpdateSequenceNumber,
+        actual:      UpdateSequenceNumber,
+    },
+    #[error(
+        "A protocol update takes effect at {} seconds, after which the chain parameters cannot be \
+         determined.",
+        .effective_time.seconds
+    )]
+    ProtocolUpdate { effective_time: TransactionTime },
+}
+
+/// Chain parameters of a specific chain parameter version, which can be
+/// modified by update payloads.
+pub trait ChainParameters: Clone {
+    /// The level 2 keys that authorize updates of chain parameters of this
+    /// version.
+    type Authorizations: Level2Authorizations + std::fmt::Debug + Clone;
+
+    /// Apply the update payload to the chain parameters and update keys.
+    /// Protocol updates are not supported, since they change the chain
+    /// parameter version.
+    fn apply_update(
+        &mut self,
+        keys: &mut UpdateKeysCollection<Self::Authorizations>,
+        payload: &UpdatePayload,
+    ) -> Result<(), ChainParameterUpdateError>;
+}
+
+/// Apply the updates that are common to all chain parameter versions and do
+/// not modify the chain parameters themselves, i.e., updates of the root and
+/// level 1 keys, and additions of anonymity revokers and identity providers.
+fn apply_common_update<Auths>(
+    keys: &mut UpdateKeysCollection<Auths>,
+    payload: &UpdatePayload,
+) -> Result<(), ChainParameterUpdateError> {
+    match payload {
+        UpdatePayload::Root(RootUpdate::RootKeysUpdate(root_keys)) => {
+            keys.root_keys = root_keys.clone();
+        }
+        UpdatePayload::Root(RootUpdate::Level1KeysUpdate(level_1_keys))
+        | UpdatePayload::Level1(Level1Update::Level1KeysUpdate(level_1_keys)) => {
+            keys.level_1_keys = level_1_keys.clone();
+        }
+        // Anonymity revokers and identity providers are not part of the chain
+        // parameters.
+        UpdatePayload::AddAnonymityRevoker(_) | UpdatePayload::AddIdentityProvider(_) => (),
+        other => {
+            return Err(ChainParameterUpdateError::UnsupportedUpdate(
+                other.update_type(),
+            ))
+        }
+    }
+    Ok(())
+}
+
+impl ChainParameters for ChainParametersV0 {
+    type Authorizations = AuthorizationsV0;
+
+    fn apply_update(
+        &mut self,
+        keys: &mut UpdateKeysCollection<Self::Authorizations>,
+        payload: &UpdatePayload,
+    ) -> Result<(), ChainParameterUpdateError> {
+        match payload {
+            UpdatePayload::ElectionDifficulty(ed) => self.election_difficulty = *ed,
+            UpdatePayload::EuroPerEnergy(rate) => self.euro_per_energy = *rate,
+            UpdatePayload::MicroGTUPerEuro(rate) => self.micro_ccd_per_euro = *rate,
+            UpdatePayload::FoundationAccount(address) => self.foundation_account = *address,
+            UpdatePayload::MintDistribution(md) => {
+                self.reward_parameters.mint_distribution = md.clone()
+            }
+            UpdatePayload::TransactionFeeDistribution(tfd) => {
+                self.reward_parameters.transaction_fee_distribution = tfd.clone()
+            }
+            UpdatePayload::GASRewards(gas) => self.reward_parameters.gas_rewards = gas.clone(),
+            UpdatePayload::BakerStakeThreshold(bp) => {
+                self.minimum_threshold_for_baking = bp.minimum_threshold_for_baking
+            }
+            UpdatePayload::Root(RootUpdate::Level2KeysUpdate(auths))
+            | UpdatePayload::Level1(Level1Update::Level2KeysUpdate(auths)) => {
+                keys.level_2_keys = auths.as_ref().clone()
+            }
+            other => return apply_common_update(keys, other),
+        }
+        Ok(())
+    }
+}
+
+impl ChainParameters for ChainParametersV1 {
+    type Authorizations = AuthorizationsV1;
+
+    fn apply_update(
+        &mut self,
+        keys: &mut UpdateKeysCollection<Self::Authorizations>,
+        payload: &UpdatePayload,
+    ) -> Result<(), ChainParameterUpdateError> {
+        match payload {
+            UpdatePayload::ElectionDifficulty(ed) => self.election_difficulty = *ed,
+            UpdatePayload::EuroPerEnergy(rate) => self.euro_per_energy = *rate,
+            UpdatePayload::MicroGTUPerEuro(rate) => self.micro_ccd_per_euro = *rate,
+            UpdatePayload::FoundationAccount(address) => self.foundation_account = *address,
+            UpdatePayload::MintDistributionCPV1(md) => {
+                self.reward_parameters.mint_distribution = md.clone()
+            }
+            UpdatePayload::TransactionFeeDistribution(tfd) => {
+                self.reward_parameters.transaction_fee_distribution = tfd.clone()
+            }
+            UpdatePayload::GASRewards(gas) => self.reward_parameters.gas_rewards = gas.clone(),
+            UpdatePayload::CooldownParametersCPV1(cp) => self.cooldown_parameters = *cp,
+            UpdatePayload::PoolParametersCPV1(pp) => self.pool_parameters = pp.clone(),
+            UpdatePayload::TimeParametersCPV1(tp) => self.time_parameters = *tp,
+            UpdatePayload::Root(RootUpdate::Level2KeysUpdateV1(auths))
+            | UpdatePayload::Level1(Level1Update::Level2KeysUpdateV1(auths)) => {
+                keys.level_2_keys = auths.as_ref().clone()
+            }
+            other => return apply_common_update(keys, other),
+        }
+        Ok(())
+    }
+}
+
+impl ChainParameters for ChainParametersV2 {
+    type Authorizations = AuthorizationsV1;
+
+    fn apply_update(
+        &mut self,
+        keys: &mut UpdateKeysCollection<Self::Authorizations>,
+        payload: &UpdatePayload,
+    ) -> Result<(), ChainParameterUpdateError> {
+        match payload {
+            UpdatePayload::EuroPerEnergy(rate) => self.euro_per_energy = *rate,
+            UpdatePayload::MicroGTUPerEuro(rate) => self.micro_ccd_per_euro = *rate,
+            UpdatePayload::FoundationAccount(address) => self.foundation_account = *address,
+            UpdatePayload::MintDistributionCPV1(md) => {
+                self.reward_parameters.mint_distribution = md.clone()
+            }
+            UpdatePayload::TransactionFeeDistribution(tfd) => {
+                self.reward_parameters.transaction_fee_distribution = tfd.clone()
+            }
+            UpdatePayload::GASRewardsCPV2(gas) => self.reward_parameters.gas_rewards = gas.clone(),
+            UpdatePayload::CooldownParametersCPV1(cp) => self.cooldown_parameters = *cp,
+            UpdatePayload::PoolParametersCPV1(pp) => self.pool_parameters = pp.clone(),
+            UpdatePayload::TimeParametersCPV1(tp) => self.time_parameters = *tp,
+            UpdatePayload::TimeoutParametersCPV2(tp) => self.timeout_parameters = *tp,
+            UpdatePayload::MinBlockTimeCPV2(mbt) => self.min_block_time = *mbt,
+            UpdatePayload::BlockEnergyLimitCPV2(limit) => self.block_energy_limit = *limit,
+            UpdatePayload::FinalizationCommitteeParametersCPV2(fcp) => {
+                self.finalization_committee_parameters = *fcp
+            }
+            UpdatePayload::Root(RootUpdate::Level2KeysUpdateV1(auths))
+            | UpdatePayload::Level1(Level1Update::Level2KeysUpdateV1(auths)) => {
+                keys.level_2_keys = auths.as_ref().clone()
+            }
+            other => return apply_common_update(keys, other),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+/// An update that has been enqueued, but has not yet taken effect.
+pub struct PendingUpdate {
+    pub effective_time: TransactionTime,
+    pub payload:        UpdatePayload,
+}
+
+#[derive(Debug, Clone)]
+/// The chain parameters and update keys in effect, together with the queues of
+/// pending updates. Each type of update has its own queue with its own
+/// sequence numbers, as on the chain.
+pub struct UpdateQueues<CP: ChainParameters> {
+    /// The chain parameters currently in effect.
+    pub parameters:        CP,
+    /// The update keys currently in effect.
+    pub keys:              UpdateKeysCollection<CP::Authorizations>,
+    next_sequence_numbers: BTreeMap<UpdateType, UpdateSequenceNumber>,
+    pending:               BTreeMap<UpdateType, Vec<PendingUpdate>>,
+}
+
+impl<CP: ChainParameters> UpdateQueues<CP> {
+    /// Construct the update queues with the given parameters and keys in
+    /// effect, no pending updates, and the next sequence number of every
+    /// queue being 1.
+    pub fn new(parameters: CP, keys: UpdateKeysCollection<CP::Authorizations>) -> Self {
+        Self {
+            parameters,
+            keys,
+            next_sequence_numbers: BTreeMap::new(),
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// The sequence number the next update of the given type must have.
+    pub fn next_sequence_number(&self, update_type: UpdateType) -> UpdateSequenceNumber {
+        self.next_sequence_numbers
+            .get(&update_type)
+            .copied()
+            .unwrap_or(UpdateSequenceNumber { number: 1 })
+    }
+
+    /// Set the next sequence number of the queue for the given update type.
+    /// This is used to initialize the queues from the state of the chain.
+    pub fn set_next_sequence_number(
+        &mut self,
+        update_type: UpdateType,
+        seq_number: UpdateSequenceNumber,
+    ) {
+        self.next_sequence_numbers.insert(update_type, seq_number);
+    }
+
+    /// The updates of the given type that are enqueued but not yet effective,
+    /// ordered by effective time.
+    pub fn pending(&self, update_type: UpdateType) -> &[PendingUpdate] {
+        self.pending
+            .get(&update_type)
+            .map_or(&[], |queue| queue.as_slice())
+    }
+
+    /// Enqueue an update. The sequence number in the header must be the next
+    /// sequence number of the queue for the update type. Updates with an
+    /// effective time of 0, other than protocol updates, take effect
+    /// immediately. As on the chain, the update replaces any pending updates
+    /// in the same queue that are effective at the same time or later, which
+    /// for an immediate update are all of them.
+    pub fn enqueue(
+        &mut self,
+        header: &UpdateHeader,
+        payload: UpdatePayload,
+    ) -> Result<(), ChainParameterUpdateError> {
+        let update_type = payload.update_type();
+        let expected = self.next_sequence_number(update_type);
+        if header.seq_number != expected {
+            return Err(ChainParameterUpdateError::SequenceNumberMismatch {
+                update_type,
+                expected,
+                actual: header.seq_number,
+            });
+        }
+        let is_protocol = matches!(payload, UpdatePayload::Protocol(_));
+        let immediate = header.effective_time.seconds == 0 && !is_protocol;
+        if !is_protocol {
+            // Check that the update applies to this chain parameter version
+            // before accepting it.
+            let mut parameters = self.parameters.clone();
+            let mut keys = self.keys.clone();
+            parameters.apply_update(&mut keys, &payload)?;
+            if immediate {
+                self.parameters = parameters;
+                self.keys = keys;
+            }
+        }
+        self.next_sequence_numbers
+            .insert(update_type, expected.next());
+        let queue = self.pending.entry(update_type).or_default();
+        queue.retain(|pu| pu.effective_time < header.effective_time);
+        if !immediate {
+            queue.push(PendingUpdate {
+                effective_time: header.effective_time,
+                payload,
+            });
+        }
+        Ok(())
+    }
+
+    /// Apply all pending updates that are effective at or before the given
+    /// time, in order of effective time. If a protocol update becomes
+    /// effective, the updates before it are applied and an error is returned,
+    /// since the chain parameters after a protocol update cannot be
+    /// determined.
+    pub fn advance_to(&mut self, time: TransactionTime) -> Result<(), ChainParameterUpdateError> {
+        let mut effective = Vec::new();
+        for (update_type, queue) in self.pending.iter_mut() {
+            let split = queue.partition_point(|pu| pu.effective_time <= time);
+            effective.extend(queue.drain(..split).map(|pu| (*update_type, pu)));
+        }
+        // The sort is stable, so updates with the same effective time are
+        // applied in the order of their queues.
+        effective.sort_by_key(|(_, pu)| pu.effective_time);
+        let remaining = match effective
+            .iter()
+            .position(|(_, pu)| matches!(pu.payload, UpdatePayload::Protocol(_)))
+        {
+            Some(index) => effective.split_off(index),
+            None => Vec::new(),
+        };
+        for (_, pu) in effective {
+            self.parameters.apply_update(&mut self.keys, &pu.payload)?;
+        }
+        if let Some((_, protocol_update)) = remaining.first() {
+            let effective_time = protocol_update.effective_time;
+            // Put back the updates that were not applied.
+            for (update_type, pu) in remaining {
+                let queue = self.pending.entry(update_type).or_default();
+                let position = queue.partition_point(|p| p.effective_time <= pu.effective_time);
+                queue.insert(position, pu);
+            }
+            return Err(ChainParameterUpdateError::ProtocolUpdate { effective_time });
+        }
+        Ok(())
+    }
+
+    /// Enqueue the given updates in order, and return the resulting state at
+    /// the given time. This does not modify `self`.
+    pub fn apply_updates(
+        &self,
+        updates: impl IntoIterator<Item = (UpdateHeader, UpdatePayload)>,
+        time: TransactionTime,
+    ) -> Result<Self, ChainParameterUpdateError> {
+        let mut state = self.clone();
+        for (header, payload) in updates {
+            state.enqueue(&header, payload)?;
+        }
+        state.advance_to(time)?;
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transactions::PayloadSize;
+    use std::{collections::BTreeSet, convert::TryFrom};
+
+    fn test_state() -> UpdateQueues<ChainParametersV0> {
+        let mut rng = rand::thread_rng();
+        let key_pair = UpdateKeyPair::generate(&mut rng);
+        let threshold = UpdateKeysThreshold::try_from(1).unwrap();
+        let access_structure = AccessStructure {
+            authorized_keys: BTreeSet::from([UpdateKeysIndex { index: 0 }]),
+            threshold,
+        };
+        let level_2_keys = AuthorizationsV0 {
+            keys: vec![UpdatePublicKey::from(&key_pair)],
+            emergency: access_structure.clone(),
+            protocol: access_structure.clone(),
+            election_difficulty: access_structure.clone(),
+            euro_per_energy: access_structure.clone(),
+            micro_gtu_per_euro: access_structure.clone(),
+            foundation_account: access_structure.clone(),
+            mint_distribution: access_structure.clone(),
+            transaction_fee_distribution: access_structure.clone(),
+            param_gas_rewards: access_structure.clone(),
+            pool_parameters: access_structure.clone(),
+            add_anonymity_revoker: access_structure.clone(),
+            add_identity_provider: access_structure,
+        };
+        let keys = UpdateKeysCollection {
+            root_keys: HigherLevelAccessStructure {
+                keys: vec![UpdatePublicKey::from(&key_pair)],
+                threshold,
+                _phantom: Default::default(),
+            },
+            level_1_keys: HigherLevelAccessStructure {
+                keys: vec![UpdatePublicKey::from(&key_pair)],
+                threshold,
+                _phantom: Default::default(),
+            },
+            level_2_keys,
+        };
+        let fraction = |parts| AmountFraction::new(parts).unwrap();
+        let parameters = ChainParametersV0 {
+            election_difficulty:          ElectionDifficulty::new(25_000).unwrap(),
+            euro_per_energy:              ExchangeRate::new(1, 50_000).unwrap(),
+            micro_ccd_per_euro:           ExchangeRate::new(100, 1).unwrap(),
+            baker_cooldown_epochs:        Epoch { epoch: 166 },
+            account_creation_limit:       10,
+            reward_parameters:            RewardParametersV0 {
+                mint_distribution:            MintDistributionV0 {
+                    mint_per_slot:       MintRate {
+                        mantissa: 7555999,
+                        exponent: 16,
+                    },
+                    baking_reward:       fraction(60_000),
+                    finalization_reward: fraction(30_000),
+                },
+                transaction_fee_distribution: TransactionFeeDistribution {
+                    baker:       fraction(45_000),
+                    gas_account: fraction(45_000),
+                },
+                gas_rewards:                  GASRewards {
+                    baker:              fraction(25_000),
+                    finalization_proof: fraction(50),
+                    account_creation:   fraction(200),
+                    chain_update:       fraction(50),
+                },
+            },
+            foundation_account:           AccountAddress([0u8; 32]),
+            minimum_threshold_for_baking: Amount::from_ccd(15_000),
+        };
+        UpdateQueues::new(parameters, keys)
+    }
+
+    fn header(seq_number: u64, effective_time: u64) -> UpdateHeader {
+        UpdateHeader {
+            seq_number:     UpdateSequenceNumber { number: seq_number },
+            effective_time: TransactionTime::from_seconds(effective_time),
+            timeout:        TransactionTime::from_seconds(effective_time),
+            payload_size:   PayloadSize { size: 0 },
+        }
+    }
+
+    fn threshold_update(ccd: u64) -> UpdatePayload {
+        UpdatePayload::BakerStakeThreshold(BakerParameters {
+            minimum_threshold_for_baking: Amount::from_ccd(ccd),
+        })
+    }
+
+    #[test]
+    fn test_update_queues() {
+        let mut state = test_state();
+        state
+            .enqueue(&header(1, 100), threshold_update(20_000))
+            .expect("First update is accepted.");
+        assert!(matches!(
+            state.enqueue(&header(1, 200), threshold_update(30_000)),
+            Err(ChainParameterUpdateError::SequenceNumberMismatch { .. })
+        ));
+        state
+            .enqueue(&header(2, 200), threshold_update(30_000))
+            .expect("Second update is accepted.");
+        assert!(matches!(
+            state.enqueue(
+                &header(1, 100),
+                UpdatePayload::GASRewardsCPV2(GASRewardsV1 {
+                    baker:            AmountFraction::new(1).unwrap(),
+                    account_creation: AmountFraction::new(1).unwrap(),
+                    chain_update:     AmountFraction::new(1).unwrap(),
+                })
+            ),
+            Err(ChainParameterUpdateError::UnsupportedUpdate(
+                UpdateType::UpdateGASRewardsCPV2
+            ))
+        ));
+        assert_eq!(state.pending(UpdateType::UpdatePoolParameters).len(), 2);
+
+        let at_150 = state
+            .apply_updates(None, TransactionTime::from_seconds(150))
+            .expect("Updates apply.");
+        assert_eq!(
+            at_150.parameters.minimum_threshold_for_baking,
+            Amount::from_ccd(20_000)
+        );
+        assert_eq!(at_150.pending(UpdateType::UpdatePoolParameters).len(), 1);
+
+        // An update with an earlier effective time replaces the later one.
+        let replaced = state
+            .apply_updates(
+                [(header(3, 150), threshold_update(25_000))],
+                TransactionTime::from_seconds(300),
+            )
+            .expect("Updates apply.");
+        assert_eq!(
+            replaced.parameters.minimum_threshold_for_baking,
+            Amount::from_ccd(25_000)
+        );
+        assert_eq!(
+            replaced.next_sequence_number(UpdateType::UpdatePoolParameters),
+            UpdateSequenceNumber { number: 4 }
+        );
+    }
+
+    #[test]
+    fn test_immediate_update_clears_queue() {
+        let mut state = test_state();
+        state
+            .enqueue(&header(1, 100), threshold_update(20_000))
+            .expect("Pending update is accepted.");
+        state
+            .enqueue(&header(2, 0), threshold_update(30_000))
+            .expect("Immediate update is accepted.");
+        assert_eq!(
+            state.parameters.minimum_threshold_for_baking,
+            Amount::from_ccd(30_000)
+        );
+        assert!(state.pending(UpdateType::UpdatePoolParameters).is_empty());
+        let at_200 = state
+            .apply_updates(None, TransactionTime::from_seconds(200))
+            .expect("Updates apply.");
+        assert_eq!(
+            at_200.parameters.minimum_threshold_for_baking,
+            Amount::from_ccd(30_000),
+            "The earlier pending update does not override the immediate one."
+        );
+    }
+}
@@ -19,6 +19,16 @@ pub const MAX_MEMO_SIZE: usize = 256;
 /// Maximum allowed size of the Wasm module to deploy on the chain.
 pub const MAX_WASM_MODULE_SIZE: u32 = 8 * 65536;
 
+/// Maximum allowed size of a Wasm module of version 0 to deploy on the chain.
+pub const MAX_WASM_MODULE_SIZE_V0: u32 = 65536;
+
+/// Maximum size of the parameter of a smart contract call before protocol
+/// version 5. From protocol version 5 the limit is [`MAX_PARAMETER_LEN`].
+pub const MAX_PARAMETER_LEN_V0: usize = 1024;
+
+/// Maximum number of releases in a transfer with schedule.
+pub const MAX_SCHEDULE_LENGTH: usize = 255;
+
 /// Curve used for encrypted transfers. This is the same as the anonymity
 /// revoker curve.
 pub type EncryptedAmountsCurve = crate::id::constants::ArCurve;
@@ -0,0 +1,474 @@
+//! Estimation of the energy needed to initialize or update a V1 smart
+//! contract, by running the call locally with the smart contract engine.
+//!
+//! The energy of [`construct::init_contract`] and
+//! [`construct::update_contract`] depends on the execution of the contract,
+//! so it cannot be computed from the payload alone. The functions in this
+//! module execute the call against a module artifact, and in the case of
+//! updates, against a given contract state. The interpreter energy used by the
+//! execution is converted to NRG, the administrative costs of the call and the
+//! [base cost](cost::base_cost) of the transaction are added, and a
+//! recommended amount of energy with a safety margin is returned.
+//!
+//! The local execution has no access to the chain. Calls that transfer CCD,
+//! invoke other contracts, or query the chain cannot be estimated this way and
+//! result in [`DryRunError::Interrupted`].
+use crate::{
+    base::{Energy, ProtocolVersion},
+    transactions::{
+        construct::{self, TRANSACTION_HEADER_SIZE},
+        cost, InitContractPayload, Payload, PayloadLike, UpdateContractPayload,
+    },
+};
+use concordium_smart_contract_engine::{
+    v0::HasInitContext,
+    v1::{
+        self,
+        trie::{BackingStoreLoad, PersistentState},
+        HasReceiveContext, InitResult, InvokeFromArtifactCtx, ReceiveParams, ReceiveResult,
+    },
+    InterpreterEnergy,
+};
+use thiserror::Error;
+
+/// The amount of interpreter energy that corresponds to one unit of
+/// [`Energy`].
+pub const INTERPRETER_ENERGY_FACTOR: u64 = 1000;
+
+/// Convert energy to interpreter energy. This saturates at the maximum
+/// interpreter energy.
+pub fn to_interpreter_energy(energy: Energy) -> InterpreterEnergy {
+    InterpreterEnergy::new(energy.energy.saturating_mul(INTERPRETER_ENERGY_FACTOR))
+}
+
+/// Convert interpreter energy to energy, rounding down. This matches the
+/// conversion done by the scheduler.
+pub fn from_interpreter_energy(energy: InterpreterEnergy) -> Energy {
+    Energy::from(energy.energy / INTERPRETER_ENERGY_FACTOR)
+}
+
+/// A module artifact to execute calls against.
+#[derive(Debug, Clone, Copy)]
+pub struct ModuleArtifact<'a> {
+    /// The artifact of the module, serialized in the format specified by the
+    /// `concordium-wasm` crate.
+    pub artifact:    &'a [u8],
+    /// The size of the deployed `.wasm` module in bytes. This determines the
+    /// cost of looking up the module.
+    pub module_size: u64,
+}
+
+/// Configuration of an estimate.
+#[derive(Debug, Clone, Copy)]
+pub struct EstimationConfig {
+    /// The protocol version whose rules and costs apply. This must be protocol
+    /// version 4 or later, since V1 contracts do not exist before that.
+    pub protocol_version: ProtocolVersion,
+    /// The number of signatures the transaction will have.
+    pub num_sigs:         u32,
+    /// The maximum amount of energy the execution may use.
+    pub max_energy:       Energy,
+    /// The safety margin in percent that is added to the estimate.
+    pub margin_percent:   u64,
+}
+
+impl EstimationConfig {
+    /// The default safety margin in percent.
+    pub const DEFAULT_MARGIN_PERCENT: u64 = 10;
+    /// The default maximum amount of energy the execution may use. This is
+    /// the default maximum energy of a block.
+    pub const DEFAULT_MAX_ENERGY: Energy = Energy { energy: 3_000_000 };
+
+    /// Configuration with the default maximum energy and safety margin.
+    pub fn new(protocol_version: ProtocolVersion, num_sigs: u32) -> Self {
+        Self {
+            protocol_version,
+            num_sigs,
+            max_energy: Self::DEFAULT_MAX_ENERGY,
+            margin_percent: Self::DEFAULT_MARGIN_PERCENT,
+        }
+    }
+
+    fn receive_params(&self) -> Result<ReceiveParams, DryRunError> {
+        match self.protocol_version {
+            ProtocolVersion::P1 | ProtocolVersion::P2 | ProtocolVersion::P3 => Err(
+                DryRunError::UnsupportedProtocolVersion(self.protocol_version),
+            ),
+            ProtocolVersion::P4 => Ok(ReceiveParams::new_p4()),
+            ProtocolVersion::P5 => Ok(ReceiveParams::new_p5()),
+            ProtocolVersion::P6 => Ok(ReceiveParams::new_p6()),
+            ProtocolVersion::P7 => Ok(ReceiveParams::new_p7()),
+        }
+    }
+
+    /// Combine the cost of executing the call with the base cost of a
+    /// transaction with the given payload.
+    fn estimate(&self, payload: &Payload, execution: Energy) -> EnergyEstimate {
+        let size = TRANSACTION_HEADER_SIZE + u64::from(payload.encode().size().size);
+        let base = cost::base_cost(size, self.num_sigs);
+        let total = u128::from((base + execution).energy);
+        let recommended = total * (100 + u128::from(self.margin_percent));
+        // Round up, so that a non-zero margin always adds energy.
+        let recommended = u64::try_from(recommended.div_ceil(100)).unwrap_or(u64::MAX);
+        EnergyEstimate {
+            base,
+            execution,
+            recommended: Energy::from(recommended),
+        }
+    }
+}
+
+/// The result of a successful estimate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnergyEstimate {
+    /// The [base cost](cost::base_cost) of the transaction, which accounts for
+    /// its size and the number of signatures.
+    pub base:        Energy,
+    /// The cost of the call. This is the energy used by the execution together
+    /// with the administrative costs of the call.
+    pub execution:   Energy,
+    /// The recommended total energy of the transaction. This is the sum of the
+    /// base cost and the cost of the call, plus the safety margin.
+    pub recommended: Energy,
+}
+
+impl EnergyEstimate {
+    /// The recommended energy without the base cost. This is the amount to
+    /// supply to [`construct::init_contract`] or
+    /// [`construct::update_contract`], which add the base cost themselves.
+    pub fn recommended_execution(&self) -> Energy {
+        self.recommended
+            .checked_sub(self.base)
+            .unwrap_or(Energy::from(0))
+    }
+
+    /// The recommended total energy, for use with
+    /// [`construct::GivenEnergy::Absolute`].
+    pub fn given_energy(&self) -> construct::GivenEnergy {
+        construct::GivenEnergy::Absolute(self.recommended)
+    }
+}
+
+#[derive(Debug, Error)]
+/// An error that prevents estimating the energy of a call.
+pub enum DryRunError {
+    #[error("V1 contracts are not supported in protocol version {0}.")]
+    UnsupportedProtocolVersion(ProtocolVersion),
+    #[error("Unable to execute the call: {0}")]
+    Execution(#[source] anyhow::Error),
+    #[error("The contract rejected the call with reason {reason}.")]
+    Rejected {
+        /// The reason code returned by the contract.
+        reason:       i32,
+        /// The return value produced by the contract.
+        return_value: Vec<u8>,
+        /// The energy used before the contract rejected the call.
+        used:         Energy,
+    },
+    #[error("Execution of the contract failed: {error}")]
+    Trap {
+        error: anyhow::Error,
+        /// The energy used before execution failed.
+        used:  Energy,
+    },
+    #[error("Execution of the contract exceeded the maximum energy {max}.")]
+    OutOfEnergy { max: Energy },
+    #[error(
+        "The contract invoked an operation that cannot be executed locally, after using {used} \
+         energy."
+    )]
+    Interrupted {
+        /// The energy used before the operation was invoked.
+        used: Energy,
+    },
+}
+
+/// The energy used by an execution that started with `start` interpreter
+/// energy and ended with `remaining`.
+fn used_energy(start: InterpreterEnergy, remaining: InterpreterEnergy) -> Energy {
+    from_interpreter_energy(InterpreterEnergy::new(
+        start.energy.saturating_sub(remaining.energy),
+    ))
+}
+
+/// Estimate the energy needed to initialize a contract instance with the given
+/// payload. The `init_ctx` supplies the chain metadata, the sender and its
+/// policies that the init function observes.
+pub fn estimate_init_contract(
+    module: ModuleArtifact,
+    init_ctx: impl HasInitContext,
+    payload: &InitContractPayload,
+    config: &EstimationConfig,
+) -> Result<EnergyEstimate, DryRunError> {
+    let params = config.receive_params()?;
+    let energy = to_interpreter_energy(config.max_energy);
+    let loader = v1::trie::Loader { inner: Vec::new() };
+    let result = v1::invoke_init_from_artifact::<_, ()>(
+        InvokeFromArtifactCtx {
+            artifact: module.artifact,
+            amount: payload.amount,
+            parameter: payload.param.as_ref(),
+            energy,
+        },
+        init_ctx,
+        payload.init_name.as_contract_name().get_chain_name(),
+        loader,
+        params.limit_logs_and_return_values,
+    )
+    .map_err(DryRunError::Execution)?;
+    let used = match result {
+        InitResult::Success {
+            remaining_energy, ..
+        } => used_energy(energy, remaining_energy) + cost::INITIALIZE_CONTRACT_INSTANCE_CREATE,
+        InitResult::Reject {
+            reason,
+            return_value,
+            remaining_energy,
+            ..
+        } => {
+            return Err(DryRunError::Rejected {
+                reason,
+                return_value,
+                used: used_energy(energy, remaining_energy),
+            })
+        }
+        InitResult::Trap {
+            error,
+            remaining_energy,
+            ..
+        } => {
+            return Err(DryRunError::Trap {
+                error,
+                used: used_energy(energy, remaining_energy),
+            })
+        }
+        InitResult::OutOfEnergy { .. } => {
+            return Err(DryRunError::OutOfEnergy {
+                max: config.max_energy,
+            })
+        }
+    };
+    let execution = cost::lookup_module(config.protocol_version, module.module_size)
+        + used
+        + cost::INITIALIZE_CONTRACT_INSTANCE_BASE;
+    let payload = Payload::InitContract {
+        payload: payload.clone(),
+    };
+    Ok(config.estimate(&payload, execution))
+}
+
+/// Estimate the energy needed to update a contract instance with the given
+/// payload. The call is executed against `state`, the current state of the
+/// instance, which is loaded using `loader`. The state itself is not modified.
+/// The `receive_ctx` supplies the chain metadata, the invoker and the instance
+/// that the receive function observes.
+pub fn estimate_update_contract<Ctx: HasReceiveContext>(
+    module: ModuleArtifact,
+    receive_ctx: Ctx,
+    state: &PersistentState,
+    mut loader: impl BackingStoreLoad,
+    payload: &UpdateContractPayload,
+    config: &EstimationConfig,
+) -> Result<EnergyEstimate, DryRunError> {
+    let params = config.receive_params()?;
+    let energy = to_interpreter_energy(config.max_energy);
+    let mut mutable_state = state.thaw();
+    let inner = mutable_state.get_inner(&mut loader);
+    let instance_state = v1::InstanceState::new(loader, inner);
+    let result = v1::invoke_receive_from_artifact::<_, _, Ctx, ()>(
+        InvokeFromArtifactCtx {
+            artifact: module.artifact,
+            amount: payload.amount,
+            parameter: payload.message.as_ref(),
+            energy,
+        },
+        receive_ctx,
+        payload.receive_name.as_receive_name(),
+        instance_state,
+        params,
+    )
+    .map_err(DryRunError::Execution)?;
+    let used = match result {
+        ReceiveResult::Success {
+            remaining_energy, ..
+        } => used_energy(energy, remaining_energy),
+        ReceiveResult::Interrupt {
+            remaining_energy, ..
+        } => {
+            return Err(DryRunError::Interrupted {
+                used: used_energy(energy, remaining_energy),
+            })
+        }
+        ReceiveResult::Reject {
+            reason,
+            return_value,
+            remaining_energy,
+            ..
+        } => {
+            return Err(DryRunError::Rejected {
+                reason,
+                return_value,
+                used: used_energy(energy, remaining_energy),
+            })
+        }
+        ReceiveResult::Trap {
+            error,
+            remaining_energy,
+            ..
+        } => {
+            return Err(DryRunError::Trap {
+                error,
+                used: used_energy(energy, remaining_energy),
+            })
+        }
+        ReceiveResult::OutOfEnergy { .. } => {
+            return Err(DryRunError::OutOfEnergy {
+                max: config.max_energy,
+            })
+        }
+    };
+    let execution = cost::lookup_module(config.protocol_version, module.module_size)
+        + used
+        + cost::UPDATE_CONTRACT_INSTANCE_BASE;
+    let payload = Payload::Update {
+        payload: payload.clone(),
+    };
+    Ok(config.estimate(&payload, execution))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concordium_contracts_common::{
+        AccountAddress, Address, Amount, ChainMetadata, ContractAddress, ModuleReference,
+        OwnedContractName, OwnedEntrypointName, OwnedParameter, OwnedReceiveName, Timestamp,
+    };
+    use concordium_smart_contract_engine::{
+        v0,
+        wasm::{output::Output, utils, validate::ValidationConfig, CostConfigurationV1},
+    };
+
+    /// Compile the module to a serialized artifact.
+    fn compile(source: &[u8]) -> Vec<u8> {
+        let module = utils::instantiate_with_metering::<v1::ProcessedImports>(
+            ValidationConfig::V1,
+            CostConfigurationV1,
+            &v1::ConcordiumAllowedImports {
+                support_upgrade:            true,
+                enable_debug:               false,
+                support_secp256k1_recovery: true,
+            },
+            source,
+        )
+        .expect("The module is valid.");
+        let mut out = Vec::new();
+        module
+            .artifact
+            .output(&mut out)
+            .expect("Serializing the artifact succeeds.");
+        out
+    }
+
+    fn receive_ctx(entrypoint: &str) -> v1::ReceiveContext<Vec<u8>> {
+        let owner = AccountAddress([0u8; 32]);
+        v1::ReceiveContext {
+            common:     v0::ReceiveContext {
+                metadata: ChainMetadata {
+                    slot_time: Timestamp::from_timestamp_millis(0),
+                },
+                invoker: owner,
+                self_address: ContractAddress::new(0, 0),
+                self_balance: Amount::zero(),
+                sender: Address::Account(owner),
+                owner,
+                sender_policies: Vec::new(),
+            },
+            entrypoint: OwnedEntrypointName::new_unchecked(entrypoint.into()),
+        }
+    }
+
+    fn update_payload(receive_name: &str) -> UpdateContractPayload {
+        UpdateContractPayload {
+            amount:       Amount::zero(),
+            address:      ContractAddress::new(0, 0),
+            receive_name: OwnedReceiveName::new_unchecked(receive_name.into()),
+            message:      OwnedParameter::empty(),
+        }
+    }
+
+    #[test]
+    fn test_estimate_contract_calls() {
+        let source =
+            include_bytes!("../../../smart-contracts/testdata/contracts/v1/extra-exports.wasm");
+        let artifact = compile(source);
+        let module = ModuleArtifact {
+            artifact:    &artifact,
+            module_size: source.len() as u64,
+        };
+        let config = EstimationConfig::new(ProtocolVersion::P6, 1);
+
+        let init_ctx = v0::InitContext {
+            metadata:        ChainMetadata {
+                slot_time: Timestamp::from_timestamp_millis(0),
+            },
+            init_origin:     AccountAddress([0u8; 32]),
+            sender_policies: Vec::<u8>::new(),
+        };
+        let init_payload = InitContractPayload {
+            amount:    Amount::zero(),
+            mod_ref:   ModuleReference::from([0u8; 32]),
+            init_name: OwnedContractName::new_unchecked("init_contract".into()),
+            param:     OwnedParameter::empty(),
+        };
+        let estimate = estimate_init_contract(module, &init_ctx, &init_payload, &config)
+            .expect("Initialization succeeds.");
+        assert!(
+            estimate.execution
+                >= cost::INITIALIZE_CONTRACT_INSTANCE_BASE
+                    + cost::INITIALIZE_CONTRACT_INSTANCE_CREATE
+        );
+        assert!(estimate.recommended > estimate.base + estimate.execution);
+        assert_eq!(
+            estimate.recommended_execution() + estimate.base,
+            estimate.recommended
+        );
+
+        let estimate = estimate_update_contract(
+            module,
+            receive_ctx("call"),
+            &PersistentState::Empty,
+            v1::trie::Loader { inner: Vec::new() },
+            &update_payload("contract.call"),
+            &config,
+        )
+        .expect("The update succeeds.");
+        assert!(estimate.execution >= cost::UPDATE_CONTRACT_INSTANCE_BASE);
+
+        // Upgrades need the chain, so they cannot be estimated locally.
+        let source =
+            include_bytes!("../../../smart-contracts/testdata/contracts/v1/upgrading_0.wasm");
+        let artifact = compile(source);
+        let module = ModuleArtifact {
+            artifact:    &artifact,
+            module_size: source.len() as u64,
+        };
+        let mut payload = update_payload("a.bump");
+        payload.message = OwnedParameter::new_unchecked(vec![0u8; 32]);
+        let result = estimate_update_contract(
+            module,
+            receive_ctx("bump"),
+            &PersistentState::Empty,
+            v1::trie::Loader { inner: Vec::new() },
+            &payload,
+            &config,
+        );
+        assert!(matches!(result, Err(DryRunError::Interrupted { .. })));
+
+        let config = EstimationConfig::new(ProtocolVersion::P3, 1);
+        let result = estimate_init_contract(module, &init_ctx, &init_payload, &config);
+        assert!(matches!(
+            result,
+            Err(DryRunError::UnsupportedProtocolVersion(ProtocolVersion::P3))
+        ));
+    }
+}
@@ -0,0 +1,1111 @@
+//! Human-readable explanations of block items.
+//!
+//! The main entrypoint is [`explain_block_item`] which decodes a
+//! [`BlockItem`] into a [`BlockItemExplanation`]. The explanation is a
+//! structured value that can be serialized to JSON, and its [`Display`]
+//! implementation renders it as text suitable for showing to a person before
+//! they sign or submit the block item.
+use crate::{
+    base::{
+        AccountThreshold, AmountFraction, CredentialRegistrationID, DelegationTarget, Energy,
+        Nonce, OpenStatus, UpdateSequenceNumber,
+    },
+    common::{
+        self,
+        types::{Amount, CredentialIndex, Timestamp, TransactionTime},
+        ParseResult, SerdeSerialize,
+    },
+    hashes,
+    id::types::{AccountAddress, AccountCredential},
+    transactions::{
+        compute_transaction_sign_hash, construct::TRANSACTION_HEADER_SIZE, cost,
+        AccountTransaction, BlockItem, EncodedPayload, Payload,
+    },
+    updates::{UpdateInstruction, UpdatePayload, UpdateType},
+};
+use concordium_contracts_common::{
+    schema::{Type, VersionedModuleSchema},
+    ContractAddress, ModuleReference, SignatureThreshold, WasmVersion,
+};
+use std::fmt::{self, Display};
+
+/// An explanation of a block item.
+#[derive(Debug, Clone, SerdeSerialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum BlockItemExplanation {
+    AccountTransaction(AccountTransactionExplanation),
+    CredentialDeployment(CredentialDeploymentExplanation),
+    UpdateInstruction(UpdateInstructionExplanation),
+}
+
+/// An explanation of an account transaction.
+#[derive(Debug, Clone, SerdeSerialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountTransactionExplanation {
+    /// Hash of the transaction that identifies it on the chain.
+    pub hash:           hashes::TransactionHash,
+    /// The hash that the sender signs.
+    pub sign_hash:      hashes::TransactionSignHash,
+    pub sender:         AccountAddress,
+    pub nonce:          Nonce,
+    /// Latest time the transaction can be included in a block.
+    pub expiry:         TransactionTime,
+    /// The maximum energy the sender is willing to pay for.
+    pub energy_limit:   Energy,
+    /// The number of signatures on the transaction.
+    pub num_signatures: u32,
+    pub cost:           CostEstimate,
+    pub payload:        PayloadExplanation,
+}
+
+/// An estimate of the energy cost of an account transaction.
+#[derive(Debug, Clone, Copy, SerdeSerialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostEstimate {
+    /// The cost that every transaction pays, which depends on its size and
+    /// the number of signatures.
+    pub base_cost: Energy,
+    /// The total cost of the transaction, if it can be determined from the
+    /// transaction alone. This is not the case for smart contract calls and
+    /// credential updates, whose cost depends on the state of the chain.
+    pub total:     Option<Energy>,
+}
+
+/// An explanation of a credential deployment.
+#[derive(Debug, Clone, SerdeSerialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialDeploymentExplanation {
+    pub hash:          hashes::TransactionHash,
+    /// Latest time the deployment can be included in a block.
+    pub expiry:        TransactionTime,
+    /// Whether the credential is an initial credential, created by the
+    /// identity provider, as opposed to a normal credential.
+    pub initial:       bool,
+    pub credential_id: CredentialRegistrationID,
+    /// The number of keys of the new account.
+    pub num_keys:      usize,
+    /// The number of signatures required from the keys.
+    pub threshold:     SignatureThreshold,
+}
+
+/// An explanation of a chain update instruction.
+#[derive(Debug, Clone, SerdeSerialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInstructionExplanation {
+    pub hash:           hashes::TransactionHash,
+    pub seq_number:     UpdateSequenceNumber,
+    /// The time at which the update takes effect. 0 means immediately.
+    pub effective_time: TransactionTime,
+    /// The time after which the update can no longer be enqueued.
+    pub timeout:        TransactionTime,
+    pub update_type:    UpdateType,
+    pub payload:        UpdatePayload,
+    pub num_signatures: usize,
+}
+
+/// An explanation of the payload of an account transaction.
+#[derive(Debug, Clone, SerdeSerialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum PayloadExplanation {
+    DeployModule {
+        module_reference: ModuleReference,
+        version:          WasmVersion,
+        /// Size of the module source in bytes.
+        size:             u64,
+    },
+    InitContract {
+        amount:           Amount,
+        module_reference: ModuleReference,
+        contract:         String,
+        parameter:        ParameterExplanation,
+    },
+    Update {
+        amount:     Amount,
+        address:    ContractAddress,
+        contract:   String,
+        entrypoint: String,
+        parameter:  ParameterExplanation,
+    },
+    Transfer {
+        to:     AccountAddress,
+        amount: Amount,
+        memo:   Option<DataExplanation>,
+    },
+    TransferWithSchedule {
+        to:       AccountAddress,
+        /// The sum of all releases, or [`None`] if the sum is larger than the
+        /// maximum [`Amount`]. Such a transaction will be rejected by the
+        /// chain.
+        total:    Option<Amount>,
+        releases: Vec<(Timestamp, Amount)>,
+        memo:     Option<DataExplanation>,
+    },
+    EncryptedTransfer {
+        to:   AccountAddress,
+        memo: Option<DataExplanation>,
+    },
+    TransferToEncrypted {
+        amount: Amount,
+    },
+    TransferToPublic {
+        amount: Amount,
+    },
+    AddBaker {
+        stake:            Amount,
+        restake_earnings: bool,
+    },
+    RemoveBaker,
+    UpdateBakerStake {
+        stake: Amount,
+    },
+    UpdateBakerRestakeEarnings {
+        restake_earnings: bool,
+    },
+    UpdateBakerKeys,
+    ConfigureBaker {
+        capital: Option<Amount>,
+        restake_earnings: Option<bool>,
+        open_for_delegation: Option<OpenStatus>,
+        /// Whether new baker keys are registered.
+        keys_updated: bool,
+        metadata_url: Option<String>,
+        transaction_fee_commission: Option<AmountFraction>,
+        baking_reward_commission: Option<AmountFraction>,
+        finalization_reward_commission: Option<AmountFraction>,
+    },
+    ConfigureDelegation {
+        capital:           Option<Amount>,
+        restake_earnings:  Option<bool>,
+        delegation_target: Option<DelegationTarget>,
+    },
+    UpdateCredentialKeys {
+        credential_id: CredentialRegistrationID,
+        num_keys:      usize,
+        threshold:     SignatureThreshold,
+    },
+    UpdateCredentials {
+        added:         Vec<CredentialIndex>,
+        removed:       Vec<CredentialRegistrationID>,
+        new_threshold: AccountThreshold,
+    },
+    RegisterData {
+        data: DataExplanation,
+    },
+    /// The payload could not be decoded. Such a transaction will be rejected
+    /// by the chain, but the sender still pays for it.
+    Invalid {
+        error: String,
+    },
+}
+
+/// A memo or registered data. These are arbitrary bytes, but by convention
+/// they are usually CBOR encoded.
+#[derive(Debug, Clone, PartialEq, Eq, SerdeSerialize)]
+#[serde(rename_all = "camelCase", tag = "type", content = "value")]
+pub enum DataExplanation {
+    /// A CBOR encoded text string.
+    CborText(String),
+    /// A CBOR encoded integer.
+    CborInteger(i128),
+    /// Bytes that are not CBOR, but are valid UTF-8.
+    Text(String),
+    /// Bytes that could not be decoded, hex encoded.
+    Raw(String),
+}
+
+/// The parameter of a smart contract call.
+#[derive(Debug, Clone, SerdeSerialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum ParameterExplanation {
+    /// The parameter decoded using the schema of the contract.
+    Decoded { value: serde_json::Value },
+    /// The parameter could not be decoded, either since no schema was supplied,
+    /// or the schema did not describe the parameter.
+    Raw {
+        /// The hex encoded parameter.
+        parameter: String,
+        reason:    String,
+    },
+}
+
+/// Explain the block item. If `schema` is supplied it is used to decode the
+/// parameters of smart contract calls. Since an update transaction only names
+/// the contract instance, it is the responsibility of the caller to supply the
+/// schema of the module of the instance.
+pub fn explain_block_item(
+    item: &BlockItem<EncodedPayload>,
+    schema: Option<&VersionedModuleSchema>,
+) -> BlockItemExplanation {
+    let hash = item.hash();
+    match item {
+        BlockItem::AccountTransaction(at) => {
+            BlockItemExplanation::AccountTransaction(explain_account_transaction(hash, at, schema))
+        }
+        BlockItem::CredentialDeployment(acm) => {
+            let (initial, credential_id, keys) = match &acm.credential {
+                AccountCredential::Initial { icdi } => {
+                    (true, icdi.values.reg_id, &icdi.values.cred_account)
+                }
+                AccountCredential::Normal { cdi } => {
+                    (false, cdi.values.cred_id, &cdi.values.cred_key_info)
+                }
+            };
+            BlockItemExplanation::CredentialDeployment(CredentialDeploymentExplanation {
+                hash,
+                expiry: acm.message_expiry,
+                initial,
+                credential_id: CredentialRegistrationID::new(credential_id),
+                num_keys: keys.keys.len(),
+                threshold: keys.threshold,
+            })
+        }
+        BlockItem::UpdateInstruction(ui) => {
+            BlockItemExplanation::UpdateInstruction(explain_update_instruction(hash, ui))
+        }
+    }
+}
+
+/// Deserialize a block item and explain it. See [`explain_block_item`].
+pub fn explain_block_item_bytes(
+    bytes: &[u8],
+    schema: Option<&VersionedModuleSchema>,
+) -> ParseResult<BlockItemExplanation> {
+    let mut source = std::io::Cursor::new(bytes);
+    let item: BlockItem<EncodedPayload> = common::from_bytes(&mut source)?;
+    anyhow::ensure!(
+        source.position() == bytes.len() as u64,
+        "{} bytes of input remaining after the block item.",
+        bytes.len() as u64 - source.position()
+    );
+    Ok(explain_block_item(&item, schema))
+}
+
+fn explain_account_transaction(
+    hash: hashes::TransactionHash,
+    at: &AccountTransaction<EncodedPayload>,
+    schema: Option<&VersionedModuleSchema>,
+) -> AccountTransactionExplanation {
+    let num_signatures = at.signature.num_signatures();
+    let size = TRANSACTION_HEADER_SIZE + u64::from(at.header.payload_size.size);
+    let base_cost = cost::base_cost(size, num_signatures);
+    let (payload, specific_cost) = match at.payload.decode() {
+        Ok(payload) => (explain_payload(&payload, schema), payload_cost(&payload)),
+        Err(e) => (
+            PayloadExplanation::Invalid {
+                error: e.to_string(),
+            },
+            None,
+        ),
+    };
+    AccountTransactionExplanation {
+        hash,
+        sign_hash: compute_transaction_sign_hash(&at.header, &at.payload),
+        sender: at.header.sender,
+        nonce: at.header.nonce,
+        expiry: at.header.expiry,
+        energy_limit: at.header.energy_amount,
+        num_signatures,
+        cost: CostEstimate {
+            base_cost,
+            total: specific_cost.map(|c| base_cost + c),
+        },
+        payload,
+    }
+}
+
+/// The cost of the transaction in addition to the base cost, if it only
+/// depends on the payload.
+fn payload_cost(payload: &Payload) -> Option<Energy> {
+    let energy = match payload {
+        Payload::DeployModule { module } => cost::deploy_module(module.source.size()),
+        Payload::InitContract { .. } | Payload::Update { .. } => return None,
+        Payload::Transfer { .. } | Payload::TransferWithMemo { .. } => cost::SIMPLE_TRANSFER,
+        Payload::TransferWithSchedule { schedule, .. }
+        | Payload::TransferWithScheduleAndMemo { schedule, .. } => {
+            cost::scheduled_transfer(u16::try_from(schedule.len()).ok()?)
+        }
+        Payload::EncryptedAmountTransfer { .. }
+        | Payload::EncryptedAmountTransferWithMemo { .. } => cost::ENCRYPTED_TRANSFER,
+        Payload::TransferToEncrypted { .. } => cost::TRANSFER_TO_ENCRYPTED,
+        Payload::TransferToPublic { .. } => cost::TRANSFER_TO_PUBLIC,
+        Payload::AddBaker { .. } => cost::ADD_BAKER,
+        Payload::RemoveBaker => cost::REMOVE_BAKER,
+        Payload::UpdateBakerStake { .. } => cost::UPDATE_BAKER_STAKE,
+        Payload::UpdateBakerRestakeEarnings { .. } => cost::UPDATE_BAKER_RESTAKE,
+        Payload::UpdateBakerKeys { .. } => cost::UPDATE_BAKER_KEYS,
+        // These depend on the number of credentials already on the account.
+        Payload::UpdateCredentialKeys { .. } | Payload::UpdateCredentials { .. } => return None,
+        Payload::RegisterData { .. } => cost::REGISTER_DATA,
+        Payload::ConfigureBaker { data } => {
+            if data.keys_with_proofs.is_some() {
+                cost::CONFIGURE_BAKER_WITH_KEYS
+            } else {
+                cost::CONFIGURE_BAKER_WITHOUT_KEYS
+            }
+        }
+        Payload::ConfigureDelegation { .. } => cost::CONFIGURE_DELEGATION,
+    };
+    Some(energy)
+}
+
+fn explain_payload(
+    payload: &Payload,
+    schema: Option<&VersionedModuleSchema>,
+) -> PayloadExplanation {
+    match payload {
+        Payload::DeployModule { module } => PayloadExplanation::DeployModule {
+            module_reference: module.get_module_ref(),
+            version:          module.version,
+            size:             module.source.size(),
+        },
+        Payload::InitContract { payload } => {
+            let contract = payload.init_name.as_contract_name().contract_name();
+            let parameter = explain_parameter(
+                payload.param.as_ref(),
+                schema.map(|s| s.get_init_param_schema(contract)),
+            );
+            PayloadExplanation::InitContract {
+                amount: payload.amount,
+                module_reference: payload.mod_ref,
+                contract: contract.into(),
+                parameter,
+            }
+        }
+        Payload::Update { payload } => {
+            let name = payload.receive_name.as_receive_name();
+            let contract = name.contract_name();
+            let entrypoint = name.entrypoint_name();
+            let parameter = explain_parameter(
+                payload.message.as_ref(),
+                schema.map(|s| s.get_receive_param_schema(contract, entrypoint.into())),
+            );
+            PayloadExplanation::Update {
+                amount: payload.amount,
+                address: payload.address,
+                contract: contract.into(),
+                entrypoint: entrypoint.to_string(),
+                parameter,
+            }
+        }
+        Payload::Transfer { to_address, amount } => PayloadExplanation::Transfer {
+            to:     *to_address,
+            amount: *amount,
+            memo:   None,
+        },
+        Payload::TransferWithMemo {
+            to_address,
+            memo,
+            amount,
+        } => PayloadExplanation::Transfer {
+            to:     *to_address,
+            amount: *amount,
+            memo:   Some(explain_data(memo.as_ref())),
+        },
+        Payload::TransferWithSchedule { to, schedule } => explain_schedule(*to, schedule, None),
+        Payload::TransferWithScheduleAndMemo { to, memo, schedule } => {
+            explain_schedule(*to, schedule, Some(explain_data(memo.as_ref())))
+        }
+        Payload::EncryptedAmountTransfer { to, .. } => PayloadExplanation::EncryptedTransfer {
+            to:   *to,
+            memo: None,
+        },
+        Payload::EncryptedAmountTransferWithMemo { to, memo, .. } => {
+            PayloadExplanation::EncryptedTransfer {
+                to:   *to,
+                memo: Some(explain_data(memo.as_ref())),
+            }
+        }
+        Payload::TransferToEncrypted { amount } => {
+            PayloadExplanation::TransferToEncrypted { amount: *amount }
+        }
+        Payload::TransferToPublic { data } => PayloadExplanation::TransferToPublic {
+            amount: data.transfer_amount,
+        },
+        Payload::AddBaker { payload } => PayloadExplanation::AddBaker {
+            stake:            payload.baking_stake,
+            restake_earnings: payload.restake_earnings,
+        },
+        Payload::RemoveBaker => PayloadExplanation::RemoveBaker,
+        Payload::UpdateBakerStake { stake } => {
+            PayloadExplanation::UpdateBakerStake { stake: *stake }
+        }
+        Payload::UpdateBakerRestakeEarnings { restake_earnings } => {
+            PayloadExplanation::UpdateBakerRestakeEarnings {
+                restake_earnings: *restake_earnings,
+            }
+        }
+        Payload::UpdateBakerKeys { .. } => PayloadExplanation::UpdateBakerKeys,
+        Payload::UpdateCredentialKeys { cred_id, keys } => {
+            PayloadExplanation::UpdateCredentialKeys {
+                credential_id: *cred_id,
+                num_keys:      keys.keys.len(),
+                threshold:     keys.threshold,
+            }
+        }
+        Payload::UpdateCredentials {
+            new_cred_infos,
+            remove_cred_ids,
+            new_threshold,
+        } => PayloadExplanation::UpdateCredentials {
+            added:         new_cred_infos.keys().copied().collect(),
+            removed:       remove_cred_ids.clone(),
+            new_threshold: *new_threshold,
+        },
+        Payload::RegisterData { data } => PayloadExplanation::RegisterData {
+            data: explain_data(data.as_ref()),
+        },
+        Payload::ConfigureBaker { data } => PayloadExplanation::ConfigureBaker {
+            capital: data.capital,
+            restake_earnings: data.restake_earnings,
+            open_for_delegation: data.open_for_delegation,
+            keys_updated: data.keys_with_proofs.is_some(),
+            metadata_url: data.metadata_url.as_ref().map(|url| url.to_string()),
+            transaction_fee_commission: data.transaction_fee_commission,
+            baking_reward_commission: data.baking_reward_commission,
+            finalization_reward_commission: data.finalization_reward_commission,
+        },
+        Payload::ConfigureDelegation { data } => PayloadExplanation::ConfigureDelegation {
+            capital:           data.capital,
+            restake_earnings:  data.restake_earnings,
+            delegation_target: data.delegation_target.clone(),
+        },
+    }
+}
+
+fn explain_schedule(
+    to: AccountAddress,
+    schedule: &[(Timestamp, Amount)],
+    memo: Option<DataExplanation>,
+) -> PayloadExplanation {
+    let total = schedule
+        .iter()
+        .try_fold(Amount::zero(), |acc, (_, amount)| acc.checked_add(*amount));
+    PayloadExplanation::TransferWithSchedule {
+        to,
+        total,
+        releases: schedule.to_vec(),
+        memo,
+    }
+}
+
+fn explain_parameter<E: Display>(
+    parameter: &[u8],
+    schema: Option<Result<Type, E>>,
+) -> ParameterExplanation {
+    let raw = |reason: String| ParameterExplanation::Raw {
+        parameter: hex::encode(parameter),
+        reason,
+    };
+    let ty = match schema {
+        None => return raw("No schema was supplied.".into()),
+        Some(Err(e)) => return raw(format!("The schema does not describe the parameter: {}", e)),
+        Some(Ok(ty)) => ty,
+    };
+    let mut cursor = concordium_contracts_common::Cursor::new(parameter);
+    match ty.to_json(&mut cursor) {
+        Ok(value) if cursor.offset == parameter.len() => ParameterExplanation::Decoded { value },
+        Ok(_) => raw("The parameter is longer than described by the schema.".into()),
+        Err(e) => raw(format!("The parameter does not match the schema: {}", e)),
+    }
+}
+
+/// Explain a memo or registered data. This recognizes CBOR encoded text
+/// strings and integers, which is how wallets encode memos, and falls back to
+/// UTF-8 and then to hex.
+pub fn explain_data(bytes: &[u8]) -> DataExplanation {
+    match decode_cbor(bytes) {
+        Some(CborItem::Text(text)) => DataExplanation::CborText(text.into()),
+        Some(CborItem::Integer(n)) => DataExplanation::CborInteger(n),
+        None => match std::str::from_utf8(bytes) {
+            Ok(text) if !text.chars().any(char::is_control) => DataExplanation::Text(text.into()),
+            _ => DataExplanation::Raw(hex::encode(bytes)),
+        },
+    }
+}
+
+enum CborItem<'a> {
+    Text(&'a str),
+    Integer(i128),
+}
+
+/// Decode a single CBOR text string or integer that spans all of the input.
+/// Other CBOR items are not supported.
+fn decode_cbor(bytes: &[u8]) -> Option<CborItem<'_>> {
+    let (&initial, rest) = bytes.split_first()?;
+    let major = initial >> 5;
+    let (argument, rest) = match initial & 0x1f {
+        n @ 0..=23 => (u64::from(n), rest),
+        n @ 24..=27 => {
+            let len = 1usize << (n - 24);
+            if rest.len() < len {
+                return None;
+            }
+            let (arg, rest) = rest.split_at(len);
+            let argument = arg.iter().fold(0u64, |acc, b| (acc << 8) | u64::from(*b));
+            (argument, rest)
+        }
+        _ => return None,
+    };
+    match major {
+        0 if rest.is_empty() => Some(CborItem::Integer(i128::from(argument))),
+        1 if rest.is_empty() => Some(CborItem::Integer(-1 - i128::from(argument))),
+        3 if rest.len() as u64 == argument => std::str::from_utf8(rest).ok().map(CborItem::Text),
+        _ => None,
+    }
+}
+
+fn explain_update_instruction(
+    hash: hashes::TransactionHash,
+    ui: &UpdateInstruction,
+) -> UpdateInstructionExplanation {
+    UpdateInstructionExplanation {
+        hash,
+        seq_number: ui.header.seq_number,
+        effective_time: ui.header.effective_time,
+        timeout: ui.header.timeout,
+        update_type: ui.payload.update_type(),
+        payload: ui.payload.clone(),
+        num_signatures: ui.signatures.signatures.len(),
+    }
+}
+
+/// Format a time as an RFC 3339 date, falling back to the number of seconds
+/// if it is out of range.
+fn format_time(time: TransactionTime) -> String {
+    i64::try_from(time.seconds)
+        .ok()
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .map_or_else(|| format!("{} seconds", time.seconds), |t| t.to_rfc3339())
+}
+
+impl Display for BlockItemExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockItemExplanation::AccountTransaction(at) => at.fmt(f),
+            BlockItemExplanation::CredentialDeployment(cd) => cd.fmt(f),
+            BlockItemExplanation::UpdateInstruction(ui) => ui.fmt(f),
+        }
+    }
+}
+
+impl Display for AccountTransactionExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Account transaction {}", self.hash)?;
+        writeln!(f, "  Sender: {}", self.sender)?;
+        writeln!(f, "  Nonce: {}", self.nonce)?;
+        writeln!(f, "  Expiry: {}", format_time(self.expiry))?;
+        writeln!(f, "  Sign hash: {}", self.sign_hash)?;
+        writeln!(f, "  Signatures: {}", self.num_signatures)?;
+        writeln!(f, "  Energy limit: {} NRG", self.energy_limit)?;
+        match self.cost.total {
+            Some(total) => {
+                writeln!(f, "  Cost: {} NRG", total)?;
+                if total > self.energy_limit {
+                    writeln!(f, "  WARNING: The energy limit is below the cost.")?;
+                }
+            }
+            None => writeln!(
+                f,
+                "  Cost: at least {} NRG, depending on the state of the chain",
+                self.cost.base_cost
+            )?,
+        }
+        self.payload.fmt(f)
+    }
+}
+
+impl Display for CredentialDeploymentExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.initial { "initial" } else { "normal" };
+        writeln!(
+            f,
+            "Deployment of {} credential {}",
+            kind, self.credential_id
+        )?;
+        writeln!(f, "  Hash: {}", self.hash)?;
+        writeln!(f, "  Expiry: {}", format_time(self.expiry))?;
+        writeln!(
+            f,
+            "  Keys: {} with threshold {}",
+            self.num_keys,
+            u8::from(self.threshold)
+        )
+    }
+}
+
+impl Display for UpdateInstructionExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Chain update {:?} {}", self.update_type, self.hash)?;
+        writeln!(f, "  Sequence number: {}", self.seq_number)?;
+        if self.effective_time.seconds == 0 {
+            writeln!(f, "  Effective: immediately")?;
+        } else {
+            writeln!(f, "  Effective: {}", format_time(self.effective_time))?;
+        }
+        writeln!(f, "  Timeout: {}", format_time(self.timeout))?;
+        writeln!(f, "  Signatures: {}", self.num_signatures)?;
+        let payload = serde_json::to_string_pretty(&self.payload).map_err(|_| fmt::Error)?;
+        writeln!(f, "  Payload: {}", payload)
+    }
+}
+
+/// Write an optional field, skipping it if it is not set.
+fn write_opt(f: &mut fmt::Formatter<'_>, label: &str, value: Option<impl Display>) -> fmt::Result {
+    match value {
+        Some(value) => writeln!(f, "  {}: {}", label, value),
+        None => Ok(()),
+    }
+}
+
+impl Display for PayloadExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayloadExplanation::DeployModule {
+                module_reference,
+                version,
+                size,
+            } => writeln!(
+                f,
+                "Deploy {} module {} of {} bytes",
+                version, module_reference, size
+            ),
+            PayloadExplanation::InitContract {
+                amount,
+                module_reference,
+                contract,
+                parameter,
+            } => {
+                writeln!(
+                    f,
+                    "Initialize contract {} from module {} with {} CCD",
+                    contract, module_reference, amount
+                )?;
+                parameter.fmt(f)
+            }
+            PayloadExplanation::Update {
+                amount,
+                address,
+                contract,
+                entrypoint,
+                parameter,
+            } => {
+                writeln!(
+                    f,
+                    "Call entrypoint {} of contract {} at {} with {} CCD",
+                    entrypoint, contract, address, amount
+                )?;
+                parameter.fmt(f)
+            }
+            PayloadExplanation::Transfer { to, amount, memo } => {
+                writeln!(f, "Transfer {} CCD to {}", amount, to)?;
+                write_opt(f, "Memo", memo.as_ref())
+            }
+            PayloadExplanation::TransferWithSchedule {
+                to,
+                total,
+                releases,
+                memo,
+            } => {
+                match total {
+                    Some(total) => writeln!(
+                        f,
+                        "Transfer {} CCD to {} in {} releases",
+                        total,
+                        to,
+                        releases.len()
+                    )?,
+                    None => writeln!(
+                        f,
+                        "Transfer to {} in {} releases. WARNING: The sum of the releases \
+                         overflows, the transaction will fail.",
+                        to,
+                        releases.len()
+                    )?,
+                }
+                for (timestamp, amount) in releases {
+                    writeln!(f, "  {} CCD at {}", amount, timestamp)?;
+                }
+                write_opt(f, "Memo", memo.as_ref())
+            }
+            PayloadExplanation::EncryptedTransfer { to, memo } => {
+                writeln!(f, "Transfer an encrypted amount to {}", to)?;
+                write_opt(f, "Memo", memo.as_ref())
+            }
+            PayloadExplanation::TransferToEncrypted { amount } => {
+                writeln!(f, "Transfer {} CCD to the encrypted balance", amount)
+            }
+            PayloadExplanation::TransferToPublic { amount } => {
+                writeln!(f, "Transfer {} CCD to the public balance", amount)
+            }
+            PayloadExplanation::AddBaker {
+                stake,
+                restake_earnings,
+            } => writeln!(
+                f,
+                "Register as baker with stake {} CCD, restaking earnings: {}",
+                stake, restake_earnings
+            ),
+            PayloadExplanation::RemoveBaker => writeln!(f, "Remove baker"),
+            PayloadExplanation::UpdateBakerStake { stake } => {
+                writeln!(f, "Update baker stake to {} CCD", stake)
+            }
+            PayloadExplanation::UpdateBakerRestakeEarnings { restake_earnings } => {
+                writeln!(
+                    f,
+                    "Update baker restaking of earnings to {}",
+                    restake_earnings
+                )
+            }
+            PayloadExplanation::UpdateBakerKeys => writeln!(f, "Update baker keys"),
+            PayloadExplanation::ConfigureBaker {
+                capital,
+                restake_earnings,
+                open_for_delegation,
+                keys_updated,
+                metadata_url,
+                transaction_fee_commission,
+                baking_reward_commission,
+                finalization_reward_commission,
+            } => {
+                writeln!(f, "Configure baker")?;
+                write_opt(f, "Capital (CCD)", capital.as_ref())?;
+                write_opt(f, "Restake earnings", restake_earnings.as_ref())?;
+                write_opt(
+                    f,
+                    "Open for delegation",
+                    open_for_delegation.map(|s| format!("{:?}", s)),
+                )?;
+                if *keys_updated {
+                    writeln!(f, "  New baker keys")?;
+                }
+                write_opt(f, "Metadata URL", metadata_url.as_ref())?;
+                write_opt(
+                    f,
+                    "Transaction fee commission",
+                    transaction_fee_commission.as_ref(),
+                )?;
+                write_opt(
+                    f,
+                    "Baking reward commission",
+                    baking_reward_commission.as_ref(),
+                )?;
+                write_opt(
+                    f,
+                    "Finalization reward commission",
+                    finalization_reward_commission.as_ref(),
+                )
+            }
+            PayloadExplanation::ConfigureDelegation {
+                capital,
+                restake_earnings,
+                delegation_target,
+            } => {
+                writeln!(f, "Configure delegation")?;
+                write_opt(f, "Capital (CCD)", capital.as_ref())?;
+                write_opt(f, "Restake earnings", restake_earnings.as_ref())?;
+                let target = delegation_target.as_ref().map(|target| match target {
+                    DelegationTarget::Passive => "passive delegation".to_string(),
+                    DelegationTarget::Baker { baker_id } => format!("baker {}", baker_id),
+                });
+                write_opt(f, "Target", target)
+            }
+            PayloadExplanation::UpdateCredentialKeys {
+                credential_id,
+                num_keys,
+                threshold,
+            } => writeln!(
+                f,
+                "Update keys of credential {} to {} keys with threshold {}",
+                credential_id,
+                num_keys,
+                u8::from(*threshold)
+            ),
+            PayloadExplanation::UpdateCredentials {
+                added,
+                removed,
+                new_threshold,
+            } => {
+                writeln!(f, "Update credentials")?;
+                for index in added {
+                    writeln!(f, "  Add credential at index {}", index)?;
+                }
+                for credential_id in removed {
+                    writeln!(f, "  Remove credential {}", credential_id)?;
+                }
+                writeln!(f, "  New account threshold: {}", u8::from(*new_threshold))
+            }
+            PayloadExplanation::RegisterData { data } => writeln!(f, "Register data {}", data),
+            PayloadExplanation::Invalid { error } => {
+                writeln!(f, "Invalid payload, the transaction will fail: {}", error)
+            }
+        }
+    }
+}
+
+impl Display for DataExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataExplanation::CborText(text) => write!(f, "{:?}", text),
+            DataExplanation::CborInteger(n) => n.fmt(f),
+            DataExplanation::Text(text) => write!(f, "{:?} (not CBOR encoded)", text),
+            DataExplanation::Raw(hex) => write!(f, "0x{}", hex),
+        }
+    }
+}
+
+impl Display for ParameterExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterExplanation::Decoded { value } => {
+                let value = serde_json::to_string_pretty(value).map_err(|_| fmt::Error)?;
+                writeln!(f, "  Parameter: {}", value)
+            }
+            ParameterExplanation::Raw { parameter, reason } => {
+                writeln!(f, "  Parameter: 0x{} ({})", parameter, reason)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        common::types::{CredentialIndex, KeyIndex, KeyPair},
+        smart_contracts::{OwnedContractName, OwnedParameter},
+        transactions::{send, ExactSizeTransactionSigner, InitContractPayload},
+    };
+    use concordium_contracts_common::schema::{ContractV3, FunctionV2, ModuleV3};
+    use std::collections::BTreeMap;
+
+    fn make_keys() -> BTreeMap<CredentialIndex, BTreeMap<KeyIndex, KeyPair>> {
+        let mut rng = rand::thread_rng();
+        BTreeMap::from([(
+            CredentialIndex::from(0),
+            BTreeMap::from([(KeyIndex::from(0), KeyPair::generate(&mut rng))]),
+        )])
+    }
+
+    fn account_explanation(
+        item: &BlockItem<EncodedPayload>,
+        schema: Option<&VersionedModuleSchema>,
+    ) -> AccountTransactionExplanation {
+        match explain_block_item(item, schema) {
+            BlockItemExplanation::AccountTransaction(at) => at,
+            other => panic!("Expected an account transaction, got {:?}.", other),
+        }
+    }
+
+    fn make_init(
+        keys: &impl ExactSizeTransactionSigner,
+        param: Vec<u8>,
+    ) -> BlockItem<EncodedPayload> {
+        let payload = InitContractPayload {
+            amount:    Amount::from_ccd(3),
+            mod_ref:   ModuleReference::from([1u8; 32]),
+            init_name: OwnedContractName::new_unchecked("init_counter".into()),
+            param:     OwnedParameter::new_unchecked(param),
+        };
+        BlockItem::AccountTransaction(send::init_contract(
+            keys,
+            AccountAddress([2u8; 32]),
+            Nonce::from(1),
+            TransactionTime::from_seconds(100),
+            payload,
+            Energy::from(5000),
+        ))
+    }
+
+    #[test]
+    fn test_explain_transfer() {
+        let keys = make_keys();
+        let tx = send::transfer(
+            &keys,
+            AccountAddress([0u8; 32]),
+            Nonce::from(7),
+            TransactionTime::from_seconds(100),
+            AccountAddress([1u8; 32]),
+            Amount::from_ccd(10),
+        );
+        let size = TRANSACTION_HEADER_SIZE + u64::from(tx.header.payload_size.size);
+        let item = BlockItem::AccountTransaction(tx);
+        let explanation = account_explanation(&item, None);
+        assert_eq!(explanation.hash, item.hash());
+        assert_eq!(explanation.nonce, Nonce::from(7));
+        assert_eq!(explanation.num_signatures, 1);
+        assert!(matches!(
+            explanation.payload,
+            PayloadExplanation::Transfer {
+                to,
+                amount,
+                memo: None,
+            } if to == AccountAddress([1u8; 32]) && amount == Amount::from_ccd(10)
+        ));
+        // The cost of a transfer is fixed, and `send::transfer` sets the energy
+        // limit to exactly that cost.
+        let base_cost = cost::base_cost(size, 1);
+        assert_eq!(explanation.cost.base_cost, base_cost);
+        assert_eq!(
+            explanation.cost.total,
+            Some(base_cost + cost::SIMPLE_TRANSFER)
+        );
+        assert_eq!(explanation.cost.total, Some(explanation.energy_limit));
+        let text = explanation.to_string();
+        assert!(text.contains(&format!("Cost: {} NRG", base_cost + cost::SIMPLE_TRANSFER)));
+        assert!(!text.contains("WARNING"));
+
+        // Explaining the serialized block item gives the same result, and
+        // trailing bytes are rejected.
+        let mut bytes = common::to_bytes(&item);
+        let from_bytes = explain_block_item_bytes(&bytes, None).expect("Block item is valid.");
+        assert_eq!(from_bytes.to_string(), text);
+        bytes.push(0);
+        assert!(explain_block_item_bytes(&bytes, None).is_err());
+        assert!(explain_block_item_bytes(&bytes[..10], None).is_err());
+    }
+
+    #[test]
+    fn test_explain_schedule() {
+        let keys = make_keys();
+        let to = AccountAddress([1u8; 32]);
+        let schedule = vec![
+            (Timestamp::from(1000), Amount::from_ccd(1)),
+            (Timestamp::from(2000), Amount::from_ccd(2)),
+        ];
+        let item = BlockItem::AccountTransaction(send::transfer_with_schedule(
+            &keys,
+            AccountAddress([0u8; 32]),
+            Nonce::from(1),
+            TransactionTime::from_seconds(100),
+            to,
+            schedule.clone(),
+        ));
+        let explanation = account_explanation(&item, None);
+        match &explanation.payload {
+            PayloadExplanation::TransferWithSchedule {
+                total, releases, ..
+            } => {
+                assert_eq!(*total, Some(Amount::from_ccd(3)));
+                assert_eq!(releases, &schedule);
+            }
+            other => panic!("Unexpected payload {:?}.", other),
+        }
+        assert_eq!(
+            explanation
+                .cost
+                .total
+                .map(|c| c - explanation.cost.base_cost),
+            Some(cost::scheduled_transfer(2))
+        );
+        let text = explanation.payload.to_string();
+        assert!(text.starts_with(&format!(
+            "Transfer {} CCD to {} in 2 releases",
+            Amount::from_ccd(3),
+            to
+        )));
+        assert!(text.contains(&format!(
+            "  {} CCD at {}",
+            Amount::from_ccd(2),
+            Timestamp::from(2000)
+        )));
+
+        // A schedule whose total overflows is reported as such, instead of
+        // showing a wrong total.
+        let overflowing = explain_schedule(
+            to,
+            &[
+                (Timestamp::from(1000), Amount::from_micro_ccd(u64::MAX)),
+                (Timestamp::from(2000), Amount::from_micro_ccd(1)),
+            ],
+            None,
+        );
+        assert!(matches!(
+            overflowing,
+            PayloadExplanation::TransferWithSchedule { total: None, .. }
+        ));
+        assert!(overflowing.to_string().contains("overflows"));
+    }
+
+    #[test]
+    fn test_explain_parameter_with_schema() {
+        let keys = make_keys();
+        let schema = VersionedModuleSchema::V3(ModuleV3 {
+            contracts: BTreeMap::from([("counter".to_string(), ContractV3 {
+                init:    Some(FunctionV2 {
+                    parameter:    Some(Type::U32),
+                    return_value: None,
+                    error:        None,
+                }),
+                receive: BTreeMap::new(),
+                event:   None,
+            })]),
+        });
+        let item = make_init(&keys, 17u32.to_le_bytes().to_vec());
+
+        let explanation = account_explanation(&item, Some(&schema));
+        match &explanation.payload {
+            PayloadExplanation::InitContract {
+                contract,
+                parameter: ParameterExplanation::Decoded { value },
+                ..
+            } => {
+                assert_eq!(contract, "counter");
+                assert_eq!(value, &serde_json::json!(17));
+            }
+            other => panic!("Unexpected payload {:?}.", other),
+        }
+        // The cost of executing the contract is not known, so only the base
+        // cost is reported.
+        assert_eq!(explanation.cost.total, None);
+        assert!(explanation.to_string().contains(&format!(
+            "Cost: at least {} NRG, depending on the state of the chain",
+            explanation.cost.base_cost
+        )));
+
+        // Without a schema the parameter is shown in hex.
+        let raw = |item: &BlockItem<EncodedPayload>, schema| match account_explanation(item, schema)
+            .payload
+        {
+            PayloadExplanation::InitContract {
+                parameter: ParameterExplanation::Raw { parameter, .. },
+                ..
+            } => parameter,
+            other => panic!("Unexpected payload {:?}.", other),
+        };
+        assert_eq!(raw(&item, None), "11000000");
+        // As is a parameter that does not match the schema.
+        let item = make_init(&keys, vec![1, 2, 3, 4, 5]);
+        assert_eq!(raw(&item, Some(&schema)), "0102030405");
+    }
+
+    #[test]
+    fn test_explain_data() {
+        // CBOR text string "hello".
+        assert_eq!(
+            explain_data(&[0x65, b'h', b'e', b'l', b'l', b'o']),
+            DataExplanation::CborText("hello".into())
+        );
+        // CBOR unsigned integer 1000 and negative integer -500.
+        assert_eq!(
+            explain_data(&[0x19, 0x03, 0xe8]),
+            DataExplanation::CborInteger(1000)
+        );
+        assert_eq!(
+            explain_data(&[0x39, 0x01, 0xf3]),
+            DataExplanation::CborInteger(-500)
+        );
+        assert_eq!(
+            explain_data(b"hello"),
+            DataExplanation::Text("hello".into())
+        );
+        assert_eq!(
+            explain_data(&[0xff, 0x00]),
+            DataExplanation::Raw("ff00".into())
+        );
+        // Trailing bytes after a CBOR item.
+        assert_eq!(
+            explain_data(&[0x01, 0x02]),
+            DataExplanation::Raw("0102".into())
+        );
+    }
+}
@@ -1,11 +1,21 @@
 #![doc = include_str!("../README.md")]
+pub mod aliases;
 pub mod base;
+pub mod chain_parameters;
 pub mod cis2_types;
 pub mod cis3_types;
 pub mod cis4_types;
 pub mod constants;
+#[cfg(feature = "dry-run")]
+pub mod dry_run;
+pub mod explain;
 pub mod hashes;
 mod internal;
+#[cfg(feature = "pkcs11")]
+pub mod pkcs11;
+#[cfg(all(feature = "remote-signer", unix))]
+pub mod remote_signer;
+pub mod rewards;
 pub mod smart_contracts;
 pub mod transactions;
 pub mod updates;
@@ -0,0 +1,577 @@
+//! Signers whose Ed25519 keys are held by a PKCS#11 token, such as a hardware
+//! security module. The secret keys never leave the token; only the messages
+//! to sign are sent to it.
+//!
+//! A [`Pkcs11Token`] is a logged-in session with a token. The keys on the token
+//! are found by their labels:
+//! - [`Pkcs11AccountSigner`] signs account transactions. Its keys are labelled
+//!   `<name>:<credential index>:<key index>`.
+//! - [`Pkcs11UpdateSigner`] signs update instructions. Its keys are labelled
+//!   `<name>:<update key index>`.
+//! - [`Pkcs11BakerSigner`] signs with a baker's signature key, which is
+//!   labelled `<name>`.
+//!
+//! For each private key there must be a public key on the token with the same
+//! label.
+//!
+//! The account and update signers implement [`TransactionSigner`] and
+//! [`UpdateSigner`], so they can be used everywhere keys in memory can, e.g.,
+//! with
+//! [`PreAccountTransaction::sign`](crate::transactions::construct::PreAccountTransaction::sign).
+//! Those traits cannot report errors, so the implementations panic if the
+//! token fails to sign. Use the `try_sign_*` methods to handle such errors.
+//!
+//! # Testing with SoftHSM
+//!
+//! The tests use [SoftHSM](https://github.com/opendnssec/SoftHSMv2) and are
+//! ignored by default. To run them, create a token and point the tests to
+//! the SoftHSM module
+//!
+//! ```shell
+//! softhsm2-util --init-token --free --label concordium-test --so-pin 1234 --pin 1234
+//! PKCS11_MODULE=/usr/lib/softhsm/libsofthsm2.so cargo test --features pkcs11 -- --ignored pkcs11
+//! ```
+use crate::{
+    base::{BakerSignatureVerifyKey, UpdateKeysIndex, UpdatePublicKey},
+    common::types::{CredentialIndex, KeyIndex, Signature, TransactionSignature},
+    hashes,
+    id::types::VerifyKey,
+    transactions::{ExactSizeTransactionSigner, TransactionSigner},
+    updates::{UpdateInstructionSignature, UpdateSigner},
+};
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    mechanism::Mechanism,
+    object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+    types::AuthPin,
+};
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+use thiserror::Error;
+
+/// An error communicating with a PKCS#11 token, or finding the keys on it.
+#[derive(Debug, Error)]
+pub enum Pkcs11Error {
+    #[error("The PKCS#11 module failed: {0}")]
+    Module(#[from] cryptoki::error::Error),
+    #[error("No token with label {0} was found.")]
+    TokenNotFound(String),
+    #[error("No Ed25519 key with label {0} was found.")]
+    KeyNotFound(String),
+    #[error("The public key with label {0} is not an Ed25519 key.")]
+    InvalidPublicKey(String),
+    #[error("The token produced an invalid signature.")]
+    InvalidSignature,
+}
+
+/// A logged-in session with a PKCS#11 token.
+pub struct Pkcs11Token {
+    /// Sessions can only be used from one thread at a time.
+    session:  Mutex<Session>,
+    // The context is kept alive for as long as the session is.
+    _context: Pkcs11,
+}
+
+impl std::fmt::Debug for Pkcs11Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pkcs11Token").finish_non_exhaustive()
+    }
+}
+
+/// An Ed25519 key pair on a token.
+#[derive(Debug, Clone)]
+pub struct Pkcs11Key {
+    /// The label of the key pair.
+    pub label:  String,
+    private:    ObjectHandle,
+    /// The public key.
+    pub public: ed25519_dalek::VerifyingKey,
+}
+
+/// The DER encoding of the object identifier of Ed25519, which is how PKCS#11
+/// identifies the curve of a key.
+const ED25519_OID: [u8; 5] = [0x06, 0x03, 0x2b, 0x65, 0x70];
+
+/// Extract the key from a public key point. Tokens return the point either
+/// as the raw key or DER encoded as an octet string.
+fn parse_ec_point(point: &[u8]) -> Option<ed25519_dalek::VerifyingKey> {
+    let bytes = match point {
+        [0x04, 32, rest @ ..] => rest,
+        _ => point,
+    };
+    ed25519_dalek::VerifyingKey::from_bytes(bytes.try_into().ok()?).ok()
+}
+
+impl Pkcs11Token {
+    /// Load the PKCS#11 module at the given path, and log in to the token with
+    /// the given label as a normal user.
+    pub fn open(
+        module: impl AsRef<Path>,
+        token_label: &str,
+        pin: &str,
+    ) -> Result<Self, Pkcs11Error> {
+        let context = Pkcs11::new(module)?;
+        context.initialize(CInitializeArgs::OsThreads)?;
+        let mut slot = None;
+        for candidate in context.get_slots_with_token()? {
+            if context.get_token_info(candidate)?.label().trim_end() == token_label {
+                slot = Some(candidate);
+                break;
+            }
+        }
+        let slot = slot.ok_or_else(|| Pkcs11Error::TokenNotFound(token_label.into()))?;
+        let session = context.open_ro_session(slot)?;
+        session.login(UserType::User, Some(&AuthPin::new(pin.into())))?;
+        Ok(Self {
+            session:  Mutex::new(session),
+            _context: context,
+        })
+    }
+
+    fn session(&self) -> std::sync::MutexGuard<'_, Session> {
+        // A panic while holding the lock does not leave the session in an
+        // inconsistent state, so the lock can be recovered.
+        self.session.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The labels of all the Ed25519 private keys on the token.
+    pub fn labels(&self) -> Result<Vec<String>, Pkcs11Error> {
+        let session = self.session();
+        let handles = session.find_objects(&[
+            Attribute::Class(ObjectClass::PRIVATE_KEY),
+            Attribute::KeyType(KeyType::EC_EDWARDS),
+        ])?;
+        let mut labels = Vec::with_capacity(handles.len());
+        for handle in handles {
+            for attribute in session.get_attributes(handle, &[AttributeType::Label])? {
+                if let Attribute::Label(label) = attribute {
+                    labels.push(String::from_utf8_lossy(&label).into_owned());
+                }
+            }
+        }
+        Ok(labels)
+    }
+
+    /// Find the Ed25519 key pair with the given label.
+    pub fn find_key(&self, label: &str) -> Result<Pkcs11Key, Pkcs11Error> {
+        let session = self.session();
+        let find = |class| {
+            session
+                .find_objects(&[
+                    Attribute::Class(class),
+                    Attribute::KeyType(KeyType::EC_EDWARDS),
+                    Attribute::Label(label.as_bytes().to_vec()),
+                ])
+                .map(|handles| handles.first().copied())
+        };
+        let not_found = || Pkcs11Error::KeyNotFound(label.into());
+        let private = find(ObjectClass::PRIVATE_KEY)?.ok_or_else(not_found)?;
+        let public_handle = find(ObjectClass::PUBLIC_KEY)?.ok_or_else(not_found)?;
+        let attributes = session.get_attributes(public_handle, &[
+            AttributeType::EcParams,
+            AttributeType::EcPoint,
+        ])?;
+        let mut params = None;
+        let mut point = None;
+        for attribute in attributes {
+            match attribute {
+                Attribute::EcParams(value) => params = Some(value),
+                Attribute::EcPoint(value) => point = Some(value),
+                _ => (),
+            }
+        }
+        let invalid = || Pkcs11Error::InvalidPublicKey(label.into());
+        // Some tokens identify the curve by name instead of by OID.
+        if params.is_some_and(|params| {
+            params != ED25519_OID && !params.windows(7).any(|w| w == b"edwards")
+        }) {
+            return Err(invalid());
+        }
+        let public = point
+            .as_deref()
+            .and_then(parse_ec_point)
+            .ok_or_else(invalid)?;
+        Ok(Pkcs11Key {
+            label: label.into(),
+            private,
+            public,
+        })
+    }
+
+    /// Sign the message with the key. The signature is checked against the
+    /// public key, so that a faulty token cannot produce invalid signatures.
+    pub fn sign(
+        &self,
+        key: &Pkcs11Key,
+        message: &[u8],
+    ) -> Result<ed25519_dalek::Signature, Pkcs11Error> {
+        let bytes = self
+            .session()
+            .sign(&Mechanism::Eddsa, key.private, message)?;
+        let bytes: [u8; 64] = bytes
+            .try_into()
+            .map_err(|_| Pkcs11Error::InvalidSignature)?;
+        let signature = ed25519_dalek::Signature::from_bytes(&bytes);
+        key.public
+            .verify_strict(message, &signature)
+            .map_err(|_| Pkcs11Error::InvalidSignature)?;
+        Ok(signature)
+    }
+
+    /// Find the keys whose labels start with `<name>:`, and parse the rest of
+    /// the label with `index`.
+    fn find_keys<I: Ord>(
+        &self,
+        name: &str,
+        index: impl Fn(&str) -> Option<I>,
+    ) -> Result<BTreeMap<I, Pkcs11Key>, Pkcs11Error> {
+        let mut keys = BTreeMap::new();
+        for label in self.labels()? {
+            if let Some(i) = label_index(&label, name, &index) {
+                keys.insert(i, self.find_key(&label)?);
+            }
+        }
+        Ok(keys)
+    }
+}
+
+/// If the label is `<name>:<rest>`, parse `<rest>` with `index`.
+fn label_index<I>(label: &str, name: &str, index: impl Fn(&str) -> Option<I>) -> Option<I> {
+    label.strip_prefix(name)?.strip_prefix(':').and_then(index)
+}
+
+/// Parse `<credential index>:<key index>`.
+fn parse_account_key_index(rest: &str) -> Option<(CredentialIndex, KeyIndex)> {
+    let (ci, ki) = rest.split_once(':')?;
+    Some((
+        CredentialIndex::from(ci.parse::<u8>().ok()?),
+        KeyIndex::from(ki.parse::<u8>().ok()?),
+    ))
+}
+
+/// Parse `<update key index>`.
+fn parse_update_key_index(rest: &str) -> Option<UpdateKeysIndex> {
+    Some(UpdateKeysIndex {
+        index: rest.parse().ok()?,
+    })
+}
+
+/// Signs account transactions with keys on a PKCS#11 token.
+#[derive(Debug, Clone)]
+pub struct Pkcs11AccountSigner {
+    token: Arc<Pkcs11Token>,
+    keys:  BTreeMap<CredentialIndex, BTreeMap<KeyIndex, Pkcs11Key>>,
+}
+
+impl Pkcs11AccountSigner {
+    /// Use the keys with the given labels.
+    pub fn new(
+        token: Arc<Pkcs11Token>,
+        labels: &BTreeMap<CredentialIndex, BTreeMap<KeyIndex, String>>,
+    ) -> Result<Self, Pkcs11Error> {
+        let mut keys = BTreeMap::new();
+        for (ci, cred_labels) in labels {
+            let mut cred_keys = BTreeMap::new();
+            for (ki, label) in cred_labels {
+                cred_keys.insert(*ki, token.find_key(label)?);
+            }
+            keys.insert(*ci, cred_keys);
+        }
+        Ok(Self { token, keys })
+    }
+
+    /// Use all the keys labelled `<name>:<credential index>:<key index>`.
+    pub fn discover(token: Arc<Pkcs11Token>, name: &str) -> Result<Self, Pkcs11Error> {
+        let found = token.find_keys(name, parse_account_key_index)?;
+        let mut keys = BTreeMap::<CredentialIndex, BTreeMap<KeyIndex, Pkcs11Key>>::new();
+        for ((ci, ki), key) in found {
+            keys.entry(ci).or_default().insert(ki, key);
+        }
+        Ok(Self { token, keys })
+    }
+
+    /// The public keys of the signer, indexed like the signatures it produces.
+    pub fn public_keys(&self) -> BTreeMap<CredentialIndex, BTreeMap<KeyIndex, VerifyKey>> {
+        self.keys
+            .iter()
+            .map(|(ci, cred_keys)| {
+                let cred_keys = cred_keys
+                    .iter()
+                    .map(|(ki, key)| (*ki, VerifyKey::from(key.public)))
+                    .collect();
+                (*ci, cred_keys)
+            })
+            .collect()
+    }
+
+    /// Sign the transaction hash with all the keys.
+    pub fn try_sign_transaction_hash(
+        &self,
+        hash_to_sign: &hashes::TransactionSignHash,
+    ) -> Result<TransactionSignature, Pkcs11Error> {
+        let mut signatures = BTreeMap::<CredentialIndex, BTreeMap<KeyIndex, _>>::new();
+        for (ci, cred_keys) in &self.keys {
+            let mut cred_sigs = BTreeMap::new();
+            for (ki, key) in cred_keys {
+                let signature = self.token.sign(key, hash_to_sign.as_ref())?;
+                cred_sigs.insert(*ki, Signature::from(signature));
+            }
+            signatures.insert(*ci, cred_sigs);
+        }
+        Ok(TransactionSignature { signatures })
+    }
+}
+
+/// This panics if the token fails to sign.
+impl TransactionSigner for Pkcs11AccountSigner {
+    fn sign_transaction_hash(
+        &self,
+        hash_to_sign: &hashes::TransactionSignHash,
+    ) -> TransactionSignature {
+        self.try_sign_transaction_hash(hash_to_sign)
+            .expect("The PKCS#11 token failed to sign the transaction.")
+    }
+}
+
+impl ExactSizeTransactionSigner for Pkcs11AccountSigner {
+    fn num_keys(&self) -> u32 { self.keys.values().map(|v| v.len() as u32).sum::<u32>() }
+}
+
+/// Signs update instructions with keys on a PKCS#11 token.
+#[derive(Debug, Clone)]
+pub struct Pkcs11UpdateSigner {
+    token: Arc<Pkcs11Token>,
+    keys:  BTreeMap<UpdateKeysIndex, Pkcs11Key>,
+}
+
+impl Pkcs11UpdateSigner {
+    /// Use the keys with the given labels.
+    pub fn new(
+        token: Arc<Pkcs11Token>,
+        labels: &BTreeMap<UpdateKeysIndex, String>,
+    ) -> Result<Self, Pkcs11Error> {
+        let keys = labels
+            .iter()
+            .map(|(i, label)| Ok((*i, token.find_key(label)?)))
+            .collect::<Result<_, Pkcs11Error>>()?;
+        Ok(Self { token, keys })
+    }
+
+    /// Use all the keys labelled `<name>:<update key index>`.
+    pub fn discover(token: Arc<Pkcs11Token>, name: &str) -> Result<Self, Pkcs11Error> {
+        let keys = token.find_keys(name, parse_update_key_index)?;
+        Ok(Self { token, keys })
+    }
+
+    /// The public keys of the signer, indexed like the signatures it produces.
+    pub fn public_keys(&self) -> BTreeMap<UpdateKeysIndex, UpdatePublicKey> {
+        self.keys
+            .iter()
+            .map(|(i, key)| {
+                (*i, UpdatePublicKey {
+                    public: key.public.into(),
+                })
+            })
+            .collect()
+    }
+
+    /// Sign the update hash with all the keys.
+    pub fn try_sign_update_hash(
+        &self,
+        hash_to_sign: &hashes::UpdateSignHash,
+    ) -> Result<UpdateInstructionSignature, Pkcs11Error> {
+        let mut signatures = BTreeMap::new();
+        for (i, key) in &self.keys {
+            let signature = self.token.sign(key, hash_to_sign.as_ref())?;
+            signatures.insert(*i, Signature::from(signature));
+        }
+        Ok(UpdateInstructionSignature { signatures })
+    }
+}
+
+/// This panics if the token fails to sign.
+impl UpdateSigner for &Pkcs11UpdateSigner {
+    fn sign_update_hash(
+        &self,
+        hash_to_sign: &hashes::UpdateSignHash,
+    ) -> UpdateInstructionSignature {
+        self.try_sign_update_hash(hash_to_sign)
+            .expect("The PKCS#11 token failed to sign the update.")
+    }
+}
+
+/// Signs with the signature key of a baker that is held by a PKCS#11 token.
+#[derive(Debug, Clone)]
+pub struct Pkcs11BakerSigner {
+    token: Arc<Pkcs11Token>,
+    key:   Pkcs11Key,
+}
+
+impl Pkcs11BakerSigner {
+    /// Use the key with the given label.
+    pub fn new(token: Arc<Pkcs11Token>, label: &str) -> Result<Self, Pkcs11Error> {
+        let key = token.find_key(label)?;
+        Ok(Self { token, key })
+    }
+
+    /// The public key that verifies the signatures.
+    pub fn signature_verify_key(&self) -> BakerSignatureVerifyKey {
+        BakerSignatureVerifyKey {
+            verify_key: self.key.public,
+        }
+    }
+
+    /// Sign the message, such as a block, with the key.
+    pub fn sign_baker_message(
+        &self,
+        message: &[u8],
+    ) -> Result<ed25519_dalek::Signature, Pkcs11Error> {
+        self.token.sign(&self.key, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        base::{AccountThreshold, Nonce},
+        common::types::TransactionTime,
+        id::types::{AccountAddress, CredentialPublicKeys, SignatureThreshold},
+        transactions::{construct, AccountAccessStructure},
+    };
+    use concordium_contracts_common::Amount;
+
+    const TOKEN_LABEL: &str = "concordium-test";
+    const PIN: &str = "1234";
+
+    /// Open the SoftHSM test token, and generate session key pairs with the
+    /// given labels on it. Session keys are removed when the session is
+    /// closed.
+    fn test_token(labels: &[&str]) -> Arc<Pkcs11Token> {
+        let module = std::env::var("PKCS11_MODULE")
+            .unwrap_or_else(|_| "/usr/lib/softhsm/libsofthsm2.so".into());
+        let token = Pkcs11Token::open(module, TOKEN_LABEL, PIN).expect("The test token exists.");
+        for label in labels {
+            let label = Attribute::Label(label.as_bytes().to_vec());
+            token
+                .session()
+                .generate_key_pair(
+                    &Mechanism::EccEdwardsKeyPairGen,
+                    &[
+                        Attribute::Token(false),
+                        Attribute::Verify(true),
+                        Attribute::EcParams(ED25519_OID.to_vec()),
+                        label.clone(),
+                    ],
+                    &[
+                        Attribute::Token(false),
+                        Attribute::Sign(true),
+                        Attribute::Sensitive(true),
+                        label,
+                    ],
+                )
+                .expect("Generating a key pair succeeds.");
+        }
+        Arc::new(token)
+    }
+
+    #[test]
+    fn test_parse_ec_point() {
+        let mut rng = rand::thread_rng();
+        let public = ed25519_dalek::SigningKey::generate(&mut rng).verifying_key();
+        let raw = public.to_bytes();
+        assert_eq!(parse_ec_point(&raw), Some(public));
+        // DER encoded as an octet string.
+        let der = [&[0x04, 32][..], &raw].concat();
+        assert_eq!(parse_ec_point(&der), Some(public));
+        // Wrong lengths, including an octet string with trailing bytes.
+        assert_eq!(parse_ec_point(&raw[..31]), None);
+        assert_eq!(parse_ec_point(&[der.as_slice(), &[0]].concat()), None);
+        assert_eq!(parse_ec_point(&[]), None);
+    }
+
+    #[test]
+    fn test_label_index() {
+        let account = |label| label_index(label, "alice", parse_account_key_index);
+        assert_eq!(
+            account("alice:1:2"),
+            Some((CredentialIndex::from(1), KeyIndex::from(2)))
+        );
+        assert_eq!(
+            account("alice:0:255"),
+            Some((CredentialIndex::from(0), KeyIndex::from(255)))
+        );
+        // Other names, including names that share a prefix.
+        assert_eq!(account("bob:1:2"), None);
+        assert_eq!(account("alice2:1:2"), None);
+        assert_eq!(account("alice"), None);
+        // Malformed indices.
+        assert_eq!(account("alice:1"), None);
+        assert_eq!(account("alice:1:2:3"), None);
+        assert_eq!(account("alice:256:0"), None);
+        assert_eq!(account("alice:a:0"), None);
+
+        let update = |label| label_index(label, "gov", parse_update_key_index);
+        assert_eq!(update("gov:3"), Some(UpdateKeysIndex { index: 3 }));
+        assert_eq!(update("gov:70000"), None);
+        assert_eq!(update("gov:"), None);
+        assert_eq!(update("gov:0:0"), None);
+        assert_eq!(update("government:1"), None);
+    }
+
+    #[test]
+    #[ignore = "This needs a SoftHSM token. See the module documentation."]
+    fn test_pkcs11_signers() {
+        let token = test_token(&["alice:0:0", "alice:0:1", "alice:1:0", "gov:3", "baker"]);
+
+        let signer = Pkcs11AccountSigner::discover(token.clone(), "alice")
+            .expect("Discovering the keys succeeds.");
+        assert_eq!(signer.num_keys(), 3);
+        let public_keys = signer.public_keys();
+        assert_eq!(public_keys.len(), 2);
+        let tx = construct::transfer(
+            signer.num_keys(),
+            AccountAddress([1u8; 32]),
+            Nonce::from(1),
+            TransactionTime::from_seconds(1000),
+            AccountAddress([2u8; 32]),
+            Amount::from_micro_ccd(10),
+        )
+        .sign(&signer);
+        let access_structure = AccountAccessStructure {
+            keys:      public_keys
+                .into_iter()
+                .map(|(ci, keys)| {
+                    let threshold =
+                        SignatureThreshold::try_from(keys.len() as u8).expect("There are keys.");
+                    (ci, CredentialPublicKeys { keys, threshold })
+                })
+                .collect(),
+            threshold: AccountThreshold::try_from(2).expect("2 is a valid threshold."),
+        };
+        assert!(tx.verify_transaction_signature(&access_structure));
+
+        let signer = Pkcs11UpdateSigner::discover(token.clone(), "gov")
+            .expect("Discovering the keys succeeds.");
+        let public_key = signer.public_keys()[&UpdateKeysIndex { index: 3 }].clone();
+        let hash = hashes::UpdateSignHash::from([7u8; 32]);
+        let signatures = (&signer).sign_update_hash(&hash);
+        assert!(public_key
+            .public
+            .verify(hash, &signatures.signatures[&UpdateKeysIndex { index: 3 }]));
+
+        let signer = Pkcs11BakerSigner::new(token, "baker").expect("The key exists.");
+        let signature = signer
+            .sign_baker_message(b"block")
+            .expect("Signing succeeds.");
+        assert!(signer
+            .signature_verify_key()
+            .verify_key
+            .verify_strict(b"block", &signature)
+            .is_ok());
+    }
+}
@@ -0,0 +1,672 @@
+//! Signing of transactions and update instructions by a separate signer
+//! process that is reached over a Unix socket.
+//!
+//! The client side is [`RemoteSigner`], which implements
+//! [`AsyncTransactionSigner`] and [`AsyncUpdateSigner`]. The signer process
+//! runs a [`SigningService`], which decodes each request, checks it against
+//! a [`SigningPolicy`], and only signs it if the policy allows it. The keys
+//! never leave the signer process.
+//!
+//! # Protocol
+//!
+//! Each message is a 4-byte big-endian length followed by that many bytes of
+//! the message in the binary serialization format. The client sends a
+//! [`SignRequest`] and the service replies with a [`SignResponse`]. A
+//! connection can be used for any number of requests.
+use crate::{
+    base::Energy,
+    common::{
+        to_bytes, types::TransactionSignature, Buffer, Deserial, Get, ParseResult, ReadBytesExt,
+        Serial,
+    },
+    constants::MAX_PAYLOAD_SIZE,
+    id::types::AccountAddress,
+    transactions::{
+        compute_transaction_sign_hash, get_encoded_payload, AsyncTransactionSigner, EncodedPayload,
+        Payload, TransactionHeader, TransactionSigner, TransactionType,
+    },
+    updates::{
+        update::PartiallySignedUpdate, AsyncUpdateSigner, UpdateHeader, UpdateInstructionSignature,
+        UpdatePayload, UpdateSigner, UpdateType,
+    },
+};
+use concordium_contracts_common::Amount;
+use std::{collections::BTreeSet, path::PathBuf, sync::Arc};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{UnixListener, UnixStream},
+};
+
+/// The maximum size of a message in bytes. This is large enough for a
+/// request with a payload of the maximum size.
+pub const MAX_MESSAGE_SIZE: u32 = MAX_PAYLOAD_SIZE + 1024;
+
+/// A request to sign a transaction or an update instruction.
+#[derive(Debug, Clone)]
+pub enum SignRequest {
+    Transaction {
+        header:  TransactionHeader,
+        payload: EncodedPayload,
+    },
+    Update {
+        header:  UpdateHeader,
+        payload: UpdatePayload,
+    },
+}
+
+impl Serial for SignRequest {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        match self {
+            SignRequest::Transaction { header, payload } => {
+                0u8.serial(out);
+                header.serial(out);
+                payload.serial(out);
+            }
+            SignRequest::Update { header, payload } => {
+                1u8.serial(out);
+                header.serial(out);
+                payload.serial(out);
+            }
+        }
+    }
+}
+
+impl Deserial for SignRequest {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        match u8::deserial(source)? {
+            0u8 => {
+                let header: TransactionHeader = source.get()?;
+                let payload = get_encoded_payload(source, header.payload_size)?;
+                Ok(SignRequest::Transaction { header, payload })
+            }
+            1u8 => Ok(SignRequest::Update {
+                header:  source.get()?,
+                payload: source.get()?,
+            }),
+            tag => anyhow::bail!("Unknown request tag {}.", tag),
+        }
+    }
+}
+
+/// The response of the signer to a [`SignRequest`].
+#[derive(Debug, Clone)]
+pub enum SignResponse {
+    TransactionSignature(TransactionSignature),
+    UpdateSignature(UpdateInstructionSignature),
+    /// The signer refused to sign, for the given reason.
+    Rejected(String),
+}
+
+impl Serial for SignResponse {
+    fn serial<B: Buffer>(&self, out: &mut B) {
+        match self {
+            SignResponse::TransactionSignature(signature) => {
+                0u8.serial(out);
+                signature.serial(out);
+            }
+            SignResponse::UpdateSignature(signature) => {
+                1u8.serial(out);
+                signature.serial(out);
+            }
+            SignResponse::Rejected(reason) => {
+                2u8.serial(out);
+                reason.serial(out);
+            }
+        }
+    }
+}
+
+impl Deserial for SignResponse {
+    fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+        match u8::deserial(source)? {
+            0u8 => Ok(SignResponse::TransactionSignature(source.get()?)),
+            1u8 => Ok(SignResponse::UpdateSignature(source.get()?)),
+            2u8 => Ok(SignResponse::Rejected(source.get()?)),
+            tag => anyhow::bail!("Unknown response tag {}.", tag),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+/// An error in the communication with the signer process.
+pub enum RemoteSignerError {
+    #[error("Communication with the signer failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Received a malformed message: {0}")]
+    Malformed(#[source] anyhow::Error),
+    #[error("The message of {size}B exceeds the maximum size of {max}B.")]
+    MessageTooLarge { size: usize, max: u32 },
+    #[error("The signer refused to sign: {0}")]
+    Rejected(String),
+    #[error("The signer responded with a signature of the wrong kind.")]
+    UnexpectedResponse,
+}
+
+/// Write a length-prefixed message.
+async fn write_message(
+    stream: &mut (impl AsyncWrite + Unpin),
+    message: &impl Serial,
+) -> Result<(), RemoteSignerError> {
+    let bytes = to_bytes(message);
+    let len = u32::try_from(bytes.len())
+        .ok()
+        .filter(|len| *len <= MAX_MESSAGE_SIZE)
+        .ok_or(RemoteSignerError::MessageTooLarge {
+            size: bytes.len(),
+            max:  MAX_MESSAGE_SIZE,
+        })?;
+    stream.write_all(&len.to_be_bytes()).await?;
+    stream.write_all(&bytes).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+/// Read a length-prefixed message. This returns `None` if the stream is closed
+/// before the start of the message.
+async fn read_message<T: Deserial>(
+    stream: &mut (impl AsyncRead + Unpin),
+) -> Result<Option<T>, RemoteSignerError> {
+    let mut len = [0u8; 4];
+    match stream.read_exact(&mut len).await {
+        Ok(_) => (),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let len = u32::from_be_bytes(len);
+    if len > MAX_MESSAGE_SIZE {
+        return Err(RemoteSignerError::MessageTooLarge {
+            size: len as usize,
+            max:  MAX_MESSAGE_SIZE,
+        });
+    }
+    let mut bytes = vec![0u8; len as usize];
+    stream.read_exact(&mut bytes).await?;
+    let mut source = std::io::Cursor::new(&bytes);
+    let message = source.get().map_err(RemoteSignerError::Malformed)?;
+    if source.position() != u64::from(len) {
+        return Err(RemoteSignerError::Malformed(anyhow::anyhow!(
+            "The message has {} bytes of trailing data.",
+            u64::from(len) - source.position()
+        )));
+    }
+    Ok(Some(message))
+}
+
+/// A signer that forwards requests to a signer process listening on a Unix
+/// socket. A new connection is made for each request.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    path: PathBuf,
+}
+
+impl RemoteSigner {
+    /// A signer that connects to the socket at the given path.
+    pub fn new(path: impl Into<PathBuf>) -> Self { Self { path: path.into() } }
+
+    async fn request(&self, request: &SignRequest) -> Result<SignResponse, RemoteSignerError> {
+        let mut stream = UnixStream::connect(&self.path).await?;
+        write_message(&mut stream, request).await?;
+        read_message(&mut stream)
+            .await?
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into())
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncTransactionSigner for RemoteSigner {
+    type Error = RemoteSignerError;
+
+    async fn sign_transaction(
+        &self,
+        header: &TransactionHeader,
+        payload: &EncodedPayload,
+    ) -> Result<TransactionSignature, Self::Error> {
+        let request = SignRequest::Transaction {
+            header:  header.clone(),
+            payload: payload.clone(),
+        };
+        match self.request(&request).await? {
+            SignResponse::TransactionSignature(signature) => Ok(signature),
+            SignResponse::Rejected(reason) => Err(RemoteSignerError::Rejected(reason)),
+            SignResponse::UpdateSignature(_) => Err(RemoteSignerError::UnexpectedResponse),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncUpdateSigner for RemoteSigner {
+    type Error = RemoteSignerError;
+
+    async fn sign_update(
+        &self,
+        header: &UpdateHeader,
+        payload: &UpdatePayload,
+    ) -> Result<UpdateInstructionSignature, Self::Error> {
+        let request = SignRequest::Update {
+            header:  *header,
+            payload: payload.clone(),
+        };
+        match self.request(&request).await? {
+            SignResponse::UpdateSignature(signature) => Ok(signature),
+            SignResponse::Rejected(reason) => Err(RemoteSignerError::Rejected(reason)),
+            SignResponse::TransactionSignature(_) => Err(RemoteSignerError::UnexpectedResponse),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+/// The reason a [`SigningPolicy`] refuses to sign a request.
+pub enum PolicyViolation {
+    #[error("The payload could not be decoded: {0}")]
+    MalformedPayload(String),
+    #[error("Transactions of type {0} may not be signed.")]
+    TransactionTypeNotAllowed(TransactionType),
+    #[error("Updates of type {0:?} may not be signed.")]
+    UpdateTypeNotAllowed(UpdateType),
+    #[error("Transactions sent from {0} may not be signed.")]
+    SenderNotAllowed(AccountAddress),
+    #[error("Transfers to {0} may not be signed.")]
+    ReceiverNotAllowed(AccountAddress),
+    #[error("The receiver of the transaction cannot be checked, so it may not be signed.")]
+    ReceiverNotChecked,
+    #[error("The amount of the transaction cannot be checked, so it may not be signed.")]
+    AmountNotChecked,
+    #[error("The transaction allows {energy} energy, but at most {max} is allowed.")]
+    EnergyTooHigh { energy: Energy, max: Energy },
+    #[error("The transaction sends {amount} CCD, but at most {max} CCD is allowed.")]
+    AmountTooHigh { amount: Amount, max: Amount },
+    #[error("{0}")]
+    Other(String),
+}
+
+/// A policy that decides which requests the signer process signs.
+pub trait SigningPolicy: Send + Sync {
+    /// Check whether the transaction with the given header and decoded
+    /// payload may be signed.
+    fn check_transaction(
+        &self,
+        header: &TransactionHeader,
+        payload: &Payload,
+    ) -> Result<(), PolicyViolation>;
+
+    /// Check whether the update with the given header and payload may be
+    /// signed.
+    fn check_update(
+        &self,
+        header: &UpdateHeader,
+        payload: &UpdatePayload,
+    ) -> Result<(), PolicyViolation>;
+}
+
+/// A policy that restricts the kinds of transactions and updates that are
+/// signed. A restriction that is `None` allows everything, so the [`Default`]
+/// policy signs every request.
+#[derive(Debug, Clone, Default)]
+pub struct BasicPolicy {
+    /// The types of transactions that may be signed.
+    pub allowed_transaction_types: Option<BTreeSet<TransactionType>>,
+    /// The types of updates that may be signed.
+    pub allowed_update_types:      Option<BTreeSet<UpdateType>>,
+    /// The accounts that transactions may be sent from.
+    pub allowed_senders:           Option<BTreeSet<AccountAddress>>,
+    /// The accounts that CCD may be transferred to. If this is set, then
+    /// transactions that send CCD to a smart contract are not signed, since
+    /// the contract can transfer the CCD to any account.
+    pub allowed_receivers:         Option<BTreeSet<AccountAddress>>,
+    /// The maximum energy a transaction may allow.
+    pub max_energy:                Option<Energy>,
+    /// The maximum amount a transaction may send, either to an account or a
+    /// smart contract. If this is set, then encrypted transfers are not
+    /// signed, since their amount is not known.
+    pub max_amount:                Option<Amount>,
+}
+
+/// The receiver and amount of a transaction that sends CCD to some other
+/// party. A field is `None` if it cannot be determined from the payload.
+struct Transferred {
+    receiver: Option<AccountAddress>,
+    amount:   Option<Amount>,
+}
+
+/// What the transaction sends to some other party, if anything. Transactions
+/// that only move CCD within the sender account are not included.
+fn transferred(payload: &Payload) -> Option<Transferred> {
+    let total = |schedule: &[(_, Amount)]| {
+        schedule
+            .iter()
+            .try_fold(Amount::zero(), |acc, (_, amount)| acc.checked_add(*amount))
+    };
+    let (receiver, amount) = match payload {
+        Payload::Transfer { to_address, amount }
+        | Payload::TransferWithMemo {
+            to_address, amount, ..
+        } => (Some(*to_address), Some(*amount)),
+        Payload::TransferWithSchedule { to, schedule }
+        | Payload::TransferWithScheduleAndMemo { to, schedule, .. } => (Some(*to), total(schedule)),
+        Payload::EncryptedAmountTransfer { to, .. }
+        | Payload::EncryptedAmountTransferWithMemo { to, .. } => (Some(*to), None),
+        Payload::InitContract { payload } => (None, Some(payload.amount)),
+        Payload::Update { payload } => (None, Some(payload.amount)),
+        _ => return None,
+    };
+    Some(Transferred { receiver, amount })
+}
+
+impl SigningPolicy for BasicPolicy {
+    fn check_transaction(
+        &self,
+        header: &TransactionHeader,
+        payload: &Payload,
+    ) -> Result<(), PolicyViolation> {
+        let transaction_type = payload.transaction_type();
+        if let Some(allowed) = &self.allowed_transaction_types {
+            if !allowed.contains(&transaction_type) {
+                return Err(PolicyViolation::TransactionTypeNotAllowed(transaction_type));
+            }
+        }
+        if let Some(allowed) = &self.allowed_senders {
+            if !allowed.contains(&header.sender) {
+                return Err(PolicyViolation::SenderNotAllowed(header.sender));
+            }
+        }
+        if let Some(max) = self.max_energy {
+            if header.energy_amount > max {
+                return Err(PolicyViolation::EnergyTooHigh {
+                    energy: header.energy_amount,
+                    max,
+                });
+            }
+        }
+        if let Some(transferred) = transferred(payload) {
+            if let Some(allowed) = &self.allowed_receivers {
+                let receiver = transferred
+                    .receiver
+                    .ok_or(PolicyViolation::ReceiverNotChecked)?;
+                if !allowed.contains(&receiver) {
+                    return Err(PolicyViolation::ReceiverNotAllowed(receiver));
+                }
+            }
+            if let Some(max) = self.max_amount {
+                let amount = transferred
+                    .amount
+                    .ok_or(PolicyViolation::AmountNotChecked)?;
+                if amount > max {
+                    return Err(PolicyViolation::AmountTooHigh { amount, max });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_update(
+        &self,
+        _header: &UpdateHeader,
+        payload: &UpdatePayload,
+    ) -> Result<(), PolicyViolation> {
+        let update_type = payload.update_type();
+        if let Some(allowed) = &self.allowed_update_types {
+            if !allowed.contains(&update_type) {
+                return Err(PolicyViolation::UpdateTypeNotAllowed(update_type));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The signer process. This holds the keys and signs the requests that the
+/// policy allows.
+#[derive(Debug)]
+pub struct SigningService<T, U, P> {
+    /// The keys that sign transactions.
+    pub transaction_signer: T,
+    /// The keys that sign update instructions.
+    pub update_signer:      U,
+    /// The policy that decides which requests are signed.
+    pub policy:             P,
+}
+
+impl<T: TransactionSigner, U, P: SigningPolicy> SigningService<T, U, P>
+where
+    for<'a> &'a U: UpdateSigner,
+{
+    /// Check the request against the policy, and sign it if it is allowed.
+    /// The hash to sign is computed from the request, so the client cannot
+    /// obtain a signature on anything other than what the policy checked.
+    pub fn handle(&self, request: &SignRequest) -> SignResponse {
+        match request {
+            SignRequest::Transaction { header, payload } => {
+                let decoded = match payload.decode() {
+                    Ok(decoded) => decoded,
+                    Err(e) => {
+                        return SignResponse::Rejected(
+                            PolicyViolation::MalformedPayload(e.to_string()).to_string(),
+                        )
+                    }
+                };
+                if let Err(violation) = self.policy.check_transaction(header, &decoded) {
+                    return SignResponse::Rejected(violation.to_string());
+                }
+                let hash_to_sign = compute_transaction_sign_hash(header, payload);
+                SignResponse::TransactionSignature(
+                    self.transaction_signer.sign_transaction_hash(&hash_to_sign),
+                )
+            }
+            SignRequest::Update { header, payload } => {
+                if let Err(violation) = self.policy.check_update(header, payload) {
+                    return SignResponse::Rejected(violation.to_string());
+                }
+                let unsigned = PartiallySignedUpdate {
+                    header:     *header,
+                    payload:    payload.clone(),
+                    signatures: Default::default(),
+                };
+                SignResponse::UpdateSignature(
+                    (&self.update_signer).sign_update_hash(&unsigned.hash_to_sign()),
+                )
+            }
+        }
+    }
+
+    /// Handle requests on the connection until the client closes it.
+    pub async fn serve_connection(&self, mut stream: UnixStream) -> Result<(), RemoteSignerError> {
+        while let Some(request) = read_message(&mut stream).await? {
+            write_message(&mut stream, &self.handle(&request)).await?;
+        }
+        Ok(())
+    }
+}
+
+impl<T, U, P> SigningService<T, U, P>
+where
+    T: TransactionSigner + Send + Sync + 'static,
+    U: Send + Sync + 'static,
+    for<'a> &'a U: UpdateSigner,
+    P: SigningPolicy + 'static,
+{
+    /// Accept connections on the listener and handle their requests. Each
+    /// connection is handled by its own task, so a client that does not send
+    /// its request does not hold up the other clients. An error on a
+    /// connection closes that connection, but does not stop the service.
+    pub async fn serve(self: Arc<Self>, listener: &UnixListener) -> std::io::Result<()> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let service = self.clone();
+            tokio::spawn(async move {
+                // The client is responsible for reporting errors on its
+                // connection.
+                let _ = service.serve_connection(stream).await;
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        base::{Nonce, UpdateKeyPair, UpdateKeysIndex},
+        common::types::{CredentialIndex, KeyIndex, KeyPair, TransactionTime},
+        elgamal::{PublicKey, SecretKey},
+        encrypted_transfers::{
+            encrypt_amount, make_transfer_data, types::AggregatedDecryptedAmount,
+        },
+        id::{constants::ArCurve, types::GlobalContext},
+        smart_contracts::{OwnedParameter, OwnedReceiveName},
+        transactions::{
+            construct, sign_transaction, sign_transaction_async, PayloadLike, UpdateContractPayload,
+        },
+        updates::update,
+    };
+    use concordium_contracts_common::ContractAddress;
+    use std::collections::BTreeMap;
+
+    #[tokio::test]
+    async fn test_remote_signer() {
+        let mut rng = rand::thread_rng();
+        let account_keys = BTreeMap::from([(
+            CredentialIndex::from(0),
+            BTreeMap::from([(KeyIndex::from(0), KeyPair::generate(&mut rng))]),
+        )]);
+        let update_keys = BTreeMap::from([(
+            UpdateKeysIndex { index: 0 },
+            UpdateKeyPair::generate(&mut rng),
+        )]);
+        let service = Arc::new(SigningService {
+            transaction_signer: account_keys.clone(),
+            update_signer:      update_keys.clone(),
+            policy:             BasicPolicy {
+                max_amount: Some(Amount::from_micro_ccd(100)),
+                allowed_update_types: Some(BTreeSet::new()),
+                ..Default::default()
+            },
+        });
+        let path = std::env::temp_dir().join(format!("remote-signer-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).expect("Binding the socket succeeds.");
+        let signer = RemoteSigner::new(&path);
+
+        let transfer = |amount| {
+            construct::transfer(
+                1,
+                AccountAddress([1u8; 32]),
+                Nonce::from(1),
+                TransactionTime::from_seconds(1000),
+                AccountAddress([2u8; 32]),
+                Amount::from_micro_ccd(amount),
+            )
+        };
+        let client = async {
+            // A client that does not send anything does not block the others.
+            let _idle = UnixStream::connect(&path)
+                .await
+                .expect("Connecting succeeds.");
+            let tx = transfer(10);
+            let signed = sign_transaction_async(&signer, tx.header.clone(), tx.encoded.clone())
+                .await
+                .expect("The transfer is allowed.");
+            let expected = sign_transaction(&account_keys, tx.header, tx.encoded);
+            assert_eq!(signed.signature, expected.signature);
+
+            let tx = transfer(1000);
+            let result = sign_transaction_async(&signer, tx.header, tx.encoded).await;
+            assert!(matches!(result, Err(RemoteSignerError::Rejected(_))));
+
+            let payload = UpdatePayload::Protocol(crate::updates::ProtocolUpdate {
+                message: "Update".into(),
+                specification_url: "https://example.com".into(),
+                specification_hash: [0u8; 32].into(),
+                specification_auxiliary_data: Vec::new(),
+            });
+            let result = update::update_async(
+                &signer,
+                crate::base::UpdateSequenceNumber { number: 1 },
+                TransactionTime::from_seconds(0),
+                TransactionTime::from_seconds(1000),
+                payload,
+            )
+            .await;
+            assert!(matches!(result, Err(RemoteSignerError::Rejected(_))));
+        };
+        tokio::select! {
+            result = service.serve(&listener) => panic!("The service stopped: {:?}", result),
+            () = client => (),
+        }
+        std::fs::remove_file(&path).expect("Removing the socket succeeds.");
+    }
+
+    #[test]
+    fn test_policy_receivers() {
+        let mut rng = rand::thread_rng();
+        let allowed = AccountAddress([2u8; 32]);
+        let other = AccountAddress([3u8; 32]);
+        let policy = BasicPolicy {
+            allowed_receivers: Some(BTreeSet::from([allowed])),
+            ..Default::default()
+        };
+        let header = |payload: &Payload| TransactionHeader {
+            sender:        AccountAddress([1u8; 32]),
+            nonce:         Nonce::from(1),
+            energy_amount: Energy::from(10_000),
+            payload_size:  payload.encode().size(),
+            expiry:        TransactionTime::from_seconds(1000),
+        };
+        let check = |payload: Payload| policy.check_transaction(&header(&payload), &payload);
+
+        let context = GlobalContext::<ArCurve>::generate(String::from("genesis_string"));
+        let sk = SecretKey::generate_all(&mut rng);
+        let receiver_pk = PublicKey::from(&SecretKey::generate_all(&mut rng));
+        let balance = Amount::from_micro_ccd(100);
+        let input_amount = AggregatedDecryptedAmount {
+            agg_amount:           balance,
+            agg_encrypted_amount: encrypt_amount(
+                &context,
+                &PublicKey::from(&sk),
+                balance,
+                &mut rng,
+            )
+            .0,
+            agg_index:            0u64.into(),
+        };
+        let mut encrypted_transfer = |to| {
+            let data = make_transfer_data(
+                &context,
+                &receiver_pk,
+                &sk,
+                &input_amount,
+                Amount::from_micro_ccd(10),
+                &mut rng,
+            )
+            .expect("The amount can be transferred.");
+            Payload::EncryptedAmountTransfer {
+                to,
+                data: Box::new(data),
+            }
+        };
+        assert_eq!(check(encrypted_transfer(allowed)), Ok(()));
+        assert_eq!(
+            check(encrypted_transfer(other)),
+            Err(PolicyViolation::ReceiverNotAllowed(other))
+        );
+
+        let update = Payload::Update {
+            payload: UpdateContractPayload {
+                amount:       Amount::zero(),
+                address:      ContractAddress::new(0, 0),
+                receive_name: OwnedReceiveName::new_unchecked("contract.receive".into()),
+                message:      OwnedParameter::empty(),
+            },
+        };
+        assert_eq!(check(update), Err(PolicyViolation::ReceiverNotChecked));
+
+        let amount_policy = BasicPolicy {
+            max_amount: Some(Amount::from_micro_ccd(100)),
+            ..Default::default()
+        };
+        let payload = encrypted_transfer(allowed);
+        assert_eq!(
+            amount_policy.check_transaction(&header(&payload), &payload),
+            Err(PolicyViolation::AmountNotChecked)
+        );
+    }
+}
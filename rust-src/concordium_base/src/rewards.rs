@@ -0,0 +1,854 @@
+//! Estimates of the rewards of bakers and delegators, and validation of
+//! staking transactions against the pool parameters.
+//!
+//! This applies to protocol versions 4 and up, where rewards are paid out
+//! every payday. The estimates assume that each pool bakes a share of the
+//! blocks in the payday equal to its share of the effective stake, which is the
+//! expected outcome of the lottery.
+use crate::{
+    base::*,
+    chain_parameters::{ChainParametersV1, ChainParametersV2},
+    common::{SerdeDeserialize, SerdeSerialize},
+    transactions::{ConfigureBakerPayload, ConfigureDelegationPayload},
+    updates::{PoolParameters, TransactionFeeDistribution},
+};
+use concordium_contracts_common::{AccountAddress, Amount};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// The number of parts per hundred thousands of a fraction.
+fn parts(fraction: AmountFraction) -> u128 { fraction.parts_per_hundred_thousands.parts.into() }
+
+/// Compute `amount * numerator / denominator`, rounding down. The result
+/// saturates at the maximum amount, and is 0 if the denominator is 0.
+fn scale(amount: Amount, numerator: u128, denominator: u128) -> Amount {
+    if denominator == 0 {
+        return Amount::zero();
+    }
+    let micro_ccd = u128::from(amount.micro_ccd).saturating_mul(numerator) / denominator;
+    Amount::from_micro_ccd(u64::try_from(micro_ccd).unwrap_or(u64::MAX))
+}
+
+/// The given fraction of the amount, rounded down.
+fn fraction_of(amount: Amount, fraction: AmountFraction) -> Amount {
+    scale(amount, parts(fraction), 100_000)
+}
+
+/// The fraction of the amount that remains after deducting the commission.
+fn after_commission(amount: Amount, commission: AmountFraction) -> Amount {
+    scale(amount, 100_000 - parts(commission), 100_000)
+}
+
+fn sum(amounts: impl IntoIterator<Item = Amount>) -> Amount {
+    amounts.into_iter().fold(Amount::zero(), |acc, amount| {
+        acc.checked_add(amount)
+            .unwrap_or(Amount::from_micro_ccd(u64::MAX))
+    })
+}
+
+fn sub(lhs: Amount, rhs: Amount) -> Amount { lhs.checked_sub(rhs).unwrap_or_else(Amount::zero) }
+
+#[derive(Debug, Clone, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+/// A baker pool, without the delegators to it.
+pub struct BakerPool {
+    pub baker_id:         BakerId,
+    /// The stake of the baker itself.
+    pub equity_capital:   Amount,
+    pub commission_rates: CommissionRates,
+    pub open_status:      OpenStatus,
+    /// Whether the baker is in the finalization committee, and thus receives
+    /// finalization rewards.
+    pub is_finalizer:     bool,
+}
+
+#[derive(Debug, Clone, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+/// The stake of a delegator.
+pub struct Delegator {
+    pub account: AccountAddress,
+    pub stake:   Amount,
+    pub target:  DelegationTarget,
+}
+
+#[derive(Debug, Clone, Default, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+/// The stake of all bakers and delegators.
+pub struct StakeDistribution {
+    pub pools:      Vec<BakerPool>,
+    pub delegators: Vec<Delegator>,
+}
+
+impl StakeDistribution {
+    /// The pool of the given baker, if it exists.
+    pub fn pool(&self, baker_id: BakerId) -> Option<&BakerPool> {
+        self.pools.iter().find(|pool| pool.baker_id == baker_id)
+    }
+
+    /// The delegator with the given account, if it exists.
+    pub fn delegator(&self, account: &AccountAddress) -> Option<&Delegator> {
+        self.delegators
+            .iter()
+            .find(|delegator| &delegator.account == account)
+    }
+
+    /// The total capital delegated to the given target.
+    pub fn delegated_capital(&self, target: &DelegationTarget) -> Amount {
+        sum(self
+            .delegators
+            .iter()
+            .filter(|delegator| &delegator.target == target)
+            .map(|delegator| delegator.stake))
+    }
+
+    /// The total staked capital of bakers and delegators.
+    pub fn total_staked_capital(&self) -> Amount {
+        sum(self
+            .pools
+            .iter()
+            .map(|pool| pool.equity_capital)
+            .chain(self.delegators.iter().map(|delegator| delegator.stake)))
+    }
+}
+
+/// The effective stake of a pool. This is the total capital of the pool,
+/// bounded by the capital bound relative to the total staked capital, and by
+/// the leverage bound relative to the equity capital of the baker.
+pub fn effective_stake(
+    pool_parameters: &PoolParameters,
+    equity_capital: Amount,
+    delegated_capital: Amount,
+    total_staked_capital: Amount,
+) -> Amount {
+    let pool_capital = sum([equity_capital, delegated_capital]);
+    let capital_bound = fraction_of(total_staked_capital, pool_parameters.capital_bound.bound);
+    let leverage_bound = scale(
+        equity_capital,
+        pool_parameters.leverage_bound.numerator.into(),
+        pool_parameters.leverage_bound.denominator.into(),
+    );
+    pool_capital.min(capital_bound).min(leverage_bound)
+}
+
+#[derive(Debug, Clone, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
+/// The chain parameters that determine rewards.
+pub struct RewardCalculator {
+    pub mint_per_payday:              MintRate,
+    pub mint_distribution:            MintDistributionV1,
+    pub transaction_fee_distribution: TransactionFeeDistribution,
+    pub pool_parameters:              PoolParameters,
+}
+
+impl From<&ChainParametersV1> for RewardCalculator {
+    fn from(cp: &ChainParametersV1) -> Self {
+        Self {
+            mint_per_payday:              cp.time_parameters.mint_per_payday,
+            mint_distribution:            cp.reward_parameters.mint_distribution.clone(),
+            transaction_fee_distribution: cp.reward_parameters.transaction_fee_distribution.clone(),
+            pool_parameters:              cp.pool_parameters.clone(),
+        }
+    }
+}
+
+impl From<&ChainParametersV2> for RewardCalculator {
+    fn from(cp: &ChainParametersV2) -> Self {
+        Self {
+            mint_per_payday:              cp.time_parameters.mint_per_payday,
+            mint_distribution:            cp.reward_parameters.mint_distribution.clone(),
+            transaction_fee_distribution: cp.reward_parameters.transaction_fee_distribution.clone(),
+            pool_parameters:              cp.pool_parameters.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, SerdeSerialize)]
+#[serde(rename_all = "camelCase")]
+/// The estimated rewards of a baker pool in a payday.
+pub struct BakerRewards {
+    pub baker_id:        BakerId,
+    /// The stake of the pool that counts for the lottery and for rewards.
+    pub effective_stake: Amount,
+    /// The rewards of the pool before they are divided between the baker and
+    /// the delegators.
+    pub pool_rewards:    Amount,
+    /// The rewards of the baker, including the commissions on the rewards of
+    /// the delegators.
+    pub baker_rewards:   Amount,
+}
+
+#[derive(Debug, Clone, SerdeSerialize)]
+#[serde(rename_all = "camelCase")]
+/// The estimated rewards of a delegator in a payday.
+pub struct DelegatorRewards {
+    pub account: AccountAddress,
+    pub target:  DelegationTarget,
+    pub rewards: Amount,
+}
+
+#[derive(Debug, Clone, SerdeSerialize)]
+#[serde(rename_all = "camelCase")]
+/// The estimated distribution of newly minted CCD and transaction fees in a
+/// payday.
+pub struct PaydayRewards {
+    /// The amount minted in the payday.
+    pub minted:               Amount,
+    /// Rewards for baking blocks, from minting.
+    pub baking_rewards:       Amount,
+    /// Rewards for finalization, from minting.
+    pub finalization_rewards: Amount,
+    /// Rewards for the bakers of blocks, from transaction fees.
+    pub transaction_rewards:  Amount,
+    /// The part of the transaction fees that goes to the GAS account.
+    pub gas_account:          Amount,
+    /// The part of the minted amount and transaction fees that goes to the
+    /// foundation account.
+    pub foundation:           Amount,
+    pub bakers:               Vec<BakerRewards>,
+    pub delegators:           Vec<DelegatorRewards>,
+}
+
+#[derive(Debug, Error)]
+/// An error that occurs when estimating rewards.
+pub enum RewardCalculationError {
+    #[error("Delegator {account} delegates to baker {baker_id}, which does not have a pool.")]
+    UnknownPool {
+        account:  AccountAddress,
+        baker_id: BakerId,
+    },
+}
+
+/// The rewards of a pool, by the kind of reward. Commissions are charged
+/// separately on each kind.
+struct PoolRewardsByKind {
+    baking:       Amount,
+    finalization: Amount,
+    transaction:  Amount,
+}
+
+impl PoolRewardsByKind {
+    fn total(&self) -> Amount { sum([self.baking, self.finalization, self.transaction]) }
+
+    /// The rewards of a delegator with the given share of the capital of the
+    /// pool, after deducting commissions.
+    fn delegator_share(
+        &self,
+        stake: Amount,
+        pool_capital: Amount,
+        commissions: &CommissionRates,
+    ) -> Amount {
+        let share = |amount, commission| {
+            after_commission(
+                scale(
+                    amount,
+                    stake.micro_ccd.into(),
+                    pool_capital.micro_ccd.into(),
+                ),
+                commission,
+            )
+        };
+        sum([
+            share(self.baking, commissions.baking),
+            share(self.finalization, commissions.finalization),
+            share(self.transaction, commissions.transaction),
+        ])
+    }
+}
+
+impl RewardCalculator {
+    /// The amount minted in a payday given the total amount of CCD in
+    /// existence.
+    pub fn minted_per_payday(&self, total_supply: Amount) -> Amount {
+        match 10u128.checked_pow(self.mint_per_payday.exponent.into()) {
+            Some(denominator) => scale(
+                total_supply,
+                self.mint_per_payday.mantissa.into(),
+                denominator,
+            ),
+            // The mint rate is too small to mint anything.
+            None => Amount::zero(),
+        }
+    }
+
+    /// Estimate the rewards of all bakers and delegators in a payday, given the
+    /// total amount of CCD in existence and the total transaction fees paid in
+    /// the payday.
+    ///
+    /// Passive delegation receives a share of each kind of reward equal to its
+    /// share of the stake, and the passive delegators pay the passive
+    /// commissions of the pool parameters.
+    pub fn payday_rewards(
+        &self,
+        total_supply: Amount,
+        transaction_fees: Amount,
+        stakes: &StakeDistribution,
+    ) -> Result<PaydayRewards, RewardCalculationError> {
+        let minted = self.minted_per_payday(total_supply);
+        let baking_rewards = fraction_of(minted, self.mint_distribution.baking_reward);
+        let finalization_rewards = fraction_of(minted, self.mint_distribution.finalization_reward);
+        let transaction_rewards =
+            fraction_of(transaction_fees, self.transaction_fee_distribution.baker);
+        let gas_account = fraction_of(
+            transaction_fees,
+            self.transaction_fee_distribution.gas_account,
+        );
+        let foundation = sub(
+            sum([minted, transaction_fees]),
+            sum([
+                baking_rewards,
+                finalization_rewards,
+                transaction_rewards,
+                gas_account,
+            ]),
+        );
+
+        let mut delegated = BTreeMap::new();
+        for delegator in &stakes.delegators {
+            if let DelegationTarget::Baker { baker_id } = delegator.target {
+                if stakes.pool(baker_id).is_none() {
+                    return Err(RewardCalculationError::UnknownPool {
+                        account: delegator.account,
+                        baker_id,
+                    });
+                }
+                let entry = delegated.entry(baker_id).or_insert_with(Amount::zero);
+                *entry = sum([*entry, delegator.stake]);
+            }
+        }
+        let passive_capital = stakes.delegated_capital(&DelegationTarget::Passive);
+        let total_staked_capital = stakes.total_staked_capital();
+        let effective_stakes = stakes
+            .pools
+            .iter()
+            .map(|pool| {
+                let delegated_capital = delegated
+                    .get(&pool.baker_id)
+                    .copied()
+                    .unwrap_or_else(Amount::zero);
+                effective_stake(
+                    &self.pool_parameters,
+                    pool.equity_capital,
+                    delegated_capital,
+                    total_staked_capital,
+                )
+            })
+            .collect::<Vec<_>>();
+        let total_effective = u128::from(sum(effective_stakes.iter().copied()).micro_ccd)
+            + u128::from(passive_capital.micro_ccd);
+        let total_finalizer_stake = u128::from(
+            sum(stakes
+                .pools
+                .iter()
+                .zip(&effective_stakes)
+                .filter(|(pool, _)| pool.is_finalizer)
+                .map(|(_, stake)| *stake))
+            .micro_ccd,
+        ) + u128::from(passive_capital.micro_ccd);
+        let rewards_for = |stake: Amount, is_finalizer: bool| PoolRewardsByKind {
+            baking:       scale(baking_rewards, stake.micro_ccd.into(), total_effective),
+            finalization: if is_finalizer {
+                scale(
+                    finalization_rewards,
+                    stake.micro_ccd.into(),
+                    total_finalizer_stake,
+                )
+            } else {
+                Amount::zero()
+            },
+            transaction:  scale(transaction_rewards, stake.micro_ccd.into(), total_effective),
+        };
+
+        let mut bakers = Vec::with_capacity(stakes.pools.len());
+        let mut delegators = Vec::with_capacity(stakes.delegators.len());
+        for (pool, effective_stake) in stakes.pools.iter().zip(effective_stakes) {
+            let target = DelegationTarget::from(pool.baker_id);
+            let pool_capital = sum([
+                pool.equity_capital,
+                delegated
+                    .get(&pool.baker_id)
+                    .copied()
+                    .unwrap_or_else(Amount::zero),
+            ]);
+            let rewards = rewards_for(effective_stake, pool.is_finalizer);
+            let mut delegator_rewards = Amount::zero();
+            for delegator in stakes.delegators.iter().filter(|d| d.target == target) {
+                let reward =
+                    rewards.delegator_share(delegator.stake, pool_capital, &pool.commission_rates);
+                delegator_rewards = sum([delegator_rewards, reward]);
+                delegators.push(DelegatorRewards {
+                    account: delegator.account,
+                    target:  target.clone(),
+                    rewards: reward,
+                });
+            }
+            bakers.push(BakerRewards {
+                baker_id: pool.baker_id,
+                effective_stake,
+                pool_rewards: rewards.total(),
+                baker_rewards: sub(rewards.total(), delegator_rewards),
+            });
+        }
+        let passive_rewards = rewards_for(passive_capital, true);
+        let passive_commissions = CommissionRates {
+            finalization: self.pool_parameters.passive_finalization_commission,
+            baking:       self.pool_parameters.passive_baking_commission,
+            transaction:  self.pool_parameters.passive_transaction_commission,
+        };
+        for delegator in stakes
+            .delegators
+            .iter()
+            .filter(|d| d.target == DelegationTarget::Passive)
+        {
+            delegators.push(DelegatorRewards {
+                account: delegator.account,
+                target:  DelegationTarget::Passive,
+                rewards: passive_rewards.delegator_share(
+                    delegator.stake,
+                    passive_capital,
+                    &passive_commissions,
+                ),
+            });
+        }
+
+        Ok(PaydayRewards {
+            minted,
+            baking_rewards,
+            finalization_rewards,
+            transaction_rewards,
+            gas_account,
+            foundation,
+            bakers,
+            delegators,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The kinds of rewards that a pool charges commission on.
+pub enum CommissionKind {
+    Baking,
+    Finalization,
+    Transaction,
+}
+
+#[derive(Debug, Error)]
+/// The reason a staking transaction would be rejected by the chain, or would
+/// lead to stake that does not count in full.
+pub enum StakeValidationError {
+    #[error("Adding a new {0} requires all the parameters to be set.")]
+    MissingParameters(&'static str),
+    #[error("The equity capital {actual} CCD is below the minimum {minimum} CCD.")]
+    InsufficientCapital { minimum: Amount, actual: Amount },
+    #[error("The {kind:?} commission {commission} is outside the allowed range.")]
+    CommissionOutOfRange {
+        kind:       CommissionKind,
+        commission: AmountFraction,
+    },
+    #[error("Baker {0} does not have a pool.")]
+    UnknownPool(BakerId),
+    #[error("The pool of baker {0} is not open for new delegators.")]
+    PoolClosed(BakerId),
+    #[error(
+        "The capital of the pool {pool_capital} CCD would exceed the capital bound of {bound} CCD."
+    )]
+    StakeOverCapitalBound {
+        pool_capital: Amount,
+        bound:        Amount,
+    },
+    #[error(
+        "The capital of the pool {pool_capital} CCD would exceed the leverage bound of {bound} \
+         CCD."
+    )]
+    PoolOverDelegated {
+        pool_capital: Amount,
+        bound:        Amount,
+    },
+}
+
+/// Check that the capital of a pool is within the capital and leverage bounds.
+fn check_pool_bounds(
+    pool_parameters: &PoolParameters,
+    equity_capital: Amount,
+    delegated_capital: Amount,
+    total_staked_capital: Amount,
+) -> Result<(), StakeValidationError> {
+    let pool_capital = sum([equity_capital, delegated_capital]);
+    let bound = fraction_of(total_staked_capital, pool_parameters.capital_bound.bound);
+    if pool_capital > bound {
+        return Err(StakeValidationError::StakeOverCapitalBound {
+            pool_capital,
+            bound,
+        });
+    }
+    let bound = scale(
+        equity_capital,
+        pool_parameters.leverage_bound.numerator.into(),
+        pool_parameters.leverage_bound.denominator.into(),
+    );
+    if pool_capital > bound {
+        return Err(StakeValidationError::PoolOverDelegated {
+            pool_capital,
+            bound,
+        });
+    }
+    Ok(())
+}
+
+impl StakeDistribution {
+    /// Validate a transaction that configures the baker with the given id
+    /// against the pool parameters and the current stake. If the baker does
+    /// not have a pool, the transaction must add one.
+    ///
+    /// Besides the conditions that the chain checks, this checks that the
+    /// pool stays within the capital and leverage bounds. If it does not, the
+    /// transaction is accepted, but not all of the stake of the pool is
+    /// effective.
+    pub fn validate_configure_baker(
+        &self,
+        pool_parameters: &PoolParameters,
+        baker_id: BakerId,
+        payload: &ConfigureBakerPayload,
+    ) -> Result<(), StakeValidationError> {
+        let existing = self.pool(baker_id);
+        if existing.is_none()
+            && (payload.capital.is_none()
+                || payload.restake_earnings.is_none()
+                || payload.open_for_delegation.is_none()
+                || payload.keys_with_proofs.is_none()
+                || payload.metadata_url.is_none()
+                || payload.transaction_fee_commission.is_none()
+                || payload.baking_reward_commission.is_none()
+                || payload.finalization_reward_commission.is_none())
+        {
+            return Err(StakeValidationError::MissingParameters("baker"));
+        }
+        let ranges = &pool_parameters.commission_bounds;
+        for (kind, commission, range) in [
+            (
+                CommissionKind::Baking,
+                payload.baking_reward_commission,
+                &ranges.baking,
+            ),
+            (
+                CommissionKind::Finalization,
+                payload.finalization_reward_commission,
+                &ranges.finalization,
+            ),
+            (
+                CommissionKind::Transaction,
+                payload.transaction_fee_commission,
+                &ranges.transaction,
+            ),
+        ] {
+            if let Some(commission) = commission {
+                if !range.contains(&commission) {
+                    return Err(StakeValidationError::CommissionOutOfRange { kind, commission });
+                }
+            }
+        }
+        let old_capital = existing.map_or_else(Amount::zero, |pool| pool.equity_capital);
+        let capital = payload.capital.unwrap_or(old_capital);
+        // A capital of 0 removes the baker.
+        if capital == Amount::zero() {
+            return Ok(());
+        }
+        if capital < pool_parameters.minimum_equity_capital {
+            return Err(StakeValidationError::InsufficientCapital {
+                minimum: pool_parameters.minimum_equity_capital,
+                actual:  capital,
+            });
+        }
+        let total_staked_capital = sum([sub(self.total_staked_capital(), old_capital), capital]);
+        check_pool_bounds(
+            pool_parameters,
+            capital,
+            self.delegated_capital(&baker_id.into()),
+            total_staked_capital,
+        )
+    }
+
+    /// Validate a transaction that configures the delegation of the given
+    /// account against the pool parameters and the current stake. If the
+    /// account is not a delegator, the transaction must add the delegation.
+    pub fn validate_configure_delegation(
+        &self,
+        pool_parameters: &PoolParameters,
+        account: &AccountAddress,
+        payload: &ConfigureDelegationPayload,
+    ) -> Result<(), StakeValidationError> {
+        let existing = self.delegator(account);
+        let (old_stake, old_target) = match existing {
+            Some(delegator) => (delegator.stake, Some(&delegator.target)),
+            None => {
+                if payload.capital.is_none()
+                    || payload.restake_earnings.is_none()
+                    || payload.delegation_target.is_none()
+                {
+                    return Err(StakeValidationError::MissingParameters("delegator"));
+                }
+                (Amount::zero(), None)
+            }
+        };
+        let stake = payload.capital.unwrap_or(old_stake);
+        // A capital of 0 removes the delegator.
+        if stake == Amount::zero() {
+            return Ok(());
+        }
+        let target = match payload.delegation_target.as_ref().or(old_target) {
+            Some(DelegationTarget::Baker { baker_id }) => *baker_id,
+            _ => return Ok(()),
+        };
+        let pool = self
+            .pool(target)
+            .ok_or(StakeValidationError::UnknownPool(target))?;
+        let is_new_to_pool = old_target != Some(&DelegationTarget::from(target));
+        match pool.open_status {
+            OpenStatus::OpenForAll => (),
+            OpenStatus::ClosedForNew if !is_new_to_pool => (),
+            _ => return Err(StakeValidationError::PoolClosed(target)),
+        }
+        let mut delegated_capital = self.delegated_capital(&target.into());
+        if !is_new_to_pool {
+            delegated_capital = sub(delegated_capital, old_stake);
+        }
+        let total_staked_capital = sum([sub(self.total_staked_capital(), old_stake), stake]);
+        check_pool_bounds(
+            pool_parameters,
+            pool.equity_capital,
+            sum([delegated_capital, stake]),
+            total_staked_capital,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fraction(parts: u32) -> AmountFraction { AmountFraction::new(parts).unwrap() }
+
+    fn calculator() -> RewardCalculator {
+        let range = InclusiveRange {
+            min: fraction(0),
+            max: fraction(100_000),
+        };
+        RewardCalculator {
+            mint_per_payday:              MintRate {
+                mantissa: 1,
+                exponent: 3,
+            },
+            mint_distribution:            MintDistributionV1 {
+                baking_reward:       fraction(60_000),
+                finalization_reward: fraction(30_000),
+            },
+            transaction_fee_distribution: TransactionFeeDistribution {
+                baker:       fraction(50_000),
+                gas_account: fraction(40_000),
+            },
+            pool_parameters:              PoolParameters {
+                passive_finalization_commission: fraction(100_000),
+                passive_baking_commission:       fraction(10_000),
+                passive_transaction_commission:  fraction(10_000),
+                commission_bounds:               CommissionRanges {
+                    finalization: range,
+                    baking:       range,
+                    transaction:  range,
+                },
+                minimum_equity_capital:          Amount::from_ccd(100),
+                capital_bound:                   CapitalBound {
+                    bound: fraction(100_000),
+                },
+                leverage_bound:                  LeverageFactor::new_integral(3),
+            },
+        }
+    }
+
+    fn stakes() -> StakeDistribution {
+        let commission_rates = CommissionRates {
+            finalization: fraction(10_000),
+            baking:       fraction(10_000),
+            transaction:  fraction(10_000),
+        };
+        let pool = |id, ccd| BakerPool {
+            baker_id: BakerId {
+                id: AccountIndex { index: id },
+            },
+            equity_capital: Amount::from_ccd(ccd),
+            commission_rates,
+            open_status: OpenStatus::OpenForAll,
+            is_finalizer: true,
+        };
+        StakeDistribution {
+            pools:      vec![pool(0, 1000), pool(1, 1000)],
+            delegators: vec![Delegator {
+                account: AccountAddress([1u8; 32]),
+                stake:   Amount::from_ccd(1000),
+                target:  DelegationTarget::from(BakerId {
+                    id: AccountIndex { index: 0 },
+                }),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_payday_rewards() {
+        let calculator = calculator();
+        let rewards = calculator
+            .payday_rewards(
+                Amount::from_ccd(1_000_000),
+                Amount::from_ccd(100),
+                &stakes(),
+            )
+            .expect("All pools exist.");
+        assert_eq!(rewards.minted, Amount::from_ccd(1000));
+        assert_eq!(rewards.baking_rewards, Amount::from_ccd(600));
+        assert_eq!(rewards.finalization_rewards, Amount::from_ccd(300));
+        assert_eq!(rewards.transaction_rewards, Amount::from_ccd(50));
+        assert_eq!(rewards.gas_account, Amount::from_ccd(40));
+        assert_eq!(rewards.foundation, Amount::from_ccd(110));
+        // Pool 0 has twice the stake of pool 1.
+        assert_eq!(rewards.bakers[0].effective_stake, Amount::from_ccd(2000));
+        assert_eq!(
+            rewards.bakers[0].pool_rewards,
+            Amount::from_micro_ccd(633_333_333)
+        );
+        assert_eq!(
+            rewards.bakers[1].pool_rewards,
+            Amount::from_micro_ccd(316_666_666)
+        );
+        // The delegator has half the capital of pool 0 and pays 10% commission.
+        assert_eq!(
+            rewards.delegators[0].rewards,
+            Amount::from_micro_ccd(284_999_999)
+        );
+    }
+
+    #[test]
+    fn test_validate_configure_delegation() {
+        let calculator = calculator();
+        let stakes = stakes();
+        let payload = ConfigureDelegationPayload {
+            capital:           Some(Amount::from_ccd(500)),
+            restake_earnings:  Some(true),
+            delegation_target: Some(DelegationTarget::from(BakerId {
+                id: AccountIndex { index: 1 },
+            })),
+        };
+        stakes
+            .validate_configure_delegation(
+                &calculator.pool_parameters,
+                &AccountAddress([2u8; 32]),
+                &payload,
+            )
+            .expect("Delegation is within bounds.");
+        // Delegating more than twice the equity exceeds the leverage bound.
+        let payload = ConfigureDelegationPayload {
+            capital: Some(Amount::from_ccd(2500)),
+            ..payload
+        };
+        assert!(matches!(
+            stakes.validate_configure_delegation(
+                &calculator.pool_parameters,
+                &AccountAddress([2u8; 32]),
+                &payload,
+            ),
+            Err(StakeValidationError::PoolOverDelegated { .. })
+        ));
+        assert!(matches!(
+            stakes.validate_configure_delegation(
+                &calculator.pool_parameters,
+                &AccountAddress([2u8; 32]),
+                &ConfigureDelegationPayload::default(),
+            ),
+            Err(StakeValidationError::MissingParameters(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_configure_baker() {
+        let calculator = calculator();
+        let stakes = stakes();
+        let baker_0 = BakerId {
+            id: AccountIndex { index: 0 },
+        };
+        let validate = |pool_parameters: &PoolParameters, baker_id, payload| {
+            stakes.validate_configure_baker(pool_parameters, baker_id, &payload)
+        };
+        let with_capital = |ccd| ConfigureBakerPayload {
+            capital: Some(Amount::from_ccd(ccd)),
+            ..ConfigureBakerPayload::default()
+        };
+        // Baker 0 has 1000 CCD of equity and 1000 CCD delegated to it.
+        validate(&calculator.pool_parameters, baker_0, with_capital(2000))
+            .expect("Stake is within bounds.");
+        // Removing the baker is always allowed.
+        validate(&calculator.pool_parameters, baker_0, with_capital(0))
+            .expect("Removing the baker is allowed.");
+        assert!(matches!(
+            validate(&calculator.pool_parameters, baker_0, with_capital(50)),
+            Err(StakeValidationError::InsufficientCapital { .. })
+        ));
+        // With 400 CCD of equity the pool can have at most 1200 CCD.
+        assert!(matches!(
+            validate(&calculator.pool_parameters, baker_0, with_capital(400)),
+            Err(StakeValidationError::PoolOverDelegated { .. })
+        ));
+        // With a capital bound of 50% pool 0 has too much of the 3000 CCD staked.
+        let bounded = PoolParameters {
+            capital_bound: CapitalBound {
+                bound: fraction(50_000),
+            },
+            ..calculator.pool_parameters.clone()
+        };
+        assert!(matches!(
+            validate(&bounded, baker_0, ConfigureBakerPayload::default()),
+            Err(StakeValidationError::StakeOverCapitalBound { .. })
+        ));
+        let narrow = PoolParameters {
+            commission_bounds: CommissionRanges {
+                baking: InclusiveRange {
+                    min: fraction(5_000),
+                    max: fraction(10_000),
+                },
+                ..calculator.pool_parameters.commission_bounds
+            },
+            ..calculator.pool_parameters.clone()
+        };
+        assert!(matches!(
+            validate(&narrow, baker_0, ConfigureBakerPayload {
+                baking_reward_commission: Some(fraction(20_000)),
+                ..ConfigureBakerPayload::default()
+            }),
+            Err(StakeValidationError::CommissionOutOfRange {
+                kind: CommissionKind::Baking,
+                ..
+            })
+        ));
+
+        // A new baker must set all the parameters.
+        let new_baker = BakerId {
+            id: AccountIndex { index: 2 },
+        };
+        assert!(matches!(
+            validate(&calculator.pool_parameters, new_baker, with_capital(1000)),
+            Err(StakeValidationError::MissingParameters(_))
+        ));
+        let mut rng = rand::thread_rng();
+        let mut payload = ConfigureBakerPayload::new();
+        payload
+            .set_capital(Amount::from_ccd(1000))
+            .set_restake_earnings(true)
+            .set_open_for_delegation(OpenStatus::OpenForAll)
+            .add_keys(
+                &BakerKeyPairs::generate(&mut rng),
+                AccountAddress([2u8; 32]),
+                &mut rng,
+            )
+            .set_metadata_url(UrlText::try_from(String::new()).expect("Empty URL is valid."))
+            .set_transaction_fee_commission(fraction(10_000))
+            .set_baking_reward_commission(fraction(10_000))
+            .set_finalization_reward_commission(fraction(10_000));
+        validate(&calculator.pool_parameters, new_baker, payload)
+            .expect("New baker is within bounds.");
+    }
+}
@@ -5,7 +5,8 @@ use crate::{
     base::{
         AccountThreshold, AggregateSigPairing, AmountFraction, BakerAggregationVerifyKey,
         BakerElectionVerifyKey, BakerKeyPairs, BakerSignatureVerifyKey, ContractAddress,
-        CredentialRegistrationID, DelegationTarget, Energy, Nonce, OpenStatus, UrlText,
+        CredentialRegistrationID, DelegationTarget, Energy, Nonce, OpenStatus, ProtocolVersion,
+        UrlText,
     },
     common::{
         self,
@@ -68,7 +69,19 @@ impl Deserial for Memo {
     }
 }
 
-#[derive(SerdeSerialize, SerdeDeserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+#[derive(
+    SerdeSerialize,
+    SerdeDeserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Display,
+)]
 #[serde(rename_all = "camelCase")]
 // Since all variants are fieldless, the default JSON serialization will convert
 // all the variants to simple strings.
@@ -1458,6 +1471,59 @@ pub fn sign_transaction<S: TransactionSigner, P: PayloadLike>(
     }
 }
 
+#[cfg(feature = "async")]
+/// An asynchronous variant of [`TransactionSigner`] for keys that are held
+/// outside the process, e.g., by a signing service. The signer is given the
+/// header and the payload of the transaction instead of only the hash, so that
+/// it can inspect the transaction before signing it.
+///
+/// Every [`TransactionSigner`] is also an [`AsyncTransactionSigner`] that
+/// never fails.
+#[async_trait::async_trait]
+pub trait AsyncTransactionSigner: Sync {
+    /// The error returned when the transaction could not be signed.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Sign the transaction with the given header and payload, allocating and
+    /// returning the signatures.
+    async fn sign_transaction(
+        &self,
+        header: &TransactionHeader,
+        payload: &EncodedPayload,
+    ) -> Result<TransactionSignature, Self::Error>;
+}
+
+#[cfg(feature = "async")]
+#[async_trait::async_trait]
+impl<S: TransactionSigner + Sync> AsyncTransactionSigner for S {
+    type Error = std::convert::Infallible;
+
+    async fn sign_transaction(
+        &self,
+        header: &TransactionHeader,
+        payload: &EncodedPayload,
+    ) -> Result<TransactionSignature, Self::Error> {
+        Ok(self.sign_transaction_hash(&compute_transaction_sign_hash(header, payload)))
+    }
+}
+
+#[cfg(feature = "async")]
+/// Sign the header and payload with an asynchronous signer, construct the
+/// transaction, and return it. This is the asynchronous variant of
+/// [`sign_transaction`].
+pub async fn sign_transaction_async<S: AsyncTransactionSigner, P: PayloadLike>(
+    signer: &S,
+    header: TransactionHeader,
+    payload: P,
+) -> Result<AccountTransaction<P>, S::Error> {
+    let signature = signer.sign_transaction(&header, &payload.encode()).await?;
+    Ok(AccountTransaction {
+        signature,
+        header,
+        payload,
+    })
+}
+
 /// Implementations of this trait are structures which can produce public keys
 /// with which transaction signatures can be verified.
 pub trait HasAccountAccessStructure {
@@ -1928,6 +1994,29 @@ pub mod cost {
     /// the binary `.wasm` file that is sent as part of the transaction.
     pub fn deploy_module(module_size: u64) -> Energy { Energy::from(module_size / 10) }
 
+    /// Cost of looking up a smart contract module of the given size, which is
+    /// the size of the binary `.wasm` file that was deployed. Lookups became
+    /// cheaper in protocol version 7.
+    pub fn lookup_module(protocol_version: ProtocolVersion, module_size: u64) -> Energy {
+        if protocol_version >= ProtocolVersion::P7 {
+            Energy::from(module_size / 500)
+        } else {
+            Energy::from(module_size / 50)
+        }
+    }
+
+    /// Base cost of initializing a contract instance. This covers
+    /// administrative costs and is charged even if no code is run.
+    pub const INITIALIZE_CONTRACT_INSTANCE_BASE: Energy = Energy { energy: 300 };
+
+    /// Additional cost of creating a contract instance when initialization
+    /// succeeds.
+    pub const INITIALIZE_CONTRACT_INSTANCE_CREATE: Energy = Energy { energy: 200 };
+
+    /// Base cost of updating a contract instance. This covers administrative
+    /// costs and is charged even if no code is run.
+    pub const UPDATE_CONTRACT_INSTANCE_BASE: Energy = Energy { energy: 300 };
+
     /// There is a non-trivial amount of lookup
     /// that needs to be done before we can start any checking. This ensures
     /// that those lookups are not a problem. If the credential updates are
@@ -2020,6 +2109,290 @@ pub mod construct {
         }
     }
 
+    /// A transaction that is in the process of being signed by several key
+    /// holders. This is the format that is passed between the holders of
+    /// the keys of an account whose access structure requires more than one
+    /// signature. Each holder adds their signatures with
+    /// [`sign`](Self::sign), and the partial transactions of different
+    /// holders are combined with [`merge`](Self::merge). Once enough
+    /// signatures have been collected [`finalize`](Self::finalize) produces a
+    /// transaction that can be sent to the chain.
+    ///
+    /// Note that the energy of the transaction is fixed when it is
+    /// constructed, so the `num_sigs` that is supplied to the constructor
+    /// functions in this module must be the number of signatures of the
+    /// finalized transaction.
+    #[derive(Debug, Clone, SerdeSerialize, SerdeDeserialize)]
+    #[serde(
+        try_from = "partially_signed_json::PartiallySignedTransaction",
+        into = "partially_signed_json::PartiallySignedTransaction"
+    )]
+    pub struct PartiallySignedTransaction {
+        pub header:  TransactionHeader,
+        pub payload: EncodedPayload,
+        /// The signatures collected so far. There are signatures for at most
+        /// 255 credentials, and between 1 and 255 signatures per credential.
+        signatures:  BTreeMap<CredentialIndex, BTreeMap<KeyIndex, Signature>>,
+    }
+
+    #[derive(Debug, Error)]
+    /// An error that can occur when combining or finalizing a
+    /// [`PartiallySignedTransaction`].
+    pub enum PartialSignatureError {
+        #[error("The signatures are on a different transaction.")]
+        TransactionMismatch,
+        #[error("Conflicting signatures for key {key} of credential {credential}.")]
+        ConflictingSignature {
+            credential: CredentialIndex,
+            key:        KeyIndex,
+        },
+        #[error("Key {key} of credential {credential} is not part of the access structure.")]
+        UnknownKey {
+            credential: CredentialIndex,
+            key:        KeyIndex,
+        },
+        #[error("The signature by key {key} of credential {credential} is not valid.")]
+        InvalidSignature {
+            credential: CredentialIndex,
+            key:        KeyIndex,
+        },
+        #[error("The signatures do not satisfy the thresholds of the access structure.")]
+        ThresholdNotMet,
+        #[error("At most 255 credentials, each with at most 255 signatures, are allowed.")]
+        TooManySignatures,
+    }
+
+    impl From<PreAccountTransaction> for PartiallySignedTransaction {
+        fn from(value: PreAccountTransaction) -> Self { Self::new(value.header, value.encoded) }
+    }
+
+    impl PartiallySignedTransaction {
+        /// Construct a transaction with no signatures.
+        pub fn new(header: TransactionHeader, payload: EncodedPayload) -> Self {
+            Self {
+                header,
+                payload,
+                signatures: BTreeMap::new(),
+            }
+        }
+
+        /// The hash that the key holders sign.
+        pub fn hash_to_sign(&self) -> hashes::TransactionSignHash {
+            compute_transaction_sign_hash(&self.header, &self.payload)
+        }
+
+        /// The signatures collected so far. This may be empty.
+        pub fn signatures(&self) -> &BTreeMap<CredentialIndex, BTreeMap<KeyIndex, Signature>> {
+            &self.signatures
+        }
+
+        /// The total number of signatures collected so far.
+        pub fn num_signatures(&self) -> u32 {
+            self.signatures.values().map(|sigs| sigs.len() as u32).sum()
+        }
+
+        /// Sign the transaction with the provided signer and add the
+        /// signatures to the ones already collected.
+        pub fn sign(
+            &mut self,
+            signer: &impl TransactionSigner,
+        ) -> Result<(), PartialSignatureError> {
+            let signature = signer.sign_transaction_hash(&self.hash_to_sign());
+            self.add_signatures(signature.signatures)
+        }
+
+        /// Add signatures that were produced on
+        /// [`hash_to_sign`](Self::hash_to_sign) by some other means. Adding a
+        /// signature that is already present has no effect, but adding a
+        /// different signature for a key that has already signed is an error.
+        /// Credentials without any signatures are ignored. In case of an error
+        /// no signatures are added.
+        pub fn add_signatures(
+            &mut self,
+            mut signatures: BTreeMap<CredentialIndex, BTreeMap<KeyIndex, Signature>>,
+        ) -> Result<(), PartialSignatureError> {
+            signatures.retain(|_, sigs| !sigs.is_empty());
+            let mut num_creds = self.signatures.len();
+            for (credential, sigs) in signatures.iter() {
+                let num_sigs = if let Some(existing) = self.signatures.get(credential) {
+                    for (key, sig) in sigs {
+                        if existing.get(key).is_some_and(|e| e != sig) {
+                            return Err(PartialSignatureError::ConflictingSignature {
+                                credential: *credential,
+                                key:        *key,
+                            });
+                        }
+                    }
+                    existing.len() + sigs.keys().filter(|k| !existing.contains_key(*k)).count()
+                } else {
+                    num_creds += 1;
+                    sigs.len()
+                };
+                if num_sigs > u8::MAX.into() {
+                    return Err(PartialSignatureError::TooManySignatures);
+                }
+            }
+            if num_creds > u8::MAX.into() {
+                return Err(PartialSignatureError::TooManySignatures);
+            }
+            for (credential, sigs) in signatures {
+                self.signatures.entry(credential).or_default().extend(sigs);
+            }
+            Ok(())
+        }
+
+        /// Combine the signatures of another partially signed version of the
+        /// same transaction with the signatures of this one.
+        pub fn merge(
+            &mut self,
+            other: PartiallySignedTransaction,
+        ) -> Result<(), PartialSignatureError> {
+            if self.hash_to_sign() != other.hash_to_sign() {
+                return Err(PartialSignatureError::TransactionMismatch);
+            }
+            self.add_signatures(other.signatures)
+        }
+
+        /// Check that each of the collected signatures is a valid signature
+        /// by a key of the access structure. This does not check whether
+        /// there are enough signatures, see [`is_complete`](Self::is_complete)
+        /// for that.
+        pub fn verify_signatures(
+            &self,
+            keys: &impl HasAccountAccessStructure,
+        ) -> Result<(), PartialSignatureError> {
+            let hash = self.hash_to_sign();
+            for (&credential, sigs) in self.signatures.iter() {
+                for (&key, sig) in sigs {
+                    let pk = keys
+                        .credential_keys(credential)
+                        .and_then(|cred_keys| cred_keys.get(key))
+                        .ok_or(PartialSignatureError::UnknownKey { credential, key })?;
+                    if !pk.verify(&hash, sig) {
+                        return Err(PartialSignatureError::InvalidSignature { credential, key });
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Check whether the collected signatures are valid and satisfy the
+        /// thresholds of the access structure, so that the transaction can be
+        /// [finalized](Self::finalize).
+        pub fn is_complete(&self, keys: &impl HasAccountAccessStructure) -> bool {
+            verify_signature_transaction_sign_hash(
+                keys,
+                &self.hash_to_sign(),
+                &TransactionSignature {
+                    signatures: self.signatures.clone(),
+                },
+            )
+        }
+
+        /// Construct the signed transaction, checking that the collected
+        /// signatures are valid and satisfy the access structure.
+        pub fn finalize(
+            self,
+            keys: &impl HasAccountAccessStructure,
+        ) -> Result<AccountTransaction<EncodedPayload>, PartialSignatureError> {
+            self.verify_signatures(keys)?;
+            let signature = TransactionSignature {
+                signatures: self.signatures,
+            };
+            let hash = compute_transaction_sign_hash(&self.header, &self.payload);
+            if !verify_signature_transaction_sign_hash(keys, &hash, &signature) {
+                return Err(PartialSignatureError::ThresholdNotMet);
+            }
+            Ok(AccountTransaction {
+                signature,
+                header: self.header,
+                payload: self.payload,
+            })
+        }
+    }
+
+    /// The header and payload are serialized as in [`PreAccountTransaction`],
+    /// followed by the signatures. Unlike [`TransactionSignature`] the
+    /// signatures are allowed to be empty.
+    impl Serial for PartiallySignedTransaction {
+        fn serial<B: Buffer>(&self, out: &mut B) {
+            self.header.serial(out);
+            self.payload.serial(out);
+            u8::try_from(self.signatures.len())
+                .expect("There are signatures for at most 255 credentials.")
+                .serial(out);
+            for (credential, sigs) in self.signatures.iter() {
+                credential.serial(out);
+                u8::try_from(sigs.len())
+                    .expect("There are at most 255 signatures per credential.")
+                    .serial(out);
+                common::serial_map_no_length(sigs, out);
+            }
+        }
+    }
+
+    impl Deserial for PartiallySignedTransaction {
+        fn deserial<R: ReadBytesExt>(source: &mut R) -> ParseResult<Self> {
+            let header: TransactionHeader = source.get()?;
+            let payload = get_encoded_payload(source, header.payload_size)?;
+            let num_creds: u8 = source.get()?;
+            let mut signatures = BTreeMap::new();
+            let mut last = None;
+            for _ in 0..num_creds {
+                let credential = source.get()?;
+                anyhow::ensure!(
+                    last < Some(credential),
+                    "Credential indices must be strictly increasing."
+                );
+                last = Some(credential);
+                let num_sigs: u8 = source.get()?;
+                anyhow::ensure!(
+                    num_sigs > 0,
+                    "Each credential must have at least one signature."
+                );
+                let sigs = common::deserial_map_no_length(source, num_sigs.into())?;
+                signatures.insert(credential, sigs);
+            }
+            Ok(Self {
+                header,
+                payload,
+                signatures,
+            })
+        }
+    }
+
+    mod partially_signed_json {
+        use super::*;
+
+        #[derive(SerdeSerialize, SerdeDeserialize)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PartiallySignedTransaction {
+            header:     TransactionHeader,
+            payload:    EncodedPayload,
+            signatures: BTreeMap<CredentialIndex, BTreeMap<KeyIndex, Signature>>,
+        }
+
+        impl From<super::PartiallySignedTransaction> for PartiallySignedTransaction {
+            fn from(value: super::PartiallySignedTransaction) -> Self {
+                Self {
+                    header:     value.header,
+                    payload:    value.payload,
+                    signatures: value.signatures,
+                }
+            }
+        }
+
+        impl TryFrom<PartiallySignedTransaction> for super::PartiallySignedTransaction {
+            type Error = PartialSignatureError;
+
+            fn try_from(value: PartiallySignedTransaction) -> Result<Self, Self::Error> {
+                let mut tx = super::PartiallySignedTransaction::new(value.header, value.payload);
+                tx.add_signatures(value.signatures)?;
+                Ok(tx)
+            }
+        }
+    }
+
     /// Helper structure to store the intermediate state of a transaction.
     /// The problem this helps solve is that to compute the exact energy
     /// requirements for the transaction we need to know its exact size when
@@ -3183,6 +3556,262 @@ pub mod send {
     }
 }
 
+/// Checks of the validity of transactions in a given protocol version. These
+/// are the checks that can be done without access to the state of the chain,
+/// so that transactions that are certain to fail can be rejected before they
+/// are submitted.
+pub mod validity {
+    use super::*;
+    use crate::{smart_contracts::WasmVersion, updates::UpdateInstruction};
+
+    #[derive(Debug, Clone, PartialEq, Eq, Error)]
+    /// A rule that a transaction violates.
+    pub enum ValidityViolation {
+        #[error(
+            "Transactions of type {transaction_type} are not supported in protocol version \
+             {protocol_version}."
+        )]
+        UnsupportedTransactionType {
+            transaction_type: TransactionType,
+            protocol_version: ProtocolVersion,
+        },
+        #[error(
+            "Modules of version {version} are not supported in protocol version \
+             {protocol_version}."
+        )]
+        UnsupportedModuleVersion {
+            version:          WasmVersion,
+            protocol_version: ProtocolVersion,
+        },
+        #[error("The module is {size} bytes, but at most {max} bytes are allowed.")]
+        ModuleTooLarge { size: u64, max: u64 },
+        #[error("The parameter is {size} bytes, but at most {max} bytes are allowed.")]
+        ParameterTooLarge { size: usize, max: usize },
+        #[error("The release schedule is empty.")]
+        EmptySchedule,
+        #[error("The release schedule has {length} releases, but at most {max} are allowed.")]
+        ScheduleTooLong { length: usize, max: usize },
+        #[error("The release times of the schedule are not strictly increasing.")]
+        ScheduleNotIncreasing,
+        #[error("The release schedule contains a release of 0 CCD.")]
+        ZeroScheduledRelease,
+        #[error("The payload cannot be decoded: {0}")]
+        InvalidPayload(String),
+        #[error("The expiry time {} is in the past.", .expiry.seconds)]
+        Expired { expiry: TransactionTime },
+        #[error("The energy {energy} is less than the minimum {minimum} for the transaction.")]
+        InsufficientEnergy { energy: Energy, minimum: Energy },
+    }
+
+    /// Whether transactions of the given type are supported in the protocol
+    /// version.
+    pub fn transaction_type_supported(
+        transaction_type: TransactionType,
+        protocol_version: ProtocolVersion,
+    ) -> bool {
+        use ProtocolVersion::*;
+        use TransactionType::*;
+        match transaction_type {
+            AddBaker
+            | RemoveBaker
+            | UpdateBakerStake
+            | UpdateBakerRestakeEarnings
+            | UpdateBakerKeys => protocol_version <= P3,
+            ConfigureBaker | ConfigureDelegation => protocol_version >= P4,
+            TransferWithMemo | TransferWithScheduleAndMemo => protocol_version >= P2,
+            EncryptedAmountTransferWithMemo => P2 <= protocol_version && protocol_version <= P6,
+            // Encrypting amounts and transferring encrypted amounts is disabled from protocol
+            // version 7. Decrypting amounts is still possible.
+            EncryptedAmountTransfer | TransferToEncrypted => protocol_version <= P6,
+            DeployModule | InitContract | Update | Transfer | UpdateCredentialKeys
+            | TransferToPublic | TransferWithSchedule | UpdateCredentials | RegisterData => true,
+        }
+    }
+
+    fn check_parameter(
+        parameter: &[u8],
+        protocol_version: ProtocolVersion,
+        violations: &mut Vec<ValidityViolation>,
+    ) {
+        let max = if protocol_version <= ProtocolVersion::P4 {
+            MAX_PARAMETER_LEN_V0
+        } else {
+            MAX_PARAMETER_LEN
+        };
+        if parameter.len() > max {
+            violations.push(ValidityViolation::ParameterTooLarge {
+                size: parameter.len(),
+                max,
+            });
+        }
+    }
+
+    fn check_schedule(schedule: &[(Timestamp, Amount)], violations: &mut Vec<ValidityViolation>) {
+        if schedule.is_empty() {
+            violations.push(ValidityViolation::EmptySchedule);
+        }
+        if schedule.len() > MAX_SCHEDULE_LENGTH {
+            violations.push(ValidityViolation::ScheduleTooLong {
+                length: schedule.len(),
+                max:    MAX_SCHEDULE_LENGTH,
+            });
+        }
+        if schedule.windows(2).any(|w| w[0].0 >= w[1].0) {
+            violations.push(ValidityViolation::ScheduleNotIncreasing);
+        }
+        if schedule.iter().any(|(_, amount)| *amount == Amount::zero()) {
+            violations.push(ValidityViolation::ZeroScheduledRelease);
+        }
+    }
+
+    impl Payload {
+        /// Check the payload against the rules of the given protocol version.
+        /// All the rules that the payload violates are returned.
+        pub fn validate_for_protocol(
+            &self,
+            protocol_version: ProtocolVersion,
+        ) -> Result<(), Vec<ValidityViolation>> {
+            let mut violations = Vec::new();
+            self.check_for_protocol(protocol_version, &mut violations);
+            if violations.is_empty() {
+                Ok(())
+            } else {
+                Err(violations)
+            }
+        }
+
+        fn check_for_protocol(
+            &self,
+            protocol_version: ProtocolVersion,
+            violations: &mut Vec<ValidityViolation>,
+        ) {
+            let transaction_type = self.transaction_type();
+            if !transaction_type_supported(transaction_type, protocol_version) {
+                violations.push(ValidityViolation::UnsupportedTransactionType {
+                    transaction_type,
+                    protocol_version,
+                });
+            }
+            match self {
+                Payload::DeployModule { module } => {
+                    let max = match module.version {
+                        WasmVersion::V0 => MAX_WASM_MODULE_SIZE_V0,
+                        WasmVersion::V1 => {
+                            if protocol_version < ProtocolVersion::P4 {
+                                violations.push(ValidityViolation::UnsupportedModuleVersion {
+                                    version: module.version,
+                                    protocol_version,
+                                });
+                            }
+                            MAX_WASM_MODULE_SIZE
+                        }
+                    };
+                    let size = module.source.size();
+                    if size > u64::from(max) {
+                        violations.push(ValidityViolation::ModuleTooLarge {
+                            size,
+                            max: max.into(),
+                        });
+                    }
+                }
+                Payload::InitContract { payload } => {
+                    check_parameter(payload.param.as_ref(), protocol_version, violations)
+                }
+                Payload::Update { payload } => {
+                    check_parameter(payload.message.as_ref(), protocol_version, violations)
+                }
+                Payload::TransferWithSchedule { schedule, .. }
+                | Payload::TransferWithScheduleAndMemo { schedule, .. } => {
+                    check_schedule(schedule, violations)
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn check_expiry(
+        expiry: TransactionTime,
+        now: TransactionTime,
+        violations: &mut Vec<ValidityViolation>,
+    ) {
+        if expiry < now {
+            violations.push(ValidityViolation::Expired { expiry });
+        }
+    }
+
+    impl AccountTransaction<EncodedPayload> {
+        /// Check the transaction against the rules of the given protocol
+        /// version, at the time `now`. This checks the payload, that the
+        /// transaction has not expired, and that the energy covers at least
+        /// the [base cost](cost::base_cost) of the transaction.
+        pub fn validate_for_protocol(
+            &self,
+            protocol_version: ProtocolVersion,
+            now: TransactionTime,
+        ) -> Result<(), Vec<ValidityViolation>> {
+            let mut violations = Vec::new();
+            self.check_for_protocol(protocol_version, now, &mut violations);
+            if violations.is_empty() {
+                Ok(())
+            } else {
+                Err(violations)
+            }
+        }
+
+        fn check_for_protocol(
+            &self,
+            protocol_version: ProtocolVersion,
+            now: TransactionTime,
+            violations: &mut Vec<ValidityViolation>,
+        ) {
+            match self.payload.decode() {
+                Ok(payload) => payload.check_for_protocol(protocol_version, violations),
+                Err(e) => violations.push(ValidityViolation::InvalidPayload(e.to_string())),
+            }
+            check_expiry(self.header.expiry, now, violations);
+            let size =
+                construct::TRANSACTION_HEADER_SIZE + u64::from(self.header.payload_size.size);
+            let minimum = cost::base_cost(size, self.signature.num_signatures());
+            if self.header.energy_amount < minimum {
+                violations.push(ValidityViolation::InsufficientEnergy {
+                    energy: self.header.energy_amount,
+                    minimum,
+                });
+            }
+        }
+    }
+
+    impl BlockItem<EncodedPayload> {
+        /// Check the block item against the rules of the given protocol
+        /// version, at the time `now`. For account transactions this is
+        /// [`AccountTransaction::validate_for_protocol`]. For credential
+        /// deployments and update instructions only the expiry is checked.
+        pub fn validate_for_protocol(
+            &self,
+            protocol_version: ProtocolVersion,
+            now: TransactionTime,
+        ) -> Result<(), Vec<ValidityViolation>> {
+            let mut violations = Vec::new();
+            match self {
+                BlockItem::AccountTransaction(at) => {
+                    at.check_for_protocol(protocol_version, now, &mut violations)
+                }
+                BlockItem::CredentialDeployment(acm) => {
+                    check_expiry(acm.message_expiry, now, &mut violations)
+                }
+                BlockItem::UpdateInstruction(UpdateInstruction { header, .. }) => {
+                    check_expiry(header.timeout, now, &mut violations)
+                }
+            }
+            if violations.is_empty() {
+                Ok(())
+            } else {
+                Err(violations)
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -3242,4 +3871,190 @@ mod tests {
             "Transaction signature must not validate with invalid threshold."
         );
     }
+
+    #[test]
+    fn test_partially_signed_transaction() {
+        let mut rng = rand::thread_rng();
+        let holder_0 = BTreeMap::from([(
+            CredentialIndex::from(0),
+            BTreeMap::from([(KeyIndex::from(0), KeyPair::generate(&mut rng))]),
+        )]);
+        let holder_1 = BTreeMap::from([(
+            CredentialIndex::from(1),
+            BTreeMap::from([(KeyIndex::from(0), KeyPair::generate(&mut rng))]),
+        )]);
+        let keys = holder_0
+            .iter()
+            .chain(holder_1.iter())
+            .map(|(&ci, keys)| {
+                let keys = keys
+                    .iter()
+                    .map(|(&ki, kp)| (ki, VerifyKey::from(kp)))
+                    .collect();
+                (ci, CredentialPublicKeys {
+                    keys,
+                    threshold: SignatureThreshold::ONE,
+                })
+            })
+            .collect::<BTreeMap<_, _>>();
+        let access_structure = AccountAccessStructure {
+            threshold: AccountThreshold::try_from(2u8).unwrap(),
+            keys,
+        };
+        let pre = construct::transfer(
+            2,
+            AccountAddress([1u8; 32]),
+            Nonce::from(1),
+            TransactionTime::from_seconds(1000),
+            AccountAddress([2u8; 32]),
+            Amount::from_micro_ccd(10),
+        );
+        let mut partial_0 = construct::PartiallySignedTransaction::from(pre.clone());
+        let mut partial_1 = construct::PartiallySignedTransaction::from(pre);
+        partial_0.sign(&holder_0).expect("Signing succeeds.");
+        partial_1.sign(&holder_1).expect("Signing succeeds.");
+        let partial_1 = crate::common::serialize_deserialize(&partial_1)
+            .expect("Partially signed transaction can be deserialized.");
+        partial_1
+            .verify_signatures(&access_structure)
+            .expect("Each signature is valid.");
+        assert!(
+            !partial_1.is_complete(&access_structure),
+            "A single signature is not enough."
+        );
+        assert!(matches!(
+            partial_0.clone().finalize(&access_structure),
+            Err(construct::PartialSignatureError::ThresholdNotMet)
+        ));
+        partial_0.merge(partial_1).expect("Merging succeeds.");
+        assert_eq!(partial_0.num_signatures(), 2);
+        let tx = partial_0
+            .finalize(&access_structure)
+            .expect("Finalization succeeds.");
+        assert!(tx.verify_transaction_signature(&access_structure));
+    }
+
+    #[test]
+    fn test_partially_signed_transaction_too_many_signatures() {
+        let pre = construct::transfer(
+            1,
+            AccountAddress([1u8; 32]),
+            Nonce::from(1),
+            TransactionTime::from_seconds(1000),
+            AccountAddress([2u8; 32]),
+            Amount::from_micro_ccd(10),
+        );
+        let signatures = |credentials: std::ops::RangeInclusive<u8>| {
+            credentials
+                .map(|ci| {
+                    (
+                        CredentialIndex::from(ci),
+                        BTreeMap::from([(KeyIndex::from(0), Signature { sig: vec![ci] })]),
+                    )
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        let mut partial = construct::PartiallySignedTransaction::from(pre);
+        partial
+            .add_signatures(signatures(0..=254))
+            .expect("255 credentials are allowed.");
+        assert!(matches!(
+            partial.add_signatures(signatures(255..=255)),
+            Err(construct::PartialSignatureError::TooManySignatures)
+        ));
+        assert_eq!(partial.num_signatures(), 255);
+        let partial = crate::common::serialize_deserialize(&partial)
+            .expect("Partially signed transaction can be deserialized.");
+        assert_eq!(partial.num_signatures(), 255);
+
+        let mut json = serde_json::to_value(&partial).expect("Serialization succeeds.");
+        serde_json::from_value::<construct::PartiallySignedTransaction>(json.clone())
+            .expect("255 credentials are accepted when parsing JSON.");
+        json["signatures"]["255"] = serde_json::json!({"0": Signature { sig: vec![255] }});
+        assert!(
+            serde_json::from_value::<construct::PartiallySignedTransaction>(json).is_err(),
+            "More than 255 credentials are rejected when parsing JSON."
+        );
+    }
+
+    #[test]
+    fn test_partially_signed_transaction_empty_signatures() {
+        let pre = construct::transfer(
+            1,
+            AccountAddress([1u8; 32]),
+            Nonce::from(1),
+            TransactionTime::from_seconds(1000),
+            AccountAddress([2u8; 32]),
+            Amount::from_micro_ccd(10),
+        );
+        let mut partial = construct::PartiallySignedTransaction::from(pre);
+        partial
+            .add_signatures(BTreeMap::from([(
+                CredentialIndex::from(0),
+                BTreeMap::new(),
+            )]))
+            .expect("Empty signatures are ignored.");
+        assert!(partial.signatures().is_empty());
+        let partial = crate::common::serialize_deserialize(&partial)
+            .expect("Partially signed transaction can be deserialized.");
+        assert_eq!(partial.num_signatures(), 0);
+    }
+
+    #[test]
+    fn test_validate_for_protocol() {
+        use validity::ValidityViolation;
+        let configure_delegation = Payload::ConfigureDelegation {
+            data: ConfigureDelegationPayload::default(),
+        };
+        assert_eq!(
+            configure_delegation.validate_for_protocol(ProtocolVersion::P3),
+            Err(vec![ValidityViolation::UnsupportedTransactionType {
+                transaction_type: TransactionType::ConfigureDelegation,
+                protocol_version: ProtocolVersion::P3,
+            }])
+        );
+        assert_eq!(
+            configure_delegation.validate_for_protocol(ProtocolVersion::P4),
+            Ok(())
+        );
+        let schedule = Payload::TransferWithSchedule {
+            to:       AccountAddress([1u8; 32]),
+            schedule: vec![
+                (Timestamp::from_timestamp_millis(2), Amount::from_ccd(1)),
+                (Timestamp::from_timestamp_millis(1), Amount::zero()),
+            ],
+        };
+        assert_eq!(
+            schedule.validate_for_protocol(ProtocolVersion::P6),
+            Err(vec![
+                ValidityViolation::ScheduleNotIncreasing,
+                ValidityViolation::ZeroScheduledRelease
+            ])
+        );
+
+        let mut rng = rand::thread_rng();
+        let keys = BTreeMap::from([(
+            CredentialIndex::from(0),
+            BTreeMap::from([(KeyIndex::from(0), KeyPair::generate(&mut rng))]),
+        )]);
+        let tx = construct::transfer(
+            1,
+            AccountAddress([1u8; 32]),
+            Nonce::from(1),
+            TransactionTime::from_seconds(1000),
+            AccountAddress([2u8; 32]),
+            Amount::from_micro_ccd(10),
+        )
+        .sign(&keys);
+        assert_eq!(
+            tx.validate_for_protocol(ProtocolVersion::P6, TransactionTime::from_seconds(500)),
+            Ok(())
+        );
+        assert_eq!(
+            tx.validate_for_protocol(ProtocolVersion::P6, TransactionTime::from_seconds(2000)),
+            Err(vec![ValidityViolation::Expired {
+                expiry: TransactionTime::from_seconds(1000),
+            }])
+        );
+    }
 }
@@ -15,6 +15,7 @@ use crate::{
     transactions::PayloadSize,
 };
 use derive_more::*;
+use thiserror::Error;
 
 #[derive(SerdeSerialize, SerdeDeserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -467,6 +468,194 @@ impl AuthorizationsFamily for ChainParameterVersion2 {
 /// A mapping of chain parameter versions to authorization versions.
 pub type Authorizations<CPV> = <CPV as AuthorizationsFamily>::Output;
 
+/// The level 2 keys, i.e., the keys that authorize updates of chain parameters
+/// as opposed to updates of the keys themselves.
+pub trait Level2Authorizations {
+    /// The list of all level 2 keys. Access structures refer to keys by their
+    /// index in this list.
+    fn keys(&self) -> &[UpdatePublicKey];
+
+    /// The access structure that authorizes updates of the given type, or
+    /// [`None`] if updates of this type are not authorized by level 2 keys in
+    /// this version of the authorizations.
+    fn access_structure(&self, update_type: UpdateType) -> Option<&AccessStructure>;
+}
+
+impl Level2Authorizations for AuthorizationsV0 {
+    fn keys(&self) -> &[UpdatePublicKey] { &self.keys }
+
+    fn access_structure(&self, update_type: UpdateType) -> Option<&AccessStructure> {
+        use UpdateType::*;
+        match update_type {
+            UpdateProtocol => Some(&self.protocol),
+            UpdateElectionDifficulty => Some(&self.election_difficulty),
+            UpdateEuroPerEnergy => Some(&self.euro_per_energy),
+            UpdateMicroGTUPerEuro => Some(&self.micro_gtu_per_euro),
+            UpdateFoundationAccount => Some(&self.foundation_account),
+            UpdateMintDistribution => Some(&self.mint_distribution),
+            UpdateTransactionFeeDistribution => Some(&self.transaction_fee_distribution),
+            UpdateGASRewards => Some(&self.param_gas_rewards),
+            UpdatePoolParameters => Some(&self.pool_parameters),
+            UpdateAddAnonymityRevoker => Some(&self.add_anonymity_revoker),
+            UpdateAddIdentityProvider => Some(&self.add_identity_provider),
+            UpdateRootKeys
+            | UpdateLevel1Keys
+            | UpdateLevel2Keys
+            | UpdateCooldownParameters
+            | UpdateTimeParameters
+            | UpdateGASRewardsCPV2
+            | UpdateTimeoutParameters
+            | UpdateMinBlockTime
+            | UpdateBlockEnergyLimit
+            | UpdateFinalizationCommitteeParameters => None,
+        }
+    }
+}
+
+impl Level2Authorizations for AuthorizationsV1 {
+    fn keys(&self) -> &[UpdatePublicKey] { &self.v0.keys }
+
+    /// In chain parameter version 2 the consensus parameters (timeout
+    /// parameters, minimum block time and block energy limit) are authorized by
+    /// the keys that authorized election difficulty updates in earlier
+    /// versions, and the finalization committee parameters by the pool
+    /// parameter keys.
+    fn access_structure(&self, update_type: UpdateType) -> Option<&AccessStructure> {
+        use UpdateType::*;
+        match update_type {
+            UpdateCooldownParameters => Some(&self.cooldown_parameters),
+            UpdateTimeParameters => Some(&self.time_parameters),
+            UpdateGASRewardsCPV2 => Some(&self.v0.param_gas_rewards),
+            UpdateTimeoutParameters | UpdateMinBlockTime | UpdateBlockEnergyLimit => {
+                Some(&self.v0.election_difficulty)
+            }
+            UpdateFinalizationCommitteeParameters => Some(&self.v0.pool_parameters),
+            other => self.v0.access_structure(other),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+/// An error that occurs when the signatures on an update instruction do not
+/// satisfy the access structure that authorizes the update.
+pub enum UpdateSignatureError {
+    #[error("The signatures are on a different update instruction.")]
+    UpdateMismatch,
+    #[error("Conflicting signatures for update key {key}.")]
+    ConflictingSignature { key: UpdateKeysIndex },
+    #[error("There is no update key with index {key}.")]
+    UnknownKey { key: UpdateKeysIndex },
+    #[error("Update key {key} is not authorized to sign updates of type {update_type:?}.")]
+    UnauthorizedKey {
+        key:         UpdateKeysIndex,
+        update_type: UpdateType,
+    },
+    #[error("The signature by update key {key} is not valid.")]
+    InvalidSignature { key: UpdateKeysIndex },
+    #[error("The update has {actual} valid signatures, but {required} are required.")]
+    ThresholdNotMet { required: u16, actual: u16 },
+    #[error("Updates of type {0:?} are not authorized by the supplied keys.")]
+    UnsupportedUpdateType(UpdateType),
+}
+
+/// Check that the `signatures` on `hash` are valid signatures by the keys in
+/// `keys` with the given indices, that all of these keys are `authorized`, and
+/// that there are at least `threshold` of them.
+fn verify_update_signatures_worker(
+    keys: &[UpdatePublicKey],
+    authorized: impl Fn(UpdateKeysIndex) -> Result<(), UpdateSignatureError>,
+    threshold: UpdateKeysThreshold,
+    hash: &hashes::UpdateSignHash,
+    signatures: &BTreeMap<UpdateKeysIndex, Signature>,
+) -> Result<(), UpdateSignatureError> {
+    for (&key, sig) in signatures {
+        let pk = keys
+            .get(usize::from(key.index))
+            .ok_or(UpdateSignatureError::UnknownKey { key })?;
+        authorized(key)?;
+        if !pk.public.verify(hash, sig) {
+            return Err(UpdateSignatureError::InvalidSignature { key });
+        }
+    }
+    let required = u16::from(threshold);
+    // There are at most 2^16 key indices, so the number of signatures fits.
+    let actual = signatures.len() as u16;
+    if actual < required {
+        return Err(UpdateSignatureError::ThresholdNotMet { required, actual });
+    }
+    Ok(())
+}
+
+impl<Kind> HigherLevelAccessStructure<Kind> {
+    /// Check that the signatures on the update sign hash are valid and meet
+    /// the threshold of this access structure.
+    pub fn verify_update_signatures(
+        &self,
+        hash: &hashes::UpdateSignHash,
+        signatures: &BTreeMap<UpdateKeysIndex, Signature>,
+    ) -> Result<(), UpdateSignatureError> {
+        verify_update_signatures_worker(&self.keys, |_| Ok(()), self.threshold, hash, signatures)
+    }
+}
+
+#[derive(Debug, SerdeSerialize, SerdeDeserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+/// All the keys that can authorize chain updates. The level 2 keys are either
+/// [`AuthorizationsV0`] or [`AuthorizationsV1`], depending on the chain
+/// parameter version.
+pub struct UpdateKeysCollection<Auths> {
+    /// Keys that authorize updates of the root keys and the keys at the
+    /// lower levels.
+    pub root_keys:    HigherLevelAccessStructure<RootKeysKind>,
+    #[serde(rename = "level1Keys")]
+    /// Keys that authorize updates of the level 1 and level 2 keys.
+    pub level_1_keys: HigherLevelAccessStructure<Level1KeysKind>,
+    #[serde(rename = "level2Keys")]
+    /// Keys that authorize updates of chain parameters.
+    pub level_2_keys: Auths,
+}
+
+impl<Auths: Level2Authorizations> UpdateKeysCollection<Auths> {
+    /// Check that the signatures on the update sign hash of an update with the
+    /// given payload satisfy the access structure for the payload. Root
+    /// updates are authorized by the root keys, level 1 updates by the level
+    /// 1 keys, and all other updates by the level 2 keys for the update type.
+    pub fn verify_update_signatures(
+        &self,
+        payload: &UpdatePayload,
+        hash: &hashes::UpdateSignHash,
+        signatures: &BTreeMap<UpdateKeysIndex, Signature>,
+    ) -> Result<(), UpdateSignatureError> {
+        match payload {
+            UpdatePayload::Root(_) => self.root_keys.verify_update_signatures(hash, signatures),
+            UpdatePayload::Level1(_) => {
+                self.level_1_keys.verify_update_signatures(hash, signatures)
+            }
+            _ => {
+                let update_type = payload.update_type();
+                let access_structure = self
+                    .level_2_keys
+                    .access_structure(update_type)
+                    .ok_or(UpdateSignatureError::UnsupportedUpdateType(update_type))?;
+                let authorized = |key| {
+                    if access_structure.authorized_keys.contains(&key) {
+                        Ok(())
+                    } else {
+                        Err(UpdateSignatureError::UnauthorizedKey { key, update_type })
+                    }
+                };
+                verify_update_signatures_worker(
+                    self.level_2_keys.keys(),
+                    authorized,
+                    access_structure.threshold,
+                    hash,
+                    signatures,
+                )
+            }
+        }
+    }
+}
+
 #[derive(SerdeSerialize, SerdeDeserialize, common::Serialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 /// Parameters related to becoming a baker that apply to protocol versions 1-3.
@@ -677,7 +866,9 @@ pub enum UpdatePayload {
     FinalizationCommitteeParametersCPV2(FinalizationCommitteeParameters),
 }
 
-#[derive(SerdeSerialize, SerdeDeserialize, Debug, Clone, Copy)]
+#[derive(
+    SerdeSerialize, SerdeDeserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
 #[serde(rename_all = "camelCase")]
 // Since all variants are fieldless, the default JSON serialization will convert
 // all the variants to simple strings.
@@ -784,6 +975,27 @@ pub trait UpdateSigner {
         -> UpdateInstructionSignature;
 }
 
+#[cfg(feature = "async")]
+/// An asynchronous variant of [`UpdateSigner`] for keys that are held outside
+/// the process, e.g., by a signing service. The signer is given the header
+/// and the payload of the update instead of only the hash, so that it can
+/// inspect the update before signing it.
+///
+/// Every [`UpdateSigner`] is also an [`AsyncUpdateSigner`] that never fails.
+#[async_trait::async_trait]
+pub trait AsyncUpdateSigner: Sync {
+    /// The error returned when the update could not be signed.
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    /// Sign the update with the given header and payload, allocating and
+    /// returning the signatures.
+    async fn sign_update(
+        &self,
+        header: &UpdateHeader,
+        payload: &UpdatePayload,
+    ) -> Result<UpdateInstructionSignature, Self::Error>;
+}
+
 impl UpdateSigner for &BTreeMap<UpdateKeysIndex, UpdateKeyPair> {
     fn sign_update_hash(
         &self,
@@ -810,7 +1022,8 @@ impl UpdateSigner for &[(UpdateKeysIndex, UpdateKeyPair)] {
     }
 }
 
-#[derive(Debug, Clone, Copy, common::Serialize)]
+#[derive(Debug, Clone, Copy, common::Serialize, SerdeSerialize, SerdeDeserialize)]
+#[serde(rename_all = "camelCase")]
 /// A header common to all update instructions.
 pub struct UpdateHeader {
     /// Sequence number of the update. Each update queue maintains its own
@@ -883,6 +1096,154 @@ pub mod update {
             signatures,
         }
     }
+
+    #[cfg(feature = "async")]
+    #[async_trait::async_trait]
+    impl<S: UpdateSigner + Sync> AsyncUpdateSigner for S {
+        type Error = std::convert::Infallible;
+
+        async fn sign_update(
+            &self,
+            header: &UpdateHeader,
+            payload: &UpdatePayload,
+        ) -> Result<UpdateInstructionSignature, Self::Error> {
+            Ok(self.sign_update_hash(&compute_sign_hash(header, &common::to_bytes(payload))))
+        }
+    }
+
+    #[cfg(feature = "async")]
+    /// Construct an update instruction and sign it with an asynchronous
+    /// signer. This is the asynchronous variant of [`update`].
+    pub async fn update_async<S: AsyncUpdateSigner>(
+        signer: &S,
+        seq_number: UpdateSequenceNumber,
+        effective_time: TransactionTime,
+        timeout: TransactionTime,
+        payload: UpdatePayload,
+    ) -> Result<UpdateInstruction, S::Error> {
+        let header = UpdateHeader {
+            seq_number,
+            effective_time,
+            timeout,
+            payload_size: PayloadSize {
+                size: common::to_bytes(&payload).len() as u32,
+            },
+        };
+        let signatures = signer.sign_update(&header, &payload).await?;
+        Ok(UpdateInstruction {
+            header,
+            payload,
+            signatures,
+        })
+    }
+
+    /// An update instruction that is in the process of being signed by
+    /// several governance key holders. An unsigned instruction is
+    /// constructed with [`prepare`], each key holder adds their signatures
+    /// with [`sign`](Self::sign), the partial instructions are combined with
+    /// [`merge`](Self::merge), and [`finalize`](Self::finalize) produces the
+    /// update instruction once the signatures satisfy the access structure
+    /// for the update.
+    #[derive(Debug, Clone, SerdeSerialize, SerdeDeserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PartiallySignedUpdate {
+        pub header:     UpdateHeader,
+        pub payload:    UpdatePayload,
+        /// The signatures collected so far. This may be empty.
+        pub signatures: BTreeMap<UpdateKeysIndex, Signature>,
+    }
+
+    /// Construct an update instruction with no signatures.
+    pub fn prepare(
+        seq_number: UpdateSequenceNumber,
+        effective_time: TransactionTime,
+        timeout: TransactionTime,
+        payload: UpdatePayload,
+    ) -> PartiallySignedUpdate {
+        let header = UpdateHeader {
+            seq_number,
+            effective_time,
+            timeout,
+            payload_size: PayloadSize {
+                size: common::to_bytes(&payload).len() as u32,
+            },
+        };
+        PartiallySignedUpdate {
+            header,
+            payload,
+            signatures: BTreeMap::new(),
+        }
+    }
+
+    impl PartiallySignedUpdate {
+        /// The hash that the key holders sign.
+        pub fn hash_to_sign(&self) -> hashes::UpdateSignHash {
+            compute_sign_hash(&self.header, &common::to_bytes(&self.payload))
+        }
+
+        /// Sign the update with the provided signer and add the signatures to
+        /// the ones already collected.
+        pub fn sign(&mut self, signer: impl UpdateSigner) -> Result<(), UpdateSignatureError> {
+            let signatures = signer.sign_update_hash(&self.hash_to_sign());
+            self.add_signatures(signatures.signatures)
+        }
+
+        /// Add signatures that were produced on
+        /// [`hash_to_sign`](Self::hash_to_sign) by some other means. Adding a
+        /// signature that is already present has no effect, but adding a
+        /// different signature for a key that has already signed is an error.
+        /// In case of an error no signatures are added.
+        pub fn add_signatures(
+            &mut self,
+            signatures: BTreeMap<UpdateKeysIndex, Signature>,
+        ) -> Result<(), UpdateSignatureError> {
+            for (key, sig) in signatures.iter() {
+                if self
+                    .signatures
+                    .get(key)
+                    .is_some_and(|existing| existing != sig)
+                {
+                    return Err(UpdateSignatureError::ConflictingSignature { key: *key });
+                }
+            }
+            self.signatures.extend(signatures);
+            Ok(())
+        }
+
+        /// Combine the signatures of another partially signed version of the
+        /// same update with the signatures of this one.
+        pub fn merge(&mut self, other: PartiallySignedUpdate) -> Result<(), UpdateSignatureError> {
+            if self.hash_to_sign() != other.hash_to_sign() {
+                return Err(UpdateSignatureError::UpdateMismatch);
+            }
+            self.add_signatures(other.signatures)
+        }
+
+        /// Check that the collected signatures satisfy the access structure
+        /// for the payload of the update.
+        pub fn verify<Auths: Level2Authorizations>(
+            &self,
+            keys: &UpdateKeysCollection<Auths>,
+        ) -> Result<(), UpdateSignatureError> {
+            keys.verify_update_signatures(&self.payload, &self.hash_to_sign(), &self.signatures)
+        }
+
+        /// Construct the update instruction, checking that the collected
+        /// signatures satisfy the access structure for the payload.
+        pub fn finalize<Auths: Level2Authorizations>(
+            self,
+            keys: &UpdateKeysCollection<Auths>,
+        ) -> Result<UpdateInstruction, UpdateSignatureError> {
+            self.verify(keys)?;
+            Ok(UpdateInstruction {
+                header:     self.header,
+                payload:    self.payload,
+                signatures: UpdateInstructionSignature {
+                    signatures: self.signatures,
+                },
+            })
+        }
+    }
 }
 
 impl Serial for UpdatePayload {
@@ -1039,3 +1400,62 @@ impl Deserial for UpdatePayload {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_partially_signed_update() {
+        let mut rng = rand::thread_rng();
+        let key_pairs = (0..3)
+            .map(|_| UpdateKeyPair::generate(&mut rng))
+            .collect::<Vec<_>>();
+        let level_1_keys = HigherLevelAccessStructure::<Level1KeysKind> {
+            keys:      key_pairs.iter().map(UpdatePublicKey::from).collect(),
+            threshold: UpdateKeysThreshold::try_from(2).unwrap(),
+            _phantom:  Default::default(),
+        };
+        let holder = |i: u16| {
+            BTreeMap::from([(
+                UpdateKeysIndex { index: i },
+                key_pairs[usize::from(i)].clone(),
+            )])
+        };
+        let payload = UpdatePayload::Level1(Level1Update::Level1KeysUpdate(level_1_keys.clone()));
+        let unsigned = update::prepare(
+            UpdateSequenceNumber { number: 1 },
+            TransactionTime::from_seconds(0),
+            TransactionTime::from_seconds(1000),
+            payload,
+        );
+        let mut partial_0 = unsigned.clone();
+        partial_0.sign(&holder(0)).expect("Signing succeeds.");
+        let mut partial_2 = unsigned;
+        partial_2.sign(&holder(2)).expect("Signing succeeds.");
+        assert!(matches!(
+            level_1_keys.verify_update_signatures(&partial_0.hash_to_sign(), &partial_0.signatures),
+            Err(UpdateSignatureError::ThresholdNotMet {
+                required: 2,
+                actual:   1,
+            })
+        ));
+        partial_0.merge(partial_2).expect("Merging succeeds.");
+        level_1_keys
+            .verify_update_signatures(&partial_0.hash_to_sign(), &partial_0.signatures)
+            .expect("Signatures meet the threshold.");
+
+        let mut other_hash = partial_0.clone();
+        other_hash.header.seq_number = UpdateSequenceNumber { number: 2 };
+        assert!(matches!(
+            level_1_keys
+                .verify_update_signatures(&other_hash.hash_to_sign(), &other_hash.signatures),
+            Err(UpdateSignatureError::InvalidSignature { .. })
+        ));
+        assert!(matches!(
+            partial_0.merge(other_hash),
+            Err(UpdateSignatureError::UpdateMismatch)
+        ));
+    }
+}
@@ -1137,6 +1137,116 @@ pub fn impl_deserial(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
     let root = get_root();
     let container_attributes = ContainerAttributes::try_from(ast.attrs.as_slice())?;
 
+    let body_tokens =
+        impl_deserial_body(ast, &container_attributes, &source_ident, &impl_deserial_field)?;
+
+    let (impl_generics, ty_generics, where_clauses) = ast.generics.split_for_impl();
+
+    let where_clauses_tokens =
+        if let Some(attribute_bounds) = container_attributes.deserial_bounds() {
+            attribute_bounds.into_token_stream()
+        } else {
+            // Extend where clauses with Deserial predicate of each generic.
+            let where_clause_deserial: proc_macro2::TokenStream = ast
+                .generics
+                .type_params()
+                .map(|type_param| {
+                    let type_param_ident = &type_param.ident;
+                    quote! (#type_param_ident: #root::Deserial,)
+                })
+                .collect();
+
+            if let Some(where_clauses) = where_clauses {
+                let predicates = &where_clauses.predicates;
+                extend_punctuated(predicates, where_clause_deserial)
+            } else {
+                where_clause_deserial
+            }
+        };
+
+    let gen = quote! {
+        #[automatically_derived]
+        impl #impl_generics #root::Deserial for #data_name #ty_generics where #where_clauses_tokens {
+            fn deserial<#read_ident: #root::Read>(#source_ident: &mut #read_ident) -> #root::ParseResult<Self> {
+                #body_tokens
+            }
+        }
+    };
+    Ok(gen.into())
+}
+
+pub fn impl_deserial_borrowed(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let data_name = &ast.ident;
+
+    let source_ident = Ident::new("________________source", Span::call_site());
+    let root = get_root();
+    let container_attributes = ContainerAttributes::try_from(ast.attrs.as_slice())?;
+    // The lifetime of the slice the value is deserialized from. It must outlive
+    // all lifetimes of the type.
+    let de_lifetime = syn::Lifetime::new("'__de", Span::call_site());
+
+    let body_tokens =
+        impl_deserial_body(ast, &container_attributes, &source_ident, &|field, ident, source| {
+            impl_deserial_borrowed_field(field, ident, source, &de_lifetime)
+        })?;
+
+    let mut impl_generics = ast.generics.clone();
+    let mut de_lifetime_param = syn::LifetimeParam::new(de_lifetime.clone());
+    de_lifetime_param.bounds.extend(ast.generics.lifetimes().map(|param| param.lifetime.clone()));
+    impl_generics.params.insert(0, syn::GenericParam::Lifetime(de_lifetime_param));
+    let (impl_generics, ..) = impl_generics.split_for_impl();
+    let (_, ty_generics, where_clauses) = ast.generics.split_for_impl();
+
+    let where_clauses_tokens =
+        if let Some(attribute_bounds) = container_attributes.deserial_bounds() {
+            attribute_bounds.into_token_stream()
+        } else {
+            // Extend where clauses with DeserialBorrowed predicate of each generic.
+            let where_clause_deserial: proc_macro2::TokenStream = ast
+                .generics
+                .type_params()
+                .map(|type_param| {
+                    let type_param_ident = &type_param.ident;
+                    quote! (#type_param_ident: #root::DeserialBorrowed<#de_lifetime>,)
+                })
+                .collect();
+
+            if let Some(where_clauses) = where_clauses {
+                let predicates = &where_clauses.predicates;
+                extend_punctuated(predicates, where_clause_deserial)
+            } else {
+                where_clause_deserial
+            }
+        };
+
+    let gen = quote! {
+        #[automatically_derived]
+        impl #impl_generics #root::DeserialBorrowed<#de_lifetime> for #data_name #ty_generics where #where_clauses_tokens {
+            fn deserial_borrowed(#source_ident: &mut #root::Cursor<&#de_lifetime [u8]>) -> #root::ParseResult<Self> {
+                #body_tokens
+            }
+        }
+    };
+    Ok(gen.into())
+}
+
+/// Generate the body of the deserialization function of a struct or enum,
+/// reading from `source_ident`. The deserialization of the fields is generated
+/// by `deserial_field`, except for fields of forwarded enum variants, which are
+/// read from a chained source using [`impl_deserial_field`].
+fn impl_deserial_body(
+    ast: &syn::DeriveInput,
+    container_attributes: &ContainerAttributes,
+    source_ident: &syn::Ident,
+    deserial_field: &dyn Fn(
+        &syn::Field,
+        &syn::Ident,
+        &syn::Ident,
+    ) -> syn::Result<proc_macro2::TokenStream>,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let data_name = &ast.ident;
+    let root = get_root();
+
     let body_tokens = match &ast.data {
         syn::Data::Struct(data) => {
             let mut names = proc_macro2::TokenStream::new();
@@ -1151,11 +1261,7 @@ pub fn impl_deserial(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
                 syn::Fields::Named(_) => {
                     for field in data.fields.iter() {
                         let field_ident = field.ident.clone().unwrap(); // safe since named fields.
-                        field_tokens.extend(impl_deserial_field(
-                            field,
-                            &field_ident,
-                            &source_ident,
-                        )?);
+                        field_tokens.extend(deserial_field(field, &field_ident, source_ident)?);
                         names.extend(quote!(#field_ident,))
                     }
                     quote!(Ok(#data_name{#names}))
@@ -1163,7 +1269,7 @@ pub fn impl_deserial(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
                 syn::Fields::Unnamed(_) => {
                     for (i, f) in data.fields.iter().enumerate() {
                         let field_ident = format_ident!("x_{}", i);
-                        field_tokens.extend(impl_deserial_field(f, &field_ident, &source_ident)?);
+                        field_tokens.extend(deserial_field(f, &field_ident, source_ident)?);
                         names.extend(quote!(#field_ident,))
                     }
                     quote!(Ok(#data_name(#names)))
@@ -1216,7 +1322,7 @@ pub fn impl_deserial(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
                     let field_tokens: proc_macro2::TokenStream = field_names
                         .iter()
                         .zip(variant.fields.iter())
-                        .map(|(name, field)| impl_deserial_field(field, name, &source))
+                        .map(|(name, field)| deserial_field(field, name, &source))
                         .collect::<syn::Result<proc_macro2::TokenStream>>()?;
 
                     // Get the literal for the tag either from a 'tag' attribute of the index of the
@@ -1308,40 +1414,7 @@ pub fn impl_deserial(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
         }
         _ => unimplemented!("#[derive(Deserial)] is not implemented for union."),
     };
-
-    let (impl_generics, ty_generics, where_clauses) = ast.generics.split_for_impl();
-
-    let where_clauses_tokens =
-        if let Some(attribute_bounds) = container_attributes.deserial_bounds() {
-            attribute_bounds.into_token_stream()
-        } else {
-            // Extend where clauses with Deserial predicate of each generic.
-            let where_clause_deserial: proc_macro2::TokenStream = ast
-                .generics
-                .type_params()
-                .map(|type_param| {
-                    let type_param_ident = &type_param.ident;
-                    quote! (#type_param_ident: #root::Deserial,)
-                })
-                .collect();
-
-            if let Some(where_clauses) = where_clauses {
-                let predicates = &where_clauses.predicates;
-                extend_punctuated(predicates, where_clause_deserial)
-            } else {
-                where_clause_deserial
-            }
-        };
-
-    let gen = quote! {
-        #[automatically_derived]
-        impl #impl_generics #root::Deserial for #data_name #ty_generics where #where_clauses_tokens {
-            fn deserial<#read_ident: #root::Read>(#source_ident: &mut #read_ident) -> #root::ParseResult<Self> {
-                #body_tokens
-            }
-        }
-    };
-    Ok(gen.into())
+    Ok(body_tokens)
 }
 
 fn impl_deserial_field(
@@ -1370,6 +1443,33 @@ fn impl_deserial_field(
     }
 }
 
+/// Generate the deserialization of a field for `DeserialBorrowed`. Fields with
+/// a `size_length` or `ensure_ordered` attribute are deserialized using
+/// `DeserialCtx`, all other fields using `DeserialBorrowed`.
+fn impl_deserial_borrowed_field(
+    f: &syn::Field,
+    ident: &syn::Ident,
+    source: &syn::Ident,
+    de_lifetime: &syn::Lifetime,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let concordium_attributes = get_concordium_field_attributes(&f.attrs)?;
+    let ty = &f.ty;
+    if contains_attribute(&concordium_attributes, "ensure_ordered")
+        || find_length_attribute(&f.attrs)?.is_some()
+    {
+        check!(
+            !matches!(ty, syn::Type::Reference(_)),
+            ty.span(),
+            "The 'size_length' and 'ensure_ordered' attributes cannot be used on borrowed fields."
+        );
+        return impl_deserial_field(f, ident, source);
+    }
+    let root = get_root();
+    Ok(quote! {
+        let #ident = <#ty as #root::DeserialBorrowed<#de_lifetime>>::deserial_borrowed(#source)?;
+    })
+}
+
 fn impl_serial_field(
     field: &syn::Field,
     ident: &proc_macro2::TokenStream,
@@ -44,6 +44,45 @@ pub fn deserial_derive(input: TokenStream) -> TokenStream {
     unwrap_or_report(derive::impl_deserial(&ast))
 }
 
+/// Derive the `DeserialBorrowed` trait, for types which borrow from the slice
+/// they are deserialized from. See the documentation of
+/// [`derive(Deserial)`](./derive.Deserial.html) for details and supported
+/// attributes.
+///
+/// Fields of type `&'a [u8]` and `&'a str` are borrowed from the slice without
+/// copying, and are read as the number of bytes as 4 bytes little-endian
+/// followed by the bytes, the same as `Vec<u8>` and `String`. All other fields
+/// must implement `DeserialBorrowed`, which all types implementing `Deserial`
+/// do. Fields with a `size_length` or `ensure_ordered` attribute are
+/// deserialized using `DeserialCtx`, hence they cannot borrow from the slice.
+///
+/// Since `DeserialBorrowed` is implemented for all types implementing
+/// `Deserial`, it should only be derived for types which cannot derive
+/// `Deserial`, and the two cannot be derived for the same type.
+///
+/// The implementation is for any lifetime `'__de` of the slice which outlives
+/// all lifetimes of the type. By default each generic type is bound by
+/// `DeserialBorrowed<'__de>`, which can be replaced using
+/// `#[concordium(bound(deserial = ".."))]`.
+///
+/// # Example
+/// ``` ignore
+/// #[derive(Serial, DeserialBorrowed)]
+/// struct Foo<'a> {
+///     name: &'a str,
+///     data: &'a [u8],
+///     #[concordium(size_length = 1)]
+///     tags: Vec<u8>,
+/// }
+///
+/// let foo: Foo = from_bytes_borrowed(&bytes)?;
+/// ```
+#[proc_macro_derive(DeserialBorrowed, attributes(concordium))]
+pub fn deserial_borrowed_derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input);
+    unwrap_or_report(derive::impl_deserial_borrowed(&ast))
+}
+
 /// Derive the [`Serial`] trait for the type.
 ///
 /// If the type is a struct all fields must implement the [`Serial`] trait. If
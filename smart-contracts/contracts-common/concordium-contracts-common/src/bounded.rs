@@ -0,0 +1,551 @@
+//! Collections with a statically known maximum length.
+//!
+//! The types in this module behave like [`Vec`], [`String`] and
+//! [`BTreeMap`], except that the number of elements (bytes, in the case of
+//! strings) they can hold is bounded by the const generic parameter `N`.
+//! The bound is checked on every mutation, and, importantly, when
+//! deserializing, where the length prefix is checked against `N` before any
+//! elements are read. This makes them suitable for contract parameters, where
+//! the input is controlled by the sender and unbounded collections would allow
+//! the sender to make the contract spend energy on large allocations.
+//!
+//! The length prefix of the serialized form uses the smallest
+//! [`SizeLength`] that can represent `N`, see [`size_length_for`]. The
+//! [`SchemaType`] instances use the same size length, so the schema always
+//! matches the binary encoding.
+use crate::{
+    impls::*,
+    schema::{self, SchemaType, SizeLength, Type},
+    traits::*,
+    types::*,
+};
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+#[cfg(not(feature = "std"))]
+use core::{convert::TryFrom, fmt, ops::Deref};
+#[cfg(feature = "std")]
+use std::{collections::BTreeMap, convert::TryFrom, fmt, ops::Deref};
+
+/// The smallest [`SizeLength`] that can represent all lengths up to and
+/// including `max_len`.
+pub const fn size_length_for(max_len: usize) -> SizeLength {
+    if max_len <= u8::MAX as usize {
+        SizeLength::U8
+    } else if max_len <= u16::MAX as usize {
+        SizeLength::U16
+    } else if max_len as u64 <= u32::MAX as u64 {
+        SizeLength::U32
+    } else {
+        SizeLength::U64
+    }
+}
+
+/// An error indicating that a collection would exceed its maximum length.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LengthError {
+    /// The maximum length of the collection.
+    pub max_length: usize,
+    /// The length the collection would have had.
+    pub length:     usize,
+}
+
+impl fmt::Display for LengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Length {} exceeds the maximum length {}.", self.length, self.max_length)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LengthError {}
+
+/// Check that `length` is at most `max_length`.
+fn check_length(length: usize, max_length: usize) -> Result<(), LengthError> {
+    if length > max_length {
+        Err(LengthError {
+            max_length,
+            length,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Read a length prefix and ensure it does not exceed `max_length`.
+fn deserial_bounded_length<R: Read>(
+    source: &mut R,
+    size_len: SizeLength,
+    max_length: usize,
+) -> ParseResult<usize> {
+    let len = schema::deserial_length(source, size_len)?;
+    if len > max_length {
+        return Err(ParseError::default());
+    }
+    Ok(len)
+}
+
+/// A vector with at most `N` elements.
+///
+/// ```
+/// # use concordium_contracts_common::{bounded::BoundedVec, *};
+/// let mut v = BoundedVec::<u8, 2>::new();
+/// assert!(v.push(1).is_ok());
+/// assert!(v.push(2).is_ok());
+/// assert_eq!(v.push(3), Err(3));
+/// let bytes = to_bytes(&v);
+/// assert_eq!(bytes, [2, 1, 2]);
+/// // A length prefix above the bound is rejected without reading further.
+/// assert!(from_bytes::<BoundedVec<u8, 2>>(&[3, 1, 2, 3]).is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BoundedVec<T, const N: usize> {
+    inner: Vec<T>,
+}
+
+impl<T, const N: usize> BoundedVec<T, N> {
+    /// The maximum number of elements.
+    pub const MAX_LEN: usize = N;
+    /// The size length used for the length prefix when serializing.
+    pub const SIZE_LENGTH: SizeLength = size_length_for(N);
+
+    /// Construct an empty vector.
+    pub fn new() -> Self {
+        Self {
+            inner: Vec::new(),
+        }
+    }
+
+    /// Append an element, returning it back if the vector is full.
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        if self.inner.len() >= N {
+            return Err(value);
+        }
+        self.inner.push(value);
+        Ok(())
+    }
+
+    /// Remove the last element, if any.
+    pub fn pop(&mut self) -> Option<T> { self.inner.pop() }
+
+    /// Remove all elements.
+    pub fn clear(&mut self) { self.inner.clear() }
+
+    /// Whether the vector contains the maximum number of elements.
+    pub fn is_full(&self) -> bool { self.inner.len() >= N }
+
+    /// The elements as a slice.
+    pub fn as_slice(&self) -> &[T] { &self.inner }
+
+    /// Consume the vector and return the underlying [`Vec`].
+    pub fn into_inner(self) -> Vec<T> { self.inner }
+}
+
+impl<T, const N: usize> Default for BoundedVec<T, N> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<T, const N: usize> Deref for BoundedVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target { &self.inner }
+}
+
+impl<T, const N: usize> AsRef<[T]> for BoundedVec<T, N> {
+    fn as_ref(&self) -> &[T] { &self.inner }
+}
+
+impl<T, const N: usize> TryFrom<Vec<T>> for BoundedVec<T, N> {
+    type Error = LengthError;
+
+    fn try_from(inner: Vec<T>) -> Result<Self, Self::Error> {
+        check_length(inner.len(), N)?;
+        Ok(Self {
+            inner,
+        })
+    }
+}
+
+impl<T, const N: usize> From<BoundedVec<T, N>> for Vec<T> {
+    fn from(value: BoundedVec<T, N>) -> Self { value.inner }
+}
+
+impl<T, const N: usize> IntoIterator for BoundedVec<T, N> {
+    type IntoIter = <Vec<T> as IntoIterator>::IntoIter;
+    type Item = T;
+
+    fn into_iter(self) -> Self::IntoIter { self.inner.into_iter() }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a BoundedVec<T, N> {
+    type IntoIter = core::slice::Iter<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> Self::IntoIter { self.inner.iter() }
+}
+
+impl<T: Serial, const N: usize> Serial for BoundedVec<T, N> {
+    fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> {
+        schema::serial_length(self.inner.len(), Self::SIZE_LENGTH, out)?;
+        serial_vector_no_length(&self.inner, out)
+    }
+}
+
+/// Fails if the length prefix exceeds `N`. The check happens before any
+/// elements are read.
+impl<T: Deserial, const N: usize> Deserial for BoundedVec<T, N> {
+    fn deserial<R: Read>(source: &mut R) -> ParseResult<Self> {
+        let len = deserial_bounded_length(source, Self::SIZE_LENGTH, N)?;
+        let inner = deserial_vector_no_length(source, len)?;
+        Ok(Self {
+            inner,
+        })
+    }
+}
+
+impl<T: SchemaType, const N: usize> SchemaType for BoundedVec<T, N> {
+    fn get_type() -> Type { Type::List(Self::SIZE_LENGTH, Box::new(T::get_type())) }
+}
+
+/// A UTF-8 string of at most `N` bytes.
+///
+/// ```
+/// # use concordium_contracts_common::{bounded::BoundedString, *};
+/// let s = BoundedString::<5>::try_from("hello").unwrap();
+/// assert_eq!(&*s, "hello");
+/// assert!(BoundedString::<4>::try_from("hello").is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct BoundedString<const N: usize> {
+    inner: String,
+}
+
+impl<const N: usize> BoundedString<N> {
+    /// The maximum length in bytes.
+    pub const MAX_LEN: usize = N;
+    /// The size length used for the length prefix when serializing.
+    pub const SIZE_LENGTH: SizeLength = size_length_for(N);
+
+    /// Construct an empty string.
+    pub fn new() -> Self {
+        Self {
+            inner: String::new(),
+        }
+    }
+
+    /// Append a string slice, failing and leaving the string unchanged if the
+    /// result would exceed `N` bytes.
+    pub fn push_str(&mut self, s: &str) -> Result<(), LengthError> {
+        check_length(self.inner.len().saturating_add(s.len()), N)?;
+        self.inner.push_str(s);
+        Ok(())
+    }
+
+    /// The string as a string slice.
+    pub fn as_str(&self) -> &str { &self.inner }
+
+    /// Consume the string and return the underlying [`String`].
+    pub fn into_inner(self) -> String { self.inner }
+}
+
+impl<const N: usize> Deref for BoundedString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target { &self.inner }
+}
+
+impl<const N: usize> AsRef<str> for BoundedString<N> {
+    fn as_ref(&self) -> &str { &self.inner }
+}
+
+impl<const N: usize> fmt::Display for BoundedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.inner.fmt(f) }
+}
+
+impl<const N: usize> TryFrom<String> for BoundedString<N> {
+    type Error = LengthError;
+
+    fn try_from(inner: String) -> Result<Self, Self::Error> {
+        check_length(inner.len(), N)?;
+        Ok(Self {
+            inner,
+        })
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for BoundedString<N> {
+    type Error = LengthError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        check_length(s.len(), N)?;
+        Ok(Self {
+            inner: s.into(),
+        })
+    }
+}
+
+impl<const N: usize> From<BoundedString<N>> for String {
+    fn from(value: BoundedString<N>) -> Self { value.inner }
+}
+
+impl<const N: usize> Serial for BoundedString<N> {
+    fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> {
+        schema::serial_length(self.inner.len(), Self::SIZE_LENGTH, out)?;
+        out.write_all(self.inner.as_bytes())
+    }
+}
+
+/// Fails if the length prefix exceeds `N` bytes or the bytes are not valid
+/// UTF-8.
+impl<const N: usize> Deserial for BoundedString<N> {
+    fn deserial<R: Read>(source: &mut R) -> ParseResult<Self> {
+        let len = deserial_bounded_length(source, Self::SIZE_LENGTH, N)?;
+        let bytes = deserial_vector_no_length(source, len)?;
+        let inner = String::from_utf8(bytes).map_err(|_| ParseError::default())?;
+        Ok(Self {
+            inner,
+        })
+    }
+}
+
+impl<const N: usize> SchemaType for BoundedString<N> {
+    fn get_type() -> Type { Type::String(Self::SIZE_LENGTH) }
+}
+
+/// A map with at most `N` entries.
+///
+/// ```
+/// # use concordium_contracts_common::{bounded::BoundedMap, *};
+/// let mut m = BoundedMap::<u8, u8, 1>::new();
+/// assert_eq!(m.insert(1, 10), Ok(None));
+/// // Replacing an existing key does not grow the map.
+/// assert_eq!(m.insert(1, 11), Ok(Some(10)));
+/// assert_eq!(m.insert(2, 20), Err((2, 20)));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BoundedMap<K, V, const N: usize> {
+    inner: BTreeMap<K, V>,
+}
+
+impl<K, V, const N: usize> BoundedMap<K, V, N> {
+    /// The maximum number of entries.
+    pub const MAX_LEN: usize = N;
+    /// The size length used for the length prefix when serializing.
+    pub const SIZE_LENGTH: SizeLength = size_length_for(N);
+
+    /// Construct an empty map.
+    pub fn new() -> Self {
+        Self {
+            inner: BTreeMap::new(),
+        }
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self) { self.inner.clear() }
+
+    /// Whether the map contains the maximum number of entries.
+    pub fn is_full(&self) -> bool { self.inner.len() >= N }
+
+    /// Consume the map and return the underlying [`BTreeMap`].
+    pub fn into_inner(self) -> BTreeMap<K, V> { self.inner }
+}
+
+impl<K: Ord, V, const N: usize> BoundedMap<K, V, N> {
+    /// Insert an entry, returning the previous value for the key if there was
+    /// one. If the key is new and the map is full, the entry is returned back
+    /// and the map is unchanged.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)> {
+        if self.inner.len() >= N && !self.inner.contains_key(&key) {
+            return Err((key, value));
+        }
+        Ok(self.inner.insert(key, value))
+    }
+
+    /// Remove the entry for the key, returning its value if it was present.
+    pub fn remove(&mut self, key: &K) -> Option<V> { self.inner.remove(key) }
+}
+
+impl<K, V, const N: usize> Default for BoundedMap<K, V, N> {
+    fn default() -> Self { Self::new() }
+}
+
+impl<K, V, const N: usize> Deref for BoundedMap<K, V, N> {
+    type Target = BTreeMap<K, V>;
+
+    fn deref(&self) -> &Self::Target { &self.inner }
+}
+
+impl<K, V, const N: usize> TryFrom<BTreeMap<K, V>> for BoundedMap<K, V, N> {
+    type Error = LengthError;
+
+    fn try_from(inner: BTreeMap<K, V>) -> Result<Self, Self::Error> {
+        check_length(inner.len(), N)?;
+        Ok(Self {
+            inner,
+        })
+    }
+}
+
+impl<K, V, const N: usize> From<BoundedMap<K, V, N>> for BTreeMap<K, V> {
+    fn from(value: BoundedMap<K, V, N>) -> Self { value.inner }
+}
+
+impl<K, V, const N: usize> IntoIterator for BoundedMap<K, V, N> {
+    type IntoIter = <BTreeMap<K, V> as IntoIterator>::IntoIter;
+    type Item = (K, V);
+
+    fn into_iter(self) -> Self::IntoIter { self.inner.into_iter() }
+}
+
+impl<'a, K, V, const N: usize> IntoIterator for &'a BoundedMap<K, V, N> {
+    type IntoIter = <&'a BTreeMap<K, V> as IntoIterator>::IntoIter;
+    type Item = (&'a K, &'a V);
+
+    fn into_iter(self) -> Self::IntoIter { self.inner.iter() }
+}
+
+impl<K: Serial + Ord, V: Serial, const N: usize> Serial for BoundedMap<K, V, N> {
+    fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> {
+        schema::serial_length(self.inner.len(), Self::SIZE_LENGTH, out)?;
+        serial_map_no_length(&self.inner, out)
+    }
+}
+
+/// Fails if the length prefix exceeds `N` or if a key is duplicated.
+/// As for [`BTreeMap`], the order of the keys is not checked.
+impl<K: Deserial + Ord, V: Deserial, const N: usize> Deserial for BoundedMap<K, V, N> {
+    fn deserial<R: Read>(source: &mut R) -> ParseResult<Self> {
+        let len = deserial_bounded_length(source, Self::SIZE_LENGTH, N)?;
+        let inner = deserial_map_no_length_no_order_check(source, len)?;
+        Ok(Self {
+            inner,
+        })
+    }
+}
+
+impl<K: SchemaType, V: SchemaType, const N: usize> SchemaType for BoundedMap<K, V, N> {
+    fn get_type() -> Type {
+        Type::Map(Self::SIZE_LENGTH, Box::new(K::get_type()), Box::new(V::get_type()))
+    }
+}
+
+#[cfg(feature = "derive-serde")]
+mod serde_impls {
+    use super::*;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    impl<T: Serialize, const N: usize> Serialize for BoundedVec<T, N> {
+        fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            self.inner.serialize(ser)
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>, const N: usize> Deserialize<'de> for BoundedVec<T, N> {
+        fn deserialize<D: Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+            Self::try_from(Vec::deserialize(des)?).map_err(D::Error::custom)
+        }
+    }
+
+    impl<const N: usize> Serialize for BoundedString<N> {
+        fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            self.inner.serialize(ser)
+        }
+    }
+
+    impl<'de, const N: usize> Deserialize<'de> for BoundedString<N> {
+        fn deserialize<D: Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+            Self::try_from(String::deserialize(des)?).map_err(D::Error::custom)
+        }
+    }
+
+    impl<K: Serialize, V: Serialize, const N: usize> Serialize for BoundedMap<K, V, N> {
+        fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            self.inner.serialize(ser)
+        }
+    }
+
+    impl<'de, K: Deserialize<'de> + Ord, V: Deserialize<'de>, const N: usize> Deserialize<'de>
+        for BoundedMap<K, V, N>
+    {
+        fn deserialize<D: Deserializer<'de>>(des: D) -> Result<Self, D::Error> {
+            Self::try_from(BTreeMap::deserialize(des)?).map_err(D::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_length_for() {
+        assert_eq!(size_length_for(0), SizeLength::U8);
+        assert_eq!(size_length_for(255), SizeLength::U8);
+        assert_eq!(size_length_for(256), SizeLength::U16);
+        assert_eq!(size_length_for(65535), SizeLength::U16);
+        assert_eq!(size_length_for(65536), SizeLength::U32);
+        assert_eq!(size_length_for(u32::MAX as usize), SizeLength::U32);
+        assert_eq!(size_length_for(u32::MAX as usize + 1), SizeLength::U64);
+    }
+
+    #[test]
+    fn test_bounded_vec_serial_deserial_is_id() {
+        let v = BoundedVec::<u32, 300>::try_from(vec![1, 2, 3]).unwrap();
+        let bytes = to_bytes(&v);
+        assert_eq!(&bytes[..2], &[3, 0], "Length should be written as a u16.");
+        assert_eq!(from_bytes::<BoundedVec<u32, 300>>(&bytes), Ok(v));
+    }
+
+    #[test]
+    fn test_bounded_vec_rejects_oversized() {
+        assert!(BoundedVec::<u8, 2>::try_from(vec![1, 2, 3]).is_err());
+        // The length prefix claims far more elements than are present; the bound
+        // must be checked before attempting to read them.
+        let mut bytes = to_bytes(&(1_000_000u32));
+        bytes.extend_from_slice(&[0; 16]);
+        assert!(from_bytes::<BoundedVec<u8, 65536>>(&bytes).is_err());
+        // Exactly at the bound is accepted.
+        assert!(from_bytes::<BoundedVec<u8, 2>>(&[2, 7, 8]).is_ok());
+    }
+
+    #[test]
+    fn test_bounded_string() {
+        let mut s = BoundedString::<5>::try_from("hel").unwrap();
+        assert!(s.push_str("lo!").is_err());
+        assert_eq!(s.as_str(), "hel");
+        assert!(s.push_str("lo").is_ok());
+        let bytes = to_bytes(&s);
+        assert_eq!(bytes, b"\x05hello");
+        assert_eq!(from_bytes::<BoundedString<5>>(&bytes), Ok(s));
+        assert!(from_bytes::<BoundedString<4>>(&bytes).is_err());
+        assert!(from_bytes::<BoundedString<5>>(&[2, 0xc3, 0x28]).is_err(), "Invalid UTF-8.");
+    }
+
+    #[test]
+    fn test_bounded_map() {
+        let mut m = BoundedMap::<u8, u16, 2>::new();
+        assert_eq!(m.insert(2, 20), Ok(None));
+        assert_eq!(m.insert(1, 10), Ok(None));
+        assert_eq!(m.insert(3, 30), Err((3, 30)));
+        assert_eq!(m.insert(1, 11), Ok(Some(10)));
+        let bytes = to_bytes(&m);
+        assert_eq!(bytes, [2, 1, 11, 0, 2, 20, 0]);
+        assert_eq!(from_bytes::<BoundedMap<u8, u16, 2>>(&bytes), Ok(m));
+        assert!(from_bytes::<BoundedMap<u8, u16, 1>>(&bytes).is_err());
+        assert!(
+            from_bytes::<BoundedMap<u8, u16, 2>>(&[2, 1, 0, 0, 1, 0, 0]).is_err(),
+            "Duplicate keys must be rejected."
+        );
+    }
+
+    #[test]
+    fn test_bounded_schema_types() {
+        assert_eq!(
+            BoundedVec::<u8, 10>::get_type(),
+            Type::List(SizeLength::U8, Box::new(Type::U8))
+        );
+        assert_eq!(BoundedString::<1000>::get_type(), Type::String(SizeLength::U16));
+        assert_eq!(
+            BoundedMap::<u8, u8, 100_000>::get_type(),
+            Type::Map(SizeLength::U32, Box::new(Type::U8), Box::new(Type::U8))
+        );
+    }
+}
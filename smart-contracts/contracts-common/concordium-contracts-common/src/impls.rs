@@ -474,6 +474,38 @@ impl Serial for &str {
     }
 }
 
+/// Serialized by writing an `u32` representing the number of bytes, then
+/// writing the bytes. Similar to `Vec<u8>`.
+impl Serial for &[u8] {
+    fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> {
+        let len = self.len() as u32;
+        len.serial(out)?;
+        out.write_all(self)
+    }
+}
+
+/// Borrowed by reading an `u32` representing the number of bytes, then taking
+/// that number of bytes from the slice.
+impl<'de: 'a, 'a> DeserialBorrowed<'de> for &'a [u8] {
+    fn deserial_borrowed(source: &mut Cursor<&'de [u8]>) -> ParseResult<Self> {
+        let len: u32 = source.get()?;
+        let data: &'de [u8] = source.data;
+        let end = source.offset.checked_add(len as usize).ok_or_else(ParseError::default)?;
+        let bytes = data.get(source.offset..end).ok_or_else(ParseError::default)?;
+        source.offset = end;
+        Ok(bytes)
+    }
+}
+
+/// Borrowed by reading an `u32` representing the number of bytes, then taking
+/// that number of bytes from the slice and checking they are valid utf8.
+impl<'de: 'a, 'a> DeserialBorrowed<'de> for &'a str {
+    fn deserial_borrowed(source: &mut Cursor<&'de [u8]>) -> ParseResult<Self> {
+        let bytes: &'de [u8] = DeserialBorrowed::deserial_borrowed(source)?;
+        core::str::from_utf8(bytes).map_err(|_| ParseError::default())
+    }
+}
+
 impl<A: Serial> Serial for &A {
     fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> { (*self).serial(out) }
 }
@@ -1465,6 +1497,18 @@ pub fn from_bytes<S: Deserial>(source: &[u8]) -> ParseResult<S> {
     cursor.get()
 }
 
+/// Dual to `to_bytes` for types which borrow from the input, see
+/// [`DeserialBorrowed`]. Unlike [`from_bytes`] this fails if not all of the
+/// input is consumed.
+pub fn from_bytes_borrowed<'a, S: DeserialBorrowed<'a>>(source: &'a [u8]) -> ParseResult<S> {
+    let mut cursor = Cursor::new(source);
+    let value = S::deserial_borrowed(&mut cursor)?;
+    if cursor.offset != source.len() {
+        return Err(ParseError::default());
+    }
+    Ok(value)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -92,17 +92,28 @@ use std::{string::String, vec::Vec};
 mod traits;
 #[macro_use]
 mod impls;
+pub mod bounded;
 pub mod constants;
 pub mod hashes;
 pub mod schema;
+#[cfg(feature = "std")]
+pub mod schema_codegen;
+#[cfg(feature = "std")]
+pub mod schema_compatibility;
+#[cfg(feature = "derive-serde")]
+pub mod schema_inspect;
 #[cfg(feature = "derive-serde")]
 pub mod schema_json;
+#[cfg(feature = "derive-serde")]
+pub mod schema_stream;
+#[cfg(feature = "derive-serde")]
+pub mod serde_binary;
 mod types;
 
 #[cfg(feature = "smart-contract")]
 pub use concordium_contracts_common_derive::*;
 #[cfg(not(feature = "smart-contract"))]
-pub use concordium_contracts_common_derive::{Deserial, Serial, Serialize};
+pub use concordium_contracts_common_derive::{Deserial, DeserialBorrowed, Serial, Serialize};
 
 pub use impls::*;
 pub use traits::*;
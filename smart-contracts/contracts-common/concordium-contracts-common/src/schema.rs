@@ -68,6 +68,14 @@ pub struct ModuleV3 {
     pub contracts: BTreeMap<String, ContractV3>,
 }
 
+/// Contains all the contract schemas for a smart contract module V1 with a V4
+/// schema. The layout is the same as for V3 schemas, but the types can use the
+/// variants only supported by V4 schemas, see [`Type::uses_v4_types`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleV4 {
+    pub contracts: BTreeMap<String, ContractV3>,
+}
+
 /// Represents the different schema versions.
 ///
 /// The serialization of this type includes the versioning information. The
@@ -86,6 +94,9 @@ pub enum VersionedModuleSchema {
     V2(ModuleV2),
     /// Version 3 schema, only supported by V1 smart contracts.
     V3(ModuleV3),
+    /// Version 4 schema, only supported by V1 smart contracts. Extends version
+    /// 3 with types for hashes, keys, signatures and token IDs.
+    V4(ModuleV4),
 }
 
 /// Describes all the schemas of a V0 smart contract.
@@ -126,6 +137,13 @@ pub struct ContractV3 {
 impl ContractV3 {
     /// Extract the event schema if it exists.
     pub fn event(&self) -> Option<&Type> { self.event.as_ref() }
+
+    /// Whether any type of the contract is only supported by V4 schemas, see
+    /// [`Type::uses_v4_types`].
+    pub fn uses_v4_types(&self) -> bool {
+        self.init.iter().chain(self.receive.values()).any(FunctionV2::uses_v4_types)
+            || self.event.as_ref().is_some_and(Type::uses_v4_types)
+    }
 }
 
 /// Describes the schema of an init or a receive function for V1 contracts with
@@ -186,6 +204,15 @@ impl FunctionV2 {
 
     /// Extract the error schema if it exists.
     pub fn error(&self) -> Option<&Type> { self.error.as_ref() }
+
+    /// Whether any type of the function is only supported by V4 schemas, see
+    /// [`Type::uses_v4_types`].
+    pub fn uses_v4_types(&self) -> bool {
+        [&self.parameter, &self.return_value, &self.error]
+            .into_iter()
+            .flatten()
+            .any(Type::uses_v4_types)
+    }
 }
 
 /// Schema for the fields of a struct or some enum variant.
@@ -293,6 +320,24 @@ pub enum Type {
     ByteArray(u32),
     /// An enum with a tag.
     TaggedEnum(BTreeMap<u8, (String, Fields)>),
+    /// A 32-byte hash. Only supported by V4 schemas.
+    Hash,
+    /// A reference to a smart contract module, i.e., the 32-byte hash of the
+    /// module. Only supported by V4 schemas.
+    ModuleReference,
+    /// An ed25519 public key of 32 bytes. Only supported by V4 schemas.
+    PublicKeyEd25519,
+    /// An ECDSA public key over the secp256k1 curve of 33 bytes, in compressed
+    /// form. Only supported by V4 schemas.
+    PublicKeyEcdsaSecp256k1,
+    /// An ed25519 signature of 64 bytes. Only supported by V4 schemas.
+    SignatureEd25519,
+    /// An ECDSA signature over the secp256k1 curve of 64 bytes, in compact
+    /// form. Only supported by V4 schemas.
+    SignatureEcdsaSecp256k1,
+    /// A CIS-2 token ID. It is serialized with the length as a single byte
+    /// followed by at most 255 bytes. Only supported by V4 schemas.
+    TokenId,
 }
 
 impl Type {
@@ -309,6 +354,54 @@ impl Type {
             t => t,
         }
     }
+
+    /// Whether the type, or any type nested in it, is one of the types only
+    /// supported by V4 schemas, i.e., [`Type::Hash`],
+    /// [`Type::ModuleReference`], the public key and signature types, and
+    /// [`Type::TokenId`].
+    pub fn uses_v4_types(&self) -> bool {
+        let fields_use_v4_types = |fields: &Fields| match fields {
+            Fields::Named(fields) => fields.iter().any(|(_, ty)| ty.uses_v4_types()),
+            Fields::Unnamed(fields) => fields.iter().any(Type::uses_v4_types),
+            Fields::None => false,
+        };
+        match self {
+            Type::Hash
+            | Type::ModuleReference
+            | Type::PublicKeyEd25519
+            | Type::PublicKeyEcdsaSecp256k1
+            | Type::SignatureEd25519
+            | Type::SignatureEcdsaSecp256k1
+            | Type::TokenId => true,
+            Type::Pair(left, right) | Type::Map(_, left, right) => {
+                left.uses_v4_types() || right.uses_v4_types()
+            }
+            Type::List(_, ty) | Type::Set(_, ty) | Type::Array(_, ty) => ty.uses_v4_types(),
+            Type::Struct(fields) => fields_use_v4_types(fields),
+            Type::Enum(variants) => variants.iter().any(|(_, fields)| fields_use_v4_types(fields)),
+            Type::TaggedEnum(variants) => {
+                variants.values().any(|(_, fields)| fields_use_v4_types(fields))
+            }
+            _ => false,
+        }
+    }
+
+    /// The type without the meaning attached by the types only supported by
+    /// V4 schemas. The result has the same binary and JSON representation, but
+    /// is supported by all schema versions. For all other types this is
+    /// `None`.
+    #[cfg(feature = "std")]
+    pub(crate) fn byte_representation(&self) -> Option<Type> {
+        match self {
+            Type::Hash | Type::ModuleReference | Type::PublicKeyEd25519 => {
+                Some(Type::ByteArray(32))
+            }
+            Type::PublicKeyEcdsaSecp256k1 => Some(Type::ByteArray(33)),
+            Type::SignatureEd25519 | Type::SignatureEcdsaSecp256k1 => Some(Type::ByteArray(64)),
+            Type::TokenId => Some(Type::ByteList(SizeLength::U8)),
+            _ => None,
+        }
+    }
 }
 
 impl SchemaType for () {
@@ -353,12 +446,62 @@ impl SchemaType for Amount {
 impl SchemaType for ModuleReference {
     fn get_type() -> Type { Type::ByteArray(32) }
 }
+impl SchemaType for crate::hashes::Hash {
+    fn get_type() -> Type { Type::ByteArray(32) }
+}
 impl SchemaType for AccountAddress {
     fn get_type() -> Type { Type::AccountAddress }
 }
 impl SchemaType for ContractAddress {
     fn get_type() -> Type { Type::ContractAddress }
 }
+
+/// Types that have a more precise schema type than their byte representation,
+/// which is only supported by V4 schemas. See [`WithSchemaV4`].
+pub trait SchemaTypeV4 {
+    fn get_type_v4() -> Type;
+}
+impl SchemaTypeV4 for ModuleReference {
+    fn get_type_v4() -> Type { Type::ModuleReference }
+}
+impl SchemaTypeV4 for crate::hashes::Hash {
+    fn get_type_v4() -> Type { Type::Hash }
+}
+impl SchemaTypeV4 for PublicKeyEd25519 {
+    fn get_type_v4() -> Type { Type::PublicKeyEd25519 }
+}
+impl SchemaTypeV4 for PublicKeyEcdsaSecp256k1 {
+    fn get_type_v4() -> Type { Type::PublicKeyEcdsaSecp256k1 }
+}
+impl SchemaTypeV4 for SignatureEd25519 {
+    fn get_type_v4() -> Type { Type::SignatureEd25519 }
+}
+impl SchemaTypeV4 for SignatureEcdsaSecp256k1 {
+    fn get_type_v4() -> Type { Type::SignatureEcdsaSecp256k1 }
+}
+
+/// A wrapper that opts in to the more precise schema type of `T`, e.g.,
+/// [`Type::PublicKeyEd25519`] instead of `Type::ByteArray(32)` for
+/// [`PublicKeyEd25519`]. It is serialized in the same way as `T`.
+///
+/// Schemas using these types are only supported by V4 schemas, so a module
+/// using the wrapper in its schema gets a V4 schema, which tools must support
+/// to use the schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WithSchemaV4<T>(pub T);
+
+impl<T: SchemaTypeV4> SchemaType for WithSchemaV4<T> {
+    fn get_type() -> Type { T::get_type_v4() }
+}
+
+impl<T: Serial> Serial for WithSchemaV4<T> {
+    fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> { self.0.serial(out) }
+}
+
+impl<T: Deserial> Deserial for WithSchemaV4<T> {
+    fn deserial<R: Read>(source: &mut R) -> ParseResult<Self> { Ok(WithSchemaV4(source.get()?)) }
+}
+
 impl SchemaType for Address {
     fn get_type() -> Type {
         Type::Enum(Vec::from([
@@ -407,6 +550,10 @@ impl SchemaType for [u8] {
     fn get_type() -> Type { Type::ByteList(SizeLength::U32) }
 }
 
+impl SchemaType for &[u8] {
+    fn get_type() -> Type { <[u8]>::get_type() }
+}
+
 impl SchemaType for String {
     fn get_type() -> Type { Type::String(SizeLength::U32) }
 }
@@ -500,6 +647,13 @@ impl Serial for ModuleV3 {
     }
 }
 
+impl Serial for ModuleV4 {
+    fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> {
+        self.contracts.serial(out)?;
+        Ok(())
+    }
+}
+
 impl Serial for VersionedModuleSchema {
     fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> {
         // Prefix for versioned module schema, used to distinquish from the unversioned.
@@ -521,6 +675,10 @@ impl Serial for VersionedModuleSchema {
                 out.write_u8(3)?;
                 module.serial(out)?;
             }
+            VersionedModuleSchema::V4(module) => {
+                out.write_u8(4)?;
+                module.serial(out)?;
+            }
         }
         Ok(())
     }
@@ -566,6 +724,16 @@ impl Deserial for ModuleV3 {
     }
 }
 
+impl Deserial for ModuleV4 {
+    fn deserial<R: Read>(source: &mut R) -> ParseResult<Self> {
+        let len: u32 = source.get()?;
+        let contracts = deserial_map_no_length_no_order_check(source, len as usize)?;
+        Ok(ModuleV4 {
+            contracts,
+        })
+    }
+}
+
 impl Deserial for VersionedModuleSchema {
     fn deserial<R: Read>(source: &mut R) -> ParseResult<Self> {
         // First we ensure the prefix is correct.
@@ -591,11 +759,47 @@ impl Deserial for VersionedModuleSchema {
                 let module = source.get()?;
                 Ok(VersionedModuleSchema::V3(module))
             }
+            4 => {
+                let module = source.get()?;
+                Ok(VersionedModuleSchema::V4(module))
+            }
             _ => Err(ParseError {}),
         }
     }
 }
 
+impl VersionedModuleSchema {
+    /// Whether any type in the schema is only supported by V4 schemas, see
+    /// [`Type::uses_v4_types`].
+    pub fn uses_v4_types(&self) -> bool {
+        match self {
+            VersionedModuleSchema::V0(module) => module.contracts.values().any(|contract| {
+                contract
+                    .state
+                    .iter()
+                    .chain(contract.init.iter())
+                    .chain(contract.receive.values())
+                    .any(Type::uses_v4_types)
+            }),
+            VersionedModuleSchema::V1(module) => module.contracts.values().any(|contract| {
+                contract.init.iter().chain(contract.receive.values()).any(|function| {
+                    function.parameter().is_some_and(Type::uses_v4_types)
+                        || function.return_value().is_some_and(Type::uses_v4_types)
+                })
+            }),
+            VersionedModuleSchema::V2(module) => module.contracts.values().any(|contract| {
+                contract.init.iter().chain(contract.receive.values()).any(FunctionV2::uses_v4_types)
+            }),
+            VersionedModuleSchema::V3(module) => {
+                module.contracts.values().any(ContractV3::uses_v4_types)
+            }
+            VersionedModuleSchema::V4(module) => {
+                module.contracts.values().any(ContractV3::uses_v4_types)
+            }
+        }
+    }
+}
+
 impl Serial for ContractV0 {
     fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> {
         self.state.serial(out)?;
@@ -892,6 +1096,13 @@ impl Serial for Type {
                 out.write_u8(31)?;
                 fields.serial(out)
             }
+            Type::Hash => out.write_u8(32),
+            Type::ModuleReference => out.write_u8(33),
+            Type::PublicKeyEd25519 => out.write_u8(34),
+            Type::PublicKeyEcdsaSecp256k1 => out.write_u8(35),
+            Type::SignatureEd25519 => out.write_u8(36),
+            Type::SignatureEcdsaSecp256k1 => out.write_u8(37),
+            Type::TokenId => out.write_u8(38),
         }
     }
 }
@@ -983,6 +1194,13 @@ impl Deserial for Type {
                 let variants = source.get()?;
                 Ok(Type::TaggedEnum(variants))
             }
+            32 => Ok(Type::Hash),
+            33 => Ok(Type::ModuleReference),
+            34 => Ok(Type::PublicKeyEd25519),
+            35 => Ok(Type::PublicKeyEcdsaSecp256k1),
+            36 => Ok(Type::SignatureEd25519),
+            37 => Ok(Type::SignatureEcdsaSecp256k1),
+            38 => Ok(Type::TokenId),
 
             _ => Err(ParseError::default()),
         }
@@ -1061,6 +1279,8 @@ mod impls {
         ErrorNotSupported,
         #[error("Receive function schema has no return value schema")]
         NoReturnValueInReceive,
+        #[error("Init function schema has no return value schema")]
+        NoReturnValueInInit,
         #[error("Return values not supported for this module version")]
         ReturnValueNotSupported,
         #[error("Event schema not found in contract schema")]
@@ -1103,9 +1323,13 @@ mod impls {
                     .clone();
                 VersionedContractSchema::V2(contract_schema)
             }
-            VersionedModuleSchema::V3(module_schema) => {
-                let contract_schema = module_schema
-                    .contracts
+            VersionedModuleSchema::V3(ModuleV3 {
+                contracts,
+            })
+            | VersionedModuleSchema::V4(ModuleV4 {
+                contracts,
+            }) => {
+                let contract_schema = contracts
                     .get(contract_name)
                     .ok_or(VersionedSchemaError::NoContractInModule)?
                     .clone();
@@ -1131,6 +1355,7 @@ mod impls {
                     Some(1) => VersionedModuleSchema::V1(from_bytes(schema_bytes)?),
                     Some(2) => VersionedModuleSchema::V2(from_bytes(schema_bytes)?),
                     Some(3) => VersionedModuleSchema::V3(from_bytes(schema_bytes)?),
+                    Some(4) => VersionedModuleSchema::V4(from_bytes(schema_bytes)?),
                     Some(_) => return Err(VersionedSchemaError::InvalidSchemaVersion),
                     None => return Err(VersionedSchemaError::MissingSchemaVersion),
                 },
@@ -1340,6 +1565,43 @@ mod impls {
 
             Ok(return_value_schema)
         }
+
+        /// Returns an init function's return value schema from a versioned
+        /// module schema.
+        pub fn get_init_return_value_schema(
+            &self,
+            contract_name: &str,
+        ) -> Result<Type, VersionedSchemaError> {
+            let versioned_contract_schema = get_versioned_contract_schema(self, contract_name)?;
+            let return_value_schema = match versioned_contract_schema {
+                VersionedContractSchema::V0(_) => {
+                    return Err(VersionedSchemaError::ReturnValueNotSupported)
+                }
+                VersionedContractSchema::V1(contract_schema) => contract_schema
+                    .init
+                    .as_ref()
+                    .ok_or(VersionedSchemaError::NoInitInContract)?
+                    .return_value()
+                    .ok_or(VersionedSchemaError::NoReturnValueInInit)?
+                    .clone(),
+                VersionedContractSchema::V2(contract_schema) => contract_schema
+                    .init
+                    .as_ref()
+                    .ok_or(VersionedSchemaError::NoInitInContract)?
+                    .return_value()
+                    .ok_or(VersionedSchemaError::NoReturnValueInInit)?
+                    .clone(),
+                VersionedContractSchema::V3(contract_schema) => contract_schema
+                    .init
+                    .as_ref()
+                    .ok_or(VersionedSchemaError::NoInitInContract)?
+                    .return_value()
+                    .ok_or(VersionedSchemaError::NoReturnValueInInit)?
+                    .clone(),
+            };
+
+            Ok(return_value_schema)
+        }
     }
 
     #[cfg(test)]
@@ -1406,6 +1668,65 @@ mod impls {
                 .unwrap();
             assert_eq!(extracted_type, Type::I32)
         }
+
+        #[test]
+        fn test_getting_init_return_value_schema() {
+            assert!(matches!(
+                module_schema().get_init_return_value_schema("TestContract"),
+                Err(VersionedSchemaError::NoReturnValueInInit)
+            ));
+            let module_schema = VersionedModuleSchema::V3(ModuleV3 {
+                contracts: BTreeMap::from([("TestContract".into(), ContractV3 {
+                    init:    Some(FunctionV2 {
+                        parameter:    None,
+                        return_value: Some(Type::U64),
+                        error:        None,
+                    }),
+                    receive: BTreeMap::new(),
+                    event:   None,
+                })]),
+            });
+            let extracted_type =
+                module_schema.get_init_return_value_schema("TestContract").unwrap();
+            assert_eq!(extracted_type, Type::U64)
+        }
+
+        #[test]
+        fn test_with_schema_v4() {
+            let key = PublicKeyEd25519([7; 32]);
+            assert_eq!(PublicKeyEd25519::get_type(), Type::ByteArray(32));
+            assert_eq!(WithSchemaV4::<PublicKeyEd25519>::get_type(), Type::PublicKeyEd25519);
+            assert_eq!(to_bytes(&WithSchemaV4(key)), to_bytes(&key));
+            assert_eq!(from_bytes(&to_bytes(&key)), Ok(WithSchemaV4(key)));
+        }
+
+        #[test]
+        fn test_v4_schema() {
+            let contracts = BTreeMap::from([("TestContract".into(), ContractV3 {
+                init:    None,
+                receive: BTreeMap::new(),
+                event:   Some(Type::List(
+                    SizeLength::U8,
+                    Box::new(WithSchemaV4::<PublicKeyEd25519>::get_type()),
+                )),
+            })]);
+            let v3 = VersionedModuleSchema::V3(ModuleV3 {
+                contracts: contracts.clone(),
+            });
+            let v4 = VersionedModuleSchema::V4(ModuleV4 {
+                contracts,
+            });
+            assert!(v3.uses_v4_types());
+            let parsed = from_bytes::<VersionedModuleSchema>(&to_bytes(&v3))
+                .expect("V3 schemas with V4 types deserialize.");
+            assert!(matches!(parsed, VersionedModuleSchema::V3(_)));
+            assert_eq!(to_bytes(&parsed), to_bytes(&v3));
+            let parsed = VersionedModuleSchema::new(&to_bytes(&v4), &None).unwrap();
+            assert_eq!(
+                parsed.get_event_schema("TestContract").unwrap(),
+                Type::List(SizeLength::U8, Box::new(Type::PublicKeyEd25519))
+            );
+        }
     }
 }
 
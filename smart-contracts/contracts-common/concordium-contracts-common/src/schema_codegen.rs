@@ -0,0 +1,1137 @@
+//! Generation of client type definitions from contract schemas.
+//!
+//! The schemas embedded in smart contract modules describe the parameters,
+//! return values, errors and events of every entrypoint. This module turns a
+//! [`VersionedModuleSchema`] into source code so clients do not have to write
+//! these types by hand:
+//!
+//! - [`typescript_module`] produces TypeScript type definitions describing the
+//!   JSON representation of the values, i.e., the JSON produced by
+//!   `Type::to_json` and accepted by `Type::serial_value`.
+//! - [`rust_module`] produces Rust structs and enums deriving `Serial` and
+//!   `Deserial` whose binary serialization matches the schema.
+//!
+//! Every schema type gets a definition named after the contract, the function
+//! and the role of the type, e.g., `Cis2NftTransferParameter` for the
+//! parameter of the `transfer` entrypoint of the `cis2_nft` contract. Init
+//! functions use `Init` as the function name, and events and (V0) contract
+//! states use `Event` and `State`.
+use crate::schema::*;
+use std::{collections::BTreeSet, fmt::Write};
+
+/// Indentation used in the generated code.
+const INDENT: &str = "    ";
+
+/// Header added to the generated code.
+const GENERATED_HEADER: &str = "Generated from a contract schema. Do not edit by hand.";
+
+/// A type described by the schema, along with the name and the description
+/// used for its generated definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaTypeEntry<'a> {
+    /// Name of the generated definition. Unique among the entries of a
+    /// module.
+    pub name:        String,
+    /// Description of the role of the type, used as documentation.
+    pub description: String,
+    /// The schema type.
+    pub ty:          &'a Type,
+}
+
+/// Collect all the types described by the module schema in the order they
+/// appear in the schema.
+pub fn schema_type_entries(schema: &VersionedModuleSchema) -> Vec<SchemaTypeEntry<'_>> {
+    let mut entries = EntryCollector::default();
+    match schema {
+        VersionedModuleSchema::V0(module) => {
+            for (contract_name, contract) in module.contracts.iter() {
+                entries.push_state(contract_name, contract.state.as_ref());
+                entries.push_function(contract_name, None, contract.init.as_ref(), None, None);
+                for (function_name, ty) in contract.receive.iter() {
+                    entries.push_function(contract_name, Some(function_name), Some(ty), None, None);
+                }
+            }
+        }
+        VersionedModuleSchema::V1(module) => {
+            for (contract_name, contract) in module.contracts.iter() {
+                if let Some(function) = &contract.init {
+                    entries.push_function(
+                        contract_name,
+                        None,
+                        function.parameter(),
+                        function.return_value(),
+                        None,
+                    );
+                }
+                for (function_name, function) in contract.receive.iter() {
+                    entries.push_function(
+                        contract_name,
+                        Some(function_name),
+                        function.parameter(),
+                        function.return_value(),
+                        None,
+                    );
+                }
+            }
+        }
+        VersionedModuleSchema::V2(module) => {
+            for (contract_name, contract) in module.contracts.iter() {
+                if let Some(function) = &contract.init {
+                    entries.push_function_v2(contract_name, None, function);
+                }
+                for (function_name, function) in contract.receive.iter() {
+                    entries.push_function_v2(contract_name, Some(function_name), function);
+                }
+            }
+        }
+        VersionedModuleSchema::V3(ModuleV3 {
+            contracts,
+        })
+        | VersionedModuleSchema::V4(ModuleV4 {
+            contracts,
+        }) => {
+            for (contract_name, contract) in contracts.iter() {
+                if let Some(function) = &contract.init {
+                    entries.push_function_v2(contract_name, None, function);
+                }
+                for (function_name, function) in contract.receive.iter() {
+                    entries.push_function_v2(contract_name, Some(function_name), function);
+                }
+                entries.push_event(contract_name, contract.event());
+            }
+        }
+    }
+    entries.entries
+}
+
+/// Helper for [`schema_type_entries`] ensuring names are unique.
+#[derive(Default)]
+struct EntryCollector<'a> {
+    names:   BTreeSet<String>,
+    entries: Vec<SchemaTypeEntry<'a>>,
+}
+
+impl<'a> EntryCollector<'a> {
+    fn push(&mut self, name: String, description: String, ty: Option<&'a Type>) {
+        if let Some(ty) = ty {
+            let name = fresh_name(&mut self.names, &name);
+            self.entries.push(SchemaTypeEntry {
+                name,
+                description,
+                ty,
+            });
+        }
+    }
+
+    fn push_function(
+        &mut self,
+        contract_name: &str,
+        function_name: Option<&str>,
+        parameter: Option<&'a Type>,
+        return_value: Option<&'a Type>,
+        error: Option<&'a Type>,
+    ) {
+        let (prefix, function) = match function_name {
+            Some(function_name) => (
+                format!("{}{}", to_pascal_case(contract_name), to_pascal_case(function_name)),
+                format!("the `{}` entrypoint of contract `{}`", function_name, contract_name),
+            ),
+            None => (
+                format!("{}Init", to_pascal_case(contract_name)),
+                format!("the init function of contract `{}`", contract_name),
+            ),
+        };
+        self.push(format!("{}Parameter", prefix), format!("Parameter of {}.", function), parameter);
+        self.push(
+            format!("{}ReturnValue", prefix),
+            format!("Return value of {}.", function),
+            return_value,
+        );
+        self.push(format!("{}Error", prefix), format!("Error of {}.", function), error);
+    }
+
+    fn push_function_v2(
+        &mut self,
+        contract_name: &str,
+        function_name: Option<&str>,
+        function: &'a FunctionV2,
+    ) {
+        self.push_function(
+            contract_name,
+            function_name,
+            function.parameter(),
+            function.return_value(),
+            function.error(),
+        )
+    }
+
+    fn push_event(&mut self, contract_name: &str, ty: Option<&'a Type>) {
+        self.push(
+            format!("{}Event", to_pascal_case(contract_name)),
+            format!("Events logged by contract `{}`.", contract_name),
+            ty,
+        )
+    }
+
+    fn push_state(&mut self, contract_name: &str, ty: Option<&'a Type>) {
+        self.push(
+            format!("{}State", to_pascal_case(contract_name)),
+            format!("State of contract `{}`.", contract_name),
+            ty,
+        )
+    }
+}
+
+/// Return `name` if it is not in `names`, and otherwise `name` with the
+/// smallest numeric suffix (starting from 2) that makes it unique. The
+/// returned name is added to `names`.
+fn fresh_name(names: &mut BTreeSet<String>, name: &str) -> String {
+    let mut candidate = name.to_string();
+    let mut suffix = 2u32;
+    while names.contains(&candidate) {
+        candidate = format!("{}{}", name, suffix);
+        suffix += 1;
+    }
+    names.insert(candidate.clone());
+    candidate
+}
+
+/// Convert a name such as `cis2_nft` or `balanceOf` to `Cis2Nft` and
+/// `BalanceOf`. Characters not allowed in identifiers are treated as word
+/// separators and the result is prefixed with `_` if it would otherwise start
+/// with a digit.
+fn to_pascal_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut capitalize = true;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            if capitalize {
+                out.push(c.to_ascii_uppercase());
+            } else {
+                out.push(c);
+            }
+            capitalize = false;
+        } else {
+            capitalize = true;
+        }
+    }
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// Convert a name such as `tokenId` to `token_id`. Characters not allowed in
+/// identifiers are replaced by `_`, the result is prefixed with `_` if it
+/// would otherwise start with a digit and keywords are made raw identifiers.
+fn to_snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if previous_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+            previous_lower = false;
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            out.push(c);
+            previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else {
+            out.push('_');
+            previous_lower = false;
+        }
+    }
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    if is_rust_keyword(&out) {
+        if matches!(out.as_str(), "self" | "Self" | "super" | "crate") {
+            out.push('_');
+        } else {
+            out.insert_str(0, "r#");
+        }
+    }
+    out
+}
+
+/// Whether the name is a (strict or reserved) Rust keyword.
+fn is_rust_keyword(name: &str) -> bool {
+    matches!(
+        name,
+        "as" | "break"
+            | "const"
+            | "continue"
+            | "crate"
+            | "else"
+            | "enum"
+            | "extern"
+            | "false"
+            | "fn"
+            | "for"
+            | "if"
+            | "impl"
+            | "in"
+            | "let"
+            | "loop"
+            | "match"
+            | "mod"
+            | "move"
+            | "mut"
+            | "pub"
+            | "ref"
+            | "return"
+            | "self"
+            | "Self"
+            | "static"
+            | "struct"
+            | "super"
+            | "trait"
+            | "true"
+            | "type"
+            | "unsafe"
+            | "use"
+            | "where"
+            | "while"
+            | "async"
+            | "await"
+            | "dyn"
+            | "abstract"
+            | "become"
+            | "box"
+            | "do"
+            | "final"
+            | "macro"
+            | "override"
+            | "priv"
+            | "typeof"
+            | "unsized"
+            | "virtual"
+            | "yield"
+            | "try"
+    )
+}
+
+/// Quote a string using JSON (and thus also TypeScript) string syntax.
+fn quote_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Generate a TypeScript module with a type definition for each type in the
+/// module schema.
+///
+/// The types describe the JSON representation of values, so, e.g., amounts,
+/// 128-bit and LEB128 integers are strings, and maps are arrays of key-value
+/// pairs.
+///
+/// ```
+/// # use concordium_contracts_common::{schema::*, schema_codegen::typescript_module};
+/// # use std::collections::BTreeMap;
+/// let mut receive = BTreeMap::new();
+/// receive.insert("transfer".to_string(), FunctionV2 {
+///     parameter:    Some(Type::Struct(Fields::Named(vec![
+///         ("to".into(), Type::AccountAddress),
+///         ("amount".into(), Type::Amount),
+///     ]))),
+///     return_value: None,
+///     error:        None,
+/// });
+/// let mut contracts = BTreeMap::new();
+/// contracts.insert("bank".to_string(), ContractV3 {
+///     init: None,
+///     receive,
+///     event: None,
+/// });
+/// let module = typescript_module(&VersionedModuleSchema::V3(ModuleV3 {
+///     contracts,
+/// }));
+/// assert!(module.contains(
+///     "export type BankTransferParameter = {\n    to: string;\n    amount: string;\n};"
+/// ));
+/// ```
+pub fn typescript_module(schema: &VersionedModuleSchema) -> String {
+    let mut out = format!("// {}\n", GENERATED_HEADER);
+    for entry in schema_type_entries(schema) {
+        let _ = write!(
+            out,
+            "\n/** {} */\nexport type {} = {};\n",
+            entry.description,
+            entry.name,
+            typescript_type(entry.ty, 0)
+        );
+    }
+    out
+}
+
+/// TypeScript type describing the JSON representation of values of the type.
+/// Nested object types are indented by `indent` levels.
+pub fn typescript_type(ty: &Type, indent: usize) -> String {
+    match ty {
+        Type::Unit => "null".into(),
+        Type::Bool => "boolean".into(),
+        Type::U8
+        | Type::U16
+        | Type::U32
+        | Type::U64
+        | Type::I8
+        | Type::I16
+        | Type::I32
+        | Type::I64 => "number".into(),
+        Type::U128
+        | Type::I128
+        | Type::Amount
+        | Type::AccountAddress
+        | Type::Timestamp
+        | Type::Duration
+        | Type::String(_)
+        | Type::ULeb128(_)
+        | Type::ILeb128(_)
+        | Type::ByteList(_)
+        | Type::ByteArray(_)
+        | Type::Hash
+        | Type::ModuleReference
+        | Type::PublicKeyEd25519
+        | Type::PublicKeyEcdsaSecp256k1
+        | Type::SignatureEd25519
+        | Type::SignatureEcdsaSecp256k1
+        | Type::TokenId => "string".into(),
+        Type::ContractAddress => "{ index: number; subindex: number }".into(),
+        Type::ContractName(_) => "{ contract: string }".into(),
+        Type::ReceiveName(_) => "{ contract: string; func: string }".into(),
+        Type::Pair(left, right) => {
+            format!("[{}, {}]", typescript_type(left, indent), typescript_type(right, indent))
+        }
+        Type::List(_, ty) | Type::Set(_, ty) | Type::Array(_, ty) => {
+            typescript_array(&typescript_type(ty, indent))
+        }
+        Type::Map(_, key, value) => {
+            format!("Array<[{}, {}]>", typescript_type(key, indent), typescript_type(value, indent))
+        }
+        Type::Struct(fields) => typescript_fields(fields, indent),
+        Type::Enum(variants) => {
+            typescript_variants(variants.iter().map(|(name, fields)| (name, fields)), indent)
+        }
+        Type::TaggedEnum(variants) => {
+            typescript_variants(variants.values().map(|(name, fields)| (name, fields)), indent)
+        }
+    }
+}
+
+/// TypeScript array type with elements of the given type.
+fn typescript_array(element: &str) -> String {
+    if element.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        format!("{}[]", element)
+    } else {
+        format!("Array<{}>", element)
+    }
+}
+
+/// TypeScript type describing the JSON representation of fields.
+fn typescript_fields(fields: &Fields, indent: usize) -> String {
+    match fields {
+        Fields::Named(fields) => {
+            if fields.is_empty() {
+                return "{}".into();
+            }
+            let mut out = String::from("{\n");
+            for (name, ty) in fields.iter() {
+                let _ = writeln!(
+                    out,
+                    "{}{}: {};",
+                    INDENT.repeat(indent + 1),
+                    typescript_key(name),
+                    typescript_type(ty, indent + 1)
+                );
+            }
+            out.push_str(&INDENT.repeat(indent));
+            out.push('}');
+            out
+        }
+        Fields::Unnamed(fields) => {
+            let types: Vec<_> = fields.iter().map(|ty| typescript_type(ty, indent)).collect();
+            format!("[{}]", types.join(", "))
+        }
+        Fields::None => "[]".into(),
+    }
+}
+
+/// TypeScript union type describing the JSON representation of an enum, i.e.,
+/// objects with the variant name as the only key.
+fn typescript_variants<'b>(
+    variants: impl Iterator<Item = (&'b String, &'b Fields)>,
+    indent: usize,
+) -> String {
+    let variants: Vec<_> = variants
+        .map(|(name, fields)| {
+            format!("{{ {}: {} }}", typescript_key(name), typescript_fields(fields, indent))
+        })
+        .collect();
+    if variants.is_empty() {
+        "never".into()
+    } else {
+        variants.join(" | ")
+    }
+}
+
+/// Property name, quoted if it is not a valid identifier.
+fn typescript_key(name: &str) -> String {
+    let mut chars = name.chars();
+    let valid = match chars.next() {
+        Some(c) => {
+            (c.is_ascii_alphabetic() || c == '_' || c == '$')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        }
+        None => false,
+    };
+    if valid {
+        name.to_string()
+    } else {
+        quote_string(name)
+    }
+}
+
+/// Generate a Rust module with a type definition for each type in the module
+/// schema. The structs and enums derive `Serial` and `Deserial` and their
+/// serialization matches the binary layout described by the schema.
+///
+/// The derive macros refer to the `concordium_std` crate. If `crate_path` is
+/// not `concordium_std`, e.g., `concordium_contracts_common` or
+/// `concordium_rust_sdk::types::smart_contracts::concordium_contracts_common`,
+/// the generated code imports it under the name `concordium_std`.
+///
+/// Nested structs and enums get definitions named after their position in the
+/// enclosing type, and structurally equal types share a single definition.
+/// Collections that use a length encoding other than the default `u32` are
+/// fields annotated with `#[concordium(size_length = ..)]`, and are wrapped in
+/// a newtype when they do not appear directly as a field. LEB128 integers are
+/// represented by a generated `Leb128` type keeping the encoded bytes.
+pub fn rust_module(schema: &VersionedModuleSchema, crate_path: &str) -> String {
+    let entries = schema_type_entries(schema);
+    let mut generator = RustGenerator {
+        names: entries.iter().map(|entry| entry.name.clone()).collect(),
+        ..RustGenerator::default()
+    };
+    for entry in entries.iter() {
+        generator.define_entry(entry);
+    }
+
+    let mut out = format!("// {}\n\n", GENERATED_HEADER);
+    if crate_path != "concordium_std" {
+        let _ = writeln!(out, "use {} as concordium_std;", crate_path);
+    }
+    out.push_str("use concordium_std::*;\n");
+    let collections: Vec<_> = [("BTreeMap", generator.uses_map), ("BTreeSet", generator.uses_set)]
+        .iter()
+        .filter(|(_, used)| *used)
+        .map(|(name, _)| *name)
+        .collect();
+    match collections.as_slice() {
+        [] => (),
+        [name] => {
+            let _ = writeln!(out, "use std::collections::{};", name);
+        }
+        names => {
+            let _ = writeln!(out, "use std::collections::{{{}}};", names.join(", "));
+        }
+    }
+    if generator.uses_leb128 {
+        out.push('\n');
+        out.push_str(RUST_LEB128_DEFINITION);
+    }
+    for definition in generator.definitions.iter() {
+        out.push('\n');
+        out.push_str(definition);
+    }
+    out
+}
+
+/// Derives added to all generated Rust structs and enums.
+const RUST_DERIVES: &str =
+    "#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serial, Deserial)]";
+
+/// Definition of the type used for LEB128 integers in the generated Rust code.
+const RUST_LEB128_DEFINITION: &str = r#"/// An integer in LEB128 encoding using at most `MAX_BYTES` bytes. The integer
+/// is kept in its encoded form.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Leb128<const MAX_BYTES: usize>(pub Vec<u8>);
+
+impl<const MAX_BYTES: usize> Serial for Leb128<MAX_BYTES> {
+    fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> { out.write_all(&self.0) }
+}
+
+impl<const MAX_BYTES: usize> Deserial for Leb128<MAX_BYTES> {
+    fn deserial<R: Read>(source: &mut R) -> ParseResult<Self> {
+        let mut bytes = Vec::new();
+        loop {
+            let byte: u8 = source.get()?;
+            bytes.push(byte);
+            if byte & 0x80 == 0 {
+                return Ok(Leb128(bytes));
+            }
+            if bytes.len() >= MAX_BYTES {
+                return Err(ParseError::default());
+            }
+        }
+    }
+}
+"#;
+
+/// A Rust type expression along with the size length to use when it is a
+/// field, if different from the default `u32`.
+struct RustType {
+    expr:        String,
+    size_length: Option<SizeLength>,
+}
+
+impl RustType {
+    fn plain(expr: impl Into<String>) -> Self {
+        RustType {
+            expr:        expr.into(),
+            size_length: None,
+        }
+    }
+
+    /// A collection type whose length is encoded using `size_length`.
+    fn collection(expr: String, size_length: SizeLength) -> Self {
+        RustType {
+            expr,
+            size_length: if size_length == SizeLength::U32 {
+                None
+            } else {
+                Some(size_length)
+            },
+        }
+    }
+}
+
+/// Number of bytes used by a size length, as used in the `size_length`
+/// attribute.
+fn size_length_bytes(size_length: SizeLength) -> u8 {
+    match size_length {
+        SizeLength::U8 => 1,
+        SizeLength::U16 => 2,
+        SizeLength::U32 => 4,
+        SizeLength::U64 => 8,
+    }
+}
+
+/// Field attributes needed for a type to have the intended serialization.
+fn rust_field_attributes(ty: &RustType) -> String {
+    match ty.size_length {
+        Some(size_length) => {
+            format!("#[concordium(size_length = {})]", size_length_bytes(size_length))
+        }
+        None => String::new(),
+    }
+}
+
+/// State of the Rust code generation.
+#[derive(Default)]
+struct RustGenerator {
+    /// Names of generated definitions.
+    names:       BTreeSet<String>,
+    /// Schema types with a named definition, used to share definitions.
+    defined:     Vec<(Type, String)>,
+    /// The generated definitions.
+    definitions: Vec<String>,
+    uses_map:    bool,
+    uses_set:    bool,
+    uses_leb128: bool,
+}
+
+impl RustGenerator {
+    /// Define the type of a schema entry. The entry name is already reserved.
+    fn define_entry(&mut self, entry: &SchemaTypeEntry) {
+        let doc = format!("/// {}\n", entry.description);
+        if let Some(existing) = self.lookup(entry.ty) {
+            let definition = format!("{}pub type {} = {};\n", doc, entry.name, existing);
+            self.definitions.push(definition);
+            return;
+        }
+        match entry.ty {
+            Type::Struct(fields) => self.define_struct(entry.ty, fields, &entry.name, &doc),
+            Type::Enum(variants) => self.define_enum(
+                entry.ty,
+                variants.iter().map(|(name, fields)| (None, name, fields)),
+                &entry.name,
+                &doc,
+            ),
+            Type::TaggedEnum(variants) => self.define_enum(
+                entry.ty,
+                variants.iter().map(|(tag, (name, fields))| (Some(*tag), name, fields)),
+                &entry.name,
+                &doc,
+            ),
+            _ => {
+                let rust_type = self.rust_type(entry.ty, &entry.name);
+                if rust_type.size_length.is_some() {
+                    self.define_newtype(entry.ty, &rust_type, &entry.name, &doc);
+                } else {
+                    let definition =
+                        format!("{}pub type {} = {};\n", doc, entry.name, rust_type.expr);
+                    self.definitions.push(definition);
+                }
+            }
+        }
+    }
+
+    /// Name of the definition of the schema type, if one has been generated.
+    fn lookup(&self, ty: &Type) -> Option<String> {
+        self.defined.iter().find(|(defined, _)| defined == ty).map(|(_, name)| name.clone())
+    }
+
+    /// Rust type of a value used in a position where field attributes cannot
+    /// be specified, e.g., as a list element.
+    fn nested_type(&mut self, ty: &Type, hint: &str) -> String {
+        let rust_type = self.rust_type(ty, hint);
+        if rust_type.size_length.is_none() {
+            return rust_type.expr;
+        }
+        if let Some(existing) = self.lookup(ty) {
+            return existing;
+        }
+        let name = fresh_name(&mut self.names, hint);
+        self.define_newtype(ty, &rust_type, &name, "");
+        name
+    }
+
+    /// Rust type of a schema type, generating definitions for nested structs
+    /// and enums named based on `hint`.
+    fn rust_type(&mut self, ty: &Type, hint: &str) -> RustType {
+        match ty {
+            Type::Unit => RustType::plain("()"),
+            Type::Bool => RustType::plain("bool"),
+            Type::U8 => RustType::plain("u8"),
+            Type::U16 => RustType::plain("u16"),
+            Type::U32 => RustType::plain("u32"),
+            Type::U64 => RustType::plain("u64"),
+            Type::U128 => RustType::plain("u128"),
+            Type::I8 => RustType::plain("i8"),
+            Type::I16 => RustType::plain("i16"),
+            Type::I32 => RustType::plain("i32"),
+            Type::I64 => RustType::plain("i64"),
+            Type::I128 => RustType::plain("i128"),
+            Type::Amount => RustType::plain("Amount"),
+            Type::AccountAddress => RustType::plain("AccountAddress"),
+            Type::ContractAddress => RustType::plain("ContractAddress"),
+            Type::Timestamp => RustType::plain("Timestamp"),
+            Type::Duration => RustType::plain("Duration"),
+            Type::Pair(left, right) => {
+                let left = self.nested_type(left, &format!("{}First", hint));
+                let right = self.nested_type(right, &format!("{}Second", hint));
+                RustType::plain(format!("({}, {})", left, right))
+            }
+            Type::List(size_length, ty) => {
+                let item = self.nested_type(ty, &format!("{}Item", hint));
+                RustType::collection(format!("Vec<{}>", item), *size_length)
+            }
+            Type::Set(size_length, ty) => {
+                self.uses_set = true;
+                let item = self.nested_type(ty, &format!("{}Item", hint));
+                RustType::collection(format!("BTreeSet<{}>", item), *size_length)
+            }
+            Type::Map(size_length, key, value) => {
+                self.uses_map = true;
+                let key = self.nested_type(key, &format!("{}Key", hint));
+                let value = self.nested_type(value, &format!("{}Value", hint));
+                RustType::collection(format!("BTreeMap<{}, {}>", key, value), *size_length)
+            }
+            Type::Array(len, ty) => {
+                let item = self.nested_type(ty, &format!("{}Item", hint));
+                RustType::plain(format!("[{}; {}]", item, len))
+            }
+            Type::Struct(fields) => {
+                if let Some(existing) = self.lookup(ty) {
+                    return RustType::plain(existing);
+                }
+                let name = fresh_name(&mut self.names, hint);
+                self.define_struct(ty, fields, &name, "");
+                RustType::plain(name)
+            }
+            Type::Enum(variants) => {
+                if let Some(existing) = self.lookup(ty) {
+                    return RustType::plain(existing);
+                }
+                let name = fresh_name(&mut self.names, hint);
+                self.define_enum(
+                    ty,
+                    variants.iter().map(|(name, fields)| (None, name, fields)),
+                    &name,
+                    "",
+                );
+                RustType::plain(name)
+            }
+            Type::TaggedEnum(variants) => {
+                if let Some(existing) = self.lookup(ty) {
+                    return RustType::plain(existing);
+                }
+                let name = fresh_name(&mut self.names, hint);
+                self.define_enum(
+                    ty,
+                    variants.iter().map(|(tag, (name, fields))| (Some(*tag), name, fields)),
+                    &name,
+                    "",
+                );
+                RustType::plain(name)
+            }
+            Type::String(size_length) => RustType::collection("String".into(), *size_length),
+            // The owned name types are serialized with a 2 byte length. For other
+            // lengths the names are represented as strings.
+            Type::ContractName(SizeLength::U16) => RustType::plain("OwnedContractName"),
+            Type::ReceiveName(SizeLength::U16) => RustType::plain("OwnedReceiveName"),
+            Type::ContractName(size_length) | Type::ReceiveName(size_length) => {
+                RustType::collection("String".into(), *size_length)
+            }
+            Type::ULeb128(max_bytes) | Type::ILeb128(max_bytes) => {
+                self.uses_leb128 = true;
+                RustType::plain(format!("Leb128<{}>", max_bytes))
+            }
+            Type::ByteList(size_length) => RustType::collection("Vec<u8>".into(), *size_length),
+            Type::ByteArray(len) => RustType::plain(format!("[u8; {}]", len)),
+            Type::Hash => RustType::plain("hashes::Hash"),
+            Type::ModuleReference => RustType::plain("ModuleReference"),
+            Type::PublicKeyEd25519 => RustType::plain("PublicKeyEd25519"),
+            Type::PublicKeyEcdsaSecp256k1 => RustType::plain("PublicKeyEcdsaSecp256k1"),
+            Type::SignatureEd25519 => RustType::plain("SignatureEd25519"),
+            Type::SignatureEcdsaSecp256k1 => RustType::plain("SignatureEcdsaSecp256k1"),
+            Type::TokenId => RustType::collection("Vec<u8>".into(), SizeLength::U8),
+        }
+    }
+
+    /// Define a newtype wrapping a type that needs field attributes.
+    fn define_newtype(&mut self, ty: &Type, rust_type: &RustType, name: &str, doc: &str) {
+        self.defined.push((ty.clone(), name.to_string()));
+        let definition = format!(
+            "{}{}\npub struct {}({} pub {});\n",
+            doc,
+            RUST_DERIVES,
+            name,
+            rust_field_attributes(rust_type),
+            rust_type.expr
+        );
+        self.definitions.push(definition);
+    }
+
+    /// Define a struct with the given fields.
+    fn define_struct(&mut self, ty: &Type, fields: &Fields, name: &str, doc: &str) {
+        self.defined.push((ty.clone(), name.to_string()));
+        let body = match self.rust_fields(fields, name, "pub ") {
+            Some(body) if matches!(fields, Fields::Named(_)) => format!(" {}\n", body),
+            Some(body) => format!("{};\n", body),
+            None => ";\n".into(),
+        };
+        let definition = format!("{}{}\npub struct {}{}", doc, RUST_DERIVES, name, body);
+        self.definitions.push(definition);
+    }
+
+    /// Define an enum with the given variants and, for tagged enums, the tag
+    /// of each variant.
+    fn define_enum<'b>(
+        &mut self,
+        ty: &Type,
+        variants: impl Iterator<Item = (Option<u8>, &'b String, &'b Fields)>,
+        name: &str,
+        doc: &str,
+    ) {
+        self.defined.push((ty.clone(), name.to_string()));
+        let mut body = String::new();
+        let mut tagged = false;
+        let mut variant_names = BTreeSet::new();
+        for (tag, variant_name, fields) in variants {
+            if let Some(tag) = tag {
+                tagged = true;
+                let _ = writeln!(body, "{}#[concordium(tag = {})]", INDENT, tag);
+            }
+            let variant_ident = fresh_name(&mut variant_names, &to_pascal_case(variant_name));
+            let hint = format!("{}{}", name, variant_ident);
+            let fields = match self.rust_fields(fields, &hint, "") {
+                Some(body) if matches!(fields, Fields::Named(_)) => format!(" {}", body),
+                Some(body) => body,
+                None => String::new(),
+            };
+            let _ = writeln!(body, "{}{}{},", INDENT, variant_ident, fields);
+        }
+        let repr = if tagged {
+            "#[concordium(repr(u8))]\n"
+        } else {
+            ""
+        };
+        let definition =
+            format!("{}{}\n{}pub enum {} {{\n{}}}\n", doc, RUST_DERIVES, repr, name, body);
+        self.definitions.push(definition);
+    }
+
+    /// The fields of a struct or enum variant, i.e., a braced list of named
+    /// fields or a parenthesized list of unnamed fields. Returns `None` for
+    /// [`Fields::None`].
+    fn rust_fields(&mut self, fields: &Fields, hint: &str, visibility: &str) -> Option<String> {
+        match fields {
+            Fields::Named(fields) => {
+                let mut field_names = BTreeSet::new();
+                let mut body = String::from("{\n");
+                let indent = if visibility.is_empty() {
+                    INDENT.repeat(2)
+                } else {
+                    INDENT.to_string()
+                };
+                for (field_name, ty) in fields.iter() {
+                    let ident = fresh_name(&mut field_names, &to_snake_case(field_name));
+                    let rust_type =
+                        self.rust_type(ty, &format!("{}{}", hint, to_pascal_case(field_name)));
+                    let attribute = rust_field_attributes(&rust_type);
+                    if !attribute.is_empty() {
+                        let _ = writeln!(body, "{}{}", indent, attribute);
+                    }
+                    if ident.trim_start_matches("r#") != field_name {
+                        let _ = writeln!(
+                            body,
+                            "{}#[concordium(rename = {})]",
+                            indent,
+                            quote_string(field_name)
+                        );
+                    }
+                    let _ =
+                        writeln!(body, "{}{}{}: {},", indent, visibility, ident, rust_type.expr);
+                }
+                body.push_str(&indent[INDENT.len()..]);
+                body.push('}');
+                Some(body)
+            }
+            Fields::Unnamed(fields) => {
+                let mut types = Vec::with_capacity(fields.len());
+                for (i, ty) in fields.iter().enumerate() {
+                    let rust_type = self.rust_type(ty, &format!("{}{}", hint, i));
+                    let attribute = rust_field_attributes(&rust_type);
+                    let attribute = if attribute.is_empty() {
+                        attribute
+                    } else {
+                        attribute + " "
+                    };
+                    types.push(format!("{}{}{}", attribute, visibility, rust_type.expr));
+                }
+                Some(format!("({})", types.join(", ")))
+            }
+            Fields::None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    /// A V3 module schema with a single contract `cis2_nft` with a `transfer`
+    /// entrypoint and an event.
+    fn module_schema(parameter: Type, event: Type) -> VersionedModuleSchema {
+        let mut receive = BTreeMap::new();
+        receive.insert("transfer".to_string(), FunctionV2 {
+            parameter:    Some(parameter),
+            return_value: Some(Type::Unit),
+            error:        None,
+        });
+        let mut contracts = BTreeMap::new();
+        contracts.insert("cis2_nft".to_string(), ContractV3 {
+            init: Some(FunctionV2 {
+                parameter:    None,
+                return_value: None,
+                error:        Some(Type::U8),
+            }),
+            receive,
+            event: Some(event),
+        });
+        VersionedModuleSchema::V3(ModuleV3 {
+            contracts,
+        })
+    }
+
+    fn transfer_schema() -> Type {
+        Type::List(
+            SizeLength::U16,
+            Box::new(Type::Struct(Fields::Named(vec![
+                ("token_id".into(), Type::ByteList(SizeLength::U8)),
+                ("amount".into(), Type::ULeb128(37)),
+                (
+                    "from".into(),
+                    Type::Enum(vec![
+                        ("Account".into(), Fields::Unnamed(vec![Type::AccountAddress])),
+                        ("Contract".into(), Fields::Unnamed(vec![Type::ContractAddress])),
+                    ]),
+                ),
+                (
+                    "data".into(),
+                    Type::List(SizeLength::U16, Box::new(Type::ByteList(SizeLength::U16))),
+                ),
+            ]))),
+        )
+    }
+
+    fn event_schema() -> Type {
+        let mut variants = BTreeMap::new();
+        variants.insert(
+            255,
+            ("Transfer".to_string(), Fields::Named(vec![("tokenId".into(), Type::U32)])),
+        );
+        variants.insert(254, ("Mint".to_string(), Fields::None));
+        Type::TaggedEnum(variants)
+    }
+
+    #[test]
+    fn test_entry_names() {
+        let schema = module_schema(Type::U8, Type::U8);
+        let names: Vec<_> =
+            schema_type_entries(&schema).into_iter().map(|entry| entry.name).collect();
+        assert_eq!(names, [
+            "Cis2NftInitError",
+            "Cis2NftTransferParameter",
+            "Cis2NftTransferReturnValue",
+            "Cis2NftEvent"
+        ]);
+    }
+
+    #[test]
+    fn test_case_conversion() {
+        assert_eq!(to_pascal_case("cis2_nft"), "Cis2Nft");
+        assert_eq!(to_pascal_case("balanceOf"), "BalanceOf");
+        assert_eq!(to_pascal_case("2fa.check"), "_2faCheck");
+        assert_eq!(to_snake_case("tokenId"), "token_id");
+        assert_eq!(to_snake_case("type"), "r#type");
+        assert_eq!(to_snake_case("self"), "self_");
+        assert_eq!(to_snake_case("max-size"), "max_size");
+    }
+
+    #[test]
+    fn test_typescript_module() {
+        let module = typescript_module(&module_schema(transfer_schema(), event_schema()));
+        let expected = r#"// Generated from a contract schema. Do not edit by hand.
+
+/** Error of the init function of contract `cis2_nft`. */
+export type Cis2NftInitError = number;
+
+/** Parameter of the `transfer` entrypoint of contract `cis2_nft`. */
+export type Cis2NftTransferParameter = Array<{
+    token_id: string;
+    amount: string;
+    from: { Account: [string] } | { Contract: [{ index: number; subindex: number }] };
+    data: string[];
+}>;
+
+/** Return value of the `transfer` entrypoint of contract `cis2_nft`. */
+export type Cis2NftTransferReturnValue = null;
+
+/** Events logged by contract `cis2_nft`. */
+export type Cis2NftEvent = { Mint: [] } | { Transfer: {
+    tokenId: number;
+} };
+"#;
+        assert_eq!(module, expected);
+    }
+
+    #[test]
+    fn test_rust_module() {
+        let module = rust_module(
+            &module_schema(transfer_schema(), event_schema()),
+            "concordium_contracts_common",
+        );
+        let expected = r#"// Generated from a contract schema. Do not edit by hand.
+
+use concordium_contracts_common as concordium_std;
+use concordium_std::*;
+
+/// An integer in LEB128 encoding using at most `MAX_BYTES` bytes. The integer
+/// is kept in its encoded form.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Leb128<const MAX_BYTES: usize>(pub Vec<u8>);
+
+impl<const MAX_BYTES: usize> Serial for Leb128<MAX_BYTES> {
+    fn serial<W: Write>(&self, out: &mut W) -> Result<(), W::Err> { out.write_all(&self.0) }
+}
+
+impl<const MAX_BYTES: usize> Deserial for Leb128<MAX_BYTES> {
+    fn deserial<R: Read>(source: &mut R) -> ParseResult<Self> {
+        let mut bytes = Vec::new();
+        loop {
+            let byte: u8 = source.get()?;
+            bytes.push(byte);
+            if byte & 0x80 == 0 {
+                return Ok(Leb128(bytes));
+            }
+            if bytes.len() >= MAX_BYTES {
+                return Err(ParseError::default());
+            }
+        }
+    }
+}
+
+/// Error of the init function of contract `cis2_nft`.
+pub type Cis2NftInitError = u8;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serial, Deserial)]
+pub enum Cis2NftTransferParameterItemFrom {
+    Account(AccountAddress),
+    Contract(ContractAddress),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serial, Deserial)]
+pub struct Cis2NftTransferParameterItemDataItem(#[concordium(size_length = 2)] pub Vec<u8>);
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serial, Deserial)]
+pub struct Cis2NftTransferParameterItem {
+    #[concordium(size_length = 1)]
+    pub token_id: Vec<u8>,
+    pub amount: Leb128<37>,
+    pub from: Cis2NftTransferParameterItemFrom,
+    #[concordium(size_length = 2)]
+    pub data: Vec<Cis2NftTransferParameterItemDataItem>,
+}
+
+/// Parameter of the `transfer` entrypoint of contract `cis2_nft`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serial, Deserial)]
+pub struct Cis2NftTransferParameter(#[concordium(size_length = 2)] pub Vec<Cis2NftTransferParameterItem>);
+
+/// Return value of the `transfer` entrypoint of contract `cis2_nft`.
+pub type Cis2NftTransferReturnValue = ();
+
+/// Events logged by contract `cis2_nft`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serial, Deserial)]
+#[concordium(repr(u8))]
+pub enum Cis2NftEvent {
+    #[concordium(tag = 254)]
+    Mint,
+    #[concordium(tag = 255)]
+    Transfer {
+        #[concordium(rename = "tokenId")]
+        token_id: u32,
+    },
+}
+"#;
+        assert_eq!(module, expected);
+    }
+
+    #[test]
+    fn test_rust_shared_definitions() {
+        let point =
+            Type::Struct(Fields::Named(vec![("x".into(), Type::I32), ("y".into(), Type::I32)]));
+        let module = rust_module(&module_schema(point.clone(), point), "concordium_std");
+        assert!(module.contains("pub struct Cis2NftTransferParameter {"));
+        assert!(module.contains("pub type Cis2NftEvent = Cis2NftTransferParameter;"));
+        assert!(!module.contains("as concordium_std"));
+        assert!(!module.contains("std::collections"));
+    }
+}
@@ -0,0 +1,1018 @@
+//! Checking whether contract schemas of an upgraded module are compatible with
+//! the schemas of the module it replaces.
+//!
+//! When a contract is upgraded the existing clients keep encoding parameters
+//! and decoding return values, errors and events using the old schema. The
+//! function [`check_compatibility`] compares the schemas of the two modules
+//! and reports, for every type described by the schemas, whether it is
+//! unchanged, changed in a compatible way, changed in a binary-incompatible
+//! way, added or removed.
+//!
+//! A type change is considered compatible if every value serialized according
+//! to the old type is deserialized by the new type, to the same value. For
+//! example, appending variants to an enum is compatible, whereas adding a
+//! field to a struct or changing the size of an integer is not. Compatible
+//! changes might still affect clients, e.g., renaming a field changes the JSON
+//! representation of values. Such changes are listed with the compatible
+//! change.
+//!
+//! Return values, errors and events are produced by the contract and decoded
+//! by clients, so for these the check also goes in the other direction: if
+//! the new type has values that cannot be deserialized by the old type, such
+//! as new enum variants, the change is reported as
+//! [`Widened`](ChangeKind::Widened) since clients using the old schema fail to
+//! decode them.
+use crate::schema::*;
+use std::{collections::BTreeMap, fmt};
+
+/// Name of an init or receive function of a contract.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FunctionName {
+    /// The init function of the contract.
+    Init,
+    /// A receive function (entrypoint) of the contract.
+    Receive(String),
+}
+
+/// The part of a contract schema a [`SchemaChange`] concerns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaItem {
+    /// The contract as a whole.
+    Contract,
+    /// A function as a whole.
+    Function(FunctionName),
+    /// The parameter of a function.
+    Parameter(FunctionName),
+    /// The return value of a function.
+    ReturnValue(FunctionName),
+    /// The error of a function.
+    Error(FunctionName),
+    /// The events of the contract.
+    Event,
+    /// The state of the contract. Only present in V0 schemas.
+    State,
+}
+
+/// How a [`SchemaItem`] changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The item is only present in the new schema.
+    Added,
+    /// The item is only present in the old schema.
+    Removed,
+    /// The type changed, but values serialized according to the old type are
+    /// still deserialized to the same values by the new type.
+    Compatible {
+        /// Description of the individual changes.
+        changes: Vec<String>,
+    },
+    /// The type of a return value, error or event changed in a compatible
+    /// way, but the new type has values that are not deserialized by the old
+    /// type, so clients using the old schema cannot decode all outputs of the
+    /// new contract.
+    Widened {
+        /// Description of the individual changes.
+        changes: Vec<String>,
+    },
+    /// The type changed such that values serialized according to the old type
+    /// are no longer deserialized to the same values by the new type.
+    Incompatible {
+        /// Description of the changes breaking compatibility.
+        problems: Vec<String>,
+        /// Description of the remaining, compatible, changes.
+        changes:  Vec<String>,
+    },
+}
+
+impl ChangeKind {
+    /// Whether the change keeps existing clients working, i.e., whether the
+    /// item was added or changed in a compatible way.
+    pub fn is_compatible(&self) -> bool {
+        matches!(self, ChangeKind::Added | ChangeKind::Compatible { .. })
+    }
+}
+
+/// A change of some item of a contract schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaChange {
+    /// Name of the contract.
+    pub contract: String,
+    /// The item that changed.
+    pub item:     SchemaItem,
+    /// How the item changed.
+    pub kind:     ChangeKind,
+}
+
+/// The result of comparing two module schemas. Items that are unchanged are
+/// not included.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompatibilityReport {
+    /// The changes, ordered by contract, and by function within a contract.
+    pub changes: Vec<SchemaChange>,
+}
+
+impl CompatibilityReport {
+    /// Whether all changes are compatible, i.e., nothing was removed and no
+    /// type changed in a binary-incompatible way.
+    pub fn is_compatible(&self) -> bool {
+        self.changes.iter().all(|change| change.kind.is_compatible())
+    }
+
+    /// The changes that are not compatible.
+    pub fn incompatible_changes(&self) -> impl Iterator<Item = &SchemaChange> {
+        self.changes.iter().filter(|change| !change.kind.is_compatible())
+    }
+}
+
+impl fmt::Display for FunctionName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FunctionName::Init => write!(f, "init"),
+            FunctionName::Receive(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Human readable description of a function of the contract.
+fn describe_function(contract: &str, function: &FunctionName) -> String {
+    match function {
+        FunctionName::Init => format!("init function `init_{}`", contract),
+        FunctionName::Receive(name) => format!("entrypoint `{}.{}`", contract, name),
+    }
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let contract = &self.contract;
+        match &self.item {
+            SchemaItem::Contract => write!(f, "contract `{}`", contract)?,
+            SchemaItem::Function(function) => {
+                write!(f, "{}", describe_function(contract, function))?
+            }
+            SchemaItem::Parameter(function) => {
+                write!(f, "parameter of {}", describe_function(contract, function))?
+            }
+            SchemaItem::ReturnValue(function) => {
+                write!(f, "return value of {}", describe_function(contract, function))?
+            }
+            SchemaItem::Error(function) => {
+                write!(f, "error of {}", describe_function(contract, function))?
+            }
+            SchemaItem::Event => write!(f, "events of contract `{}`", contract)?,
+            SchemaItem::State => write!(f, "state of contract `{}`", contract)?,
+        }
+        match &self.kind {
+            ChangeKind::Added => write!(f, ": added"),
+            ChangeKind::Removed => write!(f, ": removed"),
+            ChangeKind::Compatible {
+                changes,
+            } => {
+                write!(f, ": compatible")?;
+                for change in changes.iter() {
+                    write!(f, "\n  - {}", change)?;
+                }
+                Ok(())
+            }
+            ChangeKind::Widened {
+                changes,
+            } => {
+                write!(f, ": widened, clients using the old schema cannot decode all values")?;
+                for change in changes.iter() {
+                    write!(f, "\n  - {}", change)?;
+                }
+                Ok(())
+            }
+            ChangeKind::Incompatible {
+                problems,
+                changes,
+            } => {
+                write!(f, ": binary-incompatible")?;
+                for problem in problems.iter() {
+                    write!(f, "\n  ! {}", problem)?;
+                }
+                for change in changes.iter() {
+                    write!(f, "\n  - {}", change)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for CompatibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.changes.is_empty() {
+            return writeln!(f, "No changes.");
+        }
+        for change in self.changes.iter() {
+            writeln!(f, "{}", change)?;
+        }
+        if self.is_compatible() {
+            writeln!(f, "All changes are compatible.")
+        } else {
+            writeln!(
+                f,
+                "{} of {} changes are not compatible.",
+                self.incompatible_changes().count(),
+                self.changes.len()
+            )
+        }
+    }
+}
+
+/// The types of a function described by a schema.
+#[derive(Default)]
+struct FunctionTypes<'a> {
+    parameter:    Option<&'a Type>,
+    return_value: Option<&'a Type>,
+    error:        Option<&'a Type>,
+}
+
+/// The types of a contract described by a schema, independent of the schema
+/// version.
+#[derive(Default)]
+struct ContractTypes<'a> {
+    state:     Option<&'a Type>,
+    event:     Option<&'a Type>,
+    functions: BTreeMap<FunctionName, FunctionTypes<'a>>,
+}
+
+impl<'a> From<&'a FunctionV1> for FunctionTypes<'a> {
+    fn from(function: &'a FunctionV1) -> Self {
+        FunctionTypes {
+            parameter:    function.parameter(),
+            return_value: function.return_value(),
+            error:        None,
+        }
+    }
+}
+
+impl<'a> From<&'a FunctionV2> for FunctionTypes<'a> {
+    fn from(function: &'a FunctionV2) -> Self {
+        FunctionTypes {
+            parameter:    function.parameter(),
+            return_value: function.return_value(),
+            error:        function.error(),
+        }
+    }
+}
+
+/// Collect the types of the functions of a contract.
+fn contract_functions<'a, F: 'a>(
+    init: Option<&'a F>,
+    receive: &'a BTreeMap<String, F>,
+) -> BTreeMap<FunctionName, FunctionTypes<'a>>
+where
+    FunctionTypes<'a>: From<&'a F>, {
+    let mut functions = BTreeMap::new();
+    if let Some(init) = init {
+        functions.insert(FunctionName::Init, init.into());
+    }
+    for (name, function) in receive.iter() {
+        functions.insert(FunctionName::Receive(name.clone()), function.into());
+    }
+    functions
+}
+
+/// Collect the types of all contracts described by the module schema.
+fn module_types(schema: &VersionedModuleSchema) -> BTreeMap<&str, ContractTypes<'_>> {
+    let mut contracts = BTreeMap::new();
+    match schema {
+        VersionedModuleSchema::V0(module) => {
+            for (name, contract) in module.contracts.iter() {
+                let mut functions = BTreeMap::new();
+                functions.insert(FunctionName::Init, FunctionTypes {
+                    parameter: contract.init.as_ref(),
+                    ..FunctionTypes::default()
+                });
+                for (function_name, ty) in contract.receive.iter() {
+                    functions.insert(FunctionName::Receive(function_name.clone()), FunctionTypes {
+                        parameter: Some(ty),
+                        ..FunctionTypes::default()
+                    });
+                }
+                contracts.insert(name.as_str(), ContractTypes {
+                    state: contract.state.as_ref(),
+                    event: None,
+                    functions,
+                });
+            }
+        }
+        VersionedModuleSchema::V1(module) => {
+            for (name, contract) in module.contracts.iter() {
+                contracts.insert(name.as_str(), ContractTypes {
+                    functions: contract_functions(contract.init.as_ref(), &contract.receive),
+                    ..ContractTypes::default()
+                });
+            }
+        }
+        VersionedModuleSchema::V2(module) => {
+            for (name, contract) in module.contracts.iter() {
+                contracts.insert(name.as_str(), ContractTypes {
+                    functions: contract_functions(contract.init.as_ref(), &contract.receive),
+                    ..ContractTypes::default()
+                });
+            }
+        }
+        VersionedModuleSchema::V3(ModuleV3 {
+            contracts: module_contracts,
+        })
+        | VersionedModuleSchema::V4(ModuleV4 {
+            contracts: module_contracts,
+        }) => {
+            for (name, contract) in module_contracts.iter() {
+                contracts.insert(name.as_str(), ContractTypes {
+                    event: contract.event(),
+                    functions: contract_functions(contract.init.as_ref(), &contract.receive),
+                    ..ContractTypes::default()
+                });
+            }
+        }
+    }
+    contracts
+}
+
+/// Compare the schemas of two modules, where `new` is the schema of the
+/// module replacing the module with schema `old`.
+///
+/// Contracts and functions present in the old schema but not in the new schema
+/// are reported as removed. Since the schemas can be of different versions,
+/// the comparison is done on the types described by the schemas.
+///
+/// ```
+/// # use concordium_contracts_common::{schema::*, schema_compatibility::*};
+/// # use std::collections::BTreeMap;
+/// fn module(event: Type) -> VersionedModuleSchema {
+///     let mut contracts = BTreeMap::new();
+///     contracts.insert("token".to_string(), ContractV3 {
+///         init:    None,
+///         receive: BTreeMap::new(),
+///         event:   Some(event),
+///     });
+///     VersionedModuleSchema::V3(ModuleV3 {
+///         contracts,
+///     })
+/// }
+/// let old = module(Type::Enum(vec![("Mint".into(), Fields::Unnamed(vec![Type::U64]))]));
+/// let new = module(Type::Enum(vec![
+///     ("Mint".into(), Fields::Unnamed(vec![Type::U64])),
+///     ("Burn".into(), Fields::Unnamed(vec![Type::U64])),
+/// ]));
+/// let report = check_compatibility(&old, &new);
+/// // Events logged by the old contract can be decoded with the new schema, but
+/// // clients using the old schema cannot decode `Burn` events.
+/// assert!(!report.is_compatible());
+/// assert_eq!(
+///     report.to_string(),
+///     "events of contract `token`: widened, clients using the old schema cannot decode all \
+///      values\n  - value: variant `Burn` (tag 1) added\n1 of 1 changes are not compatible.\n"
+/// );
+/// // Removing the variant again is not compatible either.
+/// assert!(matches!(
+///     check_compatibility(&new, &old).changes[0].kind,
+///     ChangeKind::Incompatible { .. }
+/// ));
+/// ```
+pub fn check_compatibility(
+    old: &VersionedModuleSchema,
+    new: &VersionedModuleSchema,
+) -> CompatibilityReport {
+    let mut old_contracts = module_types(old);
+    let new_contracts = module_types(new);
+    let mut report = CompatibilityReport::default();
+    let mut push = |contract: &str, item: SchemaItem, kind: ChangeKind| {
+        report.changes.push(SchemaChange {
+            contract: contract.to_string(),
+            item,
+            kind,
+        })
+    };
+    for (name, new_contract) in new_contracts.iter() {
+        let Some(mut old_contract) = old_contracts.remove(name) else {
+            push(name, SchemaItem::Contract, ChangeKind::Added);
+            continue;
+        };
+        if let Some(kind) = compare_optional(old_contract.state, new_contract.state) {
+            push(name, SchemaItem::State, kind);
+        }
+        for (function_name, new_function) in new_contract.functions.iter() {
+            let Some(old_function) = old_contract.functions.remove(function_name) else {
+                push(name, SchemaItem::Function(function_name.clone()), ChangeKind::Added);
+                continue;
+            };
+            if let Some(kind) = compare_optional(old_function.parameter, new_function.parameter) {
+                push(name, SchemaItem::Parameter(function_name.clone()), kind);
+            }
+            if let Some(kind) = compare_output(old_function.return_value, new_function.return_value)
+            {
+                push(name, SchemaItem::ReturnValue(function_name.clone()), kind);
+            }
+            if let Some(kind) = compare_output(old_function.error, new_function.error) {
+                push(name, SchemaItem::Error(function_name.clone()), kind);
+            }
+        }
+        for function_name in old_contract.functions.into_keys() {
+            push(name, SchemaItem::Function(function_name), ChangeKind::Removed);
+        }
+        if let Some(kind) = compare_output(old_contract.event, new_contract.event) {
+            push(name, SchemaItem::Event, kind);
+        }
+    }
+    for name in old_contracts.into_keys() {
+        push(name, SchemaItem::Contract, ChangeKind::Removed);
+    }
+    report.changes.sort_by(|a, b| a.contract.cmp(&b.contract));
+    report
+}
+
+/// Compare two optional types. Returns `None` if they are equal.
+fn compare_optional(old: Option<&Type>, new: Option<&Type>) -> Option<ChangeKind> {
+    match (old, new) {
+        (None, None) => None,
+        (Some(_), None) => Some(ChangeKind::Removed),
+        (None, Some(_)) => Some(ChangeKind::Added),
+        (Some(old), Some(new)) => compare_types(old, new),
+    }
+}
+
+/// Compare two optional types of outputs of the contract. In addition to the
+/// comparison done by [`compare_optional`], a compatible change is reported as
+/// [`Widened`](ChangeKind::Widened) if values of the new type cannot be
+/// deserialized by the old type.
+fn compare_output(old: Option<&Type>, new: Option<&Type>) -> Option<ChangeKind> {
+    let (Some(old), Some(new)) = (old, new) else {
+        return compare_optional(old, new);
+    };
+    match compare_types(old, new)? {
+        ChangeKind::Compatible {
+            changes,
+        } if compare_types(new, old).is_some_and(|reverse| !reverse.is_compatible()) => {
+            Some(ChangeKind::Widened {
+                changes,
+            })
+        }
+        kind => Some(kind),
+    }
+}
+
+/// Compare two types, where values of type `old` are to be deserialized using
+/// type `new`. Returns `None` if the types are equal.
+///
+/// ```
+/// # use concordium_contracts_common::{schema::*, schema_compatibility::*};
+/// let old = Type::Struct(Fields::Named(vec![("owner".into(), Type::AccountAddress)]));
+/// let new = Type::Struct(Fields::Named(vec![
+///     ("owner".into(), Type::AccountAddress),
+///     ("amount".into(), Type::Amount),
+/// ]));
+/// assert_eq!(
+///     compare_types(&old, &new),
+///     Some(ChangeKind::Incompatible {
+///         problems: vec!["value: number of fields changed from 1 to 2".into()],
+///         changes:  Vec::new(),
+///     })
+/// );
+/// ```
+pub fn compare_types(old: &Type, new: &Type) -> Option<ChangeKind> {
+    let mut comparison = TypeComparison::default();
+    comparison.compare(old, new, "");
+    if !comparison.problems.is_empty() {
+        Some(ChangeKind::Incompatible {
+            problems: comparison.problems,
+            changes:  comparison.changes,
+        })
+    } else if !comparison.changes.is_empty() {
+        Some(ChangeKind::Compatible {
+            changes: comparison.changes,
+        })
+    } else {
+        None
+    }
+}
+
+/// Path of a part of a type, used in the description of changes.
+fn child_path(path: &str, segment: &str) -> String {
+    if path.is_empty() {
+        segment.to_string()
+    } else {
+        format!("{}.{}", path, segment)
+    }
+}
+
+/// Short description of a type used in the description of changes.
+fn describe_type(ty: &Type) -> String {
+    match ty {
+        Type::Unit => "Unit".into(),
+        Type::Bool => "Bool".into(),
+        Type::U8 => "U8".into(),
+        Type::U16 => "U16".into(),
+        Type::U32 => "U32".into(),
+        Type::U64 => "U64".into(),
+        Type::U128 => "U128".into(),
+        Type::I8 => "I8".into(),
+        Type::I16 => "I16".into(),
+        Type::I32 => "I32".into(),
+        Type::I64 => "I64".into(),
+        Type::I128 => "I128".into(),
+        Type::Amount => "Amount".into(),
+        Type::AccountAddress => "AccountAddress".into(),
+        Type::ContractAddress => "ContractAddress".into(),
+        Type::Timestamp => "Timestamp".into(),
+        Type::Duration => "Duration".into(),
+        Type::Pair(..) => "Pair".into(),
+        Type::List(size_length, _) => format!("List ({:?} length)", size_length),
+        Type::Set(size_length, _) => format!("Set ({:?} length)", size_length),
+        Type::Map(size_length, ..) => format!("Map ({:?} length)", size_length),
+        Type::Array(len, _) => format!("Array of length {}", len),
+        Type::Struct(_) => "Struct".into(),
+        Type::Enum(variants) => format!("Enum with {} variants", variants.len()),
+        Type::String(size_length) => format!("String ({:?} length)", size_length),
+        Type::ContractName(size_length) => format!("ContractName ({:?} length)", size_length),
+        Type::ReceiveName(size_length) => format!("ReceiveName ({:?} length)", size_length),
+        Type::ULeb128(max) => format!("ULeb128 (at most {} bytes)", max),
+        Type::ILeb128(max) => format!("ILeb128 (at most {} bytes)", max),
+        Type::ByteList(size_length) => format!("ByteList ({:?} length)", size_length),
+        Type::ByteArray(len) => format!("ByteArray of length {}", len),
+        Type::TaggedEnum(variants) => format!("TaggedEnum with {} variants", variants.len()),
+        Type::Hash => "Hash".into(),
+        Type::ModuleReference => "ModuleReference".into(),
+        Type::PublicKeyEd25519 => "PublicKeyEd25519".into(),
+        Type::PublicKeyEcdsaSecp256k1 => "PublicKeyEcdsaSecp256k1".into(),
+        Type::SignatureEd25519 => "SignatureEd25519".into(),
+        Type::SignatureEcdsaSecp256k1 => "SignatureEcdsaSecp256k1".into(),
+        Type::TokenId => "TokenId".into(),
+    }
+}
+
+/// Whether the type is serialized as a 64-bit unsigned integer.
+fn is_u64_encoded(ty: &Type) -> bool {
+    matches!(ty, Type::U64 | Type::Amount | Type::Timestamp | Type::Duration)
+}
+
+/// Whether the serialization of all values of the type is empty.
+fn is_empty_encoded(ty: &Type) -> bool {
+    match ty {
+        Type::Unit | Type::Struct(Fields::None) => true,
+        Type::Struct(Fields::Named(fields)) => fields.is_empty(),
+        Type::Struct(Fields::Unnamed(fields)) => fields.is_empty(),
+        _ => false,
+    }
+}
+
+/// The variants of an enum by tag, along with the number of bytes used for the
+/// tag. Returns `None` if the type is not an enum.
+#[allow(clippy::type_complexity)]
+fn enum_variants(ty: &Type) -> Option<(u8, BTreeMap<usize, (&String, &Fields)>)> {
+    match ty {
+        Type::Enum(variants) => {
+            let tag_bytes = if variants.len() <= 256 {
+                1
+            } else {
+                2
+            };
+            let variants =
+                variants.iter().enumerate().map(|(tag, (name, fields))| (tag, (name, fields)));
+            Some((tag_bytes, variants.collect()))
+        }
+        Type::TaggedEnum(variants) => {
+            let variants =
+                variants.iter().map(|(tag, (name, fields))| (usize::from(*tag), (name, fields)));
+            Some((1, variants.collect()))
+        }
+        _ => None,
+    }
+}
+
+/// Accumulated result of comparing two types.
+#[derive(Default)]
+struct TypeComparison {
+    /// Changes breaking compatibility.
+    problems: Vec<String>,
+    /// Compatible changes.
+    changes:  Vec<String>,
+}
+
+impl TypeComparison {
+    fn problem(&mut self, path: &str, description: String) {
+        let location = if path.is_empty() {
+            "value"
+        } else {
+            path
+        };
+        self.problems.push(format!("{}: {}", location, description));
+    }
+
+    fn change(&mut self, path: &str, description: String) {
+        let location = if path.is_empty() {
+            "value"
+        } else {
+            path
+        };
+        self.changes.push(format!("{}: {}", location, description));
+    }
+
+    fn compare_size_length(&mut self, old: SizeLength, new: SizeLength, path: &str) {
+        if old != new {
+            self.problem(path, format!("length prefix changed from {:?} to {:?}", old, new));
+        }
+    }
+
+    fn compare(&mut self, old: &Type, new: &Type, path: &str) {
+        if old == new {
+            return;
+        }
+        // Hashes, keys, signatures and token IDs have the same representation as
+        // the corresponding byte types.
+        if old.byte_representation().is_some() || new.byte_representation().is_some() {
+            let old_bytes = old.byte_representation().unwrap_or_else(|| old.clone());
+            let new_bytes = new.byte_representation().unwrap_or_else(|| new.clone());
+            if old_bytes == new_bytes {
+                self.change(
+                    path,
+                    format!("changed from {} to {}", describe_type(old), describe_type(new)),
+                );
+            } else {
+                self.compare(&old_bytes, &new_bytes, path);
+            }
+            return;
+        }
+        match (old, new) {
+            (Type::Pair(old_left, old_right), Type::Pair(new_left, new_right)) => {
+                self.compare(old_left, new_left, &child_path(path, "0"));
+                self.compare(old_right, new_right, &child_path(path, "1"));
+            }
+            (Type::List(old_len, old_ty), Type::List(new_len, new_ty))
+            | (Type::Set(old_len, old_ty), Type::Set(new_len, new_ty)) => {
+                self.compare_size_length(*old_len, *new_len, path);
+                self.compare(old_ty, new_ty, &child_path(path, "items"));
+            }
+            (Type::Set(old_len, old_ty), Type::List(new_len, new_ty)) => {
+                self.change(path, "changed from a set to a list".into());
+                self.compare_size_length(*old_len, *new_len, path);
+                self.compare(old_ty, new_ty, &child_path(path, "items"));
+            }
+            (Type::Map(old_len, old_key, old_value), Type::Map(new_len, new_key, new_value)) => {
+                self.compare_size_length(*old_len, *new_len, path);
+                self.compare(old_key, new_key, &child_path(path, "keys"));
+                self.compare(old_value, new_value, &child_path(path, "values"));
+            }
+            (Type::Array(old_len, old_ty), Type::Array(new_len, new_ty)) => {
+                if old_len != new_len {
+                    self.problem(
+                        path,
+                        format!("array length changed from {} to {}", old_len, new_len),
+                    );
+                }
+                self.compare(old_ty, new_ty, &child_path(path, "items"));
+            }
+            (Type::ByteArray(old_len), Type::Array(new_len, item))
+            | (Type::Array(old_len, item), Type::ByteArray(new_len))
+                if **item == Type::U8 =>
+            {
+                if old_len == new_len {
+                    self.change(
+                        path,
+                        format!("changed from {} to {}", describe_type(old), describe_type(new)),
+                    );
+                } else {
+                    self.problem(
+                        path,
+                        format!("array length changed from {} to {}", old_len, new_len),
+                    );
+                }
+            }
+            (Type::ByteList(old_len), Type::List(new_len, item))
+            | (Type::List(old_len, item), Type::ByteList(new_len))
+                if **item == Type::U8 =>
+            {
+                self.change(path, "changed between a byte list and a list of U8".into());
+                self.compare_size_length(*old_len, *new_len, path);
+            }
+            (Type::String(old_len), Type::ByteList(new_len))
+            | (Type::ContractName(old_len), Type::String(new_len))
+            | (Type::ReceiveName(old_len), Type::String(new_len)) => {
+                self.change(
+                    path,
+                    format!("changed from {} to {}", describe_type(old), describe_type(new)),
+                );
+                self.compare_size_length(*old_len, *new_len, path);
+            }
+            (Type::ULeb128(old_max), Type::ULeb128(new_max))
+            | (Type::ILeb128(old_max), Type::ILeb128(new_max)) => {
+                if new_max > old_max {
+                    self.change(
+                        path,
+                        format!("maximum size increased from {} to {} bytes", old_max, new_max),
+                    );
+                } else {
+                    self.problem(
+                        path,
+                        format!("maximum size decreased from {} to {} bytes", old_max, new_max),
+                    );
+                }
+            }
+            (Type::Struct(old_fields), Type::Struct(new_fields)) => {
+                self.compare_fields(old_fields, new_fields, path)
+            }
+            _ => {
+                if let (Some(old_variants), Some(new_variants)) =
+                    (enum_variants(old), enum_variants(new))
+                {
+                    self.compare_variants(old_variants, new_variants, path);
+                } else if (is_u64_encoded(old) && is_u64_encoded(new))
+                    || (is_empty_encoded(old) && is_empty_encoded(new))
+                {
+                    self.change(
+                        path,
+                        format!("changed from {} to {}", describe_type(old), describe_type(new)),
+                    );
+                } else {
+                    self.problem(
+                        path,
+                        format!("changed from {} to {}", describe_type(old), describe_type(new)),
+                    );
+                }
+            }
+        }
+    }
+
+    fn compare_fields(&mut self, old: &Fields, new: &Fields, path: &str) {
+        fn labelled(fields: &Fields) -> Vec<(String, Option<&String>, &Type)> {
+            match fields {
+                Fields::Named(fields) => {
+                    fields.iter().map(|(name, ty)| (name.clone(), Some(name), ty)).collect()
+                }
+                Fields::Unnamed(fields) => {
+                    fields.iter().enumerate().map(|(i, ty)| (i.to_string(), None, ty)).collect()
+                }
+                Fields::None => Vec::new(),
+            }
+        }
+        fn kind(fields: &Fields) -> &'static str {
+            match fields {
+                Fields::Named(_) => "named fields",
+                Fields::Unnamed(_) => "unnamed fields",
+                Fields::None => "no fields",
+            }
+        }
+
+        if std::mem::discriminant(old) != std::mem::discriminant(new) {
+            self.change(path, format!("changed from {} to {}", kind(old), kind(new)));
+        }
+        let old = labelled(old);
+        let new = labelled(new);
+        if old.len() != new.len() {
+            self.problem(
+                path,
+                format!("number of fields changed from {} to {}", old.len(), new.len()),
+            );
+            return;
+        }
+        for ((label, old_name, old_ty), (_, new_name, new_ty)) in old.iter().zip(new.iter()) {
+            if let (Some(old_name), Some(new_name)) = (old_name, new_name) {
+                if old_name != new_name {
+                    self.change(path, format!("field `{}` renamed to `{}`", old_name, new_name));
+                }
+            }
+            self.compare(old_ty, new_ty, &child_path(path, label));
+        }
+    }
+
+    fn compare_variants(
+        &mut self,
+        (old_tag_bytes, old): (u8, BTreeMap<usize, (&String, &Fields)>),
+        (new_tag_bytes, mut new): (u8, BTreeMap<usize, (&String, &Fields)>),
+        path: &str,
+    ) {
+        if old_tag_bytes != new_tag_bytes {
+            self.problem(
+                path,
+                format!("tag size changed from {} to {} bytes", old_tag_bytes, new_tag_bytes),
+            );
+        }
+        for (tag, (old_name, old_fields)) in old.into_iter() {
+            let Some((new_name, new_fields)) = new.remove(&tag) else {
+                self.problem(path, format!("variant `{}` (tag {}) removed", old_name, tag));
+                continue;
+            };
+            if old_name != new_name {
+                self.change(
+                    path,
+                    format!("variant `{}` (tag {}) renamed to `{}`", old_name, tag, new_name),
+                );
+            }
+            self.compare_fields(old_fields, new_fields, &child_path(path, old_name));
+        }
+        for (tag, (name, _)) in new.into_iter() {
+            self.change(path, format!("variant `{}` (tag {}) added", name, tag));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module_v2(receive: &[(&str, Option<Type>, Option<Type>)]) -> VersionedModuleSchema {
+        let receive = receive
+            .iter()
+            .map(|(name, parameter, error)| {
+                (name.to_string(), FunctionV2 {
+                    parameter:    parameter.clone(),
+                    return_value: None,
+                    error:        error.clone(),
+                })
+            })
+            .collect();
+        let mut contracts = BTreeMap::new();
+        contracts.insert("auction".to_string(), ContractV2 {
+            init: None,
+            receive,
+        });
+        VersionedModuleSchema::V2(ModuleV2 {
+            contracts,
+        })
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let schema = module_v2(&[("bid", Some(Type::Amount), None)]);
+        let report = check_compatibility(&schema, &schema);
+        assert!(report.changes.is_empty());
+        assert_eq!(report.to_string(), "No changes.\n");
+    }
+
+    #[test]
+    fn test_removed_and_added_functions() {
+        let old = module_v2(&[("bid", Some(Type::Amount), None), ("view", None, None)]);
+        let new = module_v2(&[("bid", None, Some(Type::U8)), ("finalize", None, None)]);
+        let report = check_compatibility(&old, &new);
+        assert!(!report.is_compatible());
+        assert_eq!(report.changes, vec![
+            SchemaChange {
+                contract: "auction".into(),
+                item:     SchemaItem::Parameter(FunctionName::Receive("bid".into())),
+                kind:     ChangeKind::Removed,
+            },
+            SchemaChange {
+                contract: "auction".into(),
+                item:     SchemaItem::Error(FunctionName::Receive("bid".into())),
+                kind:     ChangeKind::Added,
+            },
+            SchemaChange {
+                contract: "auction".into(),
+                item:     SchemaItem::Function(FunctionName::Receive("finalize".into())),
+                kind:     ChangeKind::Added,
+            },
+            SchemaChange {
+                contract: "auction".into(),
+                item:     SchemaItem::Function(FunctionName::Receive("view".into())),
+                kind:     ChangeKind::Removed,
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_schema_versions_are_compared_by_types() {
+        let mut receive = BTreeMap::new();
+        receive.insert("bid".to_string(), FunctionV1::Parameter(Type::Amount));
+        let mut contracts = BTreeMap::new();
+        contracts.insert("auction".to_string(), ContractV1 {
+            init: None,
+            receive,
+        });
+        let old = VersionedModuleSchema::V1(ModuleV1 {
+            contracts,
+        });
+        let new = module_v2(&[("bid", Some(Type::Amount), None)]);
+        assert!(check_compatibility(&old, &new).changes.is_empty());
+    }
+
+    #[test]
+    fn test_outputs_are_checked_in_both_directions() {
+        let status = |variants: &[&str]| {
+            Type::Enum(variants.iter().map(|name| (name.to_string(), Fields::None)).collect())
+        };
+        let old = module_v2(&[("bid", Some(status(&["Open"])), Some(status(&["TooLow"])))]);
+        let new = module_v2(&[(
+            "bid",
+            Some(status(&["Open", "Closed"])),
+            Some(status(&["TooLow", "Closed"])),
+        )]);
+        // A new variant of the parameter only means there are new values to send,
+        // whereas old clients cannot decode the new error.
+        let report = check_compatibility(&old, &new);
+        assert_eq!(report.changes, vec![
+            SchemaChange {
+                contract: "auction".into(),
+                item:     SchemaItem::Parameter(FunctionName::Receive("bid".into())),
+                kind:     ChangeKind::Compatible {
+                    changes: vec!["value: variant `Closed` (tag 1) added".into()],
+                },
+            },
+            SchemaChange {
+                contract: "auction".into(),
+                item:     SchemaItem::Error(FunctionName::Receive("bid".into())),
+                kind:     ChangeKind::Widened {
+                    changes: vec!["value: variant `Closed` (tag 1) added".into()],
+                },
+            },
+        ]);
+        assert!(!report.is_compatible());
+        // Changes that are compatible in both directions are not widening.
+        let new = module_v2(&[("bid", None, Some(status(&["BidTooLow"])))]);
+        let report = check_compatibility(&old, &new);
+        assert_eq!(report.changes[1].kind, ChangeKind::Compatible {
+            changes: vec!["value: variant `TooLow` (tag 0) renamed to `BidTooLow`".into()],
+        });
+    }
+
+    #[test]
+    fn test_compatible_type_changes() {
+        let old = Type::Struct(Fields::Named(vec![
+            ("owner".into(), Type::AccountAddress),
+            ("deadline".into(), Type::U64),
+            ("tags".into(), Type::Set(SizeLength::U8, Box::new(Type::String(SizeLength::U8)))),
+            ("amount".into(), Type::ULeb128(8)),
+        ]));
+        let new = Type::Struct(Fields::Named(vec![
+            ("owner".into(), Type::AccountAddress),
+            ("end".into(), Type::Timestamp),
+            ("tags".into(), Type::List(SizeLength::U8, Box::new(Type::ByteList(SizeLength::U8)))),
+            ("amount".into(), Type::ULeb128(16)),
+        ]));
+        assert_eq!(
+            compare_types(&old, &new),
+            Some(ChangeKind::Compatible {
+                changes: vec![
+                    "value: field `deadline` renamed to `end`".into(),
+                    "deadline: changed from U64 to Timestamp".into(),
+                    "tags: changed from a set to a list".into(),
+                    "tags.items: changed from String (U8 length) to ByteList (U8 length)".into(),
+                    "amount: maximum size increased from 8 to 16 bytes".into(),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_byte_type_changes() {
+        let old = Type::Pair(
+            Box::new(Type::ByteArray(32)),
+            Box::new(Type::List(SizeLength::U8, Box::new(Type::ByteList(SizeLength::U8)))),
+        );
+        let new = Type::Pair(
+            Box::new(Type::PublicKeyEd25519),
+            Box::new(Type::List(SizeLength::U8, Box::new(Type::TokenId))),
+        );
+        assert_eq!(
+            compare_types(&old, &new),
+            Some(ChangeKind::Compatible {
+                changes: vec![
+                    "0: changed from ByteArray of length 32 to PublicKeyEd25519".into(),
+                    "1.items: changed from ByteList (U8 length) to TokenId".into(),
+                ],
+            })
+        );
+        assert_eq!(
+            compare_types(&Type::Hash, &Type::SignatureEd25519),
+            Some(ChangeKind::Incompatible {
+                problems: vec!["value: changed from ByteArray of length 32 to ByteArray of \
+                                length 64"
+                    .into()],
+                changes:  Vec::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_incompatible_type_changes() {
+        let mut old_variants = BTreeMap::new();
+        old_variants.insert(1, ("Open".to_string(), Fields::None));
+        old_variants.insert(2, ("Closed".to_string(), Fields::Unnamed(vec![Type::U32])));
+        let mut new_variants = BTreeMap::new();
+        new_variants.insert(2, ("Closed".to_string(), Fields::Unnamed(vec![Type::U64])));
+        new_variants.insert(3, ("Cancelled".to_string(), Fields::None));
+        let old = Type::List(SizeLength::U16, Box::new(Type::TaggedEnum(old_variants)));
+        let new = Type::List(SizeLength::U32, Box::new(Type::TaggedEnum(new_variants)));
+        assert_eq!(
+            compare_types(&old, &new),
+            Some(ChangeKind::Incompatible {
+                problems: vec![
+                    "value: length prefix changed from U16 to U32".into(),
+                    "items: variant `Open` (tag 1) removed".into(),
+                    "items.Closed.0: changed from U32 to U64".into(),
+                ],
+                changes:  vec!["items: variant `Cancelled` (tag 3) added".into()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_enum_tag_size() {
+        let variants =
+            |n: usize| Type::Enum((0..n).map(|i| (format!("V{}", i), Fields::None)).collect());
+        assert!(compare_types(&variants(200), &variants(256)).unwrap().is_compatible());
+        assert!(!compare_types(&variants(200), &variants(257)).unwrap().is_compatible());
+    }
+}
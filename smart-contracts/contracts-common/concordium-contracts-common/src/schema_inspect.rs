@@ -0,0 +1,784 @@
+//! Inspection of schema-typed bytes for debugging.
+//!
+//! [`Node::decode`] decodes bytes according to a schema [`Type`] into a tree
+//! of [`Node`]s, recording which bytes belong to which part of the value. The
+//! tree is used by
+//! - [`hex_dump`] to produce a hex dump annotating the bytes with the path of
+//!   the value they encode, and
+//! - [`diff`] to compute the structural differences between two values of the
+//!   same type.
+//!
+//! Values are identified by [`Path`]s following the JSON representation of the
+//! value, e.g., `$.balances[2].Some.0` for the first field of the `Some`
+//! variant in the third element of the field `balances`.
+use crate::{
+    schema::*,
+    schema_stream::{DecodeError, DecodeLimits},
+    *,
+};
+use std::{collections::BTreeMap, fmt, ops::Range};
+
+/// An error when decoding bytes according to a schema.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Could not decode {schema} at byte {position}: {reason}")]
+pub struct InspectError {
+    /// Offset of the value that could not be decoded.
+    pub position: usize,
+    /// The schema type of the value that could not be decoded.
+    pub schema:   String,
+    /// Description of the problem.
+    pub reason:   String,
+}
+
+/// A segment of a [`Path`].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PathSegment {
+    /// A named field of a struct or variant, or the name of an enum variant.
+    Field(String),
+    /// A positional field of a pair, struct or variant.
+    Position(usize),
+    /// An element of a list, set or array.
+    Index(usize),
+    /// The value of a map entry, identified by the JSON representation of the
+    /// key.
+    Key(String),
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathSegment::Field(name) => write!(f, ".{}", name),
+            PathSegment::Position(i) => write!(f, ".{}", i),
+            PathSegment::Index(i) => write!(f, "[{}]", i),
+            PathSegment::Key(key) => write!(f, "[{}]", key),
+        }
+    }
+}
+
+/// The location of a value nested in another value. Displayed as `$`
+/// followed by the segments.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Path(pub Vec<PathSegment>);
+
+impl Path {
+    fn child(&self, segment: PathSegment) -> Path {
+        let mut segments = self.0.clone();
+        segments.push(segment);
+        Path(segments)
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "$")?;
+        for segment in &self.0 {
+            segment.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A decoded value together with the bytes encoding it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    /// The bytes encoding the value, including any length prefix or tag.
+    pub span: Range<usize>,
+    /// The decoded value.
+    pub kind: NodeKind,
+}
+
+/// The structure of a decoded value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    /// A value without nested values, in its JSON representation. Contract
+    /// addresses, contract names and receive names are leaves as well.
+    Leaf(serde_json::Value),
+    /// The fields of a pair, a struct or an enum variant.
+    Fields {
+        /// Whether the fields are named, i.e., represented as a JSON object.
+        named:  bool,
+        fields: Vec<(PathSegment, Node)>,
+    },
+    /// The elements of a list or an array. Arrays have no length prefix.
+    List {
+        length: Option<Range<usize>>,
+        items:  Vec<Node>,
+    },
+    /// The elements of a set.
+    Set {
+        length: Range<usize>,
+        items:  Vec<Node>,
+    },
+    /// The entries of a map.
+    Map {
+        length:  Range<usize>,
+        entries: Vec<(Node, Node)>,
+    },
+    /// An enum variant.
+    Variant {
+        tag:    Range<usize>,
+        name:   String,
+        fields: Box<Node>,
+    },
+}
+
+impl Node {
+    /// Decode `bytes` as a value of type `ty`. All of the bytes must be
+    /// consumed.
+    pub fn decode(ty: &Type, bytes: &[u8]) -> Result<Node, InspectError> {
+        let mut source = Cursor::new(bytes);
+        let node = decode_node(ty, &mut source)?;
+        if source.offset != bytes.len() {
+            return Err(InspectError {
+                position: source.offset,
+                schema:   format!("{:?}", ty),
+                reason:   format!("{} unexpected trailing bytes", bytes.len() - source.offset),
+            });
+        }
+        Ok(node)
+    }
+
+    /// The JSON representation of the value, as produced by
+    /// [`Type::to_json`](crate::schema::Type::to_json).
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::Value;
+        match &self.kind {
+            NodeKind::Leaf(value) => value.clone(),
+            NodeKind::Fields {
+                named: true,
+                fields,
+            } => Value::Object(
+                fields
+                    .iter()
+                    .map(|(segment, node)| (segment_key(segment), node.to_json()))
+                    .collect(),
+            ),
+            NodeKind::Fields {
+                named: false,
+                fields,
+            } => Value::Array(fields.iter().map(|(_, node)| node.to_json()).collect()),
+            NodeKind::List {
+                items,
+                ..
+            }
+            | NodeKind::Set {
+                items,
+                ..
+            } => Value::Array(items.iter().map(Node::to_json).collect()),
+            NodeKind::Map {
+                entries,
+                ..
+            } => Value::Array(
+                entries
+                    .iter()
+                    .map(|(key, value)| Value::Array(vec![key.to_json(), value.to_json()]))
+                    .collect(),
+            ),
+            NodeKind::Variant {
+                name,
+                fields,
+                ..
+            } => {
+                let mut map = serde_json::Map::new();
+                map.insert(name.clone(), fields.to_json());
+                Value::Object(map)
+            }
+        }
+    }
+}
+
+fn segment_key(segment: &PathSegment) -> String {
+    match segment {
+        PathSegment::Field(name) | PathSegment::Key(name) => name.clone(),
+        PathSegment::Position(i) | PathSegment::Index(i) => i.to_string(),
+    }
+}
+
+fn inspect_error(ty: &Type, position: usize, reason: impl Into<String>) -> InspectError {
+    InspectError {
+        position,
+        schema: format!("{:?}", ty),
+        reason: reason.into(),
+    }
+}
+
+/// Decode a length prefix and return it together with its span.
+fn decode_length(
+    ty: &Type,
+    size_len: SizeLength,
+    source: &mut Cursor<&[u8]>,
+) -> Result<(usize, Range<usize>), InspectError> {
+    let start = source.offset;
+    let length = deserial_length(source, size_len)
+        .map_err(|_| inspect_error(ty, start, "could not parse length"))?;
+    Ok((length, start..source.offset))
+}
+
+fn decode_node(ty: &Type, source: &mut Cursor<&[u8]>) -> Result<Node, InspectError> {
+    let start = source.offset;
+    let kind = match ty {
+        Type::Pair(left, right) => NodeKind::Fields {
+            named:  false,
+            fields: vec![
+                (PathSegment::Position(0), decode_node(left, source)?),
+                (PathSegment::Position(1), decode_node(right, source)?),
+            ],
+        },
+        Type::Struct(fields) => decode_fields(fields, source)?,
+        Type::List(size_len, item) => {
+            let (length, length_span) = decode_length(ty, *size_len, source)?;
+            NodeKind::List {
+                length: Some(length_span),
+                items:  decode_items(item, length, source)?,
+            }
+        }
+        Type::Set(size_len, item) => {
+            let (length, length_span) = decode_length(ty, *size_len, source)?;
+            NodeKind::Set {
+                length: length_span,
+                items:  decode_items(item, length, source)?,
+            }
+        }
+        Type::Array(length, item) => NodeKind::List {
+            length: None,
+            items:  decode_items(item, *length as usize, source)?,
+        },
+        Type::Map(size_len, key, value) => {
+            let (length, length_span) = decode_length(ty, *size_len, source)?;
+            let mut entries = Vec::new();
+            for _ in 0..length {
+                let key = decode_node(key, source)?;
+                let value = decode_node(value, source)?;
+                entries.push((key, value));
+            }
+            NodeKind::Map {
+                length: length_span,
+                entries,
+            }
+        }
+        Type::Enum(variants) => {
+            let tag = if variants.len() <= 256 {
+                u8::deserial(source).map(usize::from)
+            } else {
+                u16::deserial(source).map(usize::from)
+            }
+            .map_err(|_| inspect_error(ty, start, "could not parse tag"))?;
+            let tag_span = start..source.offset;
+            let (name, fields) = variants
+                .get(tag)
+                .ok_or_else(|| inspect_error(ty, start, format!("unknown variant tag {}", tag)))?;
+            decode_variant(tag_span, name, fields, source)?
+        }
+        Type::TaggedEnum(variants) => {
+            let tag = u8::deserial(source)
+                .map_err(|_| inspect_error(ty, start, "could not parse tag"))?;
+            let tag_span = start..source.offset;
+            let (name, fields) = variants
+                .get(&tag)
+                .ok_or_else(|| inspect_error(ty, start, format!("unknown variant tag {}", tag)))?;
+            decode_variant(tag_span, name, fields, source)?
+        }
+        _ => {
+            let mut json = Vec::new();
+            ty.write_json(source, &DecodeLimits::default(), &mut json).map_err(|e| {
+                let reason = match e {
+                    DecodeError::Parse {
+                        reason,
+                        ..
+                    } => reason,
+                    e => e.to_string(),
+                };
+                inspect_error(ty, start, reason)
+            })?;
+            let value = serde_json::from_slice(&json)
+                .map_err(|e| inspect_error(ty, start, e.to_string()))?;
+            NodeKind::Leaf(value)
+        }
+    };
+    Ok(Node {
+        span: start..source.offset,
+        kind,
+    })
+}
+
+fn decode_items(
+    item: &Type,
+    length: usize,
+    source: &mut Cursor<&[u8]>,
+) -> Result<Vec<Node>, InspectError> {
+    let mut items = Vec::new();
+    for _ in 0..length {
+        items.push(decode_node(item, source)?);
+    }
+    Ok(items)
+}
+
+fn decode_fields(fields: &Fields, source: &mut Cursor<&[u8]>) -> Result<NodeKind, InspectError> {
+    Ok(match fields {
+        Fields::Named(fields) => {
+            let mut nodes = Vec::with_capacity(fields.len());
+            for (name, ty) in fields {
+                nodes.push((PathSegment::Field(name.clone()), decode_node(ty, source)?));
+            }
+            NodeKind::Fields {
+                named:  true,
+                fields: nodes,
+            }
+        }
+        Fields::Unnamed(fields) => {
+            let mut nodes = Vec::with_capacity(fields.len());
+            for (i, ty) in fields.iter().enumerate() {
+                nodes.push((PathSegment::Position(i), decode_node(ty, source)?));
+            }
+            NodeKind::Fields {
+                named:  false,
+                fields: nodes,
+            }
+        }
+        Fields::None => NodeKind::Fields {
+            named:  false,
+            fields: Vec::new(),
+        },
+    })
+}
+
+fn decode_variant(
+    tag: Range<usize>,
+    name: &str,
+    fields: &Fields,
+    source: &mut Cursor<&[u8]>,
+) -> Result<NodeKind, InspectError> {
+    let start = source.offset;
+    let kind = decode_fields(fields, source)?;
+    Ok(NodeKind::Variant {
+        tag,
+        name: name.to_string(),
+        fields: Box::new(Node {
+            span: start..source.offset,
+            kind,
+        }),
+    })
+}
+
+/// Number of bytes shown on each line of a [`hex_dump`].
+const BYTES_PER_LINE: usize = 16;
+
+/// Produce a hex dump of `bytes` decoded as a value of type `ty`. Each line
+/// shows the offset and the bytes of a length prefix, enum tag or leaf value,
+/// followed by the path of the value and its JSON representation. Values
+/// longer than 16 bytes continue on the following lines.
+///
+/// ```
+/// # use concordium_contracts_common::{schema::*, schema_inspect::hex_dump};
+/// let ty = Type::Struct(Fields::Named(vec![
+///     ("owner".into(), Type::U16),
+///     ("tags".into(), Type::List(SizeLength::U8, Box::new(Type::Bool))),
+/// ]));
+/// let dump = hex_dump(&ty, &[1, 0, 2, 1, 0]).unwrap();
+/// assert_eq!(
+///     dump,
+///     "\
+/// 00000000  01 00                                            $.owner = 1
+/// 00000002  02                                               $.tags (length 2)
+/// 00000003  01                                               $.tags[0] = true
+/// 00000004  00                                               $.tags[1] = false
+/// "
+/// );
+/// ```
+pub fn hex_dump(ty: &Type, bytes: &[u8]) -> Result<String, InspectError> {
+    let node = Node::decode(ty, bytes)?;
+    let mut out = String::new();
+    dump_node(&node, &Path::default(), bytes, &mut out);
+    Ok(out)
+}
+
+/// Append the lines for the bytes `span` annotated with `label` to `out`.
+fn dump_line(span: &Range<usize>, label: &str, bytes: &[u8], out: &mut String) {
+    use std::fmt::Write;
+    let data = &bytes[span.clone()];
+    if data.is_empty() {
+        let _ = writeln!(out, "{:08x}  {:48} {}", span.start, "", label);
+        return;
+    }
+    for (i, chunk) in data.chunks(BYTES_PER_LINE).enumerate() {
+        let hex = chunk.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ");
+        let offset = span.start + i * BYTES_PER_LINE;
+        if i == 0 {
+            let _ = writeln!(out, "{:08x}  {:48} {}", offset, hex, label);
+        } else {
+            let _ = writeln!(out, "{:08x}  {}", offset, hex);
+        }
+    }
+}
+
+fn dump_node(node: &Node, path: &Path, bytes: &[u8], out: &mut String) {
+    match &node.kind {
+        NodeKind::Leaf(value) => {
+            dump_line(&node.span, &format!("{} = {}", path, value), bytes, out)
+        }
+        NodeKind::Fields {
+            fields,
+            ..
+        } => {
+            for (segment, field) in fields {
+                dump_node(field, &path.child(segment.clone()), bytes, out);
+            }
+        }
+        NodeKind::List {
+            length,
+            items,
+        } => {
+            if let Some(length) = length {
+                dump_line(length, &format!("{} (length {})", path, items.len()), bytes, out);
+            }
+            for (i, item) in items.iter().enumerate() {
+                dump_node(item, &path.child(PathSegment::Index(i)), bytes, out);
+            }
+        }
+        NodeKind::Set {
+            length,
+            items,
+        } => {
+            dump_line(length, &format!("{} (length {})", path, items.len()), bytes, out);
+            for (i, item) in items.iter().enumerate() {
+                dump_node(item, &path.child(PathSegment::Index(i)), bytes, out);
+            }
+        }
+        NodeKind::Map {
+            length,
+            entries,
+        } => {
+            dump_line(length, &format!("{} (length {})", path, entries.len()), bytes, out);
+            for (key, value) in entries {
+                let entry = path.child(PathSegment::Key(key.to_json().to_string()));
+                dump_node(key, &entry.child(PathSegment::Field("key".into())), bytes, out);
+                dump_node(value, &entry, bytes, out);
+            }
+        }
+        NodeKind::Variant {
+            tag,
+            name,
+            fields,
+        } => {
+            dump_line(tag, &format!("{} (variant {})", path, name), bytes, out);
+            dump_node(fields, &path.child(PathSegment::Field(name.clone())), bytes, out);
+        }
+    }
+}
+
+/// A difference between two values of the same type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// The value at the path changed. Reported for leaves, and for enums where
+    /// the variant changed.
+    Changed {
+        path: Path,
+        old:  serde_json::Value,
+        new:  serde_json::Value,
+    },
+    /// An element or map entry only present in the new value.
+    Added {
+        path:  Path,
+        value: serde_json::Value,
+    },
+    /// An element or map entry only present in the old value.
+    Removed {
+        path:  Path,
+        value: serde_json::Value,
+    },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difference::Changed {
+                path,
+                old,
+                new,
+            } => write!(f, "~ {}: {} -> {}", path, old, new),
+            Difference::Added {
+                path,
+                value,
+            } => write!(f, "+ {}: {}", path, value),
+            Difference::Removed {
+                path,
+                value,
+            } => write!(f, "- {}: {}", path, value),
+        }
+    }
+}
+
+/// Decode `old` and `new` as values of type `ty` and compute the differences
+/// between them, in the order of the fields in the schema.
+///
+/// Elements of lists and arrays are compared by position, with elements
+/// beyond the length of the other list reported as added or removed. Elements
+/// of sets, and entries of maps, are matched by value and key respectively.
+///
+/// ```
+/// # use concordium_contracts_common::{schema::*, schema_inspect::diff};
+/// let ty = Type::Struct(Fields::Named(vec![
+///     ("owner".into(), Type::U16),
+///     ("tags".into(), Type::List(SizeLength::U8, Box::new(Type::Bool))),
+/// ]));
+/// let differences = diff(&ty, &[1, 0, 1, 1], &[2, 0, 2, 1, 0]).unwrap();
+/// let lines: Vec<String> = differences.iter().map(|d| d.to_string()).collect();
+/// assert_eq!(lines, ["~ $.owner: 1 -> 2", "+ $.tags[1]: false"]);
+/// ```
+pub fn diff(ty: &Type, old: &[u8], new: &[u8]) -> Result<Vec<Difference>, InspectError> {
+    let old = Node::decode(ty, old)?;
+    let new = Node::decode(ty, new)?;
+    let mut differences = Vec::new();
+    diff_nodes(&old, &new, &Path::default(), &mut differences);
+    Ok(differences)
+}
+
+fn diff_nodes(old: &Node, new: &Node, path: &Path, out: &mut Vec<Difference>) {
+    match (&old.kind, &new.kind) {
+        (
+            NodeKind::Fields {
+                fields: old_fields,
+                ..
+            },
+            NodeKind::Fields {
+                fields: new_fields,
+                ..
+            },
+        ) => {
+            for ((segment, old), (_, new)) in old_fields.iter().zip(new_fields) {
+                diff_nodes(old, new, &path.child(segment.clone()), out);
+            }
+        }
+        (
+            NodeKind::List {
+                items: old_items,
+                ..
+            },
+            NodeKind::List {
+                items: new_items,
+                ..
+            },
+        ) => {
+            for (i, (old, new)) in old_items.iter().zip(new_items).enumerate() {
+                diff_nodes(old, new, &path.child(PathSegment::Index(i)), out);
+            }
+            for (i, old) in old_items.iter().enumerate().skip(new_items.len()) {
+                out.push(Difference::Removed {
+                    path:  path.child(PathSegment::Index(i)),
+                    value: old.to_json(),
+                });
+            }
+            for (i, new) in new_items.iter().enumerate().skip(old_items.len()) {
+                out.push(Difference::Added {
+                    path:  path.child(PathSegment::Index(i)),
+                    value: new.to_json(),
+                });
+            }
+        }
+        (
+            NodeKind::Set {
+                items: old_items,
+                ..
+            },
+            NodeKind::Set {
+                items: new_items,
+                ..
+            },
+        ) => {
+            let old_values: Vec<_> = old_items.iter().map(Node::to_json).collect();
+            let new_values: Vec<_> = new_items.iter().map(Node::to_json).collect();
+            for (i, value) in old_values.iter().enumerate() {
+                if !new_values.contains(value) {
+                    out.push(Difference::Removed {
+                        path:  path.child(PathSegment::Index(i)),
+                        value: value.clone(),
+                    });
+                }
+            }
+            for (i, value) in new_values.iter().enumerate() {
+                if !old_values.contains(value) {
+                    out.push(Difference::Added {
+                        path:  path.child(PathSegment::Index(i)),
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+        (
+            NodeKind::Map {
+                entries: old_entries,
+                ..
+            },
+            NodeKind::Map {
+                entries: new_entries,
+                ..
+            },
+        ) => {
+            let key = |node: &Node| node.to_json().to_string();
+            let new_by_key: BTreeMap<String, &Node> =
+                new_entries.iter().map(|(k, v)| (key(k), v)).collect();
+            let old_by_key: BTreeMap<String, &Node> =
+                old_entries.iter().map(|(k, v)| (key(k), v)).collect();
+            for (old_key, old_value) in old_entries {
+                let old_key = key(old_key);
+                let entry_path = path.child(PathSegment::Key(old_key.clone()));
+                match new_by_key.get(&old_key) {
+                    Some(new_value) => diff_nodes(old_value, new_value, &entry_path, out),
+                    None => out.push(Difference::Removed {
+                        path:  entry_path,
+                        value: old_value.to_json(),
+                    }),
+                }
+            }
+            for (new_key, new_value) in new_entries {
+                let new_key = key(new_key);
+                if !old_by_key.contains_key(&new_key) {
+                    out.push(Difference::Added {
+                        path:  path.child(PathSegment::Key(new_key)),
+                        value: new_value.to_json(),
+                    });
+                }
+            }
+        }
+        (
+            NodeKind::Variant {
+                name: old_name,
+                fields: old_fields,
+                ..
+            },
+            NodeKind::Variant {
+                name: new_name,
+                fields: new_fields,
+                ..
+            },
+        ) if old_name == new_name => diff_nodes(
+            old_fields,
+            new_fields,
+            &path.child(PathSegment::Field(old_name.clone())),
+            out,
+        ),
+        _ => {
+            let old = old.to_json();
+            let new = new.to_json();
+            if old != new {
+                out.push(Difference::Changed {
+                    path: path.clone(),
+                    old,
+                    new,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ty() -> Type {
+        Type::Struct(Fields::Named(vec![
+            ("account".into(), Type::AccountAddress),
+            (
+                "balances".into(),
+                Type::Map(
+                    SizeLength::U8,
+                    Box::new(Type::String(SizeLength::U8)),
+                    Box::new(Type::U64),
+                ),
+            ),
+            ("operators".into(), Type::Set(SizeLength::U8, Box::new(Type::U8))),
+            (
+                "state".into(),
+                Type::Enum(vec![
+                    ("Closed".into(), Fields::None),
+                    (
+                        "Open".into(),
+                        Fields::Unnamed(vec![Type::Pair(Box::new(Type::U8), Box::new(Type::U8))]),
+                    ),
+                ]),
+            ),
+        ]))
+    }
+
+    fn value(balances: &[(&str, u64)], operators: &[u8], state: Option<(u8, u8)>) -> Vec<u8> {
+        let mut bytes = vec![0xab; 32];
+        bytes.push(balances.len() as u8);
+        for (name, amount) in balances {
+            bytes.push(name.len() as u8);
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.extend_from_slice(&amount.to_le_bytes());
+        }
+        bytes.push(operators.len() as u8);
+        bytes.extend_from_slice(operators);
+        match state {
+            None => bytes.push(0),
+            Some((a, b)) => bytes.extend_from_slice(&[1, a, b]),
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_node_to_json() {
+        let ty = ty();
+        let bytes = value(&[("a", 1), ("b", 2)], &[3, 4], Some((5, 6)));
+        let node = Node::decode(&ty, &bytes).unwrap();
+        assert_eq!(node.span, 0..bytes.len());
+        assert_eq!(node.to_json(), ty.to_json(&mut Cursor::new(&bytes)).unwrap());
+    }
+
+    #[test]
+    fn test_diff() {
+        let ty = ty();
+        let old = value(&[("a", 1), ("b", 2)], &[3, 4], Some((5, 6)));
+        let new = value(&[("b", 3), ("c", 4)], &[4, 7], Some((5, 8)));
+        let lines: Vec<String> =
+            diff(&ty, &old, &new).unwrap().iter().map(|d| d.to_string()).collect();
+        assert_eq!(lines, [
+            "- $.balances[\"a\"]: 1".to_string(),
+            "~ $.balances[\"b\"]: 2 -> 3".to_string(),
+            "+ $.balances[\"c\"]: 4".to_string(),
+            "- $.operators[0]: 3".to_string(),
+            "+ $.operators[1]: 7".to_string(),
+            "~ $.state.Open.0.1: 6 -> 8".to_string(),
+        ]);
+        let closed = value(&[("b", 3), ("c", 4)], &[4, 7], None);
+        let lines: Vec<String> =
+            diff(&ty, &new, &closed).unwrap().iter().map(|d| d.to_string()).collect();
+        assert_eq!(lines, ["~ $.state: {\"Open\":[[5,8]]} -> {\"Closed\":[]}"]);
+        assert!(diff(&ty, &old, &old).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        let ty = ty();
+        let bytes = value(&[("a", 1)], &[], None);
+        let dump = hex_dump(&ty, &bytes).unwrap();
+        let account = AccountAddress([0xab; 32]).to_string();
+        let expected = format!(
+            "\
+00000000  ab ab ab ab ab ab ab ab ab ab ab ab ab ab ab ab  $.account = \"{}\"
+00000010  ab ab ab ab ab ab ab ab ab ab ab ab ab ab ab ab
+00000020  01                                               $.balances (length 1)
+00000021  01 61                                            $.balances[\"a\"].key = \"a\"
+00000023  01 00 00 00 00 00 00 00                          $.balances[\"a\"] = 1
+0000002b  00                                               $.operators (length 0)
+0000002c  00                                               $.state (variant Closed)
+",
+            account
+        );
+        assert_eq!(dump, expected);
+    }
+
+    #[test]
+    fn test_errors() {
+        let ty = ty();
+        let mut bytes = value(&[], &[], None);
+        bytes.push(0);
+        assert_eq!(Node::decode(&ty, &bytes).unwrap_err().reason, "1 unexpected trailing bytes");
+        let bytes = value(&[("abc", 1)], &[], None);
+        let error = Node::decode(&ty, &bytes[..40]).unwrap_err();
+        assert_eq!(error.position, 37);
+        assert_eq!(error.schema, "U64");
+        assert_eq!(error.reason, "not enough data");
+    }
+}
@@ -927,6 +927,49 @@ fn write_bytes_from_json_schema_type<W: Write>(
                 Err(WrongJsonType("JSON String required".to_string()))
             }
         }
+        Type::Hash
+        | Type::ModuleReference
+        | Type::PublicKeyEd25519
+        | Type::PublicKeyEcdsaSecp256k1
+        | Type::SignatureEd25519
+        | Type::SignatureEcdsaSecp256k1 => {
+            if let Value::String(string) = json {
+                let bytes = hex::decode(string)?;
+                let expected = match schema.byte_representation() {
+                    Some(Type::ByteArray(len)) => len as usize,
+                    _ => unreachable!("Fixed size byte types are represented as byte arrays."),
+                };
+                ensure!(
+                    bytes.len() == expected,
+                    ByteArrayError(format!(
+                        "{:?} must be {} bytes ({} hex characters), but got {} bytes",
+                        schema,
+                        expected,
+                        2 * expected,
+                        bytes.len()
+                    ))
+                );
+                out.write_all(&bytes).or(Err(JsonError::FailedWriting))
+            } else {
+                Err(WrongJsonType("JSON String required".to_string()))
+            }
+        }
+        Type::TokenId => {
+            if let Value::String(string) = json {
+                let bytes = hex::decode(string)?;
+                ensure!(
+                    bytes.len() <= usize::from(u8::MAX),
+                    ByteArrayError(format!(
+                        "TokenId must be at most 255 bytes, but got {} bytes",
+                        bytes.len()
+                    ))
+                );
+                write_bytes_for_length_of_size(bytes.len(), &SizeLength::U8, out)?;
+                out.write_all(&bytes).or(Err(JsonError::FailedWriting))
+            } else {
+                Err(WrongJsonType("JSON String required".to_string()))
+            }
+        }
     }
 }
 
@@ -1238,8 +1281,13 @@ impl Display for VersionedModuleSchema {
                     }
                 }
             }
-            VersionedModuleSchema::V3(module_v3) => {
-                for (contract_name, contract_schema) in module_v3.contracts.iter() {
+            VersionedModuleSchema::V3(ModuleV3 {
+                contracts,
+            })
+            | VersionedModuleSchema::V4(ModuleV4 {
+                contracts,
+            }) => {
+                for (contract_name, contract_schema) in contracts.iter() {
                     out = format!("{}Contract: {:>11}\n", out, contract_name);
 
                     // Init Function
@@ -1476,8 +1524,448 @@ impl Type {
                 receive_name.insert("func".to_string(), "<String>".into());
                 receive_name.into()
             }
+            Self::Hash
+            | Self::ModuleReference
+            | Self::PublicKeyEd25519
+            | Self::PublicKeyEcdsaSecp256k1
+            | Self::SignatureEd25519
+            | Self::SignatureEcdsaSecp256k1 => {
+                let string_size = match self.byte_representation() {
+                    Some(Type::ByteArray(size)) => 2 * size,
+                    _ => 0,
+                };
+                format!(
+                    "<{:?} (String of size {string_size} containing lowercase hex characters.)>",
+                    self
+                )
+                .into()
+            }
+            Self::TokenId => {
+                "<TokenId (String of size at most 510 containing lowercase hex characters.)>".into()
+            }
+        }
+    }
+}
+
+/// The JSON Schema dialect of the documents produced by
+/// [`Type::to_json_schema_document`] and
+/// [`VersionedModuleSchema::to_json_schema`].
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// The largest length that can be represented by the [`SizeLength`].
+fn max_length_of_size(size_len: &SizeLength) -> u64 {
+    match size_len {
+        SizeLength::U8 => u8::MAX.into(),
+        SizeLength::U16 => u16::MAX.into(),
+        SizeLength::U32 => u32::MAX.into(),
+        SizeLength::U64 => u64::MAX,
+    }
+}
+
+/// JSON Schema of integers that are represented as JSON strings since they do
+/// not necessarily fit into a JSON number.
+fn integer_string_json_schema(signed: bool, description: String) -> Value {
+    let pattern = if signed {
+        "^-?[0-9]+$"
+    } else {
+        "^[0-9]+$"
+    };
+    json!({ "type": "string", "pattern": pattern, "description": description })
+}
+
+/// JSON Schema of an array with exactly the given items.
+fn tuple_json_schema(items: Vec<Value>) -> Value {
+    let len = items.len();
+    json!({
+        "type": "array",
+        "prefixItems": items,
+        "items": false,
+        "minItems": len,
+        "maxItems": len,
+    })
+}
+
+/// JSON Schema of an object with exactly the given properties, all of which
+/// are required.
+fn object_json_schema(properties: Map<String, Value>) -> Value {
+    let required: Vec<&String> = properties.keys().collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// JSON Schema of the variants of an enum. Each variant is an object with a
+/// single property, the name of the variant, containing the fields.
+fn variants_json_schema<'a>(variants: impl Iterator<Item = &'a (String, Fields)>) -> Value {
+    let one_of: Vec<Value> = variants
+        .map(|(name, fields)| {
+            let mut properties = Map::new();
+            properties.insert(name.clone(), fields.to_json_schema());
+            let mut variant = object_json_schema(properties);
+            variant["title"] = name.clone().into();
+            variant
+        })
+        .collect();
+    if one_of.is_empty() {
+        // An enum without variants has no values.
+        Value::Bool(false)
+    } else {
+        json!({ "oneOf": one_of })
+    }
+}
+
+impl Fields {
+    /// Construct a JSON Schema (draft 2020-12) describing the JSON used for
+    /// the [`Fields`].
+    pub fn to_json_schema(&self) -> Value {
+        match self {
+            Fields::Named(fields) => {
+                let mut properties = Map::new();
+                for (name, ty) in fields.iter() {
+                    properties.insert(name.clone(), ty.to_json_schema());
+                }
+                object_json_schema(properties)
+            }
+            Fields::Unnamed(fields) => {
+                tuple_json_schema(fields.iter().map(Type::to_json_schema).collect())
+            }
+            // Output as an empty array, but any value is accepted as input.
+            Fields::None => json!({ "type": ["null", "array"], "maxItems": 0 }),
+        }
+    }
+}
+
+impl Type {
+    /// Construct a JSON Schema (draft 2020-12) describing the JSON accepted by
+    /// [`serial_value`](Self::serial_value) and produced by
+    /// [`to_json`](Self::to_json). The result is meant to be embedded in
+    /// other schemas, use
+    /// [`to_json_schema_document`](Self::to_json_schema_document) for a
+    /// standalone document.
+    ///
+    /// Integers that do not necessarily fit into a JSON number, i.e., 128-bit
+    /// and LEB128 encoded integers, as well as amounts, are represented as
+    /// strings. Maps are arrays of key-value pairs, and the lengths of lists,
+    /// sets, maps, strings and byte lists are constrained by their
+    /// [`SizeLength`]. For strings the constraint is on the number of bytes in
+    /// the UTF-8 encoding, which `maxLength` only bounds from above.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use serde_json::json;
+    /// # use concordium_contracts_common::schema::*;
+    /// #
+    /// let schema = Type::List(SizeLength::U8, Box::new(Type::U16));
+    /// assert_eq!(
+    ///     schema.to_json_schema(),
+    ///     json!({
+    ///         "type": "array",
+    ///         "items": { "type": "integer", "minimum": 0, "maximum": 65535 },
+    ///         "maxItems": 255,
+    ///     })
+    /// );
+    /// ```
+    pub fn to_json_schema(&self) -> Value {
+        match self {
+            Type::Unit => json!({ "type": ["null", "array"], "maxItems": 0 }),
+            Type::Bool => json!({ "type": "boolean" }),
+            Type::U8 => json!({ "type": "integer", "minimum": 0, "maximum": u8::MAX }),
+            Type::U16 => json!({ "type": "integer", "minimum": 0, "maximum": u16::MAX }),
+            Type::U32 => json!({ "type": "integer", "minimum": 0, "maximum": u32::MAX }),
+            Type::U64 => json!({ "type": "integer", "minimum": 0, "maximum": u64::MAX }),
+            Type::I8 => json!({ "type": "integer", "minimum": i8::MIN, "maximum": i8::MAX }),
+            Type::I16 => json!({ "type": "integer", "minimum": i16::MIN, "maximum": i16::MAX }),
+            Type::I32 => json!({ "type": "integer", "minimum": i32::MIN, "maximum": i32::MAX }),
+            Type::I64 => json!({ "type": "integer", "minimum": i64::MIN, "maximum": i64::MAX }),
+            Type::U128 => integer_string_json_schema(false, "Unsigned 128-bit integer.".into()),
+            Type::I128 => integer_string_json_schema(true, "Signed 128-bit integer.".into()),
+            Type::ULeb128(constraint) => integer_string_json_schema(
+                false,
+                format!("Unsigned integer of at most {} bits.", 7 * u64::from(*constraint)),
+            ),
+            Type::ILeb128(constraint) => integer_string_json_schema(
+                true,
+                format!(
+                    "Signed integer of at most {} bits in two's complement.",
+                    7 * u64::from(*constraint)
+                ),
+            ),
+            Type::Amount => integer_string_json_schema(false, "Amount in microCCD.".into()),
+            Type::AccountAddress => json!({
+                "type": "string",
+                "pattern": "^[1-9A-HJ-NP-Za-km-z]{50}$",
+                "description": "Account address in base58check encoding.",
+            }),
+            Type::ContractAddress => json!({
+                "type": "object",
+                "properties": {
+                    "index": Type::U64.to_json_schema(),
+                    "subindex": Type::U64.to_json_schema(),
+                },
+                "required": ["index"],
+                "additionalProperties": false,
+            }),
+            Type::Timestamp => json!({
+                "type": "string",
+                "format": "date-time",
+                "description": "Timestamp in RFC 3339 format, e.g., `2000-01-01T12:00:00Z`.",
+            }),
+            Type::Duration => json!({
+                "type": "string",
+                "pattern": "^\\s*([0-9]+(ms|s|m|h|d)(\\s+|$))*$",
+                "description": "Duration as a sequence of measures, e.g., `10d 1h 42s`.",
+            }),
+            Type::Pair(left, right) => {
+                tuple_json_schema(vec![left.to_json_schema(), right.to_json_schema()])
+            }
+            Type::List(size_len, ty) => json!({
+                "type": "array",
+                "items": ty.to_json_schema(),
+                "maxItems": max_length_of_size(size_len),
+            }),
+            Type::Set(size_len, ty) => json!({
+                "type": "array",
+                "items": ty.to_json_schema(),
+                "maxItems": max_length_of_size(size_len),
+                "uniqueItems": true,
+            }),
+            Type::Map(size_len, key, value) => json!({
+                "type": "array",
+                "items": tuple_json_schema(vec![key.to_json_schema(), value.to_json_schema()]),
+                "maxItems": max_length_of_size(size_len),
+            }),
+            Type::Array(len, ty) => json!({
+                "type": "array",
+                "items": ty.to_json_schema(),
+                "minItems": len,
+                "maxItems": len,
+            }),
+            Type::Struct(fields) => fields.to_json_schema(),
+            Type::Enum(variants) => variants_json_schema(variants.iter()),
+            Type::TaggedEnum(variants) => variants_json_schema(variants.values()),
+            Type::String(size_len) => json!({
+                "type": "string",
+                "maxLength": max_length_of_size(size_len),
+            }),
+            Type::ContractName(_) => {
+                let mut properties = Map::new();
+                properties.insert("contract".into(), json!({ "type": "string" }));
+                object_json_schema(properties)
+            }
+            Type::ReceiveName(_) => {
+                let mut properties = Map::new();
+                properties.insert("contract".into(), json!({ "type": "string" }));
+                properties.insert("func".into(), json!({ "type": "string" }));
+                object_json_schema(properties)
+            }
+            Type::ByteList(size_len) => json!({
+                "type": "string",
+                "pattern": "^([0-9a-fA-F]{2})*$",
+                "maxLength": max_length_of_size(size_len).saturating_mul(2),
+                "description": "Bytes in hex encoding.",
+            }),
+            Type::ByteArray(len) => json!({
+                "type": "string",
+                "pattern": "^([0-9a-fA-F]{2})*$",
+                "minLength": 2 * u64::from(*len),
+                "maxLength": 2 * u64::from(*len),
+                "description": "Bytes in hex encoding.",
+            }),
+            Type::Hash
+            | Type::ModuleReference
+            | Type::PublicKeyEd25519
+            | Type::PublicKeyEcdsaSecp256k1
+            | Type::SignatureEd25519
+            | Type::SignatureEcdsaSecp256k1
+            | Type::TokenId => {
+                let mut schema =
+                    self.byte_representation().map_or(Value::Null, |ty| ty.to_json_schema());
+                if let Value::Object(map) = &mut schema {
+                    map.insert("description".into(), format!("{:?} in hex encoding.", self).into());
+                }
+                schema
+            }
         }
     }
+
+    /// Construct a standalone JSON Schema (draft 2020-12) document describing
+    /// the JSON representation of the type. See
+    /// [`to_json_schema`](Self::to_json_schema) for details.
+    pub fn to_json_schema_document(&self) -> Value {
+        let mut document = self.to_json_schema();
+        if let Value::Object(map) = &mut document {
+            map.insert("$schema".into(), JSON_SCHEMA_DIALECT.into());
+            document
+        } else {
+            // Boolean schemas cannot carry keywords.
+            json!({ "$schema": JSON_SCHEMA_DIALECT, "allOf": [document] })
+        }
+    }
+}
+
+/// Add the schemas of a function to the definitions, named by `prefix`
+/// followed by `.parameter`, `.returnValue` or `.error`.
+fn insert_function_json_schemas(
+    defs: &mut Map<String, Value>,
+    prefix: &str,
+    parameter: Option<&Type>,
+    return_value: Option<&Type>,
+    error: Option<&Type>,
+) {
+    if let Some(ty) = parameter {
+        defs.insert(format!("{}.parameter", prefix), ty.to_json_schema());
+    }
+    if let Some(ty) = return_value {
+        defs.insert(format!("{}.returnValue", prefix), ty.to_json_schema());
+    }
+    if let Some(ty) = error {
+        defs.insert(format!("{}.error", prefix), ty.to_json_schema());
+    }
+}
+
+impl VersionedModuleSchema {
+    /// Construct a JSON Schema (draft 2020-12) document with the schemas of
+    /// all the contracts in the module as definitions in `$defs`. The
+    /// definitions are named after the entrypoints as follows
+    /// - `init_<contract>.parameter`, `init_<contract>.returnValue` and
+    ///   `init_<contract>.error` for the init function,
+    /// - `<contract>.<function>.parameter`, `<contract>.<function>.returnValue`
+    ///   and `<contract>.<function>.error` for receive functions,
+    /// - `<contract>.event` for events and `<contract>.state` for the state.
+    ///
+    /// A definition is only present if the module schema includes the type.
+    /// A single type can be referred to by, e.g.,
+    /// `#/$defs/MyContract.transfer.parameter`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use concordium_contracts_common::schema::*;
+    /// # use std::collections::BTreeMap;
+    /// #
+    /// let mut receive = BTreeMap::new();
+    /// receive.insert(String::from("transfer"), FunctionV2 {
+    ///     parameter:    Some(Type::Amount),
+    ///     error:        None,
+    ///     return_value: None,
+    /// });
+    /// let mut contracts = BTreeMap::new();
+    /// contracts.insert(String::from("MyContract"), ContractV3 {
+    ///     init: None,
+    ///     receive,
+    ///     event: Some(Type::U8),
+    /// });
+    /// let schema = VersionedModuleSchema::V3(ModuleV3 {
+    ///     contracts,
+    /// });
+    ///
+    /// let document = schema.to_json_schema();
+    /// assert_eq!(document["$defs"]["MyContract.transfer.parameter"], Type::Amount.to_json_schema());
+    /// assert_eq!(document["$defs"]["MyContract.event"], Type::U8.to_json_schema());
+    /// ```
+    pub fn to_json_schema(&self) -> Value {
+        let mut defs = Map::new();
+        match self {
+            VersionedModuleSchema::V0(module) => {
+                for (contract_name, contract) in module.contracts.iter() {
+                    if let Some(ty) = &contract.state {
+                        defs.insert(format!("{}.state", contract_name), ty.to_json_schema());
+                    }
+                    let init = format!("init_{}", contract_name);
+                    insert_function_json_schemas(
+                        &mut defs,
+                        &init,
+                        contract.init.as_ref(),
+                        None,
+                        None,
+                    );
+                    for (function_name, ty) in contract.receive.iter() {
+                        let prefix = format!("{}.{}", contract_name, function_name);
+                        insert_function_json_schemas(&mut defs, &prefix, Some(ty), None, None);
+                    }
+                }
+            }
+            VersionedModuleSchema::V1(module) => {
+                for (contract_name, contract) in module.contracts.iter() {
+                    if let Some(function) = &contract.init {
+                        insert_function_json_schemas(
+                            &mut defs,
+                            &format!("init_{}", contract_name),
+                            function.parameter(),
+                            function.return_value(),
+                            None,
+                        );
+                    }
+                    for (function_name, function) in contract.receive.iter() {
+                        insert_function_json_schemas(
+                            &mut defs,
+                            &format!("{}.{}", contract_name, function_name),
+                            function.parameter(),
+                            function.return_value(),
+                            None,
+                        );
+                    }
+                }
+            }
+            VersionedModuleSchema::V2(module) => {
+                for (contract_name, contract) in module.contracts.iter() {
+                    if let Some(function) = &contract.init {
+                        insert_function_json_schemas(
+                            &mut defs,
+                            &format!("init_{}", contract_name),
+                            function.parameter(),
+                            function.return_value(),
+                            function.error(),
+                        );
+                    }
+                    for (function_name, function) in contract.receive.iter() {
+                        insert_function_json_schemas(
+                            &mut defs,
+                            &format!("{}.{}", contract_name, function_name),
+                            function.parameter(),
+                            function.return_value(),
+                            function.error(),
+                        );
+                    }
+                }
+            }
+            VersionedModuleSchema::V3(ModuleV3 {
+                contracts,
+            })
+            | VersionedModuleSchema::V4(ModuleV4 {
+                contracts,
+            }) => {
+                for (contract_name, contract) in contracts.iter() {
+                    if let Some(function) = &contract.init {
+                        insert_function_json_schemas(
+                            &mut defs,
+                            &format!("init_{}", contract_name),
+                            function.parameter(),
+                            function.return_value(),
+                            function.error(),
+                        );
+                    }
+                    for (function_name, function) in contract.receive.iter() {
+                        insert_function_json_schemas(
+                            &mut defs,
+                            &format!("{}.{}", contract_name, function_name),
+                            function.parameter(),
+                            function.return_value(),
+                            function.error(),
+                        );
+                    }
+                    if let Some(ty) = contract.event() {
+                        defs.insert(format!("{}.event", contract_name), ty.to_json_schema());
+                    }
+                }
+            }
+        }
+        json!({ "$schema": JSON_SCHEMA_DIALECT, "$defs": defs })
+    }
 }
 
 fn serial_biguint<W: Write>(bigint: BigUint, constraint: u32, out: &mut W) -> Result<(), W::Err> {
@@ -2119,6 +2607,32 @@ Contract: MySecondContract
         assert_eq!(expected, bytes)
     }
 
+    /// Tests that the types for keys, signatures, hashes and token IDs are
+    /// represented as hex strings with the expected number of bytes.
+    #[test]
+    fn test_v4_byte_types_json() {
+        let key = PublicKeyEd25519([7; 32]);
+        let bytes = Type::PublicKeyEd25519.serial_value(&json!(key.to_string())).unwrap();
+        assert_eq!(bytes, to_bytes(&key));
+        assert_eq!(
+            Type::PublicKeyEd25519.to_json(&mut Cursor::new(&bytes)).unwrap(),
+            json!(key.to_string())
+        );
+        let err = Type::PublicKeyEd25519
+            .serial_value(&json!(hex::encode([7; 33])))
+            .expect_err("Serializing should fail");
+        assert_eq!(
+            err.to_string(),
+            "PublicKeyEd25519 must be 32 bytes (64 hex characters), but got 33 bytes"
+        );
+
+        let bytes = Type::TokenId.serial_value(&json!("0a0b")).unwrap();
+        assert_eq!(bytes, [2, 0x0a, 0x0b]);
+        assert_eq!(Type::TokenId.to_json(&mut Cursor::new(&bytes)).unwrap(), json!("0a0b"));
+        assert!(Type::TokenId.serial_value(&json!(hex::encode([0; 256]))).is_err());
+        assert!(Type::Hash.to_json(&mut Cursor::new([0u8; 31])).is_err());
+    }
+
     /// Tests that attempting to serialize an invalid byte sequence as
     /// [`Type::AccountAddress`] fails with expected error type.
     #[test]
@@ -2454,6 +2968,91 @@ Contract: MySecondContract
             )
         ))
     }
+
+    #[test]
+    fn test_json_schema_struct() {
+        let schema = Type::Struct(Fields::Named(vec![
+            ("owner".into(), Type::AccountAddress),
+            (
+                "balances".into(),
+                Type::Map(SizeLength::U16, Box::new(Type::U8), Box::new(Type::ULeb128(5))),
+            ),
+            (
+                "status".into(),
+                Type::Enum(vec![
+                    ("Active".into(), Fields::None),
+                    ("Paused".into(), Fields::Unnamed(vec![Type::Timestamp])),
+                ]),
+            ),
+        ]));
+
+        let json_schema = schema.to_json_schema();
+        assert_eq!(json_schema["type"], "object");
+        assert_eq!(json_schema["required"], json!(["balances", "owner", "status"]));
+        assert_eq!(json_schema["additionalProperties"], false);
+        assert_eq!(
+            json_schema["properties"]["balances"],
+            json!({
+                "type": "array",
+                "items": {
+                    "type": "array",
+                    "prefixItems": [
+                        { "type": "integer", "minimum": 0, "maximum": 255 },
+                        {
+                            "type": "string",
+                            "pattern": "^[0-9]+$",
+                            "description": "Unsigned integer of at most 35 bits."
+                        }
+                    ],
+                    "items": false,
+                    "minItems": 2,
+                    "maxItems": 2
+                },
+                "maxItems": 65535
+            })
+        );
+        let variants = &json_schema["properties"]["status"]["oneOf"];
+        assert_eq!(variants[0]["title"], "Active");
+        assert_eq!(variants[0]["required"], json!(["Active"]));
+        assert_eq!(variants[1]["properties"]["Paused"]["prefixItems"][0]["format"], "date-time");
+
+        let document = schema.to_json_schema_document();
+        assert_eq!(document["$schema"], JSON_SCHEMA_DIALECT);
+        assert_eq!(
+            Type::Enum(Vec::new()).to_json_schema_document(),
+            json!({ "$schema": JSON_SCHEMA_DIALECT, "allOf": [false] })
+        );
+    }
+
+    #[test]
+    fn test_json_schema_module_definitions() {
+        let mut receive = BTreeMap::new();
+        receive.insert("view".to_string(), FunctionV1::ReturnValue(Type::Bool));
+        receive.insert("update".to_string(), FunctionV1::Both {
+            parameter:    Type::U32,
+            return_value: Type::Unit,
+        });
+        let mut contracts = BTreeMap::new();
+        contracts.insert("counter".to_string(), ContractV1 {
+            init: Some(FunctionV1::Parameter(Type::String(SizeLength::U8))),
+            receive,
+        });
+        let schema = VersionedModuleSchema::V1(ModuleV1 {
+            contracts,
+        });
+
+        let document = schema.to_json_schema();
+        assert_eq!(document["$schema"], JSON_SCHEMA_DIALECT);
+        let defs = document["$defs"].as_object().expect("Definitions should be an object.");
+        let names: Vec<&String> = defs.keys().collect();
+        assert_eq!(names, [
+            "counter.update.parameter",
+            "counter.update.returnValue",
+            "counter.view.returnValue",
+            "init_counter.parameter"
+        ]);
+        assert_eq!(defs["init_counter.parameter"], json!({ "type": "string", "maxLength": 255 }));
+    }
 }
 
 impl Fields {
@@ -2973,12 +3572,39 @@ impl Type {
                     (_, Err(e)) => Err(deserial_error(e.0)),
                 }
             }
+            Type::Hash
+            | Type::ModuleReference
+            | Type::PublicKeyEd25519
+            | Type::PublicKeyEcdsaSecp256k1
+            | Type::SignatureEd25519
+            | Type::SignatureEcdsaSecp256k1
+            | Type::TokenId => deserial_v4_bytes(self, source)
+                .map(|bytes| Value::String(hex::encode(bytes)))
+                .map_err(deserial_error),
         }
     }
 }
 
+/// Read the bytes of a value of one of the types only supported by V4
+/// schemas, which are represented as a fixed or length-prefixed sequence of
+/// bytes. Returns the reason in case of failure.
+fn deserial_v4_bytes<R: Read>(ty: &Type, source: &mut R) -> Result<Vec<u8>, String> {
+    let len = match ty.byte_representation() {
+        Some(Type::ByteArray(len)) => len as usize,
+        Some(Type::ByteList(_)) => u8::deserial(source)
+            .map_err(|_| format!("Could not parse {:?} length from value", ty))?
+            .into(),
+        _ => return Err(format!("{:?} is not represented as bytes", ty)),
+    };
+    let mut bytes = vec![0u8; len];
+    source
+        .read_exact(&mut bytes)
+        .map_err(|_| format!("Could not read the {} bytes of {:?} value", len, ty))?;
+    Ok(bytes)
+}
+
 /// Deserialize a uleb128 encoded [`BigUint`] from `source`.
-fn deserial_biguint<R: Read>(source: &mut R, constraint: u32) -> ParseResult<BigUint> {
+pub(crate) fn deserial_biguint<R: Read>(source: &mut R, constraint: u32) -> ParseResult<BigUint> {
     let mut result = BigUint::zero();
     let mut shift = 0;
     for _ in 0..constraint {
@@ -2995,7 +3621,7 @@ fn deserial_biguint<R: Read>(source: &mut R, constraint: u32) -> ParseResult<Big
 }
 
 /// Deserialize a ileb128 encoded [`BigInt`] from `source`.
-fn deserial_bigint<R: Read>(source: &mut R, constraint: u32) -> ParseResult<BigInt> {
+pub(crate) fn deserial_bigint<R: Read>(source: &mut R, constraint: u32) -> ParseResult<BigInt> {
     let mut result = BigInt::zero();
     let mut shift = 0;
     for _ in 0..constraint {
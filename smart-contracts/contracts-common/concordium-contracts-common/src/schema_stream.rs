@@ -0,0 +1,768 @@
+//! Streaming decoding of values described by a schema [`Type`].
+//!
+//! [`Type::to_json`](crate::schema::Type::to_json) builds the complete JSON
+//! value in memory. For large inputs, or inputs from untrusted sources, this
+//! module instead decodes the bytes into a stream of [`Token`]s passed to a
+//! [`TokenSink`], e.g., [`JsonWriter`] writing the JSON incrementally to an
+//! [`std::io::Write`]. The decoding is subject to [`DecodeLimits`] bounding
+//! the nesting depth, the length of collections, strings and byte lists, and
+//! the total number of values, such that malicious inputs cannot exhaust
+//! the stack or memory.
+//!
+//! The tokens describe the same JSON representation as produced by
+//! `Type::to_json`, except that the fields of structs are in the order of the
+//! schema.
+use crate::{schema::*, *};
+use std::io;
+
+/// Limits on the values decoded by [`decode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Maximum nesting depth of values. Each pair, collection, struct and
+    /// enum adds a level of nesting.
+    pub max_depth:  usize,
+    /// Maximum length of a single list, set, map, array, string or byte
+    /// list.
+    pub max_length: usize,
+    /// Maximum number of values decoded in total, including the values nested
+    /// in other values.
+    pub max_values: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_depth:  64,
+            max_length: 1 << 24,
+            max_values: 1 << 24,
+        }
+    }
+}
+
+/// A token of the JSON representation of a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    /// The value `null`.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// A number. All integers of at most 64 bits are represented as numbers.
+    Number(i128),
+    /// A string.
+    String(&'a str),
+    /// Start of an array. The array ends with the matching
+    /// [`Token::EndArray`].
+    BeginArray,
+    /// End of an array.
+    EndArray,
+    /// Start of an object. Each entry of the object is a [`Token::Key`]
+    /// followed by the tokens of the value. The object ends with the matching
+    /// [`Token::EndObject`].
+    BeginObject,
+    /// The key of the next entry of an object.
+    Key(&'a str),
+    /// End of an object.
+    EndObject,
+}
+
+/// A consumer of the tokens produced by [`decode`].
+pub trait TokenSink {
+    /// Error produced by the sink, aborting the decoding.
+    type Error;
+
+    /// Consume the next token.
+    fn token(&mut self, token: Token<'_>) -> Result<(), Self::Error>;
+}
+
+/// Errors that can occur when decoding a value using [`decode`].
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError<E> {
+    /// The value is nested deeper than allowed by the limits.
+    #[error("Value is nested deeper than the limit of {limit} levels at position {position}.")]
+    DepthExceeded {
+        position: u64,
+        limit:    usize,
+    },
+    /// A collection, string or byte list is longer than allowed by the limits.
+    #[error("Length {length} at position {position} exceeds the limit of {limit}.")]
+    LengthExceeded {
+        position: u64,
+        length:   usize,
+        limit:    usize,
+    },
+    /// The input contains more values than allowed by the limits.
+    #[error("More than {limit} values at position {position}.")]
+    TooManyValues {
+        position: u64,
+        limit:    usize,
+    },
+    /// The input could not be parsed according to the schema.
+    #[error("Could not parse {schema} at position {position}: {reason}")]
+    Parse {
+        position: u64,
+        schema:   String,
+        reason:   String,
+    },
+    /// The sink returned an error.
+    #[error("{0}")]
+    Sink(E),
+}
+
+/// Decode a value of type `ty` from `source`, passing the tokens of its JSON
+/// representation to `sink`. Decoding stops at the first error, after which
+/// the sink might have consumed an incomplete sequence of tokens.
+///
+/// Returns the number of bytes read from `source`. Note that `source` is not
+/// required to be fully consumed.
+pub fn decode<R: Read, S: TokenSink>(
+    ty: &Type,
+    source: &mut R,
+    limits: &DecodeLimits,
+    sink: &mut S,
+) -> Result<u64, DecodeError<S::Error>> {
+    let mut decoder = Decoder {
+        source: CountingReader {
+            inner:    source,
+            position: 0,
+        },
+        limits,
+        sink,
+        values: 0,
+    };
+    decoder.value(ty, 0)?;
+    Ok(decoder.source.position)
+}
+
+impl Type {
+    /// Decode a value of this type from `source` and write its JSON
+    /// representation to `out`, without building the value in memory. See
+    /// [`decode`] for details.
+    ///
+    /// ```
+    /// # use concordium_contracts_common::{schema::*, schema_stream::DecodeLimits, *};
+    /// let ty = Type::List(
+    ///     SizeLength::U8,
+    ///     Box::new(Type::Pair(Box::new(Type::Bool), Box::new(Type::String(SizeLength::U8)))),
+    /// );
+    /// let bytes = [1u8, 1, 3, b'y', b'e', b's'];
+    /// let mut out = Vec::new();
+    /// ty.write_json(&mut Cursor::new(&bytes), &DecodeLimits::default(), &mut out).unwrap();
+    /// assert_eq!(out, br#"[[true,"yes"]]"#);
+    /// ```
+    pub fn write_json<R: Read, W: io::Write>(
+        &self,
+        source: &mut R,
+        limits: &DecodeLimits,
+        out: W,
+    ) -> Result<u64, DecodeError<io::Error>> {
+        let mut writer = JsonWriter::new(out);
+        decode(self, source, limits, &mut writer)
+    }
+}
+
+/// A [`TokenSink`] writing compact JSON to an [`io::Write`].
+#[derive(Debug)]
+pub struct JsonWriter<W> {
+    out:   W,
+    /// For each enclosing array or object, whether it is an object and whether
+    /// it has any entries.
+    stack: Vec<(bool, bool)>,
+}
+
+impl<W: io::Write> JsonWriter<W> {
+    /// Construct a writer writing to `out`.
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Return the underlying writer.
+    pub fn into_inner(self) -> W { self.out }
+
+    /// Write a separator if the value is not the first element of an array.
+    fn begin_value(&mut self) -> io::Result<()> {
+        if let Some((false, non_empty)) = self.stack.last_mut() {
+            if *non_empty {
+                self.out.write_all(b",")?;
+            }
+            *non_empty = true;
+        }
+        Ok(())
+    }
+}
+
+impl<W: io::Write> TokenSink for JsonWriter<W> {
+    type Error = io::Error;
+
+    fn token(&mut self, token: Token<'_>) -> Result<(), Self::Error> {
+        match token {
+            Token::Null => {
+                self.begin_value()?;
+                self.out.write_all(b"null")
+            }
+            Token::Bool(b) => {
+                self.begin_value()?;
+                self.out.write_all(
+                    if b {
+                        b"true"
+                    } else {
+                        b"false"
+                    },
+                )
+            }
+            Token::Number(n) => {
+                self.begin_value()?;
+                write!(self.out, "{}", n)
+            }
+            Token::String(s) => {
+                self.begin_value()?;
+                serde_json::to_writer(&mut self.out, s).map_err(io::Error::from)
+            }
+            Token::BeginArray => {
+                self.begin_value()?;
+                self.stack.push((false, false));
+                self.out.write_all(b"[")
+            }
+            Token::BeginObject => {
+                self.begin_value()?;
+                self.stack.push((true, false));
+                self.out.write_all(b"{")
+            }
+            Token::EndArray => {
+                self.stack.pop();
+                self.out.write_all(b"]")
+            }
+            Token::EndObject => {
+                self.stack.pop();
+                self.out.write_all(b"}")
+            }
+            Token::Key(key) => {
+                if let Some((true, non_empty)) = self.stack.last_mut() {
+                    if *non_empty {
+                        self.out.write_all(b",")?;
+                    }
+                    *non_empty = true;
+                }
+                serde_json::to_writer(&mut self.out, key).map_err(io::Error::from)?;
+                self.out.write_all(b":")
+            }
+        }
+    }
+}
+
+/// A reader keeping track of the number of bytes read.
+struct CountingReader<'a, R> {
+    inner:    &'a mut R,
+    position: u64,
+}
+
+impl<'a, R: Read> Read for CountingReader<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> ParseResult<usize> {
+        let n = self.inner.read(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+/// Size of the chunks used when reading strings and byte lists, such that the
+/// memory allocated is bounded by the data actually present.
+const READ_CHUNK_SIZE: usize = 4096;
+
+/// State of a call to [`decode`].
+struct Decoder<'a, 'b, R, S> {
+    source: CountingReader<'b, R>,
+    limits: &'a DecodeLimits,
+    sink:   &'a mut S,
+    /// Number of values decoded so far.
+    values: usize,
+}
+
+impl<'a, 'b, R: Read, S: TokenSink> Decoder<'a, 'b, R, S> {
+    fn emit(&mut self, token: Token<'_>) -> Result<(), DecodeError<S::Error>> {
+        self.sink.token(token).map_err(DecodeError::Sink)
+    }
+
+    fn parse_error(&self, ty: &Type, reason: impl Into<String>) -> DecodeError<S::Error> {
+        DecodeError::Parse {
+            position: self.source.position,
+            schema:   format!("{:?}", ty),
+            reason:   reason.into(),
+        }
+    }
+
+    /// Deserialize a value of a fixed size type.
+    fn get<T: Deserial>(&mut self, ty: &Type) -> Result<T, DecodeError<S::Error>> {
+        T::deserial(&mut self.source).map_err(|_| self.parse_error(ty, "not enough data"))
+    }
+
+    /// Check a length against the limits.
+    fn check_length(&self, length: usize) -> Result<(), DecodeError<S::Error>> {
+        if length > self.limits.max_length {
+            return Err(DecodeError::LengthExceeded {
+                position: self.source.position,
+                length,
+                limit: self.limits.max_length,
+            });
+        }
+        Ok(())
+    }
+
+    /// Read a length encoded using `size_len`, and check it against the
+    /// limits.
+    fn length(&mut self, ty: &Type, size_len: SizeLength) -> Result<usize, DecodeError<S::Error>> {
+        let length = deserial_length(&mut self.source, size_len)
+            .map_err(|_| self.parse_error(ty, "could not parse length"))?;
+        self.check_length(length)?;
+        Ok(length)
+    }
+
+    /// Read `length` bytes. The length must have been checked against the
+    /// limits.
+    fn bytes(&mut self, ty: &Type, length: usize) -> Result<Vec<u8>, DecodeError<S::Error>> {
+        let mut bytes = Vec::with_capacity(std::cmp::min(length, READ_CHUNK_SIZE));
+        let mut chunk = [0u8; READ_CHUNK_SIZE];
+        while bytes.len() < length {
+            let n = std::cmp::min(length - bytes.len(), READ_CHUNK_SIZE);
+            self.source.read_exact(&mut chunk[..n]).map_err(|_| {
+                self.parse_error(ty, format!("expected {} bytes of data", length - bytes.len()))
+            })?;
+            bytes.extend_from_slice(&chunk[..n]);
+        }
+        Ok(bytes)
+    }
+
+    /// Read a string with a length encoded using `size_len`.
+    fn string(&mut self, ty: &Type, size_len: SizeLength) -> Result<String, DecodeError<S::Error>> {
+        let length = self.length(ty, size_len)?;
+        let bytes = self.bytes(ty, length)?;
+        String::from_utf8(bytes).map_err(|_| self.parse_error(ty, "invalid UTF-8"))
+    }
+
+    fn value(&mut self, ty: &Type, depth: usize) -> Result<(), DecodeError<S::Error>> {
+        self.values += 1;
+        if self.values > self.limits.max_values {
+            return Err(DecodeError::TooManyValues {
+                position: self.source.position,
+                limit:    self.limits.max_values,
+            });
+        }
+        if depth > self.limits.max_depth {
+            return Err(DecodeError::DepthExceeded {
+                position: self.source.position,
+                limit:    self.limits.max_depth,
+            });
+        }
+        match ty {
+            Type::Unit => self.emit(Token::Null),
+            Type::Bool => {
+                let b: bool = self.get(ty)?;
+                self.emit(Token::Bool(b))
+            }
+            Type::U8 => {
+                let n: u8 = self.get(ty)?;
+                self.emit(Token::Number(n.into()))
+            }
+            Type::U16 => {
+                let n: u16 = self.get(ty)?;
+                self.emit(Token::Number(n.into()))
+            }
+            Type::U32 => {
+                let n: u32 = self.get(ty)?;
+                self.emit(Token::Number(n.into()))
+            }
+            Type::U64 => {
+                let n: u64 = self.get(ty)?;
+                self.emit(Token::Number(n.into()))
+            }
+            Type::I8 => {
+                let n: i8 = self.get(ty)?;
+                self.emit(Token::Number(n.into()))
+            }
+            Type::I16 => {
+                let n: i16 = self.get(ty)?;
+                self.emit(Token::Number(n.into()))
+            }
+            Type::I32 => {
+                let n: i32 = self.get(ty)?;
+                self.emit(Token::Number(n.into()))
+            }
+            Type::I64 => {
+                let n: i64 = self.get(ty)?;
+                self.emit(Token::Number(n.into()))
+            }
+            Type::U128 => {
+                let n: u128 = self.get(ty)?;
+                self.emit(Token::String(&n.to_string()))
+            }
+            Type::I128 => {
+                let n: i128 = self.get(ty)?;
+                self.emit(Token::String(&n.to_string()))
+            }
+            Type::Amount => {
+                let amount: Amount = self.get(ty)?;
+                self.emit(Token::String(&amount.micro_ccd().to_string()))
+            }
+            Type::AccountAddress => {
+                let address: AccountAddress = self.get(ty)?;
+                self.emit(Token::String(&address.to_string()))
+            }
+            Type::ContractAddress => {
+                let address: ContractAddress = self.get(ty)?;
+                self.emit(Token::BeginObject)?;
+                self.emit(Token::Key("index"))?;
+                self.emit(Token::Number(address.index.into()))?;
+                self.emit(Token::Key("subindex"))?;
+                self.emit(Token::Number(address.subindex.into()))?;
+                self.emit(Token::EndObject)
+            }
+            Type::Timestamp => {
+                let timestamp: Timestamp = self.get(ty)?;
+                self.emit(Token::String(&timestamp.to_string()))
+            }
+            Type::Duration => {
+                let duration: Duration = self.get(ty)?;
+                self.emit(Token::String(&duration.to_string()))
+            }
+            Type::Pair(left, right) => {
+                self.emit(Token::BeginArray)?;
+                self.value(left, depth + 1)?;
+                self.value(right, depth + 1)?;
+                self.emit(Token::EndArray)
+            }
+            Type::List(size_len, item) | Type::Set(size_len, item) => {
+                let length = self.length(ty, *size_len)?;
+                self.emit(Token::BeginArray)?;
+                for _ in 0..length {
+                    self.value(item, depth + 1)?;
+                }
+                self.emit(Token::EndArray)
+            }
+            Type::Map(size_len, key, value) => {
+                let length = self.length(ty, *size_len)?;
+                self.emit(Token::BeginArray)?;
+                for _ in 0..length {
+                    self.emit(Token::BeginArray)?;
+                    self.value(key, depth + 1)?;
+                    self.value(value, depth + 1)?;
+                    self.emit(Token::EndArray)?;
+                }
+                self.emit(Token::EndArray)
+            }
+            Type::Array(length, item) => {
+                let length = *length as usize;
+                self.check_length(length)?;
+                self.emit(Token::BeginArray)?;
+                for _ in 0..length {
+                    self.value(item, depth + 1)?;
+                }
+                self.emit(Token::EndArray)
+            }
+            Type::Struct(fields) => self.fields(fields, depth),
+            Type::Enum(variants) => {
+                let tag = if variants.len() <= 256 {
+                    usize::from(self.get::<u8>(ty)?)
+                } else {
+                    usize::from(self.get::<u16>(ty)?)
+                };
+                let Some((name, fields)) = variants.get(tag) else {
+                    return Err(self.parse_error(ty, format!("unknown variant tag {}", tag)));
+                };
+                self.variant(name, fields, depth)
+            }
+            Type::TaggedEnum(variants) => {
+                let tag: u8 = self.get(ty)?;
+                let Some((name, fields)) = variants.get(&tag) else {
+                    return Err(self.parse_error(ty, format!("unknown variant tag {}", tag)));
+                };
+                self.variant(name, fields, depth)
+            }
+            Type::String(size_len) => {
+                let string = self.string(ty, *size_len)?;
+                self.emit(Token::String(&string))
+            }
+            Type::ContractName(size_len) => {
+                let string = self.string(ty, *size_len)?;
+                let name = ContractName::new(&string)
+                    .map_err(|e| self.parse_error(ty, format!("invalid contract name ({})", e)))?;
+                self.emit(Token::BeginObject)?;
+                self.emit(Token::Key("contract"))?;
+                self.emit(Token::String(name.contract_name()))?;
+                self.emit(Token::EndObject)
+            }
+            Type::ReceiveName(size_len) => {
+                let string = self.string(ty, *size_len)?;
+                let name = ReceiveName::new(&string)
+                    .map_err(|e| self.parse_error(ty, format!("invalid receive name ({})", e)))?;
+                self.emit(Token::BeginObject)?;
+                self.emit(Token::Key("contract"))?;
+                self.emit(Token::String(name.contract_name()))?;
+                self.emit(Token::Key("func"))?;
+                self.emit(Token::String(name.entrypoint_name().0))?;
+                self.emit(Token::EndObject)
+            }
+            Type::ULeb128(constraint) => {
+                let n = crate::schema_json::deserial_biguint(&mut self.source, *constraint)
+                    .map_err(|_| self.parse_error(ty, "invalid unsigned LEB128 integer"))?;
+                self.emit(Token::String(&n.to_string()))
+            }
+            Type::ILeb128(constraint) => {
+                let n = crate::schema_json::deserial_bigint(&mut self.source, *constraint)
+                    .map_err(|_| self.parse_error(ty, "invalid signed LEB128 integer"))?;
+                self.emit(Token::String(&n.to_string()))
+            }
+            Type::ByteList(size_len) => {
+                let length = self.length(ty, *size_len)?;
+                let bytes = self.bytes(ty, length)?;
+                self.emit(Token::String(&hex::encode(bytes)))
+            }
+            Type::ByteArray(length) => {
+                let length = *length as usize;
+                self.check_length(length)?;
+                let bytes = self.bytes(ty, length)?;
+                self.emit(Token::String(&hex::encode(bytes)))
+            }
+            Type::Hash
+            | Type::ModuleReference
+            | Type::PublicKeyEd25519
+            | Type::PublicKeyEcdsaSecp256k1
+            | Type::SignatureEd25519
+            | Type::SignatureEcdsaSecp256k1
+            | Type::TokenId => {
+                let length = match ty.byte_representation() {
+                    Some(Type::ByteList(size_len)) => self.length(ty, size_len)?,
+                    Some(Type::ByteArray(length)) => length as usize,
+                    _ => return Err(self.parse_error(ty, "not represented as bytes")),
+                };
+                let bytes = self.bytes(ty, length)?;
+                self.emit(Token::String(&hex::encode(bytes)))
+            }
+        }
+    }
+
+    /// Decode the fields of a struct or an enum variant at nesting `depth`.
+    fn fields(&mut self, fields: &Fields, depth: usize) -> Result<(), DecodeError<S::Error>> {
+        match fields {
+            Fields::Named(fields) => {
+                self.emit(Token::BeginObject)?;
+                for (name, ty) in fields.iter() {
+                    self.emit(Token::Key(name))?;
+                    self.value(ty, depth + 1)?;
+                }
+                self.emit(Token::EndObject)
+            }
+            Fields::Unnamed(fields) => {
+                self.emit(Token::BeginArray)?;
+                for ty in fields.iter() {
+                    self.value(ty, depth + 1)?;
+                }
+                self.emit(Token::EndArray)
+            }
+            Fields::None => {
+                self.emit(Token::BeginArray)?;
+                self.emit(Token::EndArray)
+            }
+        }
+    }
+
+    /// Decode the fields of an enum variant, represented as an object with the
+    /// variant name as the only key.
+    fn variant(
+        &mut self,
+        name: &str,
+        fields: &Fields,
+        depth: usize,
+    ) -> Result<(), DecodeError<S::Error>> {
+        self.emit(Token::BeginObject)?;
+        self.emit(Token::Key(name))?;
+        self.fields(fields, depth + 1)?;
+        self.emit(Token::EndObject)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sink collecting the tokens as JSON values, to compare them with the
+    /// expected tokens.
+    impl TokenSink for Vec<serde_json::Value> {
+        type Error = std::convert::Infallible;
+
+        fn token(&mut self, token: Token<'_>) -> Result<(), Self::Error> {
+            let token = match token {
+                Token::Null => serde_json::Value::Null,
+                Token::Bool(b) => b.into(),
+                Token::Number(n) => (n as i64).into(),
+                Token::String(s) => format!("string {}", s).into(),
+                Token::BeginArray => "[".into(),
+                Token::EndArray => "]".into(),
+                Token::BeginObject => "{".into(),
+                Token::Key(k) => format!("key {}", k).into(),
+                Token::EndObject => "}".into(),
+            };
+            self.push(token);
+            Ok(())
+        }
+    }
+
+    /// Check that the streamed JSON agrees with [`Type::to_json`].
+    fn check_agrees_with_to_json(ty: &Type, bytes: &[u8]) {
+        let expected = ty.to_json(&mut Cursor::new(bytes)).expect("to_json should succeed");
+        let mut out = Vec::new();
+        let read = ty
+            .write_json(&mut Cursor::new(bytes), &DecodeLimits::default(), &mut out)
+            .expect("Streaming should succeed");
+        assert_eq!(read, bytes.len() as u64);
+        let actual: serde_json::Value =
+            serde_json::from_slice(&out).expect("Streamed JSON should be valid");
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_agrees_with_to_json() {
+        let ty = Type::Struct(Fields::Named(vec![
+            ("unit".into(), Type::Unit),
+            ("flag".into(), Type::Bool),
+            ("small".into(), Type::I16),
+            ("big".into(), Type::U128),
+            ("amount".into(), Type::Amount),
+            ("account".into(), Type::AccountAddress),
+            ("contract".into(), Type::ContractAddress),
+            ("time".into(), Type::Timestamp),
+            ("duration".into(), Type::Duration),
+            ("name".into(), Type::ReceiveName(SizeLength::U16)),
+            ("init".into(), Type::ContractName(SizeLength::U8)),
+            ("text".into(), Type::String(SizeLength::U8)),
+            ("bytes".into(), Type::ByteList(SizeLength::U8)),
+            ("hash".into(), Type::ByteArray(2)),
+            ("uleb".into(), Type::ULeb128(5)),
+            ("ileb".into(), Type::ILeb128(5)),
+            (
+                "map".into(),
+                Type::Map(
+                    SizeLength::U8,
+                    Box::new(Type::U8),
+                    Box::new(Type::Pair(Box::new(Type::U32), Box::new(Type::I64))),
+                ),
+            ),
+            (
+                "variant".into(),
+                Type::Enum(vec![
+                    ("None".into(), Fields::None),
+                    ("Some".into(), Fields::Unnamed(vec![Type::Array(2, Box::new(Type::U8))])),
+                ]),
+            ),
+        ]));
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&[1, 0xfe, 0xff]);
+        bytes.extend_from_slice(&u128::MAX.to_le_bytes());
+        bytes.extend_from_slice(&1_000_000u64.to_le_bytes());
+        bytes.extend_from_slice(&[7; 32]);
+        bytes.extend_from_slice(&3u64.to_le_bytes());
+        bytes.extend_from_slice(&4u64.to_le_bytes());
+        bytes.extend_from_slice(&1_700_000_000_000u64.to_le_bytes());
+        bytes.extend_from_slice(&90_061_001u64.to_le_bytes());
+        bytes.extend_from_slice(&[8, 0]);
+        bytes.extend_from_slice(b"auc.bid\"");
+        bytes.extend_from_slice(&[8]);
+        bytes.extend_from_slice(b"init_auc");
+        bytes.extend_from_slice(&[5]);
+        bytes.extend_from_slice("h\u{e9}\n\u{1}".as_bytes());
+        bytes.extend_from_slice(&[3, 0xde, 0xad, 0x01, 0xbe, 0xef, 0xe5, 0x8e, 0x26, 0x7f]);
+        bytes
+            .extend_from_slice(&[2, 1, 1, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]);
+        bytes.extend_from_slice(&[2, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        bytes.extend_from_slice(&[1, 42, 43]);
+        check_agrees_with_to_json(&ty, &bytes);
+    }
+
+    #[test]
+    fn test_tokens() {
+        let mut variants = std::collections::BTreeMap::new();
+        variants.insert(7, ("Point".to_string(), Fields::Named(vec![("x".into(), Type::I8)])));
+        let ty = Type::TaggedEnum(variants);
+        let mut tokens: Vec<serde_json::Value> = Vec::new();
+        decode(&ty, &mut Cursor::new([7u8, 0xff]), &DecodeLimits::default(), &mut tokens).unwrap();
+        assert_eq!(tokens, [
+            serde_json::json!("{"),
+            "key Point".into(),
+            "{".into(),
+            "key x".into(),
+            (-1).into(),
+            "}".into(),
+            "}".into()
+        ]);
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let mut ty = Type::U8;
+        for _ in 0..10 {
+            ty = Type::Pair(Box::new(ty), Box::new(Type::Unit));
+        }
+        let limits = DecodeLimits {
+            max_depth: 9,
+            ..DecodeLimits::default()
+        };
+        let result = ty.write_json(&mut Cursor::new([0u8]), &limits, io::sink());
+        assert!(matches!(
+            result,
+            Err(DecodeError::DepthExceeded {
+                limit: 9,
+                ..
+            })
+        ));
+        let limits = DecodeLimits {
+            max_depth: 10,
+            ..DecodeLimits::default()
+        };
+        assert_eq!(ty.write_json(&mut Cursor::new([0u8]), &limits, io::sink()).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_length_limit() {
+        let ty = Type::ByteList(SizeLength::U32);
+        let bytes = u32::MAX.to_le_bytes();
+        let result = ty.write_json(&mut Cursor::new(bytes), &DecodeLimits::default(), io::sink());
+        assert!(matches!(
+            result,
+            Err(DecodeError::LengthExceeded {
+                position: 4,
+                length: 0xffff_ffff,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_value_limit() {
+        // A list of units does not consume any input for its elements, so only
+        // the limit on the number of values prevents unbounded output.
+        let ty = Type::List(SizeLength::U32, Box::new(Type::Unit));
+        let bytes = 1_000_000u32.to_le_bytes();
+        let limits = DecodeLimits {
+            max_values: 1000,
+            ..DecodeLimits::default()
+        };
+        let result = ty.write_json(&mut Cursor::new(bytes), &limits, io::sink());
+        assert!(matches!(
+            result,
+            Err(DecodeError::TooManyValues {
+                limit: 1000,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let ty = Type::Enum(vec![("A".into(), Fields::None)]);
+        let result = ty.write_json(&mut Cursor::new([1u8]), &DecodeLimits::default(), io::sink());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Could not parse Enum([(\"A\", None)]) at position 1: unknown variant tag 1"
+        );
+    }
+}
@@ -0,0 +1,904 @@
+//! A [`serde`] data format for the Concordium binary encoding.
+//!
+//! Types implementing [`serde::Serialize`] and [`serde::Deserialize`] can be
+//! encoded to and decoded from the same bytes as produced by [`Serial`] and
+//! consumed by [`Deserial`], e.g., for contract parameters, without
+//! maintaining separate implementations of the two. The encoding follows the
+//! conventions of the derived implementations:
+//!
+//! - `bool` is a single byte, `0` or `1`.
+//! - Integers are encoded in little-endian, using their full width.
+//! - Strings, byte arrays, sequences and maps are prefixed with their length,
+//!   encoded using the [`SizeLength`] of the [`Config`] (`U32` by default).
+//! - `Option` is a tag byte, `0` for `None` and `1` for `Some`, followed by the
+//!   value if present.
+//! - Tuples and structs are the concatenation of their fields, unit structs and
+//!   `()` are empty, and newtype structs are encoded as their contents.
+//! - Enums are the index of the variant as a single byte, followed by the
+//!   fields of the variant. Enums with more than 256 variants are not
+//!   supported.
+//!
+//! Floating point numbers and `char` have no counterpart in the encoding and
+//! are rejected. The encoding is not self-describing, hence
+//! `deserialize_any` and types relying on it, such as untagged enums, are not
+//! supported.
+//!
+//! Note that the encoding follows the `serde` implementation of a type, which
+//! may differ from its [`Serial`] implementation. For instance,
+//! [`AccountAddress`] is serialized by `serde` as its base58 string. Use
+//! [`to_vec_checked`] or [`validate`] to check that the bytes match the
+//! [`Type`] expected by the contract.
+use crate::{schema::*, schema_stream::DecodeLimits, *};
+use serde::{de, ser, Deserialize, Serialize};
+use std::io;
+
+/// Configuration of the encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    /// Size of the length prefix of strings, byte arrays, sequences and maps.
+    pub size_length: SizeLength,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            size_length: SizeLength::U32,
+        }
+    }
+}
+
+/// Errors that can occur when encoding or decoding values.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
+    /// An error reported by the `serde` implementation of a type.
+    #[error("{0}")]
+    Message(String),
+    /// Writing the bytes to the output failed.
+    #[error("Could not write to the output.")]
+    Write,
+    /// The input ended before the value was complete.
+    #[error("Unexpected end of input at position {position}.")]
+    UnexpectedEnd {
+        position: usize,
+    },
+    /// The input contains bytes after the value.
+    #[error("{remaining} trailing bytes after the value.")]
+    TrailingBytes {
+        remaining: usize,
+    },
+    /// A length does not fit in the configured [`SizeLength`], or a decoded
+    /// length does not fit in a `usize`.
+    #[error("Length {length} is not representable using {size_length:?}.")]
+    LengthOverflow {
+        length:      u64,
+        size_length: SizeLength,
+    },
+    /// A sequence or map was serialized without a known length.
+    #[error("Sequences and maps must have a known length.")]
+    UnknownLength,
+    /// An invalid tag of a `bool`, `Option` or enum was encountered.
+    #[error("Invalid {kind} tag {tag} at position {position}.")]
+    InvalidTag {
+        kind:     &'static str,
+        tag:      u8,
+        position: usize,
+    },
+    /// A string is not valid UTF-8.
+    #[error("Invalid UTF-8 in the string at position {position}.")]
+    InvalidUtf8 {
+        position: usize,
+    },
+    /// The index of an enum variant does not fit in a single byte.
+    #[error("Variant index {0} exceeds the maximum of 255.")]
+    TooManyVariants(u32),
+    /// The value uses a feature of the `serde` data model which has no
+    /// counterpart in the encoding.
+    #[error("{0} is not supported by the Concordium binary encoding.")]
+    Unsupported(&'static str),
+    /// The bytes do not match the expected schema type.
+    #[error("The value does not match the schema: {0}")]
+    Schema(String),
+}
+
+impl ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self { Error::Message(msg.to_string()) }
+}
+
+impl de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self { Error::Message(msg.to_string()) }
+}
+
+/// Encode a value.
+///
+/// ```
+/// # use concordium_contracts_common::serde_binary::*;
+/// #[derive(serde::Serialize)]
+/// struct Transfer {
+///     amount: u64,
+///     memo:   Option<String>,
+/// }
+/// let bytes = to_vec(
+///     &Transfer {
+///         amount: 10,
+///         memo:   Some("hi".into()),
+///     },
+///     Config::default(),
+/// )
+/// .unwrap();
+/// assert_eq!(bytes, [10, 0, 0, 0, 0, 0, 0, 0, 1, 2, 0, 0, 0, b'h', b'i']);
+/// ```
+pub fn to_vec<T: Serialize + ?Sized>(value: &T, config: Config) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::new();
+    to_writer(&mut out, value, config)?;
+    Ok(out)
+}
+
+/// Encode a value, writing the bytes to `out`.
+pub fn to_writer<W: Write, T: Serialize + ?Sized>(
+    out: &mut W,
+    value: &T,
+    config: Config,
+) -> Result<(), Error> {
+    value.serialize(&mut Serializer::new(out, config))
+}
+
+/// Encode a value and check that the bytes are a valid value of the schema
+/// type `ty`.
+pub fn to_vec_checked<T: Serialize + ?Sized>(
+    value: &T,
+    ty: &Type,
+    config: Config,
+) -> Result<Vec<u8>, Error> {
+    let bytes = to_vec(value, config)?;
+    validate(&bytes, ty)?;
+    Ok(bytes)
+}
+
+/// Check that `bytes` is exactly one valid value of the schema type `ty`.
+pub fn validate(bytes: &[u8], ty: &Type) -> Result<(), Error> {
+    let read = ty
+        .write_json(&mut Cursor::new(bytes), &DecodeLimits::default(), io::sink())
+        .map_err(|e| Error::Schema(e.to_string()))?;
+    if read as usize != bytes.len() {
+        return Err(Error::Schema(format!(
+            "{} trailing bytes after the value.",
+            bytes.len() - read as usize
+        )));
+    }
+    Ok(())
+}
+
+/// Decode a value, failing if any bytes remain after the value.
+///
+/// Strings and byte slices can be borrowed from the input.
+pub fn from_slice<'de, T: Deserialize<'de>>(bytes: &'de [u8], config: Config) -> Result<T, Error> {
+    let mut deserializer = Deserializer::new(bytes, config);
+    let value = T::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// A [`serde::Serializer`] writing the binary encoding of values to a
+/// [`Write`].
+#[derive(Debug)]
+pub struct Serializer<'a, W> {
+    out:    &'a mut W,
+    config: Config,
+}
+
+impl<'a, W: Write> Serializer<'a, W> {
+    /// Construct a serializer writing to `out`.
+    pub fn new(out: &'a mut W, config: Config) -> Self {
+        Self {
+            out,
+            config,
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.out.write_all(bytes).map_err(|_| Error::Write)
+    }
+
+    fn write_length(&mut self, len: usize) -> Result<(), Error> {
+        let size_length = self.config.size_length;
+        let overflow = || Error::LengthOverflow {
+            length: len as u64,
+            size_length,
+        };
+        match size_length {
+            SizeLength::U8 => self.write(&u8::try_from(len).map_err(|_| overflow())?.to_le_bytes()),
+            SizeLength::U16 => {
+                self.write(&u16::try_from(len).map_err(|_| overflow())?.to_le_bytes())
+            }
+            SizeLength::U32 => {
+                self.write(&u32::try_from(len).map_err(|_| overflow())?.to_le_bytes())
+            }
+            SizeLength::U64 => self.write(&(len as u64).to_le_bytes()),
+        }
+    }
+
+    fn write_variant(&mut self, variant_index: u32) -> Result<(), Error> {
+        let tag = u8::try_from(variant_index).map_err(|_| Error::TooManyVariants(variant_index))?;
+        self.write(&[tag])
+    }
+}
+
+impl<'a, 'b, W: Write> ser::Serializer for &'b mut Serializer<'a, W> {
+    type Error = Error;
+    type Ok = ();
+    type SerializeMap = Self;
+    type SerializeSeq = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> { self.write(&[u8::from(v)]) }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> { self.write(&v.to_le_bytes()) }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> { self.write(&v.to_le_bytes()) }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> { self.write(&v.to_le_bytes()) }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> { self.write(&v.to_le_bytes()) }
+
+    fn serialize_i128(self, v: i128) -> Result<(), Error> { self.write(&v.to_le_bytes()) }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> { self.write(&[v]) }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> { self.write(&v.to_le_bytes()) }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> { self.write(&v.to_le_bytes()) }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> { self.write(&v.to_le_bytes()) }
+
+    fn serialize_u128(self, v: u128) -> Result<(), Error> { self.write(&v.to_le_bytes()) }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), Error> { Err(Error::Unsupported("f32")) }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), Error> { Err(Error::Unsupported("f64")) }
+
+    fn serialize_char(self, _v: char) -> Result<(), Error> { Err(Error::Unsupported("char")) }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> { self.serialize_bytes(v.as_bytes()) }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.write_length(v.len())?;
+        self.write(v)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> { self.write(&[0]) }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        self.write(&[1])?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> { Ok(()) }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> { Ok(()) }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), Error> {
+        self.write_variant(variant_index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.write_variant(variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, Error> {
+        self.write_length(len.ok_or(Error::UnknownLength)?)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, Error> { Ok(self) }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Error> {
+        self.write_variant(variant_index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, Error> {
+        self.write_length(len.ok_or(Error::UnknownLength)?)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> { Ok(self) }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Error> {
+        self.write_variant(variant_index)?;
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool { false }
+}
+
+impl<'a, 'b, W: Write> ser::SerializeSeq for &'b mut Serializer<'a, W> {
+    type Error = Error;
+    type Ok = ();
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> { Ok(()) }
+}
+
+impl<'a, 'b, W: Write> ser::SerializeTuple for &'b mut Serializer<'a, W> {
+    type Error = Error;
+    type Ok = ();
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> { Ok(()) }
+}
+
+impl<'a, 'b, W: Write> ser::SerializeTupleStruct for &'b mut Serializer<'a, W> {
+    type Error = Error;
+    type Ok = ();
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> { Ok(()) }
+}
+
+impl<'a, 'b, W: Write> ser::SerializeTupleVariant for &'b mut Serializer<'a, W> {
+    type Error = Error;
+    type Ok = ();
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> { Ok(()) }
+}
+
+impl<'a, 'b, W: Write> ser::SerializeMap for &'b mut Serializer<'a, W> {
+    type Error = Error;
+    type Ok = ();
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> { Ok(()) }
+}
+
+impl<'a, 'b, W: Write> ser::SerializeStruct for &'b mut Serializer<'a, W> {
+    type Error = Error;
+    type Ok = ();
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> { Ok(()) }
+}
+
+impl<'a, 'b, W: Write> ser::SerializeStructVariant for &'b mut Serializer<'a, W> {
+    type Error = Error;
+    type Ok = ();
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), Error> { Ok(()) }
+}
+
+/// A [`serde::Deserializer`] reading the binary encoding of values from a
+/// slice.
+#[derive(Debug)]
+pub struct Deserializer<'de> {
+    input:    &'de [u8],
+    position: usize,
+    config:   Config,
+}
+
+impl<'de> Deserializer<'de> {
+    /// Construct a deserializer reading from `input`.
+    pub fn new(input: &'de [u8], config: Config) -> Self {
+        Self {
+            input,
+            position: 0,
+            config,
+        }
+    }
+
+    /// Check that the whole input has been consumed.
+    pub fn end(&self) -> Result<(), Error> {
+        let remaining = self.input.len() - self.position;
+        if remaining > 0 {
+            return Err(Error::TrailingBytes {
+                remaining,
+            });
+        }
+        Ok(())
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'de [u8], Error> {
+        let end = self.position.checked_add(n).filter(|end| *end <= self.input.len()).ok_or(
+            Error::UnexpectedEnd {
+                position: self.input.len(),
+            },
+        )?;
+        let bytes = &self.input[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn read_tag(&mut self, kind: &'static str) -> Result<bool, Error> {
+        let position = self.position;
+        match self.take_array::<1>()? {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            [tag] => Err(Error::InvalidTag {
+                kind,
+                tag,
+                position,
+            }),
+        }
+    }
+
+    fn read_length(&mut self) -> Result<usize, Error> {
+        let length = match self.config.size_length {
+            SizeLength::U8 => u8::from_le_bytes(self.take_array()?).into(),
+            SizeLength::U16 => u16::from_le_bytes(self.take_array()?).into(),
+            SizeLength::U32 => u32::from_le_bytes(self.take_array()?).into(),
+            SizeLength::U64 => u64::from_le_bytes(self.take_array()?),
+        };
+        usize::try_from(length).map_err(|_| Error::LengthOverflow {
+            length,
+            size_length: self.config.size_length,
+        })
+    }
+
+    fn read_bytes(&mut self) -> Result<&'de [u8], Error> {
+        let length = self.read_length()?;
+        self.take(length)
+    }
+
+    fn read_str(&mut self) -> Result<&'de str, Error> {
+        let length = self.read_length()?;
+        let position = self.position;
+        let bytes = self.take(length)?;
+        std::str::from_utf8(bytes).map_err(|_| Error::InvalidUtf8 {
+            position,
+        })
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    serde::forward_to_deserialize_any! {
+        identifier ignored_any
+    }
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::Unsupported("Self-describing deserialization"))
+    }
+
+    fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bool(self.read_tag("bool")?)
+    }
+
+    fn deserialize_i8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i8(i8::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_i16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i16(i16::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_i32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i32(i32::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_i64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i64(i64::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_i128<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i128(i128::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_u8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u8(u8::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_u16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u16(u16::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_u32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u32(u32::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_u64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(u64::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_u128<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u128(u128::from_le_bytes(self.take_array()?))
+    }
+
+    fn deserialize_f32<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::Unsupported("f32"))
+    }
+
+    fn deserialize_f64<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::Unsupported("f64"))
+    }
+
+    fn deserialize_char<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::Unsupported("char"))
+    }
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_str(self.read_str()?)
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_bytes(self.read_bytes()?)
+    }
+
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.read_tag("Option")? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let remaining = self.read_length()?;
+        visitor.visit_seq(Elements {
+            de: self,
+            remaining,
+        })
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_seq(Elements {
+            de:        self,
+            remaining: len,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let remaining = self.read_length()?;
+        visitor.visit_map(Elements {
+            de: self,
+            remaining,
+        })
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self)
+    }
+
+    fn is_human_readable(&self) -> bool { false }
+}
+
+/// Access to a known number of consecutive elements, or entries of a map.
+struct Elements<'a, 'de> {
+    de:        &'a mut Deserializer<'de>,
+    remaining: usize,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for Elements<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> { Some(self.remaining) }
+}
+
+impl<'de, 'a> de::MapAccess<'de> for Elements<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> { Some(self.remaining) }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let [tag] = self.take_array::<1>()?;
+        let index = de::value::U32Deserializer::<Error>::new(tag.into());
+        Ok((seed.deserialize(index)?, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> { Ok(()) }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate as concordium_std;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, crate::Serial, crate::Deserial)]
+    enum Action {
+        Stop,
+        Move(i32, i32),
+        Rename {
+            name: String,
+        },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, crate::Serial, crate::Deserial)]
+    struct Params {
+        flag:    bool,
+        amount:  u64,
+        owner:   Option<u16>,
+        actions: Vec<Action>,
+        tags:    BTreeMap<u8, String>,
+        pair:    (i8, u128),
+    }
+
+    fn params() -> Params {
+        Params {
+            flag:    true,
+            amount:  1_000_000,
+            owner:   Some(7),
+            actions: vec![Action::Stop, Action::Move(-1, 2), Action::Rename {
+                name: "new".into(),
+            }],
+            tags:    [(1, "one".to_string()), (2, "two".to_string())].into_iter().collect(),
+            pair:    (-5, u128::MAX),
+        }
+    }
+
+    #[test]
+    fn test_matches_derived_serial() {
+        let value = params();
+        let bytes = to_vec(&value, Config::default()).expect("Encoding succeeds.");
+        assert_eq!(bytes, to_bytes(&value));
+        let decoded: Params = from_slice(&bytes, Config::default()).expect("Decoding succeeds.");
+        assert_eq!(decoded, value);
+        assert_eq!(from_bytes::<Params>(&bytes).expect("Deserial succeeds."), value);
+    }
+
+    #[test]
+    fn test_size_length() {
+        let config = Config {
+            size_length: SizeLength::U8,
+        };
+        let bytes = to_vec(&vec!["ab"], config).expect("Encoding succeeds.");
+        assert_eq!(bytes, [1, 2, b'a', b'b']);
+        let decoded: Vec<&str> = from_slice(&bytes, config).expect("Decoding succeeds.");
+        assert_eq!(decoded, ["ab"]);
+        assert_eq!(
+            to_vec(&vec![0u8; 256], config),
+            Err(Error::LengthOverflow {
+                length:      256,
+                size_length: SizeLength::U8,
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let config = Config::default();
+        assert_eq!(
+            from_slice::<bool>(&[2], config),
+            Err(Error::InvalidTag {
+                kind:     "bool",
+                tag:      2,
+                position: 0,
+            })
+        );
+        assert_eq!(
+            from_slice::<u32>(&[1, 2, 3], config),
+            Err(Error::UnexpectedEnd {
+                position: 3,
+            })
+        );
+        assert_eq!(
+            from_slice::<u8>(&[1, 2], config),
+            Err(Error::TrailingBytes {
+                remaining: 1,
+            })
+        );
+        assert!(from_slice::<Action>(&[3], config).is_err());
+        assert_eq!(to_vec(&1.5f64, config), Err(Error::Unsupported("f64")));
+    }
+
+    #[test]
+    fn test_schema_validation() {
+        let config = Config {
+            size_length: SizeLength::U8,
+        };
+        let ty = Type::Struct(Fields::Named(vec![
+            ("amount".into(), Type::U64),
+            ("memo".into(), Type::String(SizeLength::U8)),
+        ]));
+        #[derive(Serialize)]
+        struct Valid<'a> {
+            amount: u64,
+            memo:   &'a str,
+        }
+        #[derive(Serialize)]
+        struct Invalid {
+            amount: u32,
+        }
+        let valid = Valid {
+            amount: 3,
+            memo:   "ok",
+        };
+        assert!(to_vec_checked(&valid, &ty, config).is_ok());
+        assert!(matches!(
+            to_vec_checked(
+                &Invalid {
+                    amount: 3,
+                },
+                &ty,
+                config
+            ),
+            Err(Error::Schema(_))
+        ));
+        assert!(matches!(to_vec_checked(&(valid, 0u8), &ty, config), Err(Error::Schema(_))));
+    }
+}
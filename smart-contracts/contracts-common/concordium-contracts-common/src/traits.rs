@@ -227,6 +227,23 @@ pub trait Deserial: Sized {
     fn deserial<R: Read>(_source: &mut R) -> ParseResult<Self>;
 }
 
+/// The `DeserialBorrowed` trait provides a means of reading structures which
+/// borrow from the slice they are read from, such as structures with `&'a [u8]`
+/// or `&'a str` fields, without copying.
+///
+/// It is implemented for all types implementing [`Deserial`], and can be
+/// derived using `#[derive(DeserialBorrowed)]` for most cases.
+pub trait DeserialBorrowed<'a>: Sized {
+    /// Attempt to read a structure from the slice of the cursor, failing if an
+    /// error occurs during deserialization or reading.
+    fn deserial_borrowed(_source: &mut Cursor<&'a [u8]>) -> ParseResult<Self>;
+}
+
+impl<'a, T: Deserial> DeserialBorrowed<'a> for T {
+    #[inline(always)]
+    fn deserial_borrowed(source: &mut Cursor<&'a [u8]>) -> ParseResult<Self> { T::deserial(source) }
+}
+
 /// The `Serialize` trait provides a means of writing structures into byte-sinks
 /// (`Write`) or reading structures from byte sources (`Read`).
 ///
@@ -1116,6 +1116,17 @@ impl AccountAddress {
 
         Self(data)
     }
+
+    /// Get the counter of `alias` as an alias of `self`, that is, the `n` such
+    /// that `self.get_alias(n)` is `alias`. If the addresses are not aliases
+    /// of each other this function will return [`None`].
+    pub fn get_alias_counter(&self, alias: &AccountAddress) -> Option<u32> {
+        if self.is_alias(alias) {
+            Some(u32::from_be_bytes([0, alias.0[29], alias.0[30], alias.0[31]]))
+        } else {
+            None
+        }
+    }
 }
 
 /// Address of a contract.
@@ -3029,6 +3040,19 @@ mod test {
     use super::*;
     use std::str::FromStr;
 
+    #[test]
+    fn test_get_alias_counter() {
+        let address = AccountAddress([7u8; 32]);
+        for counter in [0, 1, 255, 256, 65536, (1 << 24) - 1] {
+            let alias = address.get_alias(counter).expect("Counter is in range.");
+            assert_eq!(address.get_alias_counter(&alias), Some(counter));
+            assert_eq!(alias.get_alias_counter(&alias), Some(counter));
+        }
+        let mut other = address;
+        other.0[0] = 8;
+        assert_eq!(address.get_alias_counter(&other), None);
+    }
+
     #[test]
     #[cfg(feature = "derive-serde")]
     fn test_json_serialization_and_deserialization_of_signature_ed25519() {
@@ -0,0 +1,11 @@
+//! Bounds must be valid where predicates.
+extern crate concordium_contracts_common as concordium_std;
+use concordium_std::*;
+
+#[derive(Serial)]
+#[concordium(bound(serial = "T Serial"))]
+struct Wrapper<T> {
+    value: T,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+//! The bound attribute replaces the default bounds, so it must include the
+//! bounds required by the fields.
+extern crate concordium_contracts_common as concordium_std;
+use concordium_std::*;
+
+#[derive(Serial)]
+#[concordium(bound(serial = ""))]
+struct Wrapper<T> {
+    value: T,
+}
+
+fn main() {}
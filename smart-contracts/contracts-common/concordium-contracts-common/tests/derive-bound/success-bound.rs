@@ -0,0 +1,38 @@
+//! Replacing the default bounds on generic parameters.
+extern crate concordium_contracts_common as concordium_std;
+use concordium_std::*;
+use std::marker::PhantomData;
+
+/// A marker type which does not implement any serialization traits.
+#[derive(Debug, PartialEq)]
+struct Unit;
+
+#[derive(Debug, PartialEq, Serial, Deserial)]
+#[concordium(bound(serial = "T: Serial", deserial = "T: Deserial"))]
+struct Tagged<T, M> {
+    value:  T,
+    marker: PhantomData<M>,
+}
+
+#[derive(Debug, PartialEq, Serial, DeserialBorrowed)]
+#[concordium(bound = "")]
+struct Borrowed<'a, M> {
+    data:   &'a [u8],
+    marker: PhantomData<M>,
+}
+
+fn main() {
+    let tagged = Tagged::<u16, Unit> {
+        value:  7,
+        marker: PhantomData,
+    };
+    let bytes = to_bytes(&tagged);
+    assert_eq!(from_bytes::<Tagged<u16, Unit>>(&bytes), Ok(tagged));
+
+    let borrowed = Borrowed::<Unit> {
+        data:   &[1, 2],
+        marker: PhantomData,
+    };
+    let bytes = to_bytes(&borrowed);
+    assert_eq!(from_bytes_borrowed::<Borrowed<Unit>>(&bytes), Ok(borrowed));
+}
@@ -0,0 +1,10 @@
+//! `Deserial` cannot produce borrowed fields, since it reads from any source.
+extern crate concordium_contracts_common as concordium_std;
+use concordium_std::*;
+
+#[derive(Deserial)]
+struct Record<'a> {
+    name: &'a str,
+}
+
+fn main() {}
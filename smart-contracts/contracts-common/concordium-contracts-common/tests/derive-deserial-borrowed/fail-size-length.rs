@@ -0,0 +1,11 @@
+//! Borrowed fields always use 4 bytes for the length.
+extern crate concordium_contracts_common as concordium_std;
+use concordium_std::*;
+
+#[derive(DeserialBorrowed)]
+struct Record<'a> {
+    #[concordium(size_length = 1)]
+    name: &'a str,
+}
+
+fn main() {}
@@ -0,0 +1,33 @@
+//! Deserializing a generic enum which borrows from the input.
+extern crate concordium_contracts_common as concordium_std;
+use concordium_std::*;
+
+#[derive(Debug, PartialEq, Serial, DeserialBorrowed)]
+enum Message<'a, T> {
+    Empty,
+    Text(&'a str),
+    Value {
+        value: T,
+        note:  Option<u8>,
+    },
+}
+
+#[derive(Debug, PartialEq, Serial, DeserialBorrowed)]
+struct Nested<'a, 'b> {
+    first:  Message<'a, u64>,
+    second: Message<'b, Message<'a, ()>>,
+}
+
+fn main() {
+    let nested = Nested {
+        first:  Message::Text("hello"),
+        second: Message::Value {
+            value: Message::Empty,
+            note:  Some(1),
+        },
+    };
+    let bytes = to_bytes(&nested);
+    let parsed: Nested = from_bytes_borrowed(&bytes).expect("Deserialization succeeds.");
+    assert_eq!(parsed, nested);
+    assert!(from_bytes_borrowed::<Message<u8>>(&[3]).is_err());
+}
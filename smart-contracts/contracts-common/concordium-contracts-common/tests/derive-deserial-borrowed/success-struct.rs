@@ -0,0 +1,41 @@
+//! Deserializing a struct which borrows from the input.
+extern crate concordium_contracts_common as concordium_std;
+use concordium_std::*;
+
+#[derive(Debug, PartialEq, Serial, DeserialBorrowed)]
+struct Record<'a> {
+    name:  &'a str,
+    data:  &'a [u8],
+    #[concordium(size_length = 1)]
+    tags:  Vec<u8>,
+    count: u32,
+}
+
+fn main() {
+    let record = Record {
+        name:  "name",
+        data:  &[1, 2, 3],
+        tags:  vec![4, 5],
+        count: 6,
+    };
+    let bytes = to_bytes(&record);
+    let parsed: Record = from_bytes_borrowed(&bytes).expect("Deserialization succeeds.");
+    assert_eq!(parsed, record);
+    // The fields point into the input instead of being copied.
+    assert!(bytes.as_ptr_range().contains(&parsed.name.as_ptr()));
+    assert!(bytes.as_ptr_range().contains(&parsed.data.as_ptr()));
+    // Trailing bytes are rejected.
+    let mut longer = bytes.clone();
+    longer.push(0);
+    assert!(from_bytes_borrowed::<Record>(&longer).is_err());
+    // As are invalid strings. The name is serialized as a 4 byte length followed
+    // by the two bytes `0xc3 0xa9`, and replacing the continuation byte makes it
+    // invalid UTF-8 while keeping the rest of the input intact.
+    let mut invalid = to_bytes(&Record {
+        name: "\u{e9}",
+        ..record
+    });
+    assert_eq!(&invalid[4..6], &[0xc3, 0xa9]);
+    invalid[5] = 0x28;
+    assert!(from_bytes_borrowed::<Record>(&invalid).is_err());
+}
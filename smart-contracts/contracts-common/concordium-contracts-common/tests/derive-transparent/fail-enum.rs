@@ -0,0 +1,11 @@
+//! Enums cannot be transparent.
+extern crate concordium_contracts_common as concordium_std;
+use concordium_contracts_common_derive::SchemaType;
+
+#[derive(SchemaType)]
+#[concordium(transparent)]
+enum Single {
+    Only(u8),
+}
+
+fn main() {}
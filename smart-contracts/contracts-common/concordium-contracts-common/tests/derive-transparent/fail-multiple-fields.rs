@@ -0,0 +1,9 @@
+//! Only structs with a single field can be transparent.
+extern crate concordium_contracts_common as concordium_std;
+use concordium_contracts_common_derive::SchemaType;
+
+#[derive(SchemaType)]
+#[concordium(transparent)]
+struct Pair(u8, u8);
+
+fn main() {}
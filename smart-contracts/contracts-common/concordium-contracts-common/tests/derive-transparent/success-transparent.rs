@@ -0,0 +1,22 @@
+//! A transparent newtype has the serialization and schema of its field.
+extern crate concordium_contracts_common as concordium_std;
+use concordium_contracts_common_derive::SchemaType;
+use concordium_std::{schema::*, *};
+
+#[derive(Debug, PartialEq, Serial, Deserial, SchemaType)]
+#[concordium(transparent)]
+struct Bytes(#[concordium(size_length = 1)] Vec<u8>);
+
+#[derive(Debug, PartialEq, Serial, Deserial, SchemaType)]
+#[concordium(transparent)]
+struct Name {
+    name: String,
+}
+
+fn main() {
+    let bytes = Bytes(vec![1, 2]);
+    assert_eq!(to_bytes(&bytes), [2, 1, 2]);
+    assert_eq!(from_bytes::<Bytes>(&[2, 1, 2]), Ok(bytes));
+    assert_eq!(Bytes::get_type(), Type::List(SizeLength::U8, Box::new(Type::U8)));
+    assert_eq!(Name::get_type(), String::get_type());
+}
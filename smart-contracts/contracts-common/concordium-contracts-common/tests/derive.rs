@@ -0,0 +1,23 @@
+//! Tests of the derive macros, checking that derived implementations compile
+//! and behave as expected, and that invalid uses fail with helpful errors.
+
+#[test]
+fn derive_deserial_borrowed() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/derive-deserial-borrowed/success-*.rs");
+    t.compile_fail("tests/derive-deserial-borrowed/fail-*.rs");
+}
+
+#[test]
+fn derive_bound() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/derive-bound/success-*.rs");
+    t.compile_fail("tests/derive-bound/fail-*.rs");
+}
+
+#[test]
+fn derive_transparent() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/derive-transparent/success-*.rs");
+    t.compile_fail("tests/derive-transparent/fail-*.rs");
+}
@@ -0,0 +1,172 @@
+//! Tests of the Rust code generated from schemas, checking that the generated
+//! types compile and that their serialization matches the schema.
+#![cfg(feature = "derive-serde")]
+
+use concordium_contracts_common::{schema::*, schema_codegen::rust_module};
+use serde_json::json;
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf};
+
+/// A V3 module schema with the given receive functions with parameters and
+/// the event type of a single contract.
+fn module_schema(
+    contract: &str,
+    parameters: Vec<(&str, Type)>,
+    event: Option<Type>,
+) -> VersionedModuleSchema {
+    let receive = parameters
+        .into_iter()
+        .map(|(name, parameter)| {
+            (name.to_string(), FunctionV2 {
+                parameter:    Some(parameter),
+                return_value: None,
+                error:        None,
+            })
+        })
+        .collect();
+    let mut contracts = BTreeMap::new();
+    contracts.insert(contract.to_string(), ContractV3 {
+        init: None,
+        receive,
+        event,
+    });
+    VersionedModuleSchema::V3(ModuleV3 {
+        contracts,
+    })
+}
+
+/// Write a test program with the Rust module generated for the schema, which
+/// deserializes each of the samples into the given generated type and checks
+/// that serializing it again gives the same bytes. The samples are serialized
+/// from JSON using the schema.
+fn write_program(
+    name: &str,
+    schema: &VersionedModuleSchema,
+    samples: &[(&str, &Type, serde_json::Value)],
+) -> PathBuf {
+    let mut program = rust_module(schema, "concordium_contracts_common");
+    program.push_str(
+        "\nfn check<T: Serial + Deserial + std::fmt::Debug>(bytes: &[u8]) {\n    let value: T = \
+         from_bytes(bytes).expect(\"The generated type deserializes the bytes.\");\n    \
+         assert_eq!(to_bytes(&value), bytes, \"{:?}\", value);\n}\n\nfn main() {\n",
+    );
+    for (type_name, ty, value) in samples {
+        let bytes = ty.serial_value(value).expect("The sample matches the schema.");
+        let _ = writeln!(program, "    check::<{}>(&{:?});", type_name, bytes);
+    }
+    program.push_str("}\n");
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("schema-codegen");
+    std::fs::create_dir_all(&dir).expect("The directory can be created.");
+    let path = dir.join(format!("{}.rs", name));
+    std::fs::write(&path, program).expect("The program can be written.");
+    path
+}
+
+#[test]
+fn generated_rust_compiles() {
+    let transfer = Type::List(
+        SizeLength::U16,
+        Box::new(Type::Struct(Fields::Named(vec![
+            ("token_id".into(), Type::ByteList(SizeLength::U8)),
+            ("amount".into(), Type::ULeb128(37)),
+            (
+                "from".into(),
+                Type::Enum(vec![
+                    ("Account".into(), Fields::Unnamed(vec![Type::AccountAddress])),
+                    ("Contract".into(), Fields::Unnamed(vec![Type::ContractAddress])),
+                ]),
+            ),
+            ("data".into(), Type::List(SizeLength::U16, Box::new(Type::ByteList(SizeLength::U16)))),
+        ]))),
+    );
+    let mut event_variants = BTreeMap::new();
+    event_variants
+        .insert(255, ("Transfer".to_string(), Fields::Named(vec![("tokenId".into(), Type::U32)])));
+    event_variants.insert(254, ("Mint".to_string(), Fields::None));
+    let event = Type::TaggedEnum(event_variants);
+    let cis2 = module_schema("cis2_nft", vec![("transfer", transfer.clone())], Some(event.clone()));
+    let account = "2wkBET2rRgE8pahuaczxKbmv7ciehqsne57F9gtzf1PVdr2VP3";
+    let cis2_path = write_program("cis2", &cis2, &[
+        (
+            "Cis2NftTransferParameter",
+            &transfer,
+            json!([
+                {
+                    "token_id": "0a0b",
+                    "amount": "1000000",
+                    "from": { "Account": [account] },
+                    "data": ["", "ff00"],
+                },
+                {
+                    "token_id": "",
+                    "amount": "0",
+                    "from": { "Contract": [{ "index": 3, "subindex": 0 }] },
+                    "data": [],
+                },
+            ]),
+        ),
+        ("Cis2NftEvent", &event, json!({ "Transfer": { "tokenId": 7 } })),
+        ("Cis2NftEvent", &event, json!({ "Mint": [] })),
+    ]);
+
+    let registry_entry = Type::Struct(Fields::Named(vec![
+        ("owner".into(), Type::AccountAddress),
+        ("key".into(), Type::PublicKeyEd25519),
+        ("expiry".into(), Type::Timestamp),
+        ("renewal".into(), Type::Duration),
+        ("balance".into(), Type::Amount),
+        ("delta".into(), Type::ILeb128(10)),
+        ("checksum".into(), Type::ByteArray(4)),
+        ("counts".into(), Type::Array(3, Box::new(Type::U16))),
+    ]));
+    let register = Type::Struct(Fields::Named(vec![
+        ("name".into(), Type::String(SizeLength::U8)),
+        ("entry".into(), registry_entry),
+        (
+            "tags".into(),
+            Type::Map(
+                SizeLength::U8,
+                Box::new(Type::String(SizeLength::U16)),
+                Box::new(Type::Pair(Box::new(Type::I128), Box::new(Type::Bool))),
+            ),
+        ),
+        ("admins".into(), Type::Set(SizeLength::U32, Box::new(Type::U64))),
+        ("callback".into(), Type::ReceiveName(SizeLength::U16)),
+        (
+            "parent".into(),
+            Type::Enum(vec![
+                ("None".into(), Fields::None),
+                ("Some".into(), Fields::Unnamed(vec![Type::Hash, Type::I8])),
+            ]),
+        ),
+        ("nothing".into(), Type::Unit),
+    ]));
+    let registry = module_schema("registry", vec![("register", register.clone())], None);
+    let registry_path = write_program("registry", &registry, &[(
+        "RegistryRegisterParameter",
+        &register,
+        json!({
+            "name": "alice",
+            "entry": {
+                "owner": account,
+                "key": "0d6e8f7c0d3ac6d4e1de2c0b8de0ba1e1cf4e7d0b0d8b5b1e2f8a7c6d5e4f3a2",
+                "expiry": "2023-05-01T12:00:00Z",
+                "renewal": "30d",
+                "balance": "25000000",
+                "delta": "-300",
+                "checksum": "deadbeef",
+                "counts": [1, 2, 65535],
+            },
+            // Maps are ordered by key, so that serializing the map again gives the same
+            // bytes.
+            "tags": [["a", ["12", true]], ["b", ["-170141183460469231731687303715884105728", false]]],
+            "admins": [1, 18446744073709551615u64],
+            "callback": { "contract": "registry", "func": "on_register" },
+            "parent": { "Some": ["aa".repeat(32), -5] },
+            "nothing": [],
+        }),
+    )]);
+
+    let t = trybuild::TestCases::new();
+    t.pass(cis2_path);
+    t.pass(registry_path);
+}
@@ -0,0 +1,570 @@
+use anyhow::{bail, Context};
+use base64::{engine::general_purpose, Engine};
+use clap::AppSettings;
+use concordium_contracts_common::{
+    schema::{Type, VersionedModuleSchema},
+    schema_codegen::{rust_module, typescript_module},
+    schema_compatibility::check_compatibility,
+    schema_inspect::{diff, hex_dump},
+    to_bytes, WasmVersion,
+};
+use concordium_smart_contract_engine::utils::{
+    check_schema_exports, embed_schema, generate_contract_schema_v0, generate_contract_schema_v3,
+    get_build_info, get_embedded_schema_v0, get_embedded_schema_v1, get_inits, get_receives,
+    NoDuplicateImport, VersionedBuildInfo,
+};
+use concordium_wasm::{
+    parse::parse_skeleton,
+    types::Module,
+    validate::{validate_module, ValidationConfig},
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(bin_name = "schema-tool")]
+enum Command {
+    #[structopt(
+        name = "codegen",
+        about = "Generate type definitions for the parameters, return values, errors and events \
+                 described by a module schema."
+    )]
+    Codegen {
+        #[structopt(flatten)]
+        schema:     SchemaInput,
+        #[structopt(
+            name = "language",
+            long = "language",
+            possible_values = &["typescript", "rust"],
+            help = "Language of the generated code. TypeScript types describe the JSON \
+                    representation of values, Rust types derive `Serial` and `Deserial` matching \
+                    the binary representation."
+        )]
+        language:   String,
+        #[structopt(
+            name = "crate-path",
+            long = "crate-path",
+            default_value = "concordium_std",
+            help = "Path of the crate providing `Serial` and `Deserial` in the generated Rust \
+                    code, e.g., `concordium_contracts_common`."
+        )]
+        crate_path: String,
+        #[structopt(
+            name = "out",
+            long = "out",
+            help = "File to write the generated code to. Defaults to standard output."
+        )]
+        out:        Option<PathBuf>,
+    },
+    #[structopt(
+        name = "check-upgrade",
+        about = "Check whether the schema of an upgraded module is compatible with the schema of \
+                 the module it replaces. Fails if any type or entrypoint is removed or changed in \
+                 a binary-incompatible way, or if a return value, error or event type gains \
+                 values that clients using the old schema cannot decode."
+    )]
+    CheckUpgrade {
+        #[structopt(
+            name = "old",
+            long = "old",
+            help = "Path to the schema of the module being replaced."
+        )]
+        old:            PathBuf,
+        #[structopt(name = "new", long = "new", help = "Path to the schema of the new module.")]
+        new:            PathBuf,
+        #[structopt(
+            name = "base64",
+            long = "base64",
+            help = "The schema files contain the schemas encoded in base64 instead of raw bytes."
+        )]
+        base64:         bool,
+        #[structopt(
+            name = "schema-version",
+            long = "schema-version",
+            help = "Version of the schemas. Only needed for schemas without embedded version \
+                    information."
+        )]
+        schema_version: Option<u8>,
+    },
+    #[structopt(
+        name = "dump",
+        about = "Print an annotated hex dump of a value, labelling the bytes of each field \
+                 according to the schema."
+    )]
+    Dump {
+        #[structopt(flatten)]
+        schema: SchemaInput,
+        #[structopt(flatten)]
+        value:  ValueType,
+        #[structopt(flatten)]
+        data:   DataFormat,
+        #[structopt(name = "data", help = "Path to the file containing the value.")]
+        path:   PathBuf,
+    },
+    #[structopt(
+        name = "diff",
+        about = "Print the structural differences between two values of the same type."
+    )]
+    Diff {
+        #[structopt(flatten)]
+        schema: SchemaInput,
+        #[structopt(flatten)]
+        value:  ValueType,
+        #[structopt(flatten)]
+        data:   DataFormat,
+        #[structopt(name = "old", help = "Path to the file containing the old value.")]
+        old:    PathBuf,
+        #[structopt(name = "new", help = "Path to the file containing the new value.")]
+        new:    PathBuf,
+    },
+    #[structopt(
+        name = "entrypoints",
+        about = "List the contracts of a Wasm module and the entrypoints of each contract."
+    )]
+    Entrypoints {
+        #[structopt(flatten)]
+        module: ModuleInput,
+    },
+    #[structopt(name = "extract-schema", about = "Extract the schema embedded in a Wasm module.")]
+    ExtractSchema {
+        #[structopt(flatten)]
+        module: ModuleInput,
+        #[structopt(flatten)]
+        out:    SchemaOutput,
+    },
+    #[structopt(
+        name = "generate-schema",
+        about = "Generate the schema of a Wasm module built with schema functions by running \
+                 them. V1 modules always produce a V3 schema."
+    )]
+    GenerateSchema {
+        #[structopt(flatten)]
+        module: ModuleInput,
+        #[structopt(flatten)]
+        out:    SchemaOutput,
+    },
+    #[structopt(
+        name = "embed-schema",
+        about = "Embed a schema into a Wasm module, replacing any schema already embedded."
+    )]
+    EmbedSchema {
+        #[structopt(flatten)]
+        module: ModuleInput,
+        #[structopt(flatten)]
+        schema: SchemaInput,
+        #[structopt(
+            name = "out",
+            long = "out",
+            help = "File to write the module to. The module is written with a version prefix if \
+                    the input has one."
+        )]
+        out:    PathBuf,
+    },
+    #[structopt(
+        name = "build-info",
+        about = "Print the build information embedded in a Wasm module by a verifiable build."
+    )]
+    BuildInfo {
+        #[structopt(flatten)]
+        module: ModuleInput,
+    },
+    #[structopt(
+        name = "verify-schema",
+        about = "Check that every contract and entrypoint described by the schema embedded in a \
+                 Wasm module is exported by the module."
+    )]
+    VerifySchema {
+        #[structopt(flatten)]
+        module: ModuleInput,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+struct SchemaInput {
+    #[structopt(name = "schema", long = "schema", help = "Path to the module schema.")]
+    path:           PathBuf,
+    #[structopt(
+        name = "base64",
+        long = "base64",
+        help = "The schema file contains the schema encoded in base64 instead of raw bytes."
+    )]
+    base64:         bool,
+    #[structopt(
+        name = "schema-version",
+        long = "schema-version",
+        help = "Version of the schema. Only needed for schemas without embedded version \
+                information."
+    )]
+    schema_version: Option<u8>,
+}
+
+impl SchemaInput {
+    fn read(&self) -> anyhow::Result<VersionedModuleSchema> {
+        read_schema(&self.path, self.base64, self.schema_version)
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct ValueType {
+    #[structopt(name = "contract", long = "contract", help = "Name of the contract.")]
+    contract:   String,
+    #[structopt(
+        name = "entrypoint",
+        long = "entrypoint",
+        help = "Name of the receive function. If omitted, the init function is used."
+    )]
+    entrypoint: Option<String>,
+    #[structopt(
+        name = "item",
+        long = "item",
+        default_value = "parameter",
+        possible_values = &["parameter", "return-value", "error", "event"],
+        help = "Which type of the function to use for decoding the value. Events are the same for \
+                all functions of a contract."
+    )]
+    item:       String,
+}
+
+impl ValueType {
+    /// Look up the type of the value in the schema.
+    fn lookup(&self, schema: &VersionedModuleSchema) -> anyhow::Result<Type> {
+        let contract = self.contract.as_str();
+        let ty = match (self.item.as_str(), &self.entrypoint) {
+            ("event", _) => schema.get_event_schema(contract),
+            ("return-value", Some(func)) => schema.get_receive_return_value_schema(contract, func),
+            ("return-value", None) => bail!("The schema has no return value for init functions."),
+            ("error", Some(func)) => schema.get_receive_error_schema(contract, func),
+            ("error", None) => schema.get_init_error_schema(contract),
+            (_, Some(func)) => schema.get_receive_param_schema(contract, func),
+            (_, None) => schema.get_init_param_schema(contract),
+        };
+        ty.with_context(|| format!("Could not find the {} type in the schema.", self.item))
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct DataFormat {
+    #[structopt(
+        name = "hex",
+        long = "hex",
+        help = "The value files contain the values encoded in hex instead of raw bytes."
+    )]
+    hex: bool,
+}
+
+impl DataFormat {
+    fn read(&self, path: &Path) -> anyhow::Result<Vec<u8>> {
+        let contents =
+            fs::read(path).with_context(|| format!("Could not read {}.", path.display()))?;
+        if self.hex {
+            let contents = std::str::from_utf8(&contents).context("Value file is not UTF-8.")?;
+            hex::decode(contents.trim())
+                .with_context(|| format!("Could not decode hex from {}.", path.display()))
+        } else {
+            Ok(contents)
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct ModuleInput {
+    #[structopt(
+        name = "module",
+        long = "module",
+        help = "Path to the Wasm module. Modules with the version prefix of deployed modules, \
+                such as the `.wasm.v1` files built by cargo-concordium, are detected \
+                automatically."
+    )]
+    path:         PathBuf,
+    #[structopt(
+        name = "wasm-version",
+        long = "wasm-version",
+        default_value = "V1",
+        help = "Version of the module, `V0` or `V1`. Only used for modules without a version \
+                prefix."
+    )]
+    wasm_version: WasmVersion,
+}
+
+/// A Wasm module together with its version.
+struct ModuleFile {
+    version:  WasmVersion,
+    /// Whether the file has the version prefix of deployed modules.
+    prefixed: bool,
+    source:   Vec<u8>,
+}
+
+impl ModuleInput {
+    fn read(&self) -> anyhow::Result<ModuleFile> {
+        let contents = fs::read(&self.path)
+            .with_context(|| format!("Could not read module file {}.", self.path.display()))?;
+        if contents.starts_with(b"\0asm") {
+            return Ok(ModuleFile {
+                version:  self.wasm_version,
+                prefixed: false,
+                source:   contents,
+            });
+        }
+        // The version prefix is the version and the length of the module, both as
+        // big-endian u32.
+        anyhow::ensure!(contents.len() >= 8, "The module file is too short.");
+        let (prefix, source) = contents.split_at(8);
+        let version = match u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) {
+            0 => WasmVersion::V0,
+            1 => WasmVersion::V1,
+            v => bail!("Unsupported module version {}.", v),
+        };
+        let len = u32::from_be_bytes([prefix[4], prefix[5], prefix[6], prefix[7]]);
+        anyhow::ensure!(
+            len as usize == source.len(),
+            "The module length {} in the version prefix does not match the actual length {}.",
+            len,
+            source.len()
+        );
+        Ok(ModuleFile {
+            version,
+            prefixed: true,
+            source: source.to_vec(),
+        })
+    }
+}
+
+impl ModuleFile {
+    /// Parse and validate the module, allowing any imports.
+    fn module(&self) -> anyhow::Result<Module> {
+        let config = match self.version {
+            WasmVersion::V0 => ValidationConfig::V0,
+            WasmVersion::V1 => ValidationConfig::V1,
+        };
+        let skeleton = parse_skeleton(&self.source).context("Could not parse the module.")?;
+        validate_module(config, &NoDuplicateImport, &skeleton).context("Invalid module.")
+    }
+
+    fn embedded_schema(&self) -> anyhow::Result<VersionedModuleSchema> {
+        match self.version {
+            WasmVersion::V0 => get_embedded_schema_v0(&self.source),
+            WasmVersion::V1 => get_embedded_schema_v1(&self.source),
+        }
+    }
+
+    /// The bytes of the file containing `source` in place of the module, with
+    /// the same version prefix as the input, if any.
+    fn with_source(&self, source: &[u8]) -> anyhow::Result<Vec<u8>> {
+        if !self.prefixed {
+            return Ok(source.to_vec());
+        }
+        let len = u32::try_from(source.len()).context("The module is too large.")?;
+        let mut out = Vec::with_capacity(source.len() + 8);
+        out.extend_from_slice(&u32::from(u8::from(self.version)).to_be_bytes());
+        out.extend_from_slice(&len.to_be_bytes());
+        out.extend_from_slice(source);
+        Ok(out)
+    }
+}
+
+#[derive(Debug, StructOpt)]
+struct SchemaOutput {
+    #[structopt(
+        name = "out",
+        long = "out",
+        help = "File to write the schema to. Defaults to standard output, in which case the \
+                schema is encoded in base64."
+    )]
+    out:    Option<PathBuf>,
+    #[structopt(
+        name = "base64",
+        long = "base64",
+        help = "Write the schema encoded in base64 instead of raw bytes."
+    )]
+    base64: bool,
+}
+
+impl SchemaOutput {
+    fn write(&self, schema: &VersionedModuleSchema) -> anyhow::Result<()> {
+        let bytes = to_bytes(schema);
+        match &self.out {
+            Some(path) => {
+                let contents = if self.base64 {
+                    general_purpose::STANDARD.encode(bytes).into_bytes()
+                } else {
+                    bytes
+                };
+                fs::write(path, contents)
+                    .with_context(|| format!("Could not write to {}.", path.display()))
+            }
+            None => {
+                println!("{}", general_purpose::STANDARD.encode(bytes));
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Read a module schema from a file containing either the raw bytes or the
+/// base64 encoding of the schema.
+fn read_schema(
+    path: &Path,
+    base64: bool,
+    schema_version: Option<u8>,
+) -> anyhow::Result<VersionedModuleSchema> {
+    let contents = fs::read(path)
+        .with_context(|| format!("Could not read schema file {}.", path.display()))?;
+    let schema = if base64 {
+        let contents = std::str::from_utf8(&contents).context("Schema file is not UTF-8.")?;
+        VersionedModuleSchema::from_base64_str(contents.trim())
+    } else {
+        VersionedModuleSchema::new(&contents, &schema_version)
+    };
+    schema.with_context(|| format!("Could not parse schema from {}.", path.display()))
+}
+
+fn main() -> anyhow::Result<()> {
+    let cmd = {
+        let app = Command::clap()
+            .setting(AppSettings::ArgRequiredElseHelp)
+            .global_setting(AppSettings::ColoredHelp);
+        let matches = app.get_matches();
+        Command::from_clap(&matches)
+    };
+
+    match cmd {
+        Command::Codegen {
+            schema,
+            language,
+            crate_path,
+            out,
+        } => {
+            let schema = schema.read()?;
+            let code = match language.as_str() {
+                "rust" => rust_module(&schema, &crate_path),
+                _ => typescript_module(&schema),
+            };
+            match out {
+                Some(path) => fs::write(&path, code)
+                    .with_context(|| format!("Could not write to {}.", path.display()))?,
+                None => print!("{}", code),
+            }
+        }
+        Command::CheckUpgrade {
+            old,
+            new,
+            base64,
+            schema_version,
+        } => {
+            let old = read_schema(&old, base64, schema_version)?;
+            let new = read_schema(&new, base64, schema_version)?;
+            let report = check_compatibility(&old, &new);
+            print!("{}", report);
+            if !report.is_compatible() {
+                bail!("The new schema is not compatible with the old schema.");
+            }
+        }
+        Command::Dump {
+            schema,
+            value,
+            data,
+            path,
+        } => {
+            let ty = value.lookup(&schema.read()?)?;
+            let bytes = data.read(&path)?;
+            print!("{}", hex_dump(&ty, &bytes)?);
+        }
+        Command::Diff {
+            schema,
+            value,
+            data,
+            old,
+            new,
+        } => {
+            let ty = value.lookup(&schema.read()?)?;
+            let old = data.read(&old)?;
+            let new = data.read(&new)?;
+            for difference in diff(&ty, &old, &new)? {
+                println!("{}", difference);
+            }
+        }
+        Command::Entrypoints {
+            module,
+        } => {
+            let file = module.read()?;
+            let module = file.module()?;
+            let receives = get_receives(&module);
+            for init in get_inits(&module) {
+                let contract = init.as_ref().strip_prefix("init_").unwrap_or(init.as_ref());
+                println!("{}", contract);
+                for receive in receives.iter() {
+                    if let Some((_, entrypoint)) =
+                        receive.as_ref().split_once('.').filter(|(name, _)| *name == contract)
+                    {
+                        println!("  {}", entrypoint);
+                    }
+                }
+            }
+        }
+        Command::ExtractSchema {
+            module,
+            out,
+        } => {
+            let schema = module.read()?.embedded_schema()?;
+            out.write(&schema)?;
+        }
+        Command::GenerateSchema {
+            module,
+            out,
+        } => {
+            let file = module.read()?;
+            let schema = match file.version {
+                WasmVersion::V0 => generate_contract_schema_v0(&file.source),
+                WasmVersion::V1 => generate_contract_schema_v3(&file.source),
+            }
+            .context("Could not generate the schema.")?;
+            out.write(&schema)?;
+        }
+        Command::EmbedSchema {
+            module,
+            schema,
+            out,
+        } => {
+            let file = module.read()?;
+            let schema = schema.read()?;
+            let mismatches = check_schema_exports(&file.module()?, &schema);
+            if let Some(mismatch) = mismatches.first() {
+                bail!("The schema does not match the module: {}", mismatch);
+            }
+            let source = embed_schema(&file.source, &schema)?;
+            fs::write(&out, file.with_source(&source)?)
+                .with_context(|| format!("Could not write to {}.", out.display()))?;
+        }
+        Command::BuildInfo {
+            module,
+        } => {
+            let file = module.read()?;
+            let VersionedBuildInfo::V0(info) = get_build_info(&file.source)
+                .context("Could not read the build information of the module.")?;
+            println!("Archive hash: {}", info.archive_hash);
+            if let Some(link) = info.source_link {
+                println!("Source link:  {}", link);
+            }
+            println!("Build image:  {}", info.image);
+            println!("Command:      {}", info.build_command.join(" "));
+        }
+        Command::VerifySchema {
+            module,
+        } => {
+            let file = module.read()?;
+            let schema = file.embedded_schema()?;
+            let mismatches = check_schema_exports(&file.module()?, &schema);
+            for mismatch in mismatches.iter() {
+                println!("{}", mismatch);
+            }
+            if !mismatches.is_empty() {
+                bail!("The embedded schema does not match the module.");
+            }
+            println!("The embedded schema matches the module.");
+        }
+    }
+    Ok(())
+}
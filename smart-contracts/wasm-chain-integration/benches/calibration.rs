@@ -0,0 +1,788 @@
+//! Calibration of the V1 energy cost model against the machine the benchmark
+//! is run on.
+//!
+//! Each [`InternalOpcode`] and each [`HostFunctionV1`] that can be executed in
+//! isolation is run in a loop until execution runs out of energy. This is
+//! repeated for several energy budgets and the time per unit of energy is
+//! fitted as the slope of the measurements, so that fixed costs, such as
+//! setting up the instance, do not affect it.
+//!
+//! The median ratio over all benchmarks is used as the reference speed of the
+//! machine, unless one is given with `--reference`, and benchmarks are reported
+//! ordered by how much they diverge from it. A benchmark with a ratio well
+//! above the reference takes longer than its energy cost suggests, i.e., it is
+//! undercharged. One well below the reference is overcharged. Note that the
+//! cost of some host functions also accounts for storage or for work done by
+//! the node, so those are expected to appear overcharged.
+//!
+//! The benchmarked functions are in `./code/v1/calibration-instructions.wat`,
+//! `./code/v1/calibration-host-functions.wat` and
+//! `./code/v1/host-functions.wat`. Since energy is charged per basic block,
+//! and some host function benchmarks need auxiliary calls, each benchmark
+//! lists all the operations it exercises.
+//!
+//! Run with
+//!
+//! ```console
+//! cargo bench --bench calibration -- [FILTER] [OPTIONS]
+//! ```
+//!
+//! and see `cargo bench --bench calibration -- --help` for the options. When
+//! only some benchmarks are selected with `FILTER`, the reference computed from
+//! the full battery should be supplied with `--reference`.
+use concordium_contracts_common::{
+    Address, Amount, ChainMetadata, ContractAddress, OwnedEntrypointName, Timestamp,
+};
+use concordium_smart_contract_engine::{
+    constants::MAX_ACTIVATION_FRAMES,
+    v0,
+    v1::{
+        trie::{low_level::MutableTrie, EmptyCollector, Loader, MutableState, PersistentState},
+        CommonFunc, ConcordiumAllowedImports, HostFunctionV1, InitOnlyFunc, InstanceState,
+        ProcessedImports, ReceiveContext, ReceiveHost, ReceiveOnlyFunc, ReceiveParams,
+        StateLessReceiveHost,
+    },
+    InterpreterEnergy, OutOfEnergy,
+};
+use concordium_wasm::{
+    artifact::{Artifact, CompiledFunction, InternalOpcode},
+    machine, parse,
+    validate::{self, ValidationConfig},
+    CostConfigurationV1,
+};
+use num_enum::TryFromPrimitive;
+use sha2::Digest;
+use std::{path::PathBuf, time::Instant};
+use structopt::StructOpt;
+
+static CONTRACT_BYTES_HOST_FUNCTIONS: &[u8] = include_bytes!("./code/v1/host-functions.wasm");
+static CONTRACT_BYTES_CALIBRATION_INSTRUCTIONS: &[u8] =
+    include_bytes!("./code/v1/calibration-instructions.wasm");
+static CONTRACT_BYTES_CALIBRATION_HOST_FUNCTIONS: &[u8] =
+    include_bytes!("./code/v1/calibration-host-functions.wasm");
+
+/// Energy budgets, in interpreter energy, that each benchmark is run with.
+/// 1000 interpreter energy is 1NRG.
+const BUDGETS: [u64; 3] = [1_000_000, 2_000_000, 4_000_000];
+
+/// All host functions available to V1 contracts.
+const ALL_HOST_FUNCTIONS: [HostFunctionV1; 36] = [
+    HostFunctionV1::Common(CommonFunc::GetParameterSize),
+    HostFunctionV1::Common(CommonFunc::GetParameterSection),
+    HostFunctionV1::Common(CommonFunc::GetPolicySection),
+    HostFunctionV1::Common(CommonFunc::LogEvent),
+    HostFunctionV1::Common(CommonFunc::GetSlotTime),
+    HostFunctionV1::Common(CommonFunc::WriteOutput),
+    HostFunctionV1::Common(CommonFunc::StateLookupEntry),
+    HostFunctionV1::Common(CommonFunc::StateCreateEntry),
+    HostFunctionV1::Common(CommonFunc::StateDeleteEntry),
+    HostFunctionV1::Common(CommonFunc::StateDeletePrefix),
+    HostFunctionV1::Common(CommonFunc::StateIteratePrefix),
+    HostFunctionV1::Common(CommonFunc::StateIteratorNext),
+    HostFunctionV1::Common(CommonFunc::StateIteratorDelete),
+    HostFunctionV1::Common(CommonFunc::StateIteratorKeySize),
+    HostFunctionV1::Common(CommonFunc::StateIteratorKeyRead),
+    HostFunctionV1::Common(CommonFunc::StateEntryRead),
+    HostFunctionV1::Common(CommonFunc::StateEntryWrite),
+    HostFunctionV1::Common(CommonFunc::StateEntrySize),
+    HostFunctionV1::Common(CommonFunc::StateEntryResize),
+    HostFunctionV1::Common(CommonFunc::VerifyEd25519),
+    HostFunctionV1::Common(CommonFunc::VerifySecp256k1),
+    HostFunctionV1::Common(CommonFunc::RecoverSecp256k1),
+    HostFunctionV1::Common(CommonFunc::HashSHA2_256),
+    HostFunctionV1::Common(CommonFunc::HashSHA3_256),
+    HostFunctionV1::Common(CommonFunc::HashKeccak256),
+    HostFunctionV1::Common(CommonFunc::DebugPrint),
+    HostFunctionV1::Init(InitOnlyFunc::GetInitOrigin),
+    HostFunctionV1::Receive(ReceiveOnlyFunc::Invoke),
+    HostFunctionV1::Receive(ReceiveOnlyFunc::GetReceiveInvoker),
+    HostFunctionV1::Receive(ReceiveOnlyFunc::GetReceiveSelfAddress),
+    HostFunctionV1::Receive(ReceiveOnlyFunc::GetReceiveSelfBalance),
+    HostFunctionV1::Receive(ReceiveOnlyFunc::GetReceiveSender),
+    HostFunctionV1::Receive(ReceiveOnlyFunc::GetReceiveOwner),
+    HostFunctionV1::Receive(ReceiveOnlyFunc::GetReceiveEntrypointSize),
+    HostFunctionV1::Receive(ReceiveOnlyFunc::GetReceiveEntryPoint),
+    HostFunctionV1::Receive(ReceiveOnlyFunc::Upgrade),
+];
+
+/// Operations that are deliberately not benchmarked, together with the reason.
+fn not_benchmarked() -> Vec<(Operation, &'static str)> {
+    vec![
+        (
+            Operation::Instruction(InternalOpcode::Unreachable as u8),
+            "terminates execution, so it cannot be run in a loop",
+        ),
+        (
+            Operation::Instruction(InternalOpcode::TickEnergy as u8),
+            "inserted by metering, so it is part of every benchmark",
+        ),
+        (
+            Operation::HostFunction(HostFunctionV1::Common(CommonFunc::DebugPrint)),
+            "only available when debugging contracts off-chain",
+        ),
+        (
+            Operation::HostFunction(HostFunctionV1::Init(InitOnlyFunc::GetInitOrigin)),
+            "only available in init functions",
+        ),
+        (
+            Operation::HostFunction(HostFunctionV1::Receive(ReceiveOnlyFunc::Invoke)),
+            "interrupts execution and most of the cost is charged by the node",
+        ),
+        (
+            Operation::HostFunction(HostFunctionV1::Receive(ReceiveOnlyFunc::Upgrade)),
+            "interrupts execution and most of the cost is charged by the node",
+        ),
+    ]
+}
+
+/// An operation whose cost is measured.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    /// An instruction of the compiled artifact, given by its opcode.
+    Instruction(u8),
+    HostFunction(HostFunctionV1),
+}
+
+impl std::fmt::Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operation::Instruction(opcode) => match InternalOpcode::try_from_primitive(*opcode) {
+                Ok(op) => write!(f, "{:?}", op),
+                Err(_) => write!(f, "opcode {:#04x}", opcode),
+            },
+            Operation::HostFunction(hf) => hf.fmt(f),
+        }
+    }
+}
+
+fn instruction(op: InternalOpcode) -> Operation { Operation::Instruction(op as u8) }
+
+fn common(func: CommonFunc) -> Operation { Operation::HostFunction(HostFunctionV1::Common(func)) }
+
+fn receive(func: ReceiveOnlyFunc) -> Operation {
+    Operation::HostFunction(HostFunctionV1::Receive(func))
+}
+
+/// A single benchmark. The exported function `export` of `artifact` is
+/// invoked as a receive function, with the given parameter and initial
+/// state, and is expected to run until it runs out of energy.
+struct Benchmark<'a> {
+    name:       String,
+    operations: Vec<Operation>,
+    artifact:   &'a Artifact<ProcessedImports, CompiledFunction>,
+    export:     String,
+    arg:        i64,
+    parameter:  Vec<u8>,
+    state:      Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+/// The fitted cost of a single benchmark.
+struct Measurement {
+    name:          String,
+    operations:    Vec<Operation>,
+    /// Time in nanoseconds per unit of interpreter energy.
+    ns_per_energy: f64,
+}
+
+#[derive(StructOpt)]
+#[structopt(
+    name = "calibration",
+    about = "Compare the time it takes to execute operations with their energy cost."
+)]
+struct Options {
+    #[structopt(
+        name = "filter",
+        help = "Only run the benchmarks whose name or operations contain this string."
+    )]
+    filter:    Option<String>,
+    #[structopt(
+        long = "samples",
+        default_value = "11",
+        parse(try_from_str = parse_samples),
+        help = "Number of runs for each energy budget. The median time is used."
+    )]
+    samples:   usize,
+    #[structopt(
+        long = "threshold",
+        default_value = "2",
+        parse(try_from_str = parse_threshold),
+        help = "Factor by which a benchmark must diverge from the reference to be reported as \
+                over- or undercharged."
+    )]
+    threshold: f64,
+    #[structopt(
+        long = "reference",
+        parse(try_from_str = parse_reference),
+        help = "Reference time per NRG, in nanoseconds. Defaults to the median of the selected \
+                benchmarks."
+    )]
+    reference: Option<f64>,
+    #[structopt(long = "json", help = "Also write the report as JSON to this file.")]
+    json:      Option<PathBuf>,
+    /// Passed by `cargo bench`.
+    #[structopt(long = "bench", hidden = true)]
+    _bench:    bool,
+}
+
+fn parse_samples(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(samples) if samples > 0 => Ok(samples),
+        Ok(_) => Err("At least one sample is needed.".into()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn parse_threshold(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(threshold) if threshold >= 1.0 => Ok(threshold),
+        Ok(_) => Err("The threshold must be at least 1.".into()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn parse_reference(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(reference) if reference > 0.0 => Ok(reference),
+        Ok(_) => Err("The reference must be positive.".into()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Construct the initial state for the benchmark from given key-value pairs.
+fn mk_state(inputs: &[(Vec<u8>, Vec<u8>)]) -> (MutableState, Loader<Vec<u8>>) {
+    let mut node = MutableTrie::empty();
+    let mut loader = Loader {
+        inner: Vec::new(),
+    };
+    for (k, v) in inputs {
+        node.insert(&mut loader, k, v.clone()).expect("No locks, so cannot fail.");
+    }
+    if let Some(trie) = node.freeze(&mut loader, &mut EmptyCollector) {
+        (PersistentState::from(trie).thaw(), loader)
+    } else {
+        (PersistentState::Empty.thaw(), loader)
+    }
+}
+
+/// The state with the most nodes on the path to the key `[17; n]`. This is
+/// the precondition of the state benchmarks in `host-functions.wat`.
+fn path_state(n: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+    (0..=n).map(|i| (vec![17u8; i], i.to_be_bytes().to_vec())).collect()
+}
+
+/// The benchmarks in `calibration-instructions.wat`, given by the name of the
+/// export without the `calibrate.` prefix, and the instructions they exercise
+/// besides the loop itself.
+fn instructions() -> Vec<(&'static str, Vec<InternalOpcode>)> {
+    use InternalOpcode::*;
+    vec![
+        ("i32.eqz", vec![I32Eqz]),
+        ("i32.clz", vec![I32Clz]),
+        ("i32.ctz", vec![I32Ctz]),
+        ("i32.popcnt", vec![I32Popcnt]),
+        ("i64.extend_i32_s", vec![I64ExtendI32S]),
+        ("i64.extend_i32_u", vec![I64ExtendI32U]),
+        ("i32.extend8_s", vec![I32Extend8S]),
+        ("i32.extend16_s", vec![I32Extend16S]),
+        ("i64.eqz", vec![I64Eqz]),
+        ("i64.clz", vec![I64Clz]),
+        ("i64.ctz", vec![I64Ctz]),
+        ("i64.popcnt", vec![I64Popcnt]),
+        ("i32.wrap_i64", vec![I32WrapI64]),
+        ("i64.extend8_s", vec![I64Extend8S]),
+        ("i64.extend16_s", vec![I64Extend16S]),
+        ("i64.extend32_s", vec![I64Extend32S]),
+        ("i32.eq", vec![I32Eq]),
+        ("i32.ne", vec![I32Ne]),
+        ("i32.lt_s", vec![I32LtS]),
+        ("i32.lt_u", vec![I32LtU]),
+        ("i32.gt_s", vec![I32GtS]),
+        ("i32.gt_u", vec![I32GtU]),
+        ("i32.le_s", vec![I32LeS]),
+        ("i32.le_u", vec![I32LeU]),
+        ("i32.ge_s", vec![I32GeS]),
+        ("i32.ge_u", vec![I32GeU]),
+        ("i32.add", vec![I32Add]),
+        ("i32.sub", vec![I32Sub]),
+        ("i32.mul", vec![I32Mul]),
+        ("i32.div_s", vec![I32DivS]),
+        ("i32.div_u", vec![I32DivU]),
+        ("i32.rem_s", vec![I32RemS]),
+        ("i32.rem_u", vec![I32RemU]),
+        ("i32.and", vec![I32And]),
+        ("i32.or", vec![I32Or]),
+        ("i32.xor", vec![I32Xor]),
+        ("i32.shl", vec![I32Shl]),
+        ("i32.shr_s", vec![I32ShrS]),
+        ("i32.shr_u", vec![I32ShrU]),
+        ("i32.rotl", vec![I32Rotl]),
+        ("i32.rotr", vec![I32Rotr]),
+        ("i64.eq", vec![I64Eq]),
+        ("i64.ne", vec![I64Ne]),
+        ("i64.lt_s", vec![I64LtS]),
+        ("i64.lt_u", vec![I64LtU]),
+        ("i64.gt_s", vec![I64GtS]),
+        ("i64.gt_u", vec![I64GtU]),
+        ("i64.le_s", vec![I64LeS]),
+        ("i64.le_u", vec![I64LeU]),
+        ("i64.ge_s", vec![I64GeS]),
+        ("i64.ge_u", vec![I64GeU]),
+        ("i64.add", vec![I64Add]),
+        ("i64.sub", vec![I64Sub]),
+        ("i64.mul", vec![I64Mul]),
+        ("i64.div_s", vec![I64DivS]),
+        ("i64.div_u", vec![I64DivU]),
+        ("i64.rem_s", vec![I64RemS]),
+        ("i64.rem_u", vec![I64RemU]),
+        ("i64.and", vec![I64And]),
+        ("i64.or", vec![I64Or]),
+        ("i64.xor", vec![I64Xor]),
+        ("i64.shl", vec![I64Shl]),
+        ("i64.shr_s", vec![I64ShrS]),
+        ("i64.shr_u", vec![I64ShrU]),
+        ("i64.rotl", vec![I64Rotl]),
+        ("i64.rotr", vec![I64Rotr]),
+        ("i32.load", vec![I32Load]),
+        ("i64.load", vec![I64Load]),
+        ("i32.load8_s", vec![I32Load8S]),
+        ("i32.load8_u", vec![I32Load8U]),
+        ("i32.load16_s", vec![I32Load16S]),
+        ("i32.load16_u", vec![I32Load16U]),
+        ("i64.load8_s", vec![I64Load8S]),
+        ("i64.load8_u", vec![I64Load8U]),
+        ("i64.load16_s", vec![I64Load16S]),
+        ("i64.load16_u", vec![I64Load16U]),
+        ("i64.load32_s", vec![I64Load32S]),
+        ("i64.load32_u", vec![I64Load32U]),
+        ("i32.store", vec![I32Store]),
+        ("i64.store", vec![I64Store]),
+        ("i32.store8", vec![I32Store8]),
+        ("i32.store16", vec![I32Store16]),
+        ("i64.store8", vec![I64Store8]),
+        ("i64.store16", vec![I64Store16]),
+        ("i64.store32", vec![I64Store32]),
+        ("memory.size", vec![MemorySize]),
+        ("memory.grow", vec![MemoryGrow]),
+        ("global.get", vec![GlobalGet]),
+        ("global.set", vec![GlobalSet]),
+        ("select", vec![Select]),
+        ("local.set", vec![Copy]),
+        ("if", vec![If]),
+        ("br", vec![Br]),
+        ("br_if", vec![BrIf]),
+        ("br_table", vec![BrTable]),
+        ("br_table_carry", vec![BrTableCarry]),
+        ("call", vec![Call, Return]),
+        ("call_indirect", vec![CallIndirect, Return]),
+    ]
+}
+
+/// Benchmarks of the functions in `calibration-instructions.wat`.
+fn instruction_benchmarks(
+    artifact: &Artifact<ProcessedImports, CompiledFunction>,
+) -> Vec<Benchmark<'_>> {
+    instructions()
+        .into_iter()
+        .map(|(name, opcodes)| Benchmark {
+            name: name.to_string(),
+            operations: opcodes.into_iter().map(instruction).collect(),
+            artifact,
+            export: format!("calibrate.{}", name),
+            arg: 0,
+            parameter: Vec::new(),
+            state: Vec::new(),
+        })
+        .collect()
+}
+
+/// Benchmarks of the functions in `calibration-host-functions.wat`. The
+/// arguments and parameters are set up to satisfy the preconditions listed
+/// there.
+fn calibration_host_function_benchmarks(
+    artifact: &Artifact<ProcessedImports, CompiledFunction>,
+) -> Vec<Benchmark<'_>> {
+    let mut benchmarks = Vec::new();
+    let mut add = |export: &str, operation: Operation, n: Option<usize>, parameter| {
+        let name = match n {
+            Some(n) => format!("{} n = {}", export, n),
+            None => export.to_string(),
+        };
+        benchmarks.push(Benchmark {
+            name,
+            operations: vec![operation],
+            artifact,
+            export: format!("calibrate.{}", export),
+            arg: n.unwrap_or(0) as i64,
+            parameter,
+            state: Vec::new(),
+        });
+    };
+
+    for n in [0, 1000] {
+        add("get_parameter_section", common(CommonFunc::GetParameterSection), Some(n), vec![
+            17u8;
+            n
+        ]);
+    }
+    add("get_policy_section", common(CommonFunc::GetPolicySection), None, Vec::new());
+    add("log_event", common(CommonFunc::LogEvent), Some(32), Vec::new());
+    add("get_slot_time", common(CommonFunc::GetSlotTime), None, Vec::new());
+    let getters = [
+        ReceiveOnlyFunc::GetReceiveInvoker,
+        ReceiveOnlyFunc::GetReceiveSelfAddress,
+        ReceiveOnlyFunc::GetReceiveSelfBalance,
+        ReceiveOnlyFunc::GetReceiveSender,
+        ReceiveOnlyFunc::GetReceiveOwner,
+        ReceiveOnlyFunc::GetReceiveEntrypointSize,
+        ReceiveOnlyFunc::GetReceiveEntryPoint,
+    ];
+    for func in getters {
+        add(&func.to_string(), receive(func), None, Vec::new());
+    }
+    for n in [10, 1000] {
+        add("state_entry_resize", common(CommonFunc::StateEntryResize), Some(n), Vec::new());
+    }
+    benchmarks
+}
+
+/// Compile a module the same way it is done on chain in protocol 6.
+fn compile(bytes: &[u8]) -> Artifact<ProcessedImports, CompiledFunction> {
+    let skeleton = parse::parse_skeleton(bytes).expect("Module should parse.");
+    let mut module = validate::validate_module(
+        ValidationConfig::V1,
+        &ConcordiumAllowedImports {
+            support_upgrade:            true,
+            enable_debug:               false,
+            support_secp256k1_recovery: true,
+        },
+        &skeleton,
+    )
+    .expect("Module should be valid.");
+    module.inject_metering(CostConfigurationV1).expect("Metering injection should succeed.");
+    module.compile::<ProcessedImports>().expect("Module should compile.")
+}
+
+/// Benchmarks of the functions in `host-functions.wat`. The parameters and
+/// state are set up to satisfy the preconditions listed there.
+fn host_function_benchmarks(
+    artifact: &Artifact<ProcessedImports, CompiledFunction>,
+) -> Vec<Benchmark<'_>> {
+    use CommonFunc::*;
+    let mut benchmarks = Vec::new();
+    let mut add =
+        |export: &str, operations: Vec<CommonFunc>, n: Option<usize>, arg, parameter, state| {
+            let name = match n {
+                Some(n) => format!("{} n = {}", export, n),
+                None => export.to_string(),
+            };
+            benchmarks.push(Benchmark {
+                name,
+                operations: operations.into_iter().map(common).collect(),
+                artifact,
+                export: format!("hostfn.{}", export),
+                arg,
+                parameter,
+                state,
+            });
+        };
+
+    add("get_parameter_size", vec![GetParameterSize], None, 0, vec![17u8; 10], Vec::new());
+    let state_functions = [
+        ("state_create_entry", vec![StateCreateEntry], false),
+        ("state_lookup_entry", vec![StateLookupEntry], false),
+        ("state_entry_size", vec![StateEntrySize], false),
+        ("state_entry_read", vec![StateEntryRead], true),
+        ("state_entry_write", vec![StateEntryWrite], true),
+        ("state_delete_entry", vec![StateCreateEntry, StateDeleteEntry], false),
+        ("state_delete_entry_nonexistent", vec![StateDeleteEntry], false),
+        ("state_iterate_prefix", vec![StateIteratePrefix], false),
+        ("state_delete_prefix", vec![StateCreateEntry, StateDeletePrefix], false),
+        ("state_iterator_key_size", vec![StateIteratorKeySize], false),
+        ("state_iterator_key_read", vec![StateIteratorKeyRead], false),
+        ("state_iterator_delete", vec![StateIteratePrefix, StateIteratorDelete], false),
+        (
+            "state_iterator_next",
+            vec![StateIteratePrefix, StateIteratorNext, StateIteratorKeySize],
+            false,
+        ),
+    ];
+    for (export, operations, arg_is_size) in state_functions {
+        for n in [2, 100] {
+            let arg = if arg_is_size {
+                n as i64
+            } else {
+                0
+            };
+            add(export, operations.clone(), Some(n), arg, vec![17u8; n], path_state(n));
+        }
+    }
+    for n in [0, 1000] {
+        add("write_output", vec![WriteOutput], Some(n), 0, vec![17u8; n], Vec::new());
+    }
+
+    for n in [0u32, 1000] {
+        let sk = ed25519_zebra::SigningKey::from([1u8; 32]);
+        let sig = sk.sign(&vec![0u8; n as usize]);
+        let pk = ed25519_zebra::VerificationKey::from(&sk);
+        let mut params = Vec::with_capacity(100);
+        params.extend_from_slice(pk.as_ref());
+        params.extend_from_slice(&<[u8; 64]>::from(sig)[..]);
+        params.extend_from_slice(&n.to_le_bytes());
+        add(
+            "verify_ed25519_signature",
+            vec![VerifyEd25519],
+            Some(n as usize),
+            0,
+            params,
+            Vec::new(),
+        );
+    }
+
+    let signer = secp256k1::Secp256k1::new();
+    let sk = secp256k1::SecretKey::from_slice(&[
+        0xc9, 0xef, 0x15, 0x44, 0x4b, 0x1e, 0x88, 0x5f, 0x0e, 0xd0, 0x36, 0xaa, 0xc8, 0x64, 0x6f,
+        0xb0, 0xc6, 0x11, 0x88, 0x6e, 0x8c, 0x40, 0x91, 0xa1, 0xb7, 0xb2, 0xb5, 0xa0, 0x95, 0xd2,
+        0xd6, 0xba,
+    ])
+    .expect("Key generated with openssl, so should be valid.");
+    let message = secp256k1::Message::from_slice(&sha2::Sha256::digest([])[..])
+        .expect("Hashes are valid messages.");
+    {
+        let sig = signer.sign_ecdsa(&message, &sk);
+        let pk = secp256k1::PublicKey::from_secret_key(&signer, &sk);
+        let mut params = Vec::with_capacity(100);
+        params.extend_from_slice(&pk.serialize());
+        params.extend_from_slice(&sig.serialize_compact());
+        params.extend_from_slice(message.as_ref());
+        add("verify_ecdsa_secp256k1_signature", vec![VerifySecp256k1], None, 0, params, Vec::new());
+    }
+    {
+        let (recovery_id, sig) = signer.sign_ecdsa_recoverable(&message, &sk).serialize_compact();
+        let mut params = Vec::with_capacity(100);
+        params.extend_from_slice(&sig);
+        params.extend_from_slice(&recovery_id.to_i32().to_le_bytes());
+        params.extend_from_slice(message.as_ref());
+        add(
+            "recover_ecdsa_secp256k1_public_key",
+            vec![RecoverSecp256k1],
+            None,
+            0,
+            params,
+            Vec::new(),
+        );
+    }
+
+    let hashes = [
+        ("hash_sha2_256", HashSHA2_256),
+        ("hash_sha3_256", HashSHA3_256),
+        ("hash_keccak_256", HashKeccak256),
+    ];
+    for (export, func) in hashes {
+        for n in [0u32, 10_000] {
+            add(export, vec![func], Some(n as usize), 0, n.to_le_bytes().to_vec(), Vec::new());
+        }
+    }
+    benchmarks
+}
+
+/// Run the benchmark once with the given energy, and return the time it took
+/// to run out of energy in nanoseconds.
+fn run_once(benchmark: &Benchmark, receive_ctx: &ReceiveContext<&[u8]>, energy: u64) -> f64 {
+    let (mut mutable_state, _) = mk_state(&benchmark.state);
+    let mut backing_store = Loader {
+        inner: Vec::new(),
+    };
+    let inner = mutable_state.get_inner(&mut backing_store);
+    let state = InstanceState::new(backing_store, inner);
+    let mut host = ReceiveHost::<_, Vec<u8>, _, _> {
+        energy: InterpreterEnergy::new(energy),
+        stateless: StateLessReceiveHost {
+            activation_frames: MAX_ACTIVATION_FRAMES,
+            logs: v0::Logs::new(),
+            receive_ctx,
+            return_value: Vec::new(),
+            parameters: vec![benchmark.parameter.clone()],
+            params: ReceiveParams::new_p6(),
+        },
+        state,
+        trace: (),
+    };
+    let args = [machine::Value::I64(benchmark.arg)];
+    let start = Instant::now();
+    let result = benchmark.artifact.run(&mut host, benchmark.export.as_str(), &args);
+    let elapsed = start.elapsed();
+    match result {
+        Ok(_) => panic!("Benchmark {} terminated without running out of energy.", benchmark.name),
+        Err(e) => assert!(
+            e.downcast_ref::<OutOfEnergy>().is_some(),
+            "Benchmark {} did not fail due to out of energy: {}.",
+            benchmark.name,
+            e
+        ),
+    }
+    // Drop the host and the state outside of the measured time.
+    drop(host);
+    drop(mutable_state);
+    elapsed.as_nanos() as f64
+}
+
+fn median(xs: &mut [f64]) -> f64 {
+    xs.sort_by(f64::total_cmp);
+    xs[xs.len() / 2]
+}
+
+/// Fit the time per unit of energy as the least-squares slope of the median
+/// time for each budget.
+fn measure(benchmark: &Benchmark, receive_ctx: &ReceiveContext<&[u8]>, samples: usize) -> f64 {
+    // Warm up caches and the allocator.
+    run_once(benchmark, receive_ctx, BUDGETS[0]);
+    let points: Vec<(f64, f64)> = BUDGETS
+        .iter()
+        .map(|&budget| {
+            let mut times: Vec<f64> =
+                (0..samples).map(|_| run_once(benchmark, receive_ctx, budget)).collect();
+            (budget as f64, median(&mut times))
+        })
+        .collect();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x) * (x - mean_x)).sum();
+    covariance / variance
+}
+
+fn main() {
+    let options = Options::from_args();
+
+    let instructions = compile(CONTRACT_BYTES_CALIBRATION_INSTRUCTIONS);
+    let calibration_host_functions = compile(CONTRACT_BYTES_CALIBRATION_HOST_FUNCTIONS);
+    let host_functions = compile(CONTRACT_BYTES_HOST_FUNCTIONS);
+
+    let mut benchmarks = instruction_benchmarks(&instructions);
+    benchmarks.extend(calibration_host_function_benchmarks(&calibration_host_functions));
+    benchmarks.extend(host_function_benchmarks(&host_functions));
+
+    let mut unmeasured = Vec::new();
+    let reasons = not_benchmarked();
+    let all_operations = (0..=u8::MAX)
+        .filter(|b| InternalOpcode::try_from_primitive(*b).is_ok())
+        .map(Operation::Instruction)
+        .chain(ALL_HOST_FUNCTIONS.into_iter().map(Operation::HostFunction));
+    for operation in all_operations {
+        if !benchmarks.iter().any(|b| b.operations.contains(&operation)) {
+            let reason = reasons
+                .iter()
+                .find_map(|(op, reason)| (*op == operation).then_some(*reason))
+                .unwrap_or("no benchmark");
+            unmeasured.push((operation, reason));
+        }
+    }
+
+    if let Some(filter) = &options.filter {
+        benchmarks.retain(|b| {
+            b.name.contains(filter.as_str())
+                || b.operations.iter().any(|op| op.to_string().contains(filter.as_str()))
+        });
+    }
+    if benchmarks.is_empty() {
+        eprintln!("No benchmarks match the filter.");
+        std::process::exit(1);
+    }
+
+    let owner = concordium_contracts_common::AccountAddress([0u8; 32]);
+    let receive_ctx: ReceiveContext<&[u8]> = ReceiveContext {
+        common:     v0::ReceiveContext {
+            metadata: ChainMetadata {
+                slot_time: Timestamp::from_timestamp_millis(0),
+            },
+            invoker: owner,
+            self_address: ContractAddress {
+                index:    0,
+                subindex: 0,
+            },
+            self_balance: Amount::from_ccd(1000),
+            sender: Address::Account(owner),
+            owner,
+            sender_policies: &[],
+        },
+        entrypoint: OwnedEntrypointName::new_unchecked("entrypoint".into()),
+    };
+
+    let mut measurements = Vec::with_capacity(benchmarks.len());
+    for (i, benchmark) in benchmarks.iter().enumerate() {
+        eprint!("\r[{}/{}] {:<60}", i + 1, benchmarks.len(), benchmark.name);
+        measurements.push(Measurement {
+            name:          benchmark.name.clone(),
+            operations:    benchmark.operations.clone(),
+            ns_per_energy: measure(benchmark, &receive_ctx, options.samples),
+        });
+    }
+    eprintln!();
+
+    let reference = match options.reference {
+        Some(ns_per_nrg) => ns_per_nrg / 1000.0,
+        None => {
+            let mut ratios: Vec<f64> = measurements.iter().map(|m| m.ns_per_energy).collect();
+            median(&mut ratios)
+        }
+    };
+    let relative = |m: &Measurement| m.ns_per_energy / reference;
+    // Order by divergence from the reference, regardless of the direction.
+    measurements.sort_by(|a, b| relative(b).ln().abs().total_cmp(&relative(a).ln().abs()));
+
+    if options.reference.is_some() {
+        println!("Reference: {:.1} ns/NRG (given).", reference * 1000.0);
+    } else {
+        println!(
+            "Reference: {:.1} ns/NRG (median of {} benchmarks).",
+            reference * 1000.0,
+            measurements.len()
+        );
+    }
+    println!("{:>9} {:>10}  {:<16} {:<44} operations", "relative", "ns/NRG", "", "benchmark");
+    for m in measurements.iter() {
+        let rel = relative(m);
+        let verdict = if rel >= options.threshold {
+            "UNDERCHARGED"
+        } else if rel <= 1.0 / options.threshold {
+            "overcharged"
+        } else {
+            ""
+        };
+        let operations: Vec<String> = m.operations.iter().map(|op| op.to_string()).collect();
+        println!(
+            "{:>9.2} {:>10.1}  {:<16} {:<44} {}",
+            rel,
+            m.ns_per_energy * 1000.0,
+            verdict,
+            m.name,
+            operations.join(", ")
+        );
+    }
+    if !unmeasured.is_empty() {
+        println!("Not measured:");
+        for (operation, reason) in unmeasured.iter() {
+            println!("  {}: {}", operation, reason);
+        }
+    }
+
+    if let Some(path) = &options.json {
+        let report = serde_json::json!({
+            "referenceNsPerNRG": reference * 1000.0,
+            "threshold": options.threshold,
+            "benchmarks": measurements.iter().map(|m| serde_json::json!({
+                "name": m.name,
+                "operations": m.operations.iter().map(|op| op.to_string()).collect::<Vec<_>>(),
+                "nsPerNRG": m.ns_per_energy * 1000.0,
+                "relative": relative(m),
+            })).collect::<Vec<_>>(),
+            "notMeasured": unmeasured.iter().map(|(operation, reason)| serde_json::json!({
+                "operation": operation.to_string(),
+                "reason": reason,
+            })).collect::<Vec<_>>(),
+        });
+        std::fs::write(path, serde_json::to_string_pretty(&report).expect("Report is valid JSON."))
+            .expect("Could not write the report.");
+    }
+}
@@ -0,0 +1,163 @@
+(module
+
+  ;; Functions used by the calibration benchmark to measure the cost of host functions that are not
+  ;; covered by host-functions.wat.
+  ;;
+  ;; Each exported function runs a loop until it runs out of energy. The body of the loop repeats
+  ;; the measured call 8 times, to amortize the cost of the loop itself.
+  ;;
+  ;; A general precondition is that at least one page of linear memory is allocated.
+  ;; Additional preconditions are listed above the relevant functions.
+
+  (import "concordium" "get_parameter_section" (func $get_parameter_section (param $index i32) (param $write_location i32) (param $length i32) (param $offset i32) (result i32)))
+  (import "concordium" "get_policy_section" (func $get_policy_section (param $write_location i32) (param $length i32) (param $offset i32) (result i32)))
+  (import "concordium" "log_event" (func $log_event (param $start i32) (param $length i32) (result i32)))
+  (import "concordium" "get_slot_time" (func $get_slot_time (result i64)))
+  (import "concordium" "get_receive_invoker" (func $get_receive_invoker (param $start i32)))
+  (import "concordium" "get_receive_self_address" (func $get_receive_self_address (param $start i32)))
+  (import "concordium" "get_receive_self_balance" (func $get_receive_self_balance (result i64)))
+  (import "concordium" "get_receive_sender" (func $get_receive_sender (param $start i32)))
+  (import "concordium" "get_receive_owner" (func $get_receive_owner (param $start i32)))
+  (import "concordium" "get_receive_entrypoint_size" (func $get_ep_size (result i32)))
+  (import "concordium" "get_receive_entrypoint" (func $get_ep (param $start i32)))
+  (import "concordium" "state_create_entry" (func $state_create_entry (param $key_start i32) (param $key_length i32) (result i64)))
+  (import "concordium" "state_entry_resize" (func $state_entry_resize (param $entry i64) (param $new_size i32) (result i32)))
+
+  (memory 1)
+
+  ;; Precondition. The first parameter has at least as many bytes as given by the argument.
+  (func (export "calibrate.get_parameter_section") (param $n i64) (result i32)
+    (local $length i32)
+    (local.set $length (i32.wrap_i64 (local.get $n)))
+    (loop $loop
+      (drop (call $get_parameter_section (i32.const 0) (i32.const 0) (local.get $length) (i32.const 0)))
+      (drop (call $get_parameter_section (i32.const 0) (i32.const 0) (local.get $length) (i32.const 0)))
+      (drop (call $get_parameter_section (i32.const 0) (i32.const 0) (local.get $length) (i32.const 0)))
+      (drop (call $get_parameter_section (i32.const 0) (i32.const 0) (local.get $length) (i32.const 0)))
+      (drop (call $get_parameter_section (i32.const 0) (i32.const 0) (local.get $length) (i32.const 0)))
+      (drop (call $get_parameter_section (i32.const 0) (i32.const 0) (local.get $length) (i32.const 0)))
+      (drop (call $get_parameter_section (i32.const 0) (i32.const 0) (local.get $length) (i32.const 0)))
+      (drop (call $get_parameter_section (i32.const 0) (i32.const 0) (local.get $length) (i32.const 0)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.get_policy_section") (param i64) (result i32)
+    (loop $loop
+      (drop (call $get_policy_section (i32.const 0) (i32.const 100) (i32.const 0)))
+      (drop (call $get_policy_section (i32.const 0) (i32.const 100) (i32.const 0)))
+      (drop (call $get_policy_section (i32.const 0) (i32.const 100) (i32.const 0)))
+      (drop (call $get_policy_section (i32.const 0) (i32.const 100) (i32.const 0)))
+      (drop (call $get_policy_section (i32.const 0) (i32.const 100) (i32.const 0)))
+      (drop (call $get_policy_section (i32.const 0) (i32.const 100) (i32.const 0)))
+      (drop (call $get_policy_section (i32.const 0) (i32.const 100) (i32.const 0)))
+      (drop (call $get_policy_section (i32.const 0) (i32.const 100) (i32.const 0)))
+      (br $loop))
+    (i32.const 0))
+
+  ;; Log events of the length given by the argument.
+  (func (export "calibrate.log_event") (param $n i64) (result i32)
+    (local $length i32)
+    (local.set $length (i32.wrap_i64 (local.get $n)))
+    (loop $loop
+      (drop (call $log_event (i32.const 0) (local.get $length)))
+      (drop (call $log_event (i32.const 0) (local.get $length)))
+      (drop (call $log_event (i32.const 0) (local.get $length)))
+      (drop (call $log_event (i32.const 0) (local.get $length)))
+      (drop (call $log_event (i32.const 0) (local.get $length)))
+      (drop (call $log_event (i32.const 0) (local.get $length)))
+      (drop (call $log_event (i32.const 0) (local.get $length)))
+      (drop (call $log_event (i32.const 0) (local.get $length)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.get_slot_time") (param i64) (result i32)
+    (loop $loop
+      (drop (call $get_slot_time)) (drop (call $get_slot_time))
+      (drop (call $get_slot_time)) (drop (call $get_slot_time))
+      (drop (call $get_slot_time)) (drop (call $get_slot_time))
+      (drop (call $get_slot_time)) (drop (call $get_slot_time))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.get_receive_invoker") (param i64) (result i32)
+    (loop $loop
+      (call $get_receive_invoker (i32.const 0)) (call $get_receive_invoker (i32.const 0))
+      (call $get_receive_invoker (i32.const 0)) (call $get_receive_invoker (i32.const 0))
+      (call $get_receive_invoker (i32.const 0)) (call $get_receive_invoker (i32.const 0))
+      (call $get_receive_invoker (i32.const 0)) (call $get_receive_invoker (i32.const 0))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.get_receive_self_address") (param i64) (result i32)
+    (loop $loop
+      (call $get_receive_self_address (i32.const 0)) (call $get_receive_self_address (i32.const 0))
+      (call $get_receive_self_address (i32.const 0)) (call $get_receive_self_address (i32.const 0))
+      (call $get_receive_self_address (i32.const 0)) (call $get_receive_self_address (i32.const 0))
+      (call $get_receive_self_address (i32.const 0)) (call $get_receive_self_address (i32.const 0))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.get_receive_self_balance") (param i64) (result i32)
+    (loop $loop
+      (drop (call $get_receive_self_balance)) (drop (call $get_receive_self_balance))
+      (drop (call $get_receive_self_balance)) (drop (call $get_receive_self_balance))
+      (drop (call $get_receive_self_balance)) (drop (call $get_receive_self_balance))
+      (drop (call $get_receive_self_balance)) (drop (call $get_receive_self_balance))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.get_receive_sender") (param i64) (result i32)
+    (loop $loop
+      (call $get_receive_sender (i32.const 0)) (call $get_receive_sender (i32.const 0))
+      (call $get_receive_sender (i32.const 0)) (call $get_receive_sender (i32.const 0))
+      (call $get_receive_sender (i32.const 0)) (call $get_receive_sender (i32.const 0))
+      (call $get_receive_sender (i32.const 0)) (call $get_receive_sender (i32.const 0))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.get_receive_owner") (param i64) (result i32)
+    (loop $loop
+      (call $get_receive_owner (i32.const 0)) (call $get_receive_owner (i32.const 0))
+      (call $get_receive_owner (i32.const 0)) (call $get_receive_owner (i32.const 0))
+      (call $get_receive_owner (i32.const 0)) (call $get_receive_owner (i32.const 0))
+      (call $get_receive_owner (i32.const 0)) (call $get_receive_owner (i32.const 0))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.get_receive_entrypoint_size") (param i64) (result i32)
+    (loop $loop
+      (drop (call $get_ep_size)) (drop (call $get_ep_size))
+      (drop (call $get_ep_size)) (drop (call $get_ep_size))
+      (drop (call $get_ep_size)) (drop (call $get_ep_size))
+      (drop (call $get_ep_size)) (drop (call $get_ep_size))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.get_receive_entrypoint") (param i64) (result i32)
+    (loop $loop
+      (call $get_ep (i32.const 0)) (call $get_ep (i32.const 0))
+      (call $get_ep (i32.const 0)) (call $get_ep (i32.const 0))
+      (call $get_ep (i32.const 0)) (call $get_ep (i32.const 0))
+      (call $get_ep (i32.const 0)) (call $get_ep (i32.const 0))
+      (br $loop))
+    (i32.const 0))
+
+  ;; Create the entry with the empty key and alternate between resizing it to the size given by the
+  ;; argument and to the empty entry, so that every call changes the size of the entry.
+  (func (export "calibrate.state_entry_resize") (param $n i64) (result i32)
+    (local $entry i64)
+    (local $size i32)
+    (local.set $size (i32.wrap_i64 (local.get $n)))
+    (local.set $entry (call $state_create_entry (i32.const 0) (i32.const 0)))
+    (loop $loop
+      (drop (call $state_entry_resize (local.get $entry) (local.get $size)))
+      (drop (call $state_entry_resize (local.get $entry) (i32.const 0)))
+      (drop (call $state_entry_resize (local.get $entry) (local.get $size)))
+      (drop (call $state_entry_resize (local.get $entry) (i32.const 0)))
+      (drop (call $state_entry_resize (local.get $entry) (local.get $size)))
+      (drop (call $state_entry_resize (local.get $entry) (i32.const 0)))
+      (drop (call $state_entry_resize (local.get $entry) (local.get $size)))
+      (drop (call $state_entry_resize (local.get $entry) (i32.const 0)))
+      (br $loop))
+    (i32.const 0))
+)
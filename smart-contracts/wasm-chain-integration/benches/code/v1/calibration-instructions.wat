@@ -0,0 +1,1174 @@
+(module
+
+  ;; Functions used by the calibration benchmark to measure the cost of single instructions.
+  ;;
+  ;; Each exported function runs a loop until it runs out of energy. The body of the loop repeats
+  ;; the measured instruction 8 times, to amortize the cost of the loop itself. The values the
+  ;; instructions operate on are non-zero, so that no instruction traps.
+
+  (memory 1)
+  (global $g (mut i32) (i32.const 0))
+  (type $nop_type (func))
+  (table 1 funcref)
+  (elem (i32.const 0) $nop)
+
+  ;; The function called by the call benchmarks. It does nothing.
+  (func $nop)
+
+  (func (export "calibrate.i32.eqz") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (drop (i32.eqz (local.get $x))) (drop (i32.eqz (local.get $x)))
+      (drop (i32.eqz (local.get $x))) (drop (i32.eqz (local.get $x)))
+      (drop (i32.eqz (local.get $x))) (drop (i32.eqz (local.get $x)))
+      (drop (i32.eqz (local.get $x))) (drop (i32.eqz (local.get $x)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.clz") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (drop (i32.clz (local.get $x))) (drop (i32.clz (local.get $x)))
+      (drop (i32.clz (local.get $x))) (drop (i32.clz (local.get $x)))
+      (drop (i32.clz (local.get $x))) (drop (i32.clz (local.get $x)))
+      (drop (i32.clz (local.get $x))) (drop (i32.clz (local.get $x)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.ctz") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (drop (i32.ctz (local.get $x))) (drop (i32.ctz (local.get $x)))
+      (drop (i32.ctz (local.get $x))) (drop (i32.ctz (local.get $x)))
+      (drop (i32.ctz (local.get $x))) (drop (i32.ctz (local.get $x)))
+      (drop (i32.ctz (local.get $x))) (drop (i32.ctz (local.get $x)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.popcnt") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (drop (i32.popcnt (local.get $x))) (drop (i32.popcnt (local.get $x)))
+      (drop (i32.popcnt (local.get $x))) (drop (i32.popcnt (local.get $x)))
+      (drop (i32.popcnt (local.get $x))) (drop (i32.popcnt (local.get $x)))
+      (drop (i32.popcnt (local.get $x))) (drop (i32.popcnt (local.get $x)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.extend_i32_s") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (drop (i64.extend_i32_s (local.get $x))) (drop (i64.extend_i32_s (local.get $x)))
+      (drop (i64.extend_i32_s (local.get $x))) (drop (i64.extend_i32_s (local.get $x)))
+      (drop (i64.extend_i32_s (local.get $x))) (drop (i64.extend_i32_s (local.get $x)))
+      (drop (i64.extend_i32_s (local.get $x))) (drop (i64.extend_i32_s (local.get $x)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.extend_i32_u") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (drop (i64.extend_i32_u (local.get $x))) (drop (i64.extend_i32_u (local.get $x)))
+      (drop (i64.extend_i32_u (local.get $x))) (drop (i64.extend_i32_u (local.get $x)))
+      (drop (i64.extend_i32_u (local.get $x))) (drop (i64.extend_i32_u (local.get $x)))
+      (drop (i64.extend_i32_u (local.get $x))) (drop (i64.extend_i32_u (local.get $x)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.extend8_s") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (drop (i32.extend8_s (local.get $x))) (drop (i32.extend8_s (local.get $x)))
+      (drop (i32.extend8_s (local.get $x))) (drop (i32.extend8_s (local.get $x)))
+      (drop (i32.extend8_s (local.get $x))) (drop (i32.extend8_s (local.get $x)))
+      (drop (i32.extend8_s (local.get $x))) (drop (i32.extend8_s (local.get $x)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.extend16_s") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (drop (i32.extend16_s (local.get $x))) (drop (i32.extend16_s (local.get $x)))
+      (drop (i32.extend16_s (local.get $x))) (drop (i32.extend16_s (local.get $x)))
+      (drop (i32.extend16_s (local.get $x))) (drop (i32.extend16_s (local.get $x)))
+      (drop (i32.extend16_s (local.get $x))) (drop (i32.extend16_s (local.get $x)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.eqz") (param i64) (result i32)
+    (local $a i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (loop $loop
+      (drop (i64.eqz (local.get $a))) (drop (i64.eqz (local.get $a)))
+      (drop (i64.eqz (local.get $a))) (drop (i64.eqz (local.get $a)))
+      (drop (i64.eqz (local.get $a))) (drop (i64.eqz (local.get $a)))
+      (drop (i64.eqz (local.get $a))) (drop (i64.eqz (local.get $a)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.clz") (param i64) (result i32)
+    (local $a i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (loop $loop
+      (drop (i64.clz (local.get $a))) (drop (i64.clz (local.get $a)))
+      (drop (i64.clz (local.get $a))) (drop (i64.clz (local.get $a)))
+      (drop (i64.clz (local.get $a))) (drop (i64.clz (local.get $a)))
+      (drop (i64.clz (local.get $a))) (drop (i64.clz (local.get $a)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.ctz") (param i64) (result i32)
+    (local $a i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (loop $loop
+      (drop (i64.ctz (local.get $a))) (drop (i64.ctz (local.get $a)))
+      (drop (i64.ctz (local.get $a))) (drop (i64.ctz (local.get $a)))
+      (drop (i64.ctz (local.get $a))) (drop (i64.ctz (local.get $a)))
+      (drop (i64.ctz (local.get $a))) (drop (i64.ctz (local.get $a)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.popcnt") (param i64) (result i32)
+    (local $a i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (loop $loop
+      (drop (i64.popcnt (local.get $a))) (drop (i64.popcnt (local.get $a)))
+      (drop (i64.popcnt (local.get $a))) (drop (i64.popcnt (local.get $a)))
+      (drop (i64.popcnt (local.get $a))) (drop (i64.popcnt (local.get $a)))
+      (drop (i64.popcnt (local.get $a))) (drop (i64.popcnt (local.get $a)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.wrap_i64") (param i64) (result i32)
+    (local $a i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (loop $loop
+      (drop (i32.wrap_i64 (local.get $a))) (drop (i32.wrap_i64 (local.get $a)))
+      (drop (i32.wrap_i64 (local.get $a))) (drop (i32.wrap_i64 (local.get $a)))
+      (drop (i32.wrap_i64 (local.get $a))) (drop (i32.wrap_i64 (local.get $a)))
+      (drop (i32.wrap_i64 (local.get $a))) (drop (i32.wrap_i64 (local.get $a)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.extend8_s") (param i64) (result i32)
+    (local $a i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (loop $loop
+      (drop (i64.extend8_s (local.get $a))) (drop (i64.extend8_s (local.get $a)))
+      (drop (i64.extend8_s (local.get $a))) (drop (i64.extend8_s (local.get $a)))
+      (drop (i64.extend8_s (local.get $a))) (drop (i64.extend8_s (local.get $a)))
+      (drop (i64.extend8_s (local.get $a))) (drop (i64.extend8_s (local.get $a)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.extend16_s") (param i64) (result i32)
+    (local $a i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (loop $loop
+      (drop (i64.extend16_s (local.get $a))) (drop (i64.extend16_s (local.get $a)))
+      (drop (i64.extend16_s (local.get $a))) (drop (i64.extend16_s (local.get $a)))
+      (drop (i64.extend16_s (local.get $a))) (drop (i64.extend16_s (local.get $a)))
+      (drop (i64.extend16_s (local.get $a))) (drop (i64.extend16_s (local.get $a)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.extend32_s") (param i64) (result i32)
+    (local $a i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (loop $loop
+      (drop (i64.extend32_s (local.get $a))) (drop (i64.extend32_s (local.get $a)))
+      (drop (i64.extend32_s (local.get $a))) (drop (i64.extend32_s (local.get $a)))
+      (drop (i64.extend32_s (local.get $a))) (drop (i64.extend32_s (local.get $a)))
+      (drop (i64.extend32_s (local.get $a))) (drop (i64.extend32_s (local.get $a)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.eq") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.eq (local.get $x) (local.get $y))) (drop (i32.eq (local.get $x) (local.get $y)))
+      (drop (i32.eq (local.get $x) (local.get $y))) (drop (i32.eq (local.get $x) (local.get $y)))
+      (drop (i32.eq (local.get $x) (local.get $y))) (drop (i32.eq (local.get $x) (local.get $y)))
+      (drop (i32.eq (local.get $x) (local.get $y))) (drop (i32.eq (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.ne") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.ne (local.get $x) (local.get $y))) (drop (i32.ne (local.get $x) (local.get $y)))
+      (drop (i32.ne (local.get $x) (local.get $y))) (drop (i32.ne (local.get $x) (local.get $y)))
+      (drop (i32.ne (local.get $x) (local.get $y))) (drop (i32.ne (local.get $x) (local.get $y)))
+      (drop (i32.ne (local.get $x) (local.get $y))) (drop (i32.ne (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.lt_s") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.lt_s (local.get $x) (local.get $y))) (drop (i32.lt_s (local.get $x) (local.get $y)))
+      (drop (i32.lt_s (local.get $x) (local.get $y))) (drop (i32.lt_s (local.get $x) (local.get $y)))
+      (drop (i32.lt_s (local.get $x) (local.get $y))) (drop (i32.lt_s (local.get $x) (local.get $y)))
+      (drop (i32.lt_s (local.get $x) (local.get $y))) (drop (i32.lt_s (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.lt_u") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.lt_u (local.get $x) (local.get $y))) (drop (i32.lt_u (local.get $x) (local.get $y)))
+      (drop (i32.lt_u (local.get $x) (local.get $y))) (drop (i32.lt_u (local.get $x) (local.get $y)))
+      (drop (i32.lt_u (local.get $x) (local.get $y))) (drop (i32.lt_u (local.get $x) (local.get $y)))
+      (drop (i32.lt_u (local.get $x) (local.get $y))) (drop (i32.lt_u (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.gt_s") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.gt_s (local.get $x) (local.get $y))) (drop (i32.gt_s (local.get $x) (local.get $y)))
+      (drop (i32.gt_s (local.get $x) (local.get $y))) (drop (i32.gt_s (local.get $x) (local.get $y)))
+      (drop (i32.gt_s (local.get $x) (local.get $y))) (drop (i32.gt_s (local.get $x) (local.get $y)))
+      (drop (i32.gt_s (local.get $x) (local.get $y))) (drop (i32.gt_s (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.gt_u") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.gt_u (local.get $x) (local.get $y))) (drop (i32.gt_u (local.get $x) (local.get $y)))
+      (drop (i32.gt_u (local.get $x) (local.get $y))) (drop (i32.gt_u (local.get $x) (local.get $y)))
+      (drop (i32.gt_u (local.get $x) (local.get $y))) (drop (i32.gt_u (local.get $x) (local.get $y)))
+      (drop (i32.gt_u (local.get $x) (local.get $y))) (drop (i32.gt_u (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.le_s") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.le_s (local.get $x) (local.get $y))) (drop (i32.le_s (local.get $x) (local.get $y)))
+      (drop (i32.le_s (local.get $x) (local.get $y))) (drop (i32.le_s (local.get $x) (local.get $y)))
+      (drop (i32.le_s (local.get $x) (local.get $y))) (drop (i32.le_s (local.get $x) (local.get $y)))
+      (drop (i32.le_s (local.get $x) (local.get $y))) (drop (i32.le_s (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.le_u") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.le_u (local.get $x) (local.get $y))) (drop (i32.le_u (local.get $x) (local.get $y)))
+      (drop (i32.le_u (local.get $x) (local.get $y))) (drop (i32.le_u (local.get $x) (local.get $y)))
+      (drop (i32.le_u (local.get $x) (local.get $y))) (drop (i32.le_u (local.get $x) (local.get $y)))
+      (drop (i32.le_u (local.get $x) (local.get $y))) (drop (i32.le_u (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.ge_s") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.ge_s (local.get $x) (local.get $y))) (drop (i32.ge_s (local.get $x) (local.get $y)))
+      (drop (i32.ge_s (local.get $x) (local.get $y))) (drop (i32.ge_s (local.get $x) (local.get $y)))
+      (drop (i32.ge_s (local.get $x) (local.get $y))) (drop (i32.ge_s (local.get $x) (local.get $y)))
+      (drop (i32.ge_s (local.get $x) (local.get $y))) (drop (i32.ge_s (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.ge_u") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.ge_u (local.get $x) (local.get $y))) (drop (i32.ge_u (local.get $x) (local.get $y)))
+      (drop (i32.ge_u (local.get $x) (local.get $y))) (drop (i32.ge_u (local.get $x) (local.get $y)))
+      (drop (i32.ge_u (local.get $x) (local.get $y))) (drop (i32.ge_u (local.get $x) (local.get $y)))
+      (drop (i32.ge_u (local.get $x) (local.get $y))) (drop (i32.ge_u (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.add") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.add (local.get $x) (local.get $y))) (drop (i32.add (local.get $x) (local.get $y)))
+      (drop (i32.add (local.get $x) (local.get $y))) (drop (i32.add (local.get $x) (local.get $y)))
+      (drop (i32.add (local.get $x) (local.get $y))) (drop (i32.add (local.get $x) (local.get $y)))
+      (drop (i32.add (local.get $x) (local.get $y))) (drop (i32.add (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.sub") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.sub (local.get $x) (local.get $y))) (drop (i32.sub (local.get $x) (local.get $y)))
+      (drop (i32.sub (local.get $x) (local.get $y))) (drop (i32.sub (local.get $x) (local.get $y)))
+      (drop (i32.sub (local.get $x) (local.get $y))) (drop (i32.sub (local.get $x) (local.get $y)))
+      (drop (i32.sub (local.get $x) (local.get $y))) (drop (i32.sub (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.mul") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.mul (local.get $x) (local.get $y))) (drop (i32.mul (local.get $x) (local.get $y)))
+      (drop (i32.mul (local.get $x) (local.get $y))) (drop (i32.mul (local.get $x) (local.get $y)))
+      (drop (i32.mul (local.get $x) (local.get $y))) (drop (i32.mul (local.get $x) (local.get $y)))
+      (drop (i32.mul (local.get $x) (local.get $y))) (drop (i32.mul (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.div_s") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.div_s (local.get $x) (local.get $y))) (drop (i32.div_s (local.get $x) (local.get $y)))
+      (drop (i32.div_s (local.get $x) (local.get $y))) (drop (i32.div_s (local.get $x) (local.get $y)))
+      (drop (i32.div_s (local.get $x) (local.get $y))) (drop (i32.div_s (local.get $x) (local.get $y)))
+      (drop (i32.div_s (local.get $x) (local.get $y))) (drop (i32.div_s (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.div_u") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.div_u (local.get $x) (local.get $y))) (drop (i32.div_u (local.get $x) (local.get $y)))
+      (drop (i32.div_u (local.get $x) (local.get $y))) (drop (i32.div_u (local.get $x) (local.get $y)))
+      (drop (i32.div_u (local.get $x) (local.get $y))) (drop (i32.div_u (local.get $x) (local.get $y)))
+      (drop (i32.div_u (local.get $x) (local.get $y))) (drop (i32.div_u (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.rem_s") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.rem_s (local.get $x) (local.get $y))) (drop (i32.rem_s (local.get $x) (local.get $y)))
+      (drop (i32.rem_s (local.get $x) (local.get $y))) (drop (i32.rem_s (local.get $x) (local.get $y)))
+      (drop (i32.rem_s (local.get $x) (local.get $y))) (drop (i32.rem_s (local.get $x) (local.get $y)))
+      (drop (i32.rem_s (local.get $x) (local.get $y))) (drop (i32.rem_s (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.rem_u") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.rem_u (local.get $x) (local.get $y))) (drop (i32.rem_u (local.get $x) (local.get $y)))
+      (drop (i32.rem_u (local.get $x) (local.get $y))) (drop (i32.rem_u (local.get $x) (local.get $y)))
+      (drop (i32.rem_u (local.get $x) (local.get $y))) (drop (i32.rem_u (local.get $x) (local.get $y)))
+      (drop (i32.rem_u (local.get $x) (local.get $y))) (drop (i32.rem_u (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.and") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.and (local.get $x) (local.get $y))) (drop (i32.and (local.get $x) (local.get $y)))
+      (drop (i32.and (local.get $x) (local.get $y))) (drop (i32.and (local.get $x) (local.get $y)))
+      (drop (i32.and (local.get $x) (local.get $y))) (drop (i32.and (local.get $x) (local.get $y)))
+      (drop (i32.and (local.get $x) (local.get $y))) (drop (i32.and (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.or") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.or (local.get $x) (local.get $y))) (drop (i32.or (local.get $x) (local.get $y)))
+      (drop (i32.or (local.get $x) (local.get $y))) (drop (i32.or (local.get $x) (local.get $y)))
+      (drop (i32.or (local.get $x) (local.get $y))) (drop (i32.or (local.get $x) (local.get $y)))
+      (drop (i32.or (local.get $x) (local.get $y))) (drop (i32.or (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.xor") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.xor (local.get $x) (local.get $y))) (drop (i32.xor (local.get $x) (local.get $y)))
+      (drop (i32.xor (local.get $x) (local.get $y))) (drop (i32.xor (local.get $x) (local.get $y)))
+      (drop (i32.xor (local.get $x) (local.get $y))) (drop (i32.xor (local.get $x) (local.get $y)))
+      (drop (i32.xor (local.get $x) (local.get $y))) (drop (i32.xor (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.shl") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.shl (local.get $x) (local.get $y))) (drop (i32.shl (local.get $x) (local.get $y)))
+      (drop (i32.shl (local.get $x) (local.get $y))) (drop (i32.shl (local.get $x) (local.get $y)))
+      (drop (i32.shl (local.get $x) (local.get $y))) (drop (i32.shl (local.get $x) (local.get $y)))
+      (drop (i32.shl (local.get $x) (local.get $y))) (drop (i32.shl (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.shr_s") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.shr_s (local.get $x) (local.get $y))) (drop (i32.shr_s (local.get $x) (local.get $y)))
+      (drop (i32.shr_s (local.get $x) (local.get $y))) (drop (i32.shr_s (local.get $x) (local.get $y)))
+      (drop (i32.shr_s (local.get $x) (local.get $y))) (drop (i32.shr_s (local.get $x) (local.get $y)))
+      (drop (i32.shr_s (local.get $x) (local.get $y))) (drop (i32.shr_s (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.shr_u") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.shr_u (local.get $x) (local.get $y))) (drop (i32.shr_u (local.get $x) (local.get $y)))
+      (drop (i32.shr_u (local.get $x) (local.get $y))) (drop (i32.shr_u (local.get $x) (local.get $y)))
+      (drop (i32.shr_u (local.get $x) (local.get $y))) (drop (i32.shr_u (local.get $x) (local.get $y)))
+      (drop (i32.shr_u (local.get $x) (local.get $y))) (drop (i32.shr_u (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.rotl") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.rotl (local.get $x) (local.get $y))) (drop (i32.rotl (local.get $x) (local.get $y)))
+      (drop (i32.rotl (local.get $x) (local.get $y))) (drop (i32.rotl (local.get $x) (local.get $y)))
+      (drop (i32.rotl (local.get $x) (local.get $y))) (drop (i32.rotl (local.get $x) (local.get $y)))
+      (drop (i32.rotl (local.get $x) (local.get $y))) (drop (i32.rotl (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.rotr") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (i32.rotr (local.get $x) (local.get $y))) (drop (i32.rotr (local.get $x) (local.get $y)))
+      (drop (i32.rotr (local.get $x) (local.get $y))) (drop (i32.rotr (local.get $x) (local.get $y)))
+      (drop (i32.rotr (local.get $x) (local.get $y))) (drop (i32.rotr (local.get $x) (local.get $y)))
+      (drop (i32.rotr (local.get $x) (local.get $y))) (drop (i32.rotr (local.get $x) (local.get $y)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.eq") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.eq (local.get $a) (local.get $b))) (drop (i64.eq (local.get $a) (local.get $b)))
+      (drop (i64.eq (local.get $a) (local.get $b))) (drop (i64.eq (local.get $a) (local.get $b)))
+      (drop (i64.eq (local.get $a) (local.get $b))) (drop (i64.eq (local.get $a) (local.get $b)))
+      (drop (i64.eq (local.get $a) (local.get $b))) (drop (i64.eq (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.ne") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.ne (local.get $a) (local.get $b))) (drop (i64.ne (local.get $a) (local.get $b)))
+      (drop (i64.ne (local.get $a) (local.get $b))) (drop (i64.ne (local.get $a) (local.get $b)))
+      (drop (i64.ne (local.get $a) (local.get $b))) (drop (i64.ne (local.get $a) (local.get $b)))
+      (drop (i64.ne (local.get $a) (local.get $b))) (drop (i64.ne (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.lt_s") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.lt_s (local.get $a) (local.get $b))) (drop (i64.lt_s (local.get $a) (local.get $b)))
+      (drop (i64.lt_s (local.get $a) (local.get $b))) (drop (i64.lt_s (local.get $a) (local.get $b)))
+      (drop (i64.lt_s (local.get $a) (local.get $b))) (drop (i64.lt_s (local.get $a) (local.get $b)))
+      (drop (i64.lt_s (local.get $a) (local.get $b))) (drop (i64.lt_s (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.lt_u") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.lt_u (local.get $a) (local.get $b))) (drop (i64.lt_u (local.get $a) (local.get $b)))
+      (drop (i64.lt_u (local.get $a) (local.get $b))) (drop (i64.lt_u (local.get $a) (local.get $b)))
+      (drop (i64.lt_u (local.get $a) (local.get $b))) (drop (i64.lt_u (local.get $a) (local.get $b)))
+      (drop (i64.lt_u (local.get $a) (local.get $b))) (drop (i64.lt_u (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.gt_s") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.gt_s (local.get $a) (local.get $b))) (drop (i64.gt_s (local.get $a) (local.get $b)))
+      (drop (i64.gt_s (local.get $a) (local.get $b))) (drop (i64.gt_s (local.get $a) (local.get $b)))
+      (drop (i64.gt_s (local.get $a) (local.get $b))) (drop (i64.gt_s (local.get $a) (local.get $b)))
+      (drop (i64.gt_s (local.get $a) (local.get $b))) (drop (i64.gt_s (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.gt_u") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.gt_u (local.get $a) (local.get $b))) (drop (i64.gt_u (local.get $a) (local.get $b)))
+      (drop (i64.gt_u (local.get $a) (local.get $b))) (drop (i64.gt_u (local.get $a) (local.get $b)))
+      (drop (i64.gt_u (local.get $a) (local.get $b))) (drop (i64.gt_u (local.get $a) (local.get $b)))
+      (drop (i64.gt_u (local.get $a) (local.get $b))) (drop (i64.gt_u (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.le_s") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.le_s (local.get $a) (local.get $b))) (drop (i64.le_s (local.get $a) (local.get $b)))
+      (drop (i64.le_s (local.get $a) (local.get $b))) (drop (i64.le_s (local.get $a) (local.get $b)))
+      (drop (i64.le_s (local.get $a) (local.get $b))) (drop (i64.le_s (local.get $a) (local.get $b)))
+      (drop (i64.le_s (local.get $a) (local.get $b))) (drop (i64.le_s (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.le_u") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.le_u (local.get $a) (local.get $b))) (drop (i64.le_u (local.get $a) (local.get $b)))
+      (drop (i64.le_u (local.get $a) (local.get $b))) (drop (i64.le_u (local.get $a) (local.get $b)))
+      (drop (i64.le_u (local.get $a) (local.get $b))) (drop (i64.le_u (local.get $a) (local.get $b)))
+      (drop (i64.le_u (local.get $a) (local.get $b))) (drop (i64.le_u (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.ge_s") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.ge_s (local.get $a) (local.get $b))) (drop (i64.ge_s (local.get $a) (local.get $b)))
+      (drop (i64.ge_s (local.get $a) (local.get $b))) (drop (i64.ge_s (local.get $a) (local.get $b)))
+      (drop (i64.ge_s (local.get $a) (local.get $b))) (drop (i64.ge_s (local.get $a) (local.get $b)))
+      (drop (i64.ge_s (local.get $a) (local.get $b))) (drop (i64.ge_s (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.ge_u") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.ge_u (local.get $a) (local.get $b))) (drop (i64.ge_u (local.get $a) (local.get $b)))
+      (drop (i64.ge_u (local.get $a) (local.get $b))) (drop (i64.ge_u (local.get $a) (local.get $b)))
+      (drop (i64.ge_u (local.get $a) (local.get $b))) (drop (i64.ge_u (local.get $a) (local.get $b)))
+      (drop (i64.ge_u (local.get $a) (local.get $b))) (drop (i64.ge_u (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.add") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.add (local.get $a) (local.get $b))) (drop (i64.add (local.get $a) (local.get $b)))
+      (drop (i64.add (local.get $a) (local.get $b))) (drop (i64.add (local.get $a) (local.get $b)))
+      (drop (i64.add (local.get $a) (local.get $b))) (drop (i64.add (local.get $a) (local.get $b)))
+      (drop (i64.add (local.get $a) (local.get $b))) (drop (i64.add (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.sub") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.sub (local.get $a) (local.get $b))) (drop (i64.sub (local.get $a) (local.get $b)))
+      (drop (i64.sub (local.get $a) (local.get $b))) (drop (i64.sub (local.get $a) (local.get $b)))
+      (drop (i64.sub (local.get $a) (local.get $b))) (drop (i64.sub (local.get $a) (local.get $b)))
+      (drop (i64.sub (local.get $a) (local.get $b))) (drop (i64.sub (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.mul") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.mul (local.get $a) (local.get $b))) (drop (i64.mul (local.get $a) (local.get $b)))
+      (drop (i64.mul (local.get $a) (local.get $b))) (drop (i64.mul (local.get $a) (local.get $b)))
+      (drop (i64.mul (local.get $a) (local.get $b))) (drop (i64.mul (local.get $a) (local.get $b)))
+      (drop (i64.mul (local.get $a) (local.get $b))) (drop (i64.mul (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.div_s") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.div_s (local.get $a) (local.get $b))) (drop (i64.div_s (local.get $a) (local.get $b)))
+      (drop (i64.div_s (local.get $a) (local.get $b))) (drop (i64.div_s (local.get $a) (local.get $b)))
+      (drop (i64.div_s (local.get $a) (local.get $b))) (drop (i64.div_s (local.get $a) (local.get $b)))
+      (drop (i64.div_s (local.get $a) (local.get $b))) (drop (i64.div_s (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.div_u") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.div_u (local.get $a) (local.get $b))) (drop (i64.div_u (local.get $a) (local.get $b)))
+      (drop (i64.div_u (local.get $a) (local.get $b))) (drop (i64.div_u (local.get $a) (local.get $b)))
+      (drop (i64.div_u (local.get $a) (local.get $b))) (drop (i64.div_u (local.get $a) (local.get $b)))
+      (drop (i64.div_u (local.get $a) (local.get $b))) (drop (i64.div_u (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.rem_s") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.rem_s (local.get $a) (local.get $b))) (drop (i64.rem_s (local.get $a) (local.get $b)))
+      (drop (i64.rem_s (local.get $a) (local.get $b))) (drop (i64.rem_s (local.get $a) (local.get $b)))
+      (drop (i64.rem_s (local.get $a) (local.get $b))) (drop (i64.rem_s (local.get $a) (local.get $b)))
+      (drop (i64.rem_s (local.get $a) (local.get $b))) (drop (i64.rem_s (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.rem_u") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.rem_u (local.get $a) (local.get $b))) (drop (i64.rem_u (local.get $a) (local.get $b)))
+      (drop (i64.rem_u (local.get $a) (local.get $b))) (drop (i64.rem_u (local.get $a) (local.get $b)))
+      (drop (i64.rem_u (local.get $a) (local.get $b))) (drop (i64.rem_u (local.get $a) (local.get $b)))
+      (drop (i64.rem_u (local.get $a) (local.get $b))) (drop (i64.rem_u (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.and") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.and (local.get $a) (local.get $b))) (drop (i64.and (local.get $a) (local.get $b)))
+      (drop (i64.and (local.get $a) (local.get $b))) (drop (i64.and (local.get $a) (local.get $b)))
+      (drop (i64.and (local.get $a) (local.get $b))) (drop (i64.and (local.get $a) (local.get $b)))
+      (drop (i64.and (local.get $a) (local.get $b))) (drop (i64.and (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.or") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.or (local.get $a) (local.get $b))) (drop (i64.or (local.get $a) (local.get $b)))
+      (drop (i64.or (local.get $a) (local.get $b))) (drop (i64.or (local.get $a) (local.get $b)))
+      (drop (i64.or (local.get $a) (local.get $b))) (drop (i64.or (local.get $a) (local.get $b)))
+      (drop (i64.or (local.get $a) (local.get $b))) (drop (i64.or (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.xor") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.xor (local.get $a) (local.get $b))) (drop (i64.xor (local.get $a) (local.get $b)))
+      (drop (i64.xor (local.get $a) (local.get $b))) (drop (i64.xor (local.get $a) (local.get $b)))
+      (drop (i64.xor (local.get $a) (local.get $b))) (drop (i64.xor (local.get $a) (local.get $b)))
+      (drop (i64.xor (local.get $a) (local.get $b))) (drop (i64.xor (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.shl") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.shl (local.get $a) (local.get $b))) (drop (i64.shl (local.get $a) (local.get $b)))
+      (drop (i64.shl (local.get $a) (local.get $b))) (drop (i64.shl (local.get $a) (local.get $b)))
+      (drop (i64.shl (local.get $a) (local.get $b))) (drop (i64.shl (local.get $a) (local.get $b)))
+      (drop (i64.shl (local.get $a) (local.get $b))) (drop (i64.shl (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.shr_s") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.shr_s (local.get $a) (local.get $b))) (drop (i64.shr_s (local.get $a) (local.get $b)))
+      (drop (i64.shr_s (local.get $a) (local.get $b))) (drop (i64.shr_s (local.get $a) (local.get $b)))
+      (drop (i64.shr_s (local.get $a) (local.get $b))) (drop (i64.shr_s (local.get $a) (local.get $b)))
+      (drop (i64.shr_s (local.get $a) (local.get $b))) (drop (i64.shr_s (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.shr_u") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.shr_u (local.get $a) (local.get $b))) (drop (i64.shr_u (local.get $a) (local.get $b)))
+      (drop (i64.shr_u (local.get $a) (local.get $b))) (drop (i64.shr_u (local.get $a) (local.get $b)))
+      (drop (i64.shr_u (local.get $a) (local.get $b))) (drop (i64.shr_u (local.get $a) (local.get $b)))
+      (drop (i64.shr_u (local.get $a) (local.get $b))) (drop (i64.shr_u (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.rotl") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.rotl (local.get $a) (local.get $b))) (drop (i64.rotl (local.get $a) (local.get $b)))
+      (drop (i64.rotl (local.get $a) (local.get $b))) (drop (i64.rotl (local.get $a) (local.get $b)))
+      (drop (i64.rotl (local.get $a) (local.get $b))) (drop (i64.rotl (local.get $a) (local.get $b)))
+      (drop (i64.rotl (local.get $a) (local.get $b))) (drop (i64.rotl (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.rotr") (param i64) (result i32)
+    (local $a i64)
+    (local $b i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (local.set $b (i64.const 0x0fedcba987654321))
+    (loop $loop
+      (drop (i64.rotr (local.get $a) (local.get $b))) (drop (i64.rotr (local.get $a) (local.get $b)))
+      (drop (i64.rotr (local.get $a) (local.get $b))) (drop (i64.rotr (local.get $a) (local.get $b)))
+      (drop (i64.rotr (local.get $a) (local.get $b))) (drop (i64.rotr (local.get $a) (local.get $b)))
+      (drop (i64.rotr (local.get $a) (local.get $b))) (drop (i64.rotr (local.get $a) (local.get $b)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.load") (param i64) (result i32)
+    (loop $loop
+      (drop (i32.load (i32.const 8))) (drop (i32.load (i32.const 8)))
+      (drop (i32.load (i32.const 8))) (drop (i32.load (i32.const 8)))
+      (drop (i32.load (i32.const 8))) (drop (i32.load (i32.const 8)))
+      (drop (i32.load (i32.const 8))) (drop (i32.load (i32.const 8)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.load") (param i64) (result i32)
+    (loop $loop
+      (drop (i64.load (i32.const 8))) (drop (i64.load (i32.const 8)))
+      (drop (i64.load (i32.const 8))) (drop (i64.load (i32.const 8)))
+      (drop (i64.load (i32.const 8))) (drop (i64.load (i32.const 8)))
+      (drop (i64.load (i32.const 8))) (drop (i64.load (i32.const 8)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.load8_s") (param i64) (result i32)
+    (loop $loop
+      (drop (i32.load8_s (i32.const 8))) (drop (i32.load8_s (i32.const 8)))
+      (drop (i32.load8_s (i32.const 8))) (drop (i32.load8_s (i32.const 8)))
+      (drop (i32.load8_s (i32.const 8))) (drop (i32.load8_s (i32.const 8)))
+      (drop (i32.load8_s (i32.const 8))) (drop (i32.load8_s (i32.const 8)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.load8_u") (param i64) (result i32)
+    (loop $loop
+      (drop (i32.load8_u (i32.const 8))) (drop (i32.load8_u (i32.const 8)))
+      (drop (i32.load8_u (i32.const 8))) (drop (i32.load8_u (i32.const 8)))
+      (drop (i32.load8_u (i32.const 8))) (drop (i32.load8_u (i32.const 8)))
+      (drop (i32.load8_u (i32.const 8))) (drop (i32.load8_u (i32.const 8)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.load16_s") (param i64) (result i32)
+    (loop $loop
+      (drop (i32.load16_s (i32.const 8))) (drop (i32.load16_s (i32.const 8)))
+      (drop (i32.load16_s (i32.const 8))) (drop (i32.load16_s (i32.const 8)))
+      (drop (i32.load16_s (i32.const 8))) (drop (i32.load16_s (i32.const 8)))
+      (drop (i32.load16_s (i32.const 8))) (drop (i32.load16_s (i32.const 8)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.load16_u") (param i64) (result i32)
+    (loop $loop
+      (drop (i32.load16_u (i32.const 8))) (drop (i32.load16_u (i32.const 8)))
+      (drop (i32.load16_u (i32.const 8))) (drop (i32.load16_u (i32.const 8)))
+      (drop (i32.load16_u (i32.const 8))) (drop (i32.load16_u (i32.const 8)))
+      (drop (i32.load16_u (i32.const 8))) (drop (i32.load16_u (i32.const 8)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.load8_s") (param i64) (result i32)
+    (loop $loop
+      (drop (i64.load8_s (i32.const 8))) (drop (i64.load8_s (i32.const 8)))
+      (drop (i64.load8_s (i32.const 8))) (drop (i64.load8_s (i32.const 8)))
+      (drop (i64.load8_s (i32.const 8))) (drop (i64.load8_s (i32.const 8)))
+      (drop (i64.load8_s (i32.const 8))) (drop (i64.load8_s (i32.const 8)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.load8_u") (param i64) (result i32)
+    (loop $loop
+      (drop (i64.load8_u (i32.const 8))) (drop (i64.load8_u (i32.const 8)))
+      (drop (i64.load8_u (i32.const 8))) (drop (i64.load8_u (i32.const 8)))
+      (drop (i64.load8_u (i32.const 8))) (drop (i64.load8_u (i32.const 8)))
+      (drop (i64.load8_u (i32.const 8))) (drop (i64.load8_u (i32.const 8)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.load16_s") (param i64) (result i32)
+    (loop $loop
+      (drop (i64.load16_s (i32.const 8))) (drop (i64.load16_s (i32.const 8)))
+      (drop (i64.load16_s (i32.const 8))) (drop (i64.load16_s (i32.const 8)))
+      (drop (i64.load16_s (i32.const 8))) (drop (i64.load16_s (i32.const 8)))
+      (drop (i64.load16_s (i32.const 8))) (drop (i64.load16_s (i32.const 8)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.load16_u") (param i64) (result i32)
+    (loop $loop
+      (drop (i64.load16_u (i32.const 8))) (drop (i64.load16_u (i32.const 8)))
+      (drop (i64.load16_u (i32.const 8))) (drop (i64.load16_u (i32.const 8)))
+      (drop (i64.load16_u (i32.const 8))) (drop (i64.load16_u (i32.const 8)))
+      (drop (i64.load16_u (i32.const 8))) (drop (i64.load16_u (i32.const 8)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.load32_s") (param i64) (result i32)
+    (loop $loop
+      (drop (i64.load32_s (i32.const 8))) (drop (i64.load32_s (i32.const 8)))
+      (drop (i64.load32_s (i32.const 8))) (drop (i64.load32_s (i32.const 8)))
+      (drop (i64.load32_s (i32.const 8))) (drop (i64.load32_s (i32.const 8)))
+      (drop (i64.load32_s (i32.const 8))) (drop (i64.load32_s (i32.const 8)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.load32_u") (param i64) (result i32)
+    (loop $loop
+      (drop (i64.load32_u (i32.const 8))) (drop (i64.load32_u (i32.const 8)))
+      (drop (i64.load32_u (i32.const 8))) (drop (i64.load32_u (i32.const 8)))
+      (drop (i64.load32_u (i32.const 8))) (drop (i64.load32_u (i32.const 8)))
+      (drop (i64.load32_u (i32.const 8))) (drop (i64.load32_u (i32.const 8)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.store") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (i32.store (i32.const 8) (local.get $x)) (i32.store (i32.const 8) (local.get $x))
+      (i32.store (i32.const 8) (local.get $x)) (i32.store (i32.const 8) (local.get $x))
+      (i32.store (i32.const 8) (local.get $x)) (i32.store (i32.const 8) (local.get $x))
+      (i32.store (i32.const 8) (local.get $x)) (i32.store (i32.const 8) (local.get $x))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.store") (param i64) (result i32)
+    (local $a i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (loop $loop
+      (i64.store (i32.const 8) (local.get $a)) (i64.store (i32.const 8) (local.get $a))
+      (i64.store (i32.const 8) (local.get $a)) (i64.store (i32.const 8) (local.get $a))
+      (i64.store (i32.const 8) (local.get $a)) (i64.store (i32.const 8) (local.get $a))
+      (i64.store (i32.const 8) (local.get $a)) (i64.store (i32.const 8) (local.get $a))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.store8") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (i32.store8 (i32.const 8) (local.get $x)) (i32.store8 (i32.const 8) (local.get $x))
+      (i32.store8 (i32.const 8) (local.get $x)) (i32.store8 (i32.const 8) (local.get $x))
+      (i32.store8 (i32.const 8) (local.get $x)) (i32.store8 (i32.const 8) (local.get $x))
+      (i32.store8 (i32.const 8) (local.get $x)) (i32.store8 (i32.const 8) (local.get $x))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i32.store16") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (i32.store16 (i32.const 8) (local.get $x)) (i32.store16 (i32.const 8) (local.get $x))
+      (i32.store16 (i32.const 8) (local.get $x)) (i32.store16 (i32.const 8) (local.get $x))
+      (i32.store16 (i32.const 8) (local.get $x)) (i32.store16 (i32.const 8) (local.get $x))
+      (i32.store16 (i32.const 8) (local.get $x)) (i32.store16 (i32.const 8) (local.get $x))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.store8") (param i64) (result i32)
+    (local $a i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (loop $loop
+      (i64.store8 (i32.const 8) (local.get $a)) (i64.store8 (i32.const 8) (local.get $a))
+      (i64.store8 (i32.const 8) (local.get $a)) (i64.store8 (i32.const 8) (local.get $a))
+      (i64.store8 (i32.const 8) (local.get $a)) (i64.store8 (i32.const 8) (local.get $a))
+      (i64.store8 (i32.const 8) (local.get $a)) (i64.store8 (i32.const 8) (local.get $a))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.store16") (param i64) (result i32)
+    (local $a i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (loop $loop
+      (i64.store16 (i32.const 8) (local.get $a)) (i64.store16 (i32.const 8) (local.get $a))
+      (i64.store16 (i32.const 8) (local.get $a)) (i64.store16 (i32.const 8) (local.get $a))
+      (i64.store16 (i32.const 8) (local.get $a)) (i64.store16 (i32.const 8) (local.get $a))
+      (i64.store16 (i32.const 8) (local.get $a)) (i64.store16 (i32.const 8) (local.get $a))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.i64.store32") (param i64) (result i32)
+    (local $a i64)
+    (local.set $a (i64.const 0x123456789abcdef0))
+    (loop $loop
+      (i64.store32 (i32.const 8) (local.get $a)) (i64.store32 (i32.const 8) (local.get $a))
+      (i64.store32 (i32.const 8) (local.get $a)) (i64.store32 (i32.const 8) (local.get $a))
+      (i64.store32 (i32.const 8) (local.get $a)) (i64.store32 (i32.const 8) (local.get $a))
+      (i64.store32 (i32.const 8) (local.get $a)) (i64.store32 (i32.const 8) (local.get $a))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.memory.size") (param i64) (result i32)
+    (loop $loop
+      (drop (memory.size)) (drop (memory.size))
+      (drop (memory.size)) (drop (memory.size))
+      (drop (memory.size)) (drop (memory.size))
+      (drop (memory.size)) (drop (memory.size))
+      (br $loop))
+    (i32.const 0))
+
+  ;; Growing by 0 pages still goes through the metering of memory allocation.
+  (func (export "calibrate.memory.grow") (param i64) (result i32)
+    (loop $loop
+      (drop (memory.grow (i32.const 0))) (drop (memory.grow (i32.const 0)))
+      (drop (memory.grow (i32.const 0))) (drop (memory.grow (i32.const 0)))
+      (drop (memory.grow (i32.const 0))) (drop (memory.grow (i32.const 0)))
+      (drop (memory.grow (i32.const 0))) (drop (memory.grow (i32.const 0)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.global.get") (param i64) (result i32)
+    (loop $loop
+      (drop (global.get $g)) (drop (global.get $g))
+      (drop (global.get $g)) (drop (global.get $g))
+      (drop (global.get $g)) (drop (global.get $g))
+      (drop (global.get $g)) (drop (global.get $g))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.global.set") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (global.set $g (local.get $x)) (global.set $g (local.get $x))
+      (global.set $g (local.get $x)) (global.set $g (local.get $x))
+      (global.set $g (local.get $x)) (global.set $g (local.get $x))
+      (global.set $g (local.get $x)) (global.set $g (local.get $x))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.select") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (select (local.get $x) (local.get $y) (local.get $x)))
+      (drop (select (local.get $x) (local.get $y) (local.get $x)))
+      (drop (select (local.get $x) (local.get $y) (local.get $x)))
+      (drop (select (local.get $x) (local.get $y) (local.get $x)))
+      (drop (select (local.get $x) (local.get $y) (local.get $x)))
+      (drop (select (local.get $x) (local.get $y) (local.get $x)))
+      (drop (select (local.get $x) (local.get $y) (local.get $x)))
+      (drop (select (local.get $x) (local.get $y) (local.get $x)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.local.set") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (local.set $y (local.get $x)) (local.set $y (local.get $x))
+      (local.set $y (local.get $x)) (local.set $y (local.get $x))
+      (local.set $y (local.get $x)) (local.set $y (local.get $x))
+      (local.set $y (local.get $x)) (local.set $y (local.get $x))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.if") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (if (local.get $x) (then)) (if (local.get $x) (then))
+      (if (local.get $x) (then)) (if (local.get $x) (then))
+      (if (local.get $x) (then)) (if (local.get $x) (then))
+      (if (local.get $x) (then)) (if (local.get $x) (then))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.br") (param i64) (result i32)
+    (loop $loop
+      (block (br 0)) (block (br 0))
+      (block (br 0)) (block (br 0))
+      (block (br 0)) (block (br 0))
+      (block (br 0)) (block (br 0))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.br_if") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (block (br_if 0 (local.get $x))) (block (br_if 0 (local.get $x)))
+      (block (br_if 0 (local.get $x))) (block (br_if 0 (local.get $x)))
+      (block (br_if 0 (local.get $x))) (block (br_if 0 (local.get $x)))
+      (block (br_if 0 (local.get $x))) (block (br_if 0 (local.get $x)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.br_table") (param i64) (result i32)
+    (local $x i32)
+    (local.set $x (i32.const 0x12345678))
+    (loop $loop
+      (block (br_table 0 0 (local.get $x))) (block (br_table 0 0 (local.get $x)))
+      (block (br_table 0 0 (local.get $x))) (block (br_table 0 0 (local.get $x)))
+      (block (br_table 0 0 (local.get $x))) (block (br_table 0 0 (local.get $x)))
+      (block (br_table 0 0 (local.get $x))) (block (br_table 0 0 (local.get $x)))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.br_table_carry") (param i64) (result i32)
+    (local $x i32)
+    (local $y i32)
+    (local.set $x (i32.const 0x12345678))
+    (local.set $y (i32.const 0x07654321))
+    (loop $loop
+      (drop (block (result i32) (br_table 0 0 (local.get $y) (local.get $x))))
+      (drop (block (result i32) (br_table 0 0 (local.get $y) (local.get $x))))
+      (drop (block (result i32) (br_table 0 0 (local.get $y) (local.get $x))))
+      (drop (block (result i32) (br_table 0 0 (local.get $y) (local.get $x))))
+      (drop (block (result i32) (br_table 0 0 (local.get $y) (local.get $x))))
+      (drop (block (result i32) (br_table 0 0 (local.get $y) (local.get $x))))
+      (drop (block (result i32) (br_table 0 0 (local.get $y) (local.get $x))))
+      (drop (block (result i32) (br_table 0 0 (local.get $y) (local.get $x))))
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.call") (param i64) (result i32)
+    (loop $loop
+      (call $nop) (call $nop)
+      (call $nop) (call $nop)
+      (call $nop) (call $nop)
+      (call $nop) (call $nop)
+      (br $loop))
+    (i32.const 0))
+
+  (func (export "calibrate.call_indirect") (param i64) (result i32)
+    (loop $loop
+      (call_indirect (type $nop_type) (i32.const 0)) (call_indirect (type $nop_type) (i32.const 0))
+      (call_indirect (type $nop_type) (i32.const 0)) (call_indirect (type $nop_type) (i32.const 0))
+      (call_indirect (type $nop_type) (i32.const 0)) (call_indirect (type $nop_type) (i32.const 0))
+      (call_indirect (type $nop_type) (i32.const 0)) (call_indirect (type $nop_type) (i32.const 0))
+      (br $loop))
+    (i32.const 0))
+)
@@ -43,6 +43,7 @@
   ;; cryptographic primitives
   (import "concordium" "verify_ed25519_signature" (func $verify_ed25519_signature (param $public_key i32) (param $signature i32) (param $message i32) (param $message_len i32) (result i32)))
   (import "concordium" "verify_ecdsa_secp256k1_signature" (func $verify_ecdsa_secp256k1_signature (param $public_key i32) (param $signature i32) (param $message i32) (result i32)))
+  (import "concordium" "recover_ecdsa_secp256k1_public_key" (func $recover_ecdsa_secp256k1_public_key (param $signature i32) (param $recovery_id i32) (param $message i32) (param $output i32) (result i32)))
   (import "concordium" "hash_sha2_256" (func $hash_sha2_256 (param $data i32) (param $data_len i32) (param $output i32)))
   (import "concordium" "hash_sha3_256" (func $hash_sha3_256 (param $data i32) (param $data_len i32) (param $output i32)))
   (import "concordium" "hash_keccak_256" (func $hash_keccak_256 (param $data i32) (param $data_len i32) (param $output i32)))
@@ -314,6 +315,15 @@
       (return (i32.const 0))
   )
 
+  (func (export "hostfn.recover_ecdsa_secp256k1_public_key") (param i64) (result i32)
+      (call $get_parameter_section (i32.const 0) (i32.const 0) (i32.const 100) (i32.const 0))
+      (loop $loop
+        (call $recover_ecdsa_secp256k1_public_key (i32.const 0) (i32.load (i32.const 64)) (i32.const 68) (i32.const 100))
+        (br_if $loop) ;; only loop if we succeeded in recovering the public key
+      )
+      (return (i32.const 0))
+  )
+
   (func (export "hostfn.hash_sha2_256") (param i64) (result i32)
       (local $len i32)
       (call $get_parameter_section (i32.const 0) (i32.const 0) (i32.const 4) (i32.const 0))
@@ -71,8 +71,9 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         let mut module = validate::validate_module(
             ValidationConfig::V1,
             &ConcordiumAllowedImports {
-                support_upgrade: true,
-                enable_debug:    false,
+                support_upgrade:            true,
+                enable_debug:               false,
+                support_secp256k1_recovery: true,
             },
             &skeleton,
         )
@@ -462,6 +463,26 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         add_crypto_primitive_benchmark(name, params, None);
     }
 
+    {
+        // public key recovery has a fixed message length
+        let name = "hostfn.recover_ecdsa_secp256k1_public_key";
+        let signer = secp256k1::Secp256k1::new();
+        let sk = secp256k1::SecretKey::from_slice(&[
+            0xc9, 0xef, 0x15, 0x44, 0x4b, 0x1e, 0x88, 0x5f, 0x0e, 0xd0, 0x36, 0xaa, 0xc8, 0x64,
+            0x6f, 0xb0, 0xc6, 0x11, 0x88, 0x6e, 0x8c, 0x40, 0x91, 0xa1, 0xb7, 0xb2, 0xb5, 0xa0,
+            0x95, 0xd2, 0xd6, 0xba,
+        ])
+        .expect("Key generated with openssl, so should be valid.");
+        let message = secp256k1::Message::from_slice(&sha2::Sha256::digest([])[..])
+            .expect("Hashes are valid messages.");
+        let (recovery_id, sig) = signer.sign_ecdsa_recoverable(&message, &sk).serialize_compact();
+        let mut params = Vec::with_capacity(100);
+        params.extend_from_slice(&sig);
+        params.extend_from_slice(&recovery_id.to_i32().to_le_bytes());
+        params.extend_from_slice(message.as_ref());
+        add_crypto_primitive_benchmark(name, params, None);
+    }
+
     {
         // n is the length of the data to be hashed
         for n in [0u32, 10, 20, 50, 100, 1000, 10_000, 100_000] {
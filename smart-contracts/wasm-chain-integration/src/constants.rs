@@ -252,6 +252,15 @@ pub fn verify_ed25519_cost(message_len: u32) -> u64 { 100_000 + 100 * u64::from(
 /// (which are meant to be hashes) the cost is constant.
 pub const VERIFY_ECDSA_SECP256K1_COST: u64 = 100_000;
 
+/// Cost of recovering a public key from a recoverable ecdsa signature over
+/// secp256k1 with the bitcoin-core implementation. Recovery works on 32 byte
+/// messages, so the cost is constant. The time per unit of energy of the
+/// `recover_ecdsa_secp256k1_public_key` and `verify_ecdsa_secp256k1_signature`
+/// benchmarks of `host-functions.wat`, as measured by the `calibration`
+/// benchmark, shows that a recovery takes the same time as a verification to
+/// within 10%, so the cost is the same.
+pub const RECOVER_ECDSA_SECP256K1_COST: u64 = VERIFY_ECDSA_SECP256K1_COST;
+
 /// Cost of computing a SHA2-256 digest of the message of the given length.
 pub fn hash_sha2_256_cost(data_len: u32) -> u64 { 500 + 7 * u64::from(data_len) }
 
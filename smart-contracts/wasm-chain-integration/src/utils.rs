@@ -8,13 +8,14 @@ use crate::{
 use anyhow::{anyhow, bail, ensure, Context};
 pub use concordium_contracts_common::WasmVersion;
 use concordium_contracts_common::{
-    self as concordium_std, from_bytes, hashes, schema, Cursor, Deserial,
+    self as concordium_std, from_bytes, hashes, schema, to_bytes, Cursor, Deserial,
 };
 use concordium_wasm::{
     artifact::{Artifact, ArtifactNamedImport, RunnableCode, TryFromImport},
     machine::{self, NoInterrupt, Value},
+    output::{write_custom_section, Output},
     parse::{parse_custom, parse_skeleton, Skeleton},
-    types::{ExportDescription, Module, Name},
+    types::{CustomSection, ExportDescription, Module, Name},
     utils,
     validate::{self, ValidationConfig},
 };
@@ -478,6 +479,28 @@ pub fn generate_contract_schema_v2(
 pub fn generate_contract_schema_v3(
     module_bytes: &[u8],
 ) -> ExecResult<schema::VersionedModuleSchema> {
+    Ok(schema::VersionedModuleSchema::V3(schema::ModuleV3 {
+        contracts: generate_contract_schemas_v3(module_bytes)?,
+    }))
+}
+
+/// Tries to generate schemas for events, parameters, return values, and errors
+/// of methods for a contract with a V4 schema. This is needed for the types for
+/// hashes, keys, signatures or token IDs, which are only supported by V4
+/// schemas, see [`schema::WithSchemaV4`].
+pub fn generate_contract_schema_v4(
+    module_bytes: &[u8],
+) -> ExecResult<schema::VersionedModuleSchema> {
+    Ok(schema::VersionedModuleSchema::V4(schema::ModuleV4 {
+        contracts: generate_contract_schemas_v3(module_bytes)?,
+    }))
+}
+
+/// Generate the schemas of the contracts for the V3 and V4 schemas, which have
+/// the same layout.
+fn generate_contract_schemas_v3(
+    module_bytes: &[u8],
+) -> ExecResult<BTreeMap<String, schema::ContractV3>> {
     let artifact = utils::instantiate::<ArtifactNamedImport, _>(
         ValidationConfig::V1,
         &NoDuplicateImport,
@@ -531,9 +554,7 @@ pub fn generate_contract_schema_v3(
         }
     }
 
-    Ok(schema::VersionedModuleSchema::V3(schema::ModuleV3 {
-        contracts: contract_schemas,
-    }))
+    Ok(contract_schemas)
 }
 
 /// Runs the given schema function and reads the resulting function schema from
@@ -608,9 +629,9 @@ pub fn get_embedded_schema_v0(bytes: &[u8]) -> ExecResult<schema::VersionedModul
     for ucs in skeleton.custom.iter() {
         let cs = parse_custom(ucs)?;
 
-        if cs.name.as_ref() == "concordium-schema" && schema_versioned_section.is_none() {
+        if cs.name.as_ref() == SCHEMA_SECTION_NAME && schema_versioned_section.is_none() {
             schema_versioned_section = Some(cs)
-        } else if cs.name.as_ref() == "concordium-schema-v1" && schema_v1_section.is_none() {
+        } else if cs.name.as_ref() == LEGACY_SCHEMA_SECTION_NAME_V0 && schema_v1_section.is_none() {
             schema_v1_section = Some(cs)
         }
     }
@@ -633,14 +654,23 @@ pub fn get_embedded_schema_v0(bytes: &[u8]) -> ExecResult<schema::VersionedModul
 /// and if this is not present try to use the custom section
 /// "concordium-schema-v2".
 pub fn get_embedded_schema_v1(bytes: &[u8]) -> ExecResult<schema::VersionedModuleSchema> {
+    match find_embedded_schema_v1(bytes)? {
+        Some(module) => Ok(module),
+        None => bail!("No schema found in the module"),
+    }
+}
+
+/// Like [`get_embedded_schema_v1`], but returns [`None`] if the module has no
+/// embedded schema.
+pub fn find_embedded_schema_v1(bytes: &[u8]) -> ExecResult<Option<schema::VersionedModuleSchema>> {
     let skeleton = parse_skeleton(bytes)?;
     let mut schema_v2_section = None;
     let mut schema_versioned_section = None;
     for ucs in skeleton.custom.iter() {
         let cs = parse_custom(ucs)?;
-        if cs.name.as_ref() == "concordium-schema" && schema_versioned_section.is_none() {
+        if cs.name.as_ref() == SCHEMA_SECTION_NAME && schema_versioned_section.is_none() {
             schema_versioned_section = Some(cs)
-        } else if cs.name.as_ref() == "concordium-schema-v2" && schema_v2_section.is_none() {
+        } else if cs.name.as_ref() == LEGACY_SCHEMA_SECTION_NAME_V1 && schema_v2_section.is_none() {
             schema_v2_section = Some(cs)
         }
     }
@@ -648,12 +678,132 @@ pub fn get_embedded_schema_v1(bytes: &[u8]) -> ExecResult<schema::VersionedModul
     if let Some(cs) = schema_versioned_section {
         let module: schema::VersionedModuleSchema =
             from_bytes(cs.contents).map_err(|_| anyhow!("Failed parsing schema"))?;
-        Ok(module)
+        Ok(Some(module))
     } else if let Some(cs) = schema_v2_section {
         let module = from_bytes(cs.contents).map_err(|_| anyhow!("Failed parsing schema"))?;
-        Ok(schema::VersionedModuleSchema::V1(module))
+        Ok(Some(schema::VersionedModuleSchema::V1(module)))
     } else {
-        bail!("No schema found in the module")
+        Ok(None)
+    }
+}
+
+/// Name of the custom section that contains the versioned module schema.
+pub const SCHEMA_SECTION_NAME: &str = "concordium-schema";
+
+/// Name of the custom section containing the unversioned schema of a V0
+/// module, which is still understood by [`get_embedded_schema_v0`].
+pub const LEGACY_SCHEMA_SECTION_NAME_V0: &str = "concordium-schema-v1";
+
+/// Name of the custom section containing the unversioned schema of a V1
+/// module, which is still understood by [`get_embedded_schema_v1`].
+pub const LEGACY_SCHEMA_SECTION_NAME_V1: &str = "concordium-schema-v2";
+
+/// Names of the custom sections containing unversioned schemas of V0 and V1
+/// modules.
+const LEGACY_SCHEMA_SECTION_NAMES: [&str; 2] =
+    [LEGACY_SCHEMA_SECTION_NAME_V0, LEGACY_SCHEMA_SECTION_NAME_V1];
+
+/// Embed the schema into a Wasm module in the custom section
+/// [`SCHEMA_SECTION_NAME`]. Any schema already embedded in the module,
+/// including in the legacy sections for unversioned schemas, is removed. The
+/// remaining custom sections are retained.
+///
+/// The input and output are the Wasm module without the version prefix of
+/// deployed modules.
+pub fn embed_schema(
+    module_bytes: &[u8],
+    schema: &schema::VersionedModuleSchema,
+) -> ExecResult<Vec<u8>> {
+    let mut skeleton = parse_skeleton(module_bytes)?;
+    let mut custom = Vec::with_capacity(skeleton.custom.len());
+    for ucs in skeleton.custom {
+        let name = parse_custom(&ucs)?.name;
+        if name.as_ref() != SCHEMA_SECTION_NAME
+            && !LEGACY_SCHEMA_SECTION_NAMES.contains(&name.as_ref())
+        {
+            custom.push(ucs);
+        }
+    }
+    skeleton.custom = custom;
+    let mut out = Vec::with_capacity(module_bytes.len());
+    skeleton.output(&mut out)?;
+    let contents = to_bytes(schema);
+    write_custom_section(&mut out, &CustomSection {
+        name:     SCHEMA_SECTION_NAME.into(),
+        contents: &contents,
+    })?;
+    Ok(out)
+}
+
+/// A discrepancy between a module schema and the functions exported by the
+/// module, see [`check_schema_exports`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SchemaExportMismatch {
+    /// The schema describes a contract for which the module has no init
+    /// function.
+    #[error("The schema describes contract '{0}', but the module does not export 'init_{0}'.")]
+    MissingContract(String),
+    /// The schema describes a receive function the module does not export.
+    #[error("The schema describes '{contract}.{entrypoint}', but the module does not export it.")]
+    MissingEntrypoint {
+        contract:   String,
+        entrypoint: String,
+    },
+}
+
+/// Check that every contract and receive function described by the schema is
+/// exported by the module. Functions exported by the module without a
+/// schema are not reported, since the schema of a function is optional.
+pub fn check_schema_exports(
+    module: &Module,
+    schema: &schema::VersionedModuleSchema,
+) -> Vec<SchemaExportMismatch> {
+    let inits = get_inits(module);
+    let receives = get_receives(module);
+    let mut out = Vec::new();
+    for (contract, entrypoints) in schema_entrypoints(schema) {
+        if !inits.iter().any(|name| name.as_ref().strip_prefix("init_") == Some(contract)) {
+            out.push(SchemaExportMismatch::MissingContract(contract.into()));
+            continue;
+        }
+        for entrypoint in entrypoints {
+            let exported = receives
+                .iter()
+                .any(|name| name.as_ref().split_once('.') == Some((contract, entrypoint)));
+            if !exported {
+                out.push(SchemaExportMismatch::MissingEntrypoint {
+                    contract:   contract.into(),
+                    entrypoint: entrypoint.into(),
+                });
+            }
+        }
+    }
+    out
+}
+
+/// The names of the contracts described by the schema, each with the names of
+/// its receive functions described by the schema.
+fn schema_entrypoints(schema: &schema::VersionedModuleSchema) -> Vec<(&str, Vec<&str>)> {
+    use schema::VersionedModuleSchema as Versioned;
+    fn names<V>(receive: &BTreeMap<String, V>) -> Vec<&str> {
+        receive.keys().map(String::as_str).collect()
+    }
+    match schema {
+        Versioned::V0(module) => {
+            module.contracts.iter().map(|(name, c)| (name.as_str(), names(&c.receive))).collect()
+        }
+        Versioned::V1(module) => {
+            module.contracts.iter().map(|(name, c)| (name.as_str(), names(&c.receive))).collect()
+        }
+        Versioned::V2(module) => {
+            module.contracts.iter().map(|(name, c)| (name.as_str(), names(&c.receive))).collect()
+        }
+        Versioned::V3(module) => {
+            module.contracts.iter().map(|(name, c)| (name.as_str(), names(&c.receive))).collect()
+        }
+        Versioned::V4(module) => {
+            module.contracts.iter().map(|(name, c)| (name.as_str(), names(&c.receive))).collect()
+        }
     }
 }
 
@@ -765,4 +915,55 @@ mod tests {
             panic!("Failed to parse versioned v1 module schema: {}", e);
         }
     }
+
+    #[test]
+    fn test_embed_and_check_schema() {
+        use super::*;
+        let data =
+            std::fs::read("../testdata/schemas/cis2-wccd-embedded-schema-v1-versioned.wasm.v1")
+                .expect("Could not read file.");
+        let module_bytes = &data[8..];
+        let schema = get_embedded_schema_v1(module_bytes).expect("Module has a schema.");
+        let embedded = embed_schema(module_bytes, &schema).expect("Embedding succeeds.");
+        let skeleton = parse_skeleton(&embedded).expect("Output is a valid module.");
+        let sections = skeleton
+            .custom
+            .iter()
+            .filter(|ucs| {
+                parse_custom(ucs).expect("Valid custom section.").name.as_ref()
+                    == SCHEMA_SECTION_NAME
+            })
+            .count();
+        assert_eq!(sections, 1, "The existing schema should be replaced.");
+        let extracted = get_embedded_schema_v1(&embedded).expect("Module has a schema.");
+        assert_eq!(to_bytes(&extracted), to_bytes(&schema));
+
+        let module = validate::validate_module(ValidationConfig::V1, &NoDuplicateImport, &skeleton)
+            .expect("Module is valid.");
+        assert!(check_schema_exports(&module, &schema).is_empty());
+
+        let contract_name = get_inits(&module)[0].as_ref()["init_".len()..].to_owned();
+        let mut contract = schema::ContractV3::default();
+        contract.receive.insert("missing".into(), schema::FunctionV2 {
+            parameter:    Some(schema::Type::Unit),
+            return_value: None,
+            error:        None,
+        });
+        let mut contracts = BTreeMap::new();
+        contracts.insert(contract_name.clone(), contract);
+        contracts.insert("other".into(), schema::ContractV3::default());
+        let mismatches = check_schema_exports(
+            &module,
+            &schema::VersionedModuleSchema::V3(schema::ModuleV3 {
+                contracts,
+            }),
+        );
+        assert_eq!(mismatches, [
+            SchemaExportMismatch::MissingEntrypoint {
+                contract:   contract_name,
+                entrypoint: "missing".into(),
+            },
+            SchemaExportMismatch::MissingContract("other".into()),
+        ]);
+    }
 }
@@ -59,8 +59,9 @@ fn test_crypto_prims() -> anyhow::Result<()> {
         let mut module = validate::validate_module(
             ValidationConfig::V1,
             &ConcordiumAllowedImports {
-                support_upgrade: true,
-                enable_debug:    false,
+                support_upgrade:            true,
+                enable_debug:               false,
+                support_secp256k1_recovery: true,
             },
             &skeleton,
         )
@@ -327,6 +328,60 @@ fn test_crypto_prims() -> anyhow::Result<()> {
         );
     }
 
+    {
+        // public key recovery has a fixed message length
+        let name = "hostfn.recover_ecdsa_secp256k1_public_key";
+        let signer = secp256k1::Secp256k1::new();
+        let sk = secp256k1::SecretKey::from_slice(&[
+            0xc9, 0xef, 0x15, 0x44, 0x4b, 0x1e, 0x88, 0x5f, 0x0e, 0xd0, 0x36, 0xaa, 0xc8, 0x64,
+            0x6f, 0xb0, 0xc6, 0x11, 0x88, 0x6e, 0x8c, 0x40, 0x91, 0xa1, 0xb7, 0xb2, 0xb5, 0xa0,
+            0x95, 0xd2, 0xd6, 0xba,
+        ])
+        .expect("Key generated with openssl, so should be valid.");
+        let pk = secp256k1::PublicKey::from_secret_key(&signer, &sk);
+        let message = secp256k1::Message::from_slice(&sha2::Sha256::digest([])[..])
+            .expect("Hashes are valid messages.");
+        let sig = signer.sign_ecdsa_recoverable(&message, &sk);
+        let make_params = |recovery_id: i32, message: &secp256k1::Message| {
+            let (_, sig_bytes) = sig.serialize_compact();
+            let mut params = Vec::with_capacity(100);
+            params.extend_from_slice(&sig_bytes);
+            params.extend_from_slice(&recovery_id.to_le_bytes());
+            params.extend_from_slice(message.as_ref());
+            params
+        };
+        let recovery_id = sig.serialize_compact().0.to_i32();
+
+        // expect recovery to succeed and produce the signer's key.
+        let rv1 = test_crypto_primitive(name, make_params(recovery_id, &message));
+        anyhow::ensure!(
+            rv1[..4] == [1, 0, 0, 0] && rv1[4..] == pk.serialize(),
+            "Incorrect recovery result for {}, case 1, got {:?}.",
+            name,
+            rv1
+        );
+
+        // different message, recovery succeeds but produces a different key.
+        let incorrect_message = secp256k1::Message::from_slice(&sha2::Sha256::digest([0])[..])
+            .expect("Hashes are valid messages.");
+        let rv2 = test_crypto_primitive(name, make_params(recovery_id, &incorrect_message));
+        anyhow::ensure!(
+            rv2[..4] == [1, 0, 0, 0] && rv2[4..] != pk.serialize(),
+            "Incorrect recovery result for {}, case 2, got {:?}.",
+            name,
+            rv2
+        );
+
+        // invalid recovery id
+        let rv3 = test_crypto_primitive(name, make_params(4, &message));
+        anyhow::ensure!(
+            rv3[..] == [0u8; 37],
+            "Incorrect recovery result for {}, case 3, got {:?}.",
+            name,
+            rv3
+        );
+    }
+
     {
         // n is the length of the data to be hashed
         for n in [0u32, 10, 20, 50, 100, 1000, 10_000, 100_000] {
@@ -0,0 +1,505 @@
+//! Decoding of the outputs of V1 contract execution into JSON.
+//!
+//! Contracts log events and produce return values as raw bytes. The module
+//! schema describes how these bytes should be interpreted. This module pairs
+//! the outputs in an [`InitResult`] or [`ReceiveResult`] with the relevant
+//! parts of the schema and produces JSON values using [`Type::to_json`].
+//!
+//! If the contract does not have an event schema then events are decoded as
+//! CIS-2 standard events, if possible.
+use super::{InitResult, ReceiveResult};
+use crate::{utils, v0, DebugInfo, ExecResult};
+use concordium_contracts_common::{
+    schema::{Fields, SizeLength, Type, VersionedModuleSchema, VersionedSchemaError},
+    Cursor,
+};
+use std::collections::BTreeMap;
+
+/// A single decoded output of contract execution. This is either an event, a
+/// return value, or an error.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "decodedWith", rename_all = "camelCase")]
+pub enum DecodedValue {
+    /// The value was decoded using the schema of the contract.
+    Schema {
+        value: serde_json::Value,
+    },
+    /// The value was decoded as a CIS-2 standard event. This is only used for
+    /// events of contracts that do not have an event schema.
+    Cis2 {
+        value: serde_json::Value,
+    },
+    /// The value could not be decoded.
+    Raw {
+        /// The hex encoding of the value.
+        #[serde(serialize_with = "hex::serde::serialize")]
+        bytes:  Vec<u8>,
+        /// A description of why the value could not be decoded.
+        reason: String,
+    },
+}
+
+impl DecodedValue {
+    /// Return the decoded JSON value, if decoding succeeded.
+    pub fn value(&self) -> Option<&serde_json::Value> {
+        match self {
+            DecodedValue::Schema {
+                value,
+            } => Some(value),
+            DecodedValue::Cis2 {
+                value,
+            } => Some(value),
+            DecodedValue::Raw {
+                ..
+            } => None,
+        }
+    }
+}
+
+/// The outcome of contract execution with events, return values and errors
+/// decoded.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(tag = "outcome", rename_all = "camelCase")]
+pub enum DecodedResult {
+    /// Execution terminated successfully.
+    #[serde(rename_all = "camelCase")]
+    Success {
+        /// The events that were logged, in the order they were logged.
+        events:       Vec<DecodedValue>,
+        /// The return value.
+        return_value: DecodedValue,
+    },
+    /// Execution was interrupted. Only events logged before the interrupt are
+    /// included.
+    Interrupt {
+        /// The events that were logged, in the order they were logged.
+        events: Vec<DecodedValue>,
+    },
+    /// The contract rejected execution.
+    Reject {
+        /// The error code returned by the contract.
+        reason: i32,
+        /// The return value, decoded using the error schema.
+        error:  DecodedValue,
+    },
+    /// Execution stopped due to a runtime error.
+    Trap {
+        /// A description of the runtime error.
+        error: String,
+    },
+    /// Execution ran out of energy.
+    OutOfEnergy,
+}
+
+/// Decoder for the outputs of a single contract in a module.
+#[derive(Debug, Clone)]
+pub struct OutputDecoder {
+    /// The schema of the module, if one is available.
+    schema:        Option<VersionedModuleSchema>,
+    /// Name of the contract, without the `init_` prefix.
+    contract_name: String,
+}
+
+impl OutputDecoder {
+    /// Construct a decoder from a schema. The contract name should not include
+    /// the `init_` prefix. If no schema is given then only CIS-2 standard
+    /// events are decoded.
+    pub fn new(schema: Option<VersionedModuleSchema>, contract_name: impl Into<String>) -> Self {
+        Self {
+            schema,
+            contract_name: contract_name.into(),
+        }
+    }
+
+    /// Construct a decoder using the schema embedded in the given V1 Wasm
+    /// module (without the version prefix). If the module has no embedded
+    /// schema then only CIS-2 standard events are decoded. An error is
+    /// returned if the module cannot be parsed or the embedded schema is
+    /// malformed.
+    pub fn from_module_source(module: &[u8], contract_name: impl Into<String>) -> ExecResult<Self> {
+        Ok(Self::new(utils::find_embedded_schema_v1(module)?, contract_name))
+    }
+
+    /// Decode a single event. If the contract has an event schema it is used,
+    /// otherwise the event is decoded as a CIS-2 standard event. If the
+    /// schema does not describe the contract the event is not decoded.
+    pub fn decode_event(&self, event: &[u8]) -> DecodedValue {
+        let event_schema =
+            match self.schema.as_ref().map(|s| s.get_event_schema(&self.contract_name)) {
+                None
+                | Some(Err(
+                    VersionedSchemaError::NoEventInContract
+                    | VersionedSchemaError::EventNotSupported,
+                )) => None,
+                Some(Ok(ty)) => Some(ty),
+                Some(Err(e)) => {
+                    return DecodedValue::Raw {
+                        bytes:  event.to_vec(),
+                        reason: e.to_string(),
+                    }
+                }
+            };
+        match event_schema {
+            Some(ty) => decode_with(&ty, event, |value| DecodedValue::Schema {
+                value,
+            }),
+            None => decode_with(&cis2_event_schema(), event, |value| DecodedValue::Cis2 {
+                value,
+            }),
+        }
+    }
+
+    /// Decode all the events in the logs, in the order they were logged.
+    pub fn decode_logs(&self, logs: &v0::Logs) -> Vec<DecodedValue> {
+        logs.iterate().map(|event| self.decode_event(event)).collect()
+    }
+
+    /// Decode the outcome of executing the init function of the contract.
+    pub fn decode_init_result<A>(&self, result: &InitResult<A>) -> DecodedResult {
+        match result {
+            InitResult::Success {
+                logs,
+                return_value,
+                ..
+            } => {
+                let schema = self
+                    .schema
+                    .as_ref()
+                    .map(|s| s.get_init_return_value_schema(&self.contract_name));
+                DecodedResult::Success {
+                    events:       self.decode_logs(logs),
+                    return_value: decode_with_schema(schema, return_value),
+                }
+            }
+            InitResult::Reject {
+                reason,
+                return_value,
+                ..
+            } => {
+                let schema =
+                    self.schema.as_ref().map(|s| s.get_init_error_schema(&self.contract_name));
+                DecodedResult::Reject {
+                    reason: *reason,
+                    error:  decode_with_schema(schema, return_value),
+                }
+            }
+            InitResult::Trap {
+                error,
+                ..
+            } => DecodedResult::Trap {
+                error: error.to_string(),
+            },
+            InitResult::OutOfEnergy {
+                ..
+            } => DecodedResult::OutOfEnergy,
+        }
+    }
+
+    /// Decode the outcome of executing the given entrypoint of the contract.
+    pub fn decode_receive_result<R, A: DebugInfo, Ctx>(
+        &self,
+        entrypoint: &str,
+        result: &ReceiveResult<R, A, Ctx>,
+    ) -> DecodedResult {
+        match result {
+            ReceiveResult::Success {
+                logs,
+                return_value,
+                ..
+            } => {
+                let schema = self
+                    .schema
+                    .as_ref()
+                    .map(|s| s.get_receive_return_value_schema(&self.contract_name, entrypoint));
+                DecodedResult::Success {
+                    events:       self.decode_logs(logs),
+                    return_value: decode_with_schema(schema, return_value),
+                }
+            }
+            ReceiveResult::Interrupt {
+                logs,
+                ..
+            } => DecodedResult::Interrupt {
+                events: self.decode_logs(logs),
+            },
+            ReceiveResult::Reject {
+                reason,
+                return_value,
+                ..
+            } => {
+                let schema = self
+                    .schema
+                    .as_ref()
+                    .map(|s| s.get_receive_error_schema(&self.contract_name, entrypoint));
+                DecodedResult::Reject {
+                    reason: *reason,
+                    error:  decode_with_schema(schema, return_value),
+                }
+            }
+            ReceiveResult::Trap {
+                error,
+                ..
+            } => DecodedResult::Trap {
+                error: error.to_string(),
+            },
+            ReceiveResult::OutOfEnergy {
+                ..
+            } => DecodedResult::OutOfEnergy,
+        }
+    }
+}
+
+/// Decode the bytes using the schema if it is available, and return a raw
+/// value explaining why the schema is not available otherwise.
+fn decode_with_schema(
+    schema: Option<Result<Type, VersionedSchemaError>>,
+    bytes: &[u8],
+) -> DecodedValue {
+    match schema {
+        Some(Ok(ty)) => decode_with(&ty, bytes, |value| DecodedValue::Schema {
+            value,
+        }),
+        Some(Err(e)) => DecodedValue::Raw {
+            bytes:  bytes.to_vec(),
+            reason: e.to_string(),
+        },
+        None => DecodedValue::Raw {
+            bytes:  bytes.to_vec(),
+            reason: "No schema available".into(),
+        },
+    }
+}
+
+/// Decode the bytes using the given type. All the bytes must be consumed for
+/// decoding to succeed.
+fn decode_with(
+    ty: &Type,
+    bytes: &[u8],
+    make: impl FnOnce(serde_json::Value) -> DecodedValue,
+) -> DecodedValue {
+    let mut cursor = Cursor::new(bytes);
+    match ty.to_json(&mut cursor) {
+        Ok(value) if cursor.offset == bytes.len() => make(value),
+        Ok(_) => DecodedValue::Raw {
+            bytes:  bytes.to_vec(),
+            reason: format!(
+                "{} trailing bytes after decoding the value",
+                bytes.len() - cursor.offset
+            ),
+        },
+        Err(e) => DecodedValue::Raw {
+            bytes:  bytes.to_vec(),
+            reason: e.display(false),
+        },
+    }
+}
+
+/// The schema of the standard events defined by CIS-2, i.e., `Transfer`,
+/// `Mint`, `Burn`, `UpdateOperator` and `TokenMetadata`. Token amounts are
+/// decoded as LEB128 encoded integers of at most 37 bytes.
+pub fn cis2_event_schema() -> Type {
+    let token_id = || Type::TokenId;
+    let token_amount = || Type::ULeb128(37);
+    let address = || {
+        Type::Enum(vec![
+            ("Account".into(), Fields::Unnamed(vec![Type::AccountAddress])),
+            ("Contract".into(), Fields::Unnamed(vec![Type::ContractAddress])),
+        ])
+    };
+    let metadata_url = Type::Struct(Fields::Named(vec![
+        ("url".into(), Type::String(SizeLength::U16)),
+        (
+            "hash".into(),
+            Type::Enum(vec![
+                ("None".into(), Fields::None),
+                ("Some".into(), Fields::Unnamed(vec![Type::Hash])),
+            ]),
+        ),
+    ]));
+    let operator_update =
+        Type::Enum(vec![("Remove".into(), Fields::None), ("Add".into(), Fields::None)]);
+    Type::TaggedEnum(BTreeMap::from([
+        (
+            255,
+            (
+                "Transfer".into(),
+                Fields::Named(vec![
+                    ("token_id".into(), token_id()),
+                    ("amount".into(), token_amount()),
+                    ("from".into(), address()),
+                    ("to".into(), address()),
+                ]),
+            ),
+        ),
+        (
+            254,
+            (
+                "Mint".into(),
+                Fields::Named(vec![
+                    ("token_id".into(), token_id()),
+                    ("amount".into(), token_amount()),
+                    ("owner".into(), address()),
+                ]),
+            ),
+        ),
+        (
+            253,
+            (
+                "Burn".into(),
+                Fields::Named(vec![
+                    ("token_id".into(), token_id()),
+                    ("amount".into(), token_amount()),
+                    ("owner".into(), address()),
+                ]),
+            ),
+        ),
+        (
+            252,
+            (
+                "UpdateOperator".into(),
+                Fields::Named(vec![
+                    ("update".into(), operator_update),
+                    ("owner".into(), address()),
+                    ("operator".into(), address()),
+                ]),
+            ),
+        ),
+        (
+            251,
+            (
+                "TokenMetadata".into(),
+                Fields::Named(vec![
+                    ("token_id".into(), token_id()),
+                    ("metadata_url".into(), metadata_url),
+                ]),
+            ),
+        ),
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concordium_contracts_common::schema::{ContractV3, FunctionV2, ModuleV3};
+    use serde_json::json;
+
+    fn test_schema() -> VersionedModuleSchema {
+        VersionedModuleSchema::V3(ModuleV3 {
+            contracts: BTreeMap::from([("test".into(), ContractV3 {
+                init:    Some(FunctionV2 {
+                    parameter:    None,
+                    return_value: Some(Type::U32),
+                    error:        Some(Type::U8),
+                }),
+                receive: BTreeMap::from([("receive".into(), FunctionV2 {
+                    parameter:    None,
+                    return_value: Some(Type::U32),
+                    error:        None,
+                })]),
+                event:   Some(Type::Enum(vec![
+                    ("Foo".into(), Fields::None),
+                    ("Bar".into(), Fields::Unnamed(vec![Type::U16])),
+                ])),
+            })]),
+        })
+    }
+
+    #[test]
+    fn test_decode_event_with_schema() {
+        let decoder = OutputDecoder::new(Some(test_schema()), "test");
+        assert_eq!(decoder.decode_event(&[1, 3, 0]), DecodedValue::Schema {
+            value: json!({"Bar": [3]}),
+        });
+        assert!(
+            matches!(decoder.decode_event(&[1, 3, 0, 0]), DecodedValue::Raw { .. }),
+            "Trailing bytes should not be accepted."
+        );
+        assert!(
+            matches!(decoder.decode_event(&[2]), DecodedValue::Raw { .. }),
+            "Unknown variants should not be accepted."
+        );
+    }
+
+    #[test]
+    fn test_decode_cis2_event_fallback() {
+        let decoder = OutputDecoder::new(None, "test");
+        // A mint event of 100 tokens with the id 0x01 to the contract <2, 0>.
+        let mut event = vec![254, 1, 1, 100, 1];
+        event.extend_from_slice(&2u64.to_le_bytes());
+        event.extend_from_slice(&0u64.to_le_bytes());
+        assert_eq!(decoder.decode_event(&event), DecodedValue::Cis2 {
+            value: json!({"Mint": {
+                "token_id": "01",
+                "amount": "100",
+                "owner": {"Contract": [{"index": 2, "subindex": 0}]}
+            }}),
+        });
+        assert!(
+            matches!(decoder.decode_event(&[0, 1, 2]), DecodedValue::Raw { .. }),
+            "Non-standard events should not be decoded."
+        );
+    }
+
+    #[test]
+    fn test_decode_event_unknown_contract() {
+        let decoder = OutputDecoder::new(Some(test_schema()), "other");
+        assert!(
+            matches!(decoder.decode_event(&[254, 1, 1, 100, 1]), DecodedValue::Raw { .. }),
+            "Events should not be decoded if the schema does not describe the contract."
+        );
+    }
+
+    #[test]
+    fn test_decode_init_success() {
+        let decoder = OutputDecoder::new(Some(test_schema()), "test");
+        let result = InitResult::Success {
+            logs:             v0::Logs::new(),
+            return_value:     vec![7, 0, 0, 0],
+            remaining_energy: 0.into(),
+            state:            crate::v1::trie::MutableState::initial_state(),
+            trace:            (),
+        };
+        assert_eq!(decoder.decode_init_result(&result), DecodedResult::Success {
+            events:       Vec::new(),
+            return_value: DecodedValue::Schema {
+                value: json!(7),
+            },
+        });
+    }
+
+    #[test]
+    fn test_decode_init_reject() {
+        let decoder = OutputDecoder::new(Some(test_schema()), "test");
+        let result = InitResult::Reject {
+            reason:           -1,
+            return_value:     vec![7],
+            remaining_energy: 0.into(),
+            trace:            (),
+        };
+        assert_eq!(decoder.decode_init_result(&result), DecodedResult::Reject {
+            reason: -1,
+            error:  DecodedValue::Schema {
+                value: json!(7),
+            },
+        });
+    }
+
+    #[test]
+    fn test_decode_from_module_without_event_schema() {
+        let data =
+            std::fs::read("../testdata/schemas/cis2-wccd-embedded-schema-v1-versioned.wasm.v1")
+                .expect("Could not read file.");
+        let decoder = OutputDecoder::from_module_source(&data[8..], "CIS2-wCCD")
+            .expect("The embedded schema should be valid.");
+        assert!(decoder.schema.is_some(), "The module has an embedded schema.");
+        // A burn event of 5 tokens with the empty id from the account 0x00..00.
+        let mut event = vec![253, 0, 5, 0];
+        event.extend_from_slice(&[0u8; 32]);
+        let decoded = decoder.decode_event(&event);
+        assert!(
+            matches!(decoded, DecodedValue::Cis2 { .. }),
+            "Events should be decoded as CIS-2 events, got {:?}.",
+            decoded
+        );
+    }
+}
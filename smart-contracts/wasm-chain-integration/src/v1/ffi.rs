@@ -409,8 +409,10 @@ unsafe extern "C" fn validate_and_process_v1(
         allow_sign_extension_instr: allow_sign_extension_instr != 0,
     };
     let allowed_imports = &ConcordiumAllowedImports {
-        support_upgrade: support_upgrade == 1,
-        enable_debug:    false, // we don't allow debugging when running as part of the chain.
+        support_upgrade:            support_upgrade == 1,
+        // we don't allow debugging when running as part of the chain.
+        enable_debug:               false,
+        support_secp256k1_recovery: false, // not yet supported by any protocol version.
     };
 
     let metered = match metering_version {
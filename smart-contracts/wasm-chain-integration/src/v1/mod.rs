@@ -30,6 +30,7 @@ mod crypto_primitives_tests;
 #[cfg(test)]
 mod tests;
 
+pub mod decode;
 #[cfg(feature = "enable-ffi")]
 mod ffi;
 pub mod trie;
@@ -1259,6 +1260,57 @@ pub(crate) mod host {
         Ok(())
     }
 
+    #[cfg_attr(not(feature = "fuzz-coverage"), inline)]
+    /// Recover the public key from a recoverable ecdsa signature over
+    /// secp256k1. The signature is given in compact form (64 bytes) together
+    /// with a recovery id which must be between 0 and 3. The message is 32
+    /// bytes. If recovery succeeds the public key is written in compressed form
+    /// (33 bytes) to the output location and 1 is returned, otherwise 0 is
+    /// returned and memory is not modified.
+    pub(crate) fn recover_ecdsa_secp256k1_public_key(
+        memory: &mut Vec<u8>,
+        stack: &mut machine::RuntimeStack,
+        energy: &mut InterpreterEnergy,
+    ) -> machine::RunResult<()> {
+        let output_start = unsafe { stack.pop_u32() };
+        let message_start = unsafe { stack.pop_u32() };
+        let recovery_id = unsafe { stack.pop_u32() };
+        let signature_start = unsafe { stack.pop_u32() };
+        let message_end = message_start as usize + 32;
+        ensure!(message_end <= memory.len(), "Illegal memory access.");
+        let signature_end = signature_start as usize + 64;
+        ensure!(signature_end <= memory.len(), "Illegal memory access.");
+        let output_end = output_start as usize + 33;
+        ensure!(output_end <= memory.len(), "Illegal memory access.");
+        // expensive operations start now.
+        energy.tick_energy(constants::RECOVER_ECDSA_SECP256K1_COST)?;
+        let recovery_id = i32::try_from(recovery_id)
+            .ok()
+            .and_then(|id| secp256k1::ecdsa::RecoveryId::from_i32(id).ok());
+        let signature = recovery_id.and_then(|recovery_id| {
+            secp256k1::ecdsa::RecoverableSignature::from_compact(
+                &memory[signature_start as usize..signature_end],
+                recovery_id,
+            )
+            .ok()
+        });
+        let message = secp256k1::Message::from_slice(&memory[message_start as usize..message_end]);
+        match (signature, message) {
+            (Some(signature), Ok(message)) => {
+                let verifier = secp256k1::Secp256k1::verification_only();
+                if let Ok(public_key) = verifier.recover_ecdsa(&message, &signature) {
+                    memory[output_start as usize..output_end]
+                        .copy_from_slice(&public_key.serialize());
+                    stack.push_value(1u32);
+                } else {
+                    stack.push_value(0u32);
+                }
+            }
+            _ => stack.push_value(0u32),
+        }
+        Ok(())
+    }
+
     #[cfg_attr(not(feature = "fuzz-coverage"), inline)]
     pub(crate) fn hash_sha2_256(
         memory: &mut Vec<u8>,
@@ -1455,6 +1507,9 @@ impl<
                 CommonFunc::VerifySecp256k1 => {
                     host::verify_ecdsa_secp256k1_signature(memory, stack, &mut self.energy)
                 }
+                CommonFunc::RecoverSecp256k1 => {
+                    host::recover_ecdsa_secp256k1_public_key(memory, stack, &mut self.energy)
+                }
                 CommonFunc::DebugPrint => {
                     host::debug_print(&mut self.trace, memory, stack, &mut self.energy)
                 }
@@ -1629,6 +1684,9 @@ impl<
                 CommonFunc::VerifySecp256k1 => {
                     host::verify_ecdsa_secp256k1_signature(memory, stack, &mut self.energy)
                 }
+                CommonFunc::RecoverSecp256k1 => {
+                    host::recover_ecdsa_secp256k1_public_key(memory, stack, &mut self.energy)
+                }
                 CommonFunc::DebugPrint => {
                     host::debug_print(&mut self.trace, memory, stack, &mut self.energy)
                 }
@@ -1978,18 +2036,22 @@ pub fn invoke_init_from_artifact<BackingStore: BackingStoreLoad, A: DebugInfo>(
 /// Common data used by the `invoke_*_from_source` family of functions.
 pub struct InvokeFromSourceCtx<'a> {
     /// The source Wasm module.
-    pub source:          &'a [u8],
+    pub source:                     &'a [u8],
     /// Amount to invoke with.
-    pub amount:          Amount,
+    pub amount:                     Amount,
     /// Parameter to supply to the call.
-    pub parameter:       ParameterRef<'a>,
+    pub parameter:                  ParameterRef<'a>,
     /// Energy to allow for execution.
-    pub energy:          InterpreterEnergy,
+    pub energy:                     InterpreterEnergy,
     /// Whether the module should be processed to allow upgrades or not.
     /// Upgrades are only allowed in protocol P5 and later. If this is set to
     /// `false` then parsing and validation will reject modules that use the
     /// `upgrade` function.
-    pub support_upgrade: bool,
+    pub support_upgrade:            bool,
+    /// Whether the module should be processed to allow the
+    /// `recover_ecdsa_secp256k1_public_key` host function. If this is set to
+    /// `false` then parsing and validation will reject modules that use it.
+    pub support_secp256k1_recovery: bool,
 }
 
 /// Invokes an init-function from a **serialized** Wasm module.
@@ -2005,8 +2067,9 @@ pub fn invoke_init_from_source<BackingStore: BackingStoreLoad, A: DebugInfo>(
     let artifact = utils::instantiate(
         validation_config,
         &ConcordiumAllowedImports {
-            support_upgrade: ctx.support_upgrade,
-            enable_debug:    A::ENABLE_DEBUG,
+            support_upgrade:            ctx.support_upgrade,
+            enable_debug:               A::ENABLE_DEBUG,
+            support_secp256k1_recovery: ctx.support_secp256k1_recovery,
         },
         ctx.source,
     )?
@@ -2042,8 +2105,9 @@ pub fn invoke_init_with_metering_from_source<BackingStore: BackingStoreLoad, A:
         validation_config,
         cost_config,
         &ConcordiumAllowedImports {
-            support_upgrade: ctx.support_upgrade,
-            enable_debug:    A::ENABLE_DEBUG,
+            support_upgrade:            ctx.support_upgrade,
+            enable_debug:               A::ENABLE_DEBUG,
+            support_secp256k1_recovery: ctx.support_secp256k1_recovery,
         },
         ctx.source,
     )?
@@ -2456,8 +2520,9 @@ pub fn invoke_receive_from_source<
     let artifact = utils::instantiate(
         validation_config,
         &ConcordiumAllowedImports {
-            support_upgrade: ctx.support_upgrade,
-            enable_debug:    A::ENABLE_DEBUG,
+            support_upgrade:            ctx.support_upgrade,
+            enable_debug:               A::ENABLE_DEBUG,
+            support_secp256k1_recovery: ctx.support_secp256k1_recovery,
         },
         ctx.source,
     )?
@@ -2498,8 +2563,9 @@ pub fn invoke_receive_with_metering_from_source<
         validation_config,
         cost_config,
         &ConcordiumAllowedImports {
-            support_upgrade: ctx.support_upgrade,
-            enable_debug:    A::ENABLE_DEBUG,
+            support_upgrade:            ctx.support_upgrade,
+            enable_debug:               A::ENABLE_DEBUG,
+            support_secp256k1_recovery: ctx.support_secp256k1_recovery,
         },
         ctx.source,
     )?
@@ -112,7 +112,14 @@ impl PrefixesMap {
     #[cfg(test)]
     pub fn is_empty(&self) -> bool { self.root.is_none() }
 
+    #[cfg(test)]
     pub fn insert(&mut self, key: &[u8]) -> Result<(), TooManyIterators> {
+        self.insert_bounded(key, u32::MAX)
+    }
+
+    /// Insert the key, failing if it already occurs `max_count` times in the
+    /// map.
+    pub fn insert_bounded(&mut self, key: &[u8], max_count: u32) -> Result<(), TooManyIterators> {
         let mut node_idx = if let Some(root) = self.root {
             root
         } else {
@@ -144,7 +151,10 @@ impl PrefixesMap {
         }
         let node = self.nodes.get_mut(node_idx).expect("Invariant violation: node does not exist.");
         if let Some(value) = node.value {
-            let new_value = value.get().checked_add(1).ok_or(TooManyIterators)?;
+            if value.get() >= max_count {
+                return Err(TooManyIterators);
+            }
+            let new_value = value.get() + 1;
             node.value = Some(unsafe { NonZeroU32::new_unchecked(new_value) });
         } else {
             node.value = Some(unsafe { NonZeroU32::new_unchecked(1) });
@@ -1301,6 +1311,10 @@ pub struct MutableTrie {
     /// List of all the nodes for all generations. Nodes for new generations are
     /// always added at the end.
     nodes:           Vec<MutableNode>,
+    /// Maximum number of iterators that may be acquired at the same root in a
+    /// generation. Tests lower this so that [`TooManyIterators`] can be
+    /// exercised, otherwise the limit is [`u32::MAX`].
+    max_iterators:   u32,
 }
 
 #[derive(Debug)]
@@ -2015,6 +2029,7 @@ impl CachedRef<Hashed<Node>> {
             nodes: vec![root_node],
             borrowed_values,
             entries,
+            max_iterators: u32::MAX,
         }
     }
 
@@ -2098,9 +2113,17 @@ impl MutableTrie {
             nodes:           Vec::new(),
             borrowed_values: Vec::new(),
             entries:         Vec::new(),
+            max_iterators:   u32::MAX,
         }
     }
 
+    /// Set the maximum number of iterators that may be acquired at the same
+    /// root. This is [`u32::MAX`] by default.
+    #[cfg(any(test, feature = "fuzz"))]
+    pub(crate) fn set_max_iterators(&mut self, max_iterators: u32) {
+        self.max_iterators = max_iterators;
+    }
+
     /// Check whether the current generation is an empty tree.
     pub fn is_empty(&self) -> bool { self.generations.last().map_or(false, |x| x.root.is_none()) }
 }
@@ -2310,6 +2333,7 @@ impl MutableTrie {
         let owned_nodes = &mut self.nodes;
         let borrowed_values = &mut self.borrowed_values;
         let entries = &mut self.entries;
+        let max_iterators = self.max_iterators;
         let generation = if let Some(generation) = self.generations.last_mut() {
             generation
         } else {
@@ -2326,7 +2350,7 @@ impl MutableTrie {
             let mut stem_iter = node.path.iter();
             match follow_stem(&mut key_iter, &mut stem_iter) {
                 FollowStem::Equal => {
-                    generation.iterator_roots.insert(key)?;
+                    generation.iterator_roots.insert_bounded(key, max_iterators)?;
                     return Ok(Some(Iterator {
                         root:         key.into(),
                         current_node: node_idx,
@@ -2339,7 +2363,7 @@ impl MutableTrie {
                 FollowStem::KeyIsPrefix {
                     stem_step,
                 } => {
-                    generation.iterator_roots.insert(key)?;
+                    generation.iterator_roots.insert_bounded(key, max_iterators)?;
                     let root: Box<[u8]> = key.into();
                     let mut key: MutStem = key.into();
                     key.push(stem_step);
@@ -26,6 +26,11 @@ pub(crate) use low_level::Iterator;
 pub mod low_level;
 mod types;
 pub use types::*;
+// Model-based testing of the trie. This is used both by the tests and by the
+// fuzzer.
+#[cfg(any(test, feature = "fuzz"))]
+#[doc(hidden)]
+pub mod model;
 
 pub use low_level::{MutableTrie, Node};
 
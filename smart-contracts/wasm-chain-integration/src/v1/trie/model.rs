@@ -0,0 +1,511 @@
+//! Model-based testing of the [`MutableTrie`] against a simple reference
+//! implementation based on a [`BTreeMap`].
+//!
+//! A sequence of [`TrieOp`]s is applied both to the trie and to the model, and
+//! the observable results of each operation are compared. This is used by the
+//! property tests in this crate, as well as by the `trie` fuzz target.
+use super::{low_level::*, types::*};
+use anyhow::{ensure, Context};
+#[cfg(feature = "fuzz")]
+use arbitrary::Arbitrary;
+use std::collections::{BTreeMap, VecDeque};
+
+/// Maximum number of iterators at the same root used in the tests. This is
+/// much lower than the default so that the [`TooManyIterators`] error can be
+/// observed.
+const MAX_ITERATORS: u32 = 4;
+
+/// An operation on the trie. Iterators are referred to by their index in the
+/// list of iterators created so far, modulo the number of iterators, so that
+/// any sequence of operations is meaningful.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+pub enum TrieOp {
+    /// Insert a value at the given key.
+    Insert {
+        key:   Vec<u8>,
+        value: Vec<u8>,
+    },
+    /// Delete the value at the given key.
+    Delete {
+        key: Vec<u8>,
+    },
+    /// Delete all the keys with the given prefix.
+    DeletePrefix {
+        prefix: Vec<u8>,
+    },
+    /// Look up the value at the given key.
+    Lookup {
+        key: Vec<u8>,
+    },
+    /// Create an iterator at the given prefix, locking that part of the trie.
+    Iter {
+        prefix: Vec<u8>,
+    },
+    /// Advance an iterator.
+    Next {
+        iterator: usize,
+    },
+    /// Delete an iterator, unlocking the part of the trie it locked.
+    DeleteIter {
+        iterator: usize,
+    },
+    /// Start a new generation.
+    NewGeneration,
+    /// Drop the most recent generation, if there is more than one.
+    PopGeneration,
+    /// Freeze the trie and thaw it again.
+    Freeze,
+    /// Freeze the trie, store it, and load it again from the backing store.
+    StoreLoad,
+    /// Freeze the trie, store it, and migrate it to a fresh backing store.
+    Migrate,
+}
+
+/// The observable result of applying a [`TrieOp`].
+#[derive(Debug, PartialEq, Eq)]
+enum Observation {
+    /// Whether the key existed, or whether an error occurred.
+    Modify(Result<bool, AttemptToModifyLockedArea>),
+    Lookup(Option<Vec<u8>>),
+    /// Whether an iterator was created, or whether an error occurred.
+    Iter(Result<bool, TooManyIterators>),
+    /// The key and value returned by the iterator.
+    Next(Option<(Vec<u8>, Vec<u8>)>),
+    /// Whether the iterator was removed.
+    DeleteIter(bool),
+    /// Whether the generation was dropped.
+    PopGeneration(bool),
+    /// The contents of the trie after freezing, and whether the hash of the
+    /// trie was preserved by storing.
+    Frozen {
+        contents:       Vec<(Vec<u8>, Vec<u8>)>,
+        hash_preserved: bool,
+    },
+    /// The operation did not apply, e.g., because it referred to an iterator
+    /// that does not exist.
+    Skipped,
+}
+
+/// The model of an iterator.
+struct ModelIterator {
+    /// The prefix the iterator was created at.
+    root:       Vec<u8>,
+    /// The generation the iterator was created in.
+    generation: usize,
+    /// The keys the iterator has yet to return. Since the area of the trie
+    /// covered by the iterator is locked these cannot change while the
+    /// iterator exists.
+    remaining:  VecDeque<Vec<u8>>,
+    /// Whether the iterator has been deleted.
+    deleted:    bool,
+}
+
+/// A generation of the model.
+#[derive(Default, Clone)]
+struct ModelGeneration {
+    map:            BTreeMap<Vec<u8>, Vec<u8>>,
+    /// Roots of the live iterators in this generation, with multiplicity.
+    iterator_roots: BTreeMap<Vec<u8>, u32>,
+}
+
+impl ModelGeneration {
+    /// Whether the key extends the root of a live iterator.
+    fn is_locked(&self, key: &[u8]) -> bool {
+        self.iterator_roots.keys().any(|root| key.starts_with(root))
+    }
+
+    /// Whether the key extends, or is extended by, the root of a live
+    /// iterator.
+    fn is_or_has_locked_prefix(&self, key: &[u8]) -> bool {
+        self.iterator_roots.keys().any(|root| key.starts_with(root) || root.starts_with(key))
+    }
+
+    fn keys_with_prefix(&self, prefix: &[u8]) -> VecDeque<Vec<u8>> {
+        self.map
+            .range(prefix.to_vec()..)
+            .map(|(k, _)| k)
+            .take_while(|k| k.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+}
+
+/// The trie under test together with the model it is compared to.
+pub struct ModelTest {
+    trie:        MutableTrie,
+    loader:      Loader<Vec<u8>>,
+    /// Iterators of the trie, paired with their models.
+    iterators:   Vec<(Iterator, ModelIterator)>,
+    /// The generations of the model. There is always at least one.
+    generations: Vec<ModelGeneration>,
+}
+
+impl Default for ModelTest {
+    fn default() -> Self { Self::new() }
+}
+
+impl ModelTest {
+    /// Start with an empty trie and an empty backing store.
+    pub fn new() -> Self {
+        Self {
+            trie:        Self::empty_trie(),
+            loader:      Loader {
+                inner: Vec::new(),
+            },
+            iterators:   Vec::new(),
+            generations: vec![ModelGeneration::default()],
+        }
+    }
+
+    fn empty_trie() -> MutableTrie {
+        let mut trie = MutableTrie::empty();
+        trie.set_max_iterators(MAX_ITERATORS);
+        trie
+    }
+
+    /// Thaw the frozen trie and make it the trie under test.
+    fn thaw(&mut self, frozen: &CachedRef<Hashed<Node>>) {
+        self.trie = frozen.make_mutable(0, &mut self.loader);
+        self.trie.set_max_iterators(MAX_ITERATORS);
+    }
+
+    fn current(&mut self) -> &mut ModelGeneration {
+        self.generations.last_mut().expect("There is always at least one generation.")
+    }
+
+    /// Index of the iterator referred to by the operation, if any.
+    fn iterator_index(&self, iterator: usize) -> Option<usize> {
+        if self.iterators.is_empty() {
+            None
+        } else {
+            Some(iterator % self.iterators.len())
+        }
+    }
+
+    /// Apply the operation to both the trie and the model, and check that the
+    /// observable results agree.
+    pub fn apply(&mut self, op: &TrieOp) -> anyhow::Result<()> {
+        let (expected, actual) = match op {
+            TrieOp::Insert {
+                key,
+                value,
+            } => {
+                let current = self.current();
+                let expected = if current.is_locked(key) {
+                    Err(AttemptToModifyLockedArea)
+                } else {
+                    Ok(current.map.insert(key.clone(), value.clone()).is_some())
+                };
+                let actual = self
+                    .trie
+                    .insert(&mut self.loader, key, value.clone())
+                    .map(|(_, existed)| existed);
+                (Observation::Modify(expected), Observation::Modify(actual))
+            }
+            TrieOp::Delete {
+                key,
+            } => {
+                let current = self.current();
+                // Deleting from an empty trie always succeeds, even if the key is locked.
+                let expected = if current.map.is_empty() {
+                    Ok(false)
+                } else if current.is_locked(key) {
+                    Err(AttemptToModifyLockedArea)
+                } else {
+                    Ok(current.map.remove(key).is_some())
+                };
+                let actual = self.trie.delete(&mut self.loader, key);
+                (Observation::Modify(expected), Observation::Modify(actual))
+            }
+            TrieOp::DeletePrefix {
+                prefix,
+            } => {
+                let current = self.current();
+                let expected = if current.map.is_empty() {
+                    Ok(false)
+                } else if current.is_or_has_locked_prefix(prefix) {
+                    Err(AttemptToModifyLockedArea)
+                } else {
+                    let to_delete = current.keys_with_prefix(prefix);
+                    for key in to_delete.iter() {
+                        current.map.remove(key);
+                    }
+                    Ok(!to_delete.is_empty())
+                };
+                let actual =
+                    match self.trie.delete_prefix(&mut self.loader, prefix, &mut EmptyCounter) {
+                        Ok(r) => r,
+                        Err(e) => match e {},
+                    };
+                (Observation::Modify(expected), Observation::Modify(actual))
+            }
+            TrieOp::Lookup {
+                key,
+            } => {
+                let expected = self.current().map.get(key).cloned();
+                let actual = self.trie.get_entry(&mut self.loader, key).and_then(|entry| {
+                    self.trie.with_entry(entry, &mut self.loader, |v| v.to_vec())
+                });
+                (Observation::Lookup(expected), Observation::Lookup(actual))
+            }
+            TrieOp::Iter {
+                prefix,
+            } => {
+                let generation = self.generations.len() - 1;
+                let current = self.current();
+                let remaining = current.keys_with_prefix(prefix);
+                let expected = if remaining.is_empty() {
+                    Ok(false)
+                } else {
+                    let count = current.iterator_roots.entry(prefix.clone()).or_insert(0);
+                    if *count >= MAX_ITERATORS {
+                        Err(TooManyIterators)
+                    } else {
+                        *count += 1;
+                        Ok(true)
+                    }
+                };
+                let actual = match self.trie.iter(&mut self.loader, prefix) {
+                    Ok(Some(iterator)) => {
+                        self.iterators.push((iterator, ModelIterator {
+                            root: prefix.clone(),
+                            generation,
+                            remaining,
+                            deleted: false,
+                        }));
+                        Ok(true)
+                    }
+                    Ok(None) => Ok(false),
+                    Err(e) => Err(e),
+                };
+                (Observation::Iter(expected), Observation::Iter(actual))
+            }
+            TrieOp::Next {
+                iterator,
+            } => {
+                let generation = self.generations.len() - 1;
+                match self.iterator_index(*iterator) {
+                    // Iterators are only advanced in the generation they were created in.
+                    // Operations in later generations may have invalidated their position.
+                    Some(idx)
+                        if self.iterators[idx].1.generation == generation
+                            && !self.iterators[idx].1.deleted =>
+                    {
+                        let (iter, model) = &mut self.iterators[idx];
+                        let expected = model.remaining.pop_front().map(|k| {
+                            let v = self.generations[generation].map[&k].clone();
+                            (k, v)
+                        });
+                        let entry = match self.trie.next(&mut self.loader, iter, &mut EmptyCounter)
+                        {
+                            Ok(entry) => entry,
+                            Err(e) => match e {},
+                        };
+                        let actual = match entry {
+                            Some(entry) => {
+                                let value = self
+                                    .trie
+                                    .with_entry(entry, &mut self.loader, |v| v.to_vec())
+                                    .context("Entry returned by the iterator does not exist.")?;
+                                Some((iter.get_key().to_vec(), value))
+                            }
+                            None => None,
+                        };
+                        (Observation::Next(expected), Observation::Next(actual))
+                    }
+                    _ => (Observation::Skipped, Observation::Skipped),
+                }
+            }
+            TrieOp::DeleteIter {
+                iterator,
+            } => {
+                let generation = self.generations.len() - 1;
+                match self.iterator_index(*iterator) {
+                    Some(idx)
+                        if self.iterators[idx].1.generation == generation
+                            && !self.iterators[idx].1.deleted =>
+                    {
+                        let (iter, model) = &mut self.iterators[idx];
+                        model.deleted = true;
+                        let roots = &mut self.generations[generation].iterator_roots;
+                        let count =
+                            roots.get_mut(&model.root).context("Live iterator has no root.")?;
+                        *count -= 1;
+                        if *count == 0 {
+                            roots.remove(&model.root);
+                        }
+                        let actual = self.trie.delete_iter(iter);
+                        (Observation::DeleteIter(true), Observation::DeleteIter(actual))
+                    }
+                    _ => (Observation::Skipped, Observation::Skipped),
+                }
+            }
+            TrieOp::NewGeneration => {
+                // Iterators are not carried over to the new generation.
+                let map = self.current().map.clone();
+                self.generations.push(ModelGeneration {
+                    map,
+                    iterator_roots: BTreeMap::new(),
+                });
+                self.trie.new_generation();
+                (Observation::Skipped, Observation::Skipped)
+            }
+            TrieOp::PopGeneration => {
+                if self.generations.len() > 1 {
+                    self.generations.pop();
+                    let generation = self.generations.len() - 1;
+                    self.iterators.retain(|(_, model)| model.generation <= generation);
+                    self.trie.normalize(generation as u32);
+                    (Observation::PopGeneration(true), Observation::PopGeneration(true))
+                } else {
+                    (Observation::PopGeneration(false), Observation::PopGeneration(false))
+                }
+            }
+            TrieOp::Freeze => {
+                let expected = self.expected_frozen();
+                let actual = match self.freeze() {
+                    Some(frozen) => {
+                        self.thaw(&frozen);
+                        self.contents()?
+                    }
+                    None => Vec::new(),
+                };
+                (expected, Observation::Frozen {
+                    contents:       actual,
+                    hash_preserved: true,
+                })
+            }
+            TrieOp::StoreLoad => {
+                let expected = self.expected_frozen();
+                let (actual, hash_preserved) = match self.freeze() {
+                    Some(mut frozen) => {
+                        let hash = frozen.hash(&mut self.loader);
+                        let top = frozen
+                            .store_update(&mut self.loader.inner)
+                            .context("Storing should succeed.")?;
+                        let root = self
+                            .loader
+                            .inner
+                            .store_raw(&top)
+                            .context("Storing to a vector should succeed.")?;
+                        let loaded =
+                            CachedRef::<Hashed<Node>>::load_from_location(&mut self.loader, root)
+                                .context("Could not load the stored trie.")?;
+                        let hash_preserved = hash == loaded.hash(&mut self.loader);
+                        self.thaw(&loaded);
+                        (self.contents()?, hash_preserved)
+                    }
+                    None => (Vec::new(), true),
+                };
+                (expected, Observation::Frozen {
+                    contents: actual,
+                    hash_preserved,
+                })
+            }
+            TrieOp::Migrate => {
+                let expected = self.expected_frozen();
+                let (actual, hash_preserved) = match self.freeze() {
+                    Some(frozen) => {
+                        let hash = frozen.hash(&mut self.loader);
+                        let mut new_store = Vec::new();
+                        let migrated = frozen
+                            .migrate(&mut new_store, &mut self.loader)
+                            .context("Migration should succeed.")?;
+                        self.loader = Loader {
+                            inner: new_store,
+                        };
+                        let hash_preserved = hash == migrated.hash(&mut self.loader);
+                        self.thaw(&migrated);
+                        (self.contents()?, hash_preserved)
+                    }
+                    None => (Vec::new(), true),
+                };
+                (expected, Observation::Frozen {
+                    contents: actual,
+                    hash_preserved,
+                })
+            }
+        };
+        ensure!(
+            expected == actual,
+            "Trie result {:?} differs from the model result {:?}.",
+            actual,
+            expected
+        );
+        Ok(())
+    }
+
+    /// Freeze the trie, leaving an empty trie in its place. All the
+    /// iterators are invalidated.
+    fn freeze(&mut self) -> Option<CachedRef<Hashed<Node>>> {
+        let trie = std::mem::replace(&mut self.trie, Self::empty_trie());
+        self.iterators.clear();
+        trie.freeze(&mut self.loader, &mut EmptyCollector)
+    }
+
+    /// Update the model to reflect freezing the trie, and return the expected
+    /// observation. Freezing retains only the current generation and drops all
+    /// the iterators.
+    fn expected_frozen(&mut self) -> Observation {
+        let map = std::mem::take(&mut self.current().map);
+        let contents = map.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        self.generations = vec![ModelGeneration {
+            map,
+            iterator_roots: BTreeMap::new(),
+        }];
+        Observation::Frozen {
+            contents,
+            hash_preserved: true,
+        }
+    }
+
+    /// Get all the entries of the trie in order by iterating over it. This
+    /// must only be used when there are no live iterators.
+    fn contents(&mut self) -> anyhow::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut out = Vec::new();
+        let mut iter = match self
+            .trie
+            .iter(&mut self.loader, &[])
+            .context("The only iterator cannot overflow.")?
+        {
+            Some(iter) => iter,
+            None => return Ok(out),
+        };
+        loop {
+            let entry = match self.trie.next(&mut self.loader, &mut iter, &mut EmptyCounter) {
+                Ok(entry) => entry,
+                Err(e) => match e {},
+            };
+            if let Some(entry) = entry {
+                let value = self
+                    .trie
+                    .with_entry(entry, &mut self.loader, |v| v.to_vec())
+                    .context("Entry returned by the iterator does not exist.")?;
+                out.push((iter.get_key().to_vec(), value));
+            } else {
+                break;
+            }
+        }
+        ensure!(self.trie.delete_iter(&iter), "Could not delete the iterator.");
+        Ok(out)
+    }
+}
+
+/// Apply the operations in sequence, comparing the trie to the model after
+/// each one. Returns an error describing the first operation whose result
+/// differs.
+pub fn run_model(ops: &[TrieOp]) -> anyhow::Result<()> {
+    let mut test = ModelTest::new();
+    for (i, op) in ops.iter().enumerate() {
+        test.apply(op).with_context(|| format!("Operation {} ({:?}) failed.", i, op))?;
+    }
+    // Finally make sure that the entire contents agree.
+    test.apply(&TrieOp::Freeze).context("Final comparison failed.")
+}
+
+/// Construct a sequence of operations from raw fuzzer input.
+#[cfg(feature = "fuzz")]
+pub fn ops_from_bytes(data: &[u8]) -> arbitrary::Result<Vec<TrieOp>> {
+    Vec::<TrieOp>::arbitrary(&mut arbitrary::Unstructured::new(data))
+}
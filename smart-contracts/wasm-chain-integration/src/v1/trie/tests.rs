@@ -1064,3 +1064,62 @@ fn prop_iterator_get_key() {
     };
     QuickCheck::new().tests(NUM_TESTS).quickcheck(prop as fn(_, _) -> anyhow::Result<()>);
 }
+
+/// Generate a key from a small alphabet so that generated keys frequently
+/// share prefixes and collide.
+fn arbitrary_key(g: &mut Gen) -> Vec<u8> {
+    let len = usize::arbitrary(g) % 5;
+    (0..len).map(|_| *g.choose(&[0x00, 0x01, 0x10, 0x11, 0xff]).unwrap()).collect()
+}
+
+/// Generate a short prefix so that iterators are frequently created at the same
+/// root.
+fn arbitrary_prefix(g: &mut Gen) -> Vec<u8> {
+    let mut prefix = arbitrary_key(g);
+    prefix.truncate(usize::arbitrary(g) % 2);
+    prefix
+}
+
+impl Arbitrary for model::TrieOp {
+    fn arbitrary(g: &mut Gen) -> Self {
+        use model::TrieOp::*;
+        match u8::arbitrary(g) % 20 {
+            0..=5 => Insert {
+                key:   arbitrary_key(g),
+                value: Vec::arbitrary(g),
+            },
+            6..=7 => Delete {
+                key: arbitrary_key(g),
+            },
+            8 => DeletePrefix {
+                prefix: arbitrary_key(g),
+            },
+            9 => Lookup {
+                key: arbitrary_key(g),
+            },
+            10..=11 => Iter {
+                prefix: arbitrary_prefix(g),
+            },
+            12..=13 => Next {
+                iterator: usize::arbitrary(g),
+            },
+            14 => DeleteIter {
+                iterator: usize::arbitrary(g),
+            },
+            15 => NewGeneration,
+            16 => PopGeneration,
+            17 => Freeze,
+            18 => StoreLoad,
+            _ => Migrate,
+        }
+    }
+}
+
+#[test]
+/// Check that the trie matches the model for arbitrary sequences of
+/// operations, including those that lock parts of the trie and those that
+/// acquire too many iterators.
+fn prop_matches_model() {
+    let prop = |ops: Vec<model::TrieOp>| -> anyhow::Result<()> { model::run_model(&ops) };
+    QuickCheck::new().tests(NUM_TESTS).quickcheck(prop as fn(Vec<_>) -> anyhow::Result<()>);
+}
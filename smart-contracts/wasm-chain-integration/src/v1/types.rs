@@ -349,6 +349,7 @@ pub enum CommonFunc {
     // Cryptographic functions
     VerifyEd25519,
     VerifySecp256k1,
+    RecoverSecp256k1,
     HashSHA2_256,
     HashSHA3_256,
     HashKeccak256,
@@ -381,6 +382,7 @@ impl std::fmt::Display for CommonFunc {
             CommonFunc::StateEntryResize => "state_entry_resize",
             CommonFunc::VerifyEd25519 => "verify_ed25519_signature",
             CommonFunc::VerifySecp256k1 => "verify_ecdsa_secp256k1_signature",
+            CommonFunc::RecoverSecp256k1 => "recover_ecdsa_secp256k1_public_key",
             CommonFunc::HashSHA2_256 => "hash_sha2_256",
             CommonFunc::HashSHA3_256 => "hash_sha3_256",
             CommonFunc::HashKeccak256 => "hash_keccak_256",
@@ -493,6 +495,7 @@ impl<'a, Ctx: Copy> Parseable<'a, Ctx> for ImportFunc {
             36 => Ok(ImportFunc::Common(CommonFunc::HashKeccak256)),
             255 => Ok(ImportFunc::Common(CommonFunc::DebugPrint)),
             37 => Ok(ImportFunc::ReceiveOnly(ReceiveOnlyFunc::Upgrade)),
+            38 => Ok(ImportFunc::Common(CommonFunc::RecoverSecp256k1)),
             tag => bail!("Unexpected ImportFunc tag {}.", tag),
         }
     }
@@ -524,6 +527,7 @@ impl Output for ImportFunc {
                 CommonFunc::WriteOutput => 22,
                 CommonFunc::VerifyEd25519 => 32,
                 CommonFunc::VerifySecp256k1 => 33,
+                CommonFunc::RecoverSecp256k1 => 38,
                 CommonFunc::HashSHA2_256 => 34,
                 CommonFunc::HashSHA3_256 => 35,
                 CommonFunc::HashKeccak256 => 36,
@@ -583,10 +587,13 @@ impl Output for ProcessedImports {
 pub struct ConcordiumAllowedImports {
     /// Whether to allow the `upgrade` function. This is supported in protocol
     /// P5 and up, but not before.
-    pub support_upgrade: bool,
+    pub support_upgrade:            bool,
     /// Allow host functions to enable debugging support. This is intended for
     /// off-chain use.
-    pub enable_debug:    bool,
+    pub enable_debug:               bool,
+    /// Whether to allow the `recover_ecdsa_secp256k1_public_key` function.
+    /// This is not yet supported by any protocol version.
+    pub support_secp256k1_recovery: bool,
 }
 
 impl validate::ValidateImportExport for ConcordiumAllowedImports {
@@ -635,6 +642,11 @@ impl validate::ValidateImportExport for ConcordiumAllowedImports {
                 "verify_ecdsa_secp256k1_signature" => {
                     type_matches!(ty => [I32, I32, I32]; I32)
                 }
+                // Public key recovery is only available if explicitly enabled.
+                "recover_ecdsa_secp256k1_public_key" => {
+                    self.support_secp256k1_recovery
+                        && type_matches!(ty => [I32, I32, I32, I32]; I32)
+                }
                 "hash_sha2_256" => type_matches!(ty => [I32, I32, I32]),
                 "hash_sha3_256" => type_matches!(ty => [I32, I32, I32]),
                 "hash_keccak_256" => type_matches!(ty => [I32, I32, I32]),
@@ -739,6 +751,9 @@ impl TryFromImport for ProcessedImports {
                 "verify_ecdsa_secp256k1_signature" => {
                     ImportFunc::Common(CommonFunc::VerifySecp256k1)
                 }
+                "recover_ecdsa_secp256k1_public_key" => {
+                    ImportFunc::Common(CommonFunc::RecoverSecp256k1)
+                }
                 "hash_sha2_256" => ImportFunc::Common(CommonFunc::HashSHA2_256),
                 "hash_sha3_256" => ImportFunc::Common(CommonFunc::HashSHA3_256),
                 "hash_keccak_256" => ImportFunc::Common(CommonFunc::HashKeccak256),
@@ -55,8 +55,9 @@ fn init_data_with_global_offset() {
     let res = instantiate::<ProcessedImports, _>(
         ValidationConfig::V0,
         &crate::v1::ConcordiumAllowedImports {
-            support_upgrade: true,
-            enable_debug:    false,
+            support_upgrade:            true,
+            enable_debug:               false,
+            support_secp256k1_recovery: false,
         },
         &contract,
     );
@@ -64,8 +65,9 @@ fn init_data_with_global_offset() {
     let res = instantiate::<ProcessedImports, _>(
         ValidationConfig::V1,
         &crate::v1::ConcordiumAllowedImports {
-            support_upgrade: true,
-            enable_debug:    false,
+            support_upgrade:            true,
+            enable_debug:               false,
+            support_secp256k1_recovery: false,
         },
         &contract,
     );
@@ -81,8 +83,9 @@ fn init_element_with_global_offset() {
     let res = instantiate::<ProcessedImports, _>(
         ValidationConfig::V0,
         &crate::v1::ConcordiumAllowedImports {
-            support_upgrade: true,
-            enable_debug:    false,
+            support_upgrade:            true,
+            enable_debug:               false,
+            support_secp256k1_recovery: false,
         },
         &contract,
     );
@@ -90,8 +93,9 @@ fn init_element_with_global_offset() {
     let res = instantiate::<ProcessedImports, _>(
         ValidationConfig::V1,
         &crate::v1::ConcordiumAllowedImports {
-            support_upgrade: true,
-            enable_debug:    false,
+            support_upgrade:            true,
+            enable_debug:               false,
+            support_secp256k1_recovery: false,
         },
         &contract,
     );
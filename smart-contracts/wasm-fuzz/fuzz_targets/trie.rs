@@ -0,0 +1,18 @@
+#![no_main]
+
+/// Fuzz target for the V1 contract state trie. The input is interpreted as a
+/// sequence of operations (insertions, deletions, iteration, generations,
+/// freezing, storing, and migration) that are applied both to the trie and to
+/// a simple reference model. The fuzzer reports a crash if any operation
+/// produces a result that differs from the model.
+use libfuzzer_sys::fuzz_target;
+
+use concordium_smart_contract_engine::v1::trie::model::{ops_from_bytes, run_model};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(ops) = ops_from_bytes(data) {
+        if let Err(e) = run_model(&ops) {
+            panic!("{:#}", e)
+        }
+    }
+});
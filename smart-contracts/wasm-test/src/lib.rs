@@ -0,0 +1,867 @@
+//! A runner for the Wasm specification test suite.
+//!
+//! The runner parses `.wast` scripts, validates and compiles the modules they
+//! define using [`concordium_wasm`], and executes the assertions against the
+//! interpreter. The result of each directive is reported as an
+//! [`AssertionResult`] so that conformance can be tracked as new Wasm
+//! proposals are enabled.
+//!
+//! Which proposals are enabled is determined by the [`ValidationConfig`] the
+//! runner is configured with. Directives that are known not to apply to a
+//! given configuration are listed in [`SKIP_LIST`], each entry being
+//! conditional on a [`Proposal`] being enabled or disabled.
+use anyhow::{bail, ensure};
+use concordium_wasm::{
+    artifact::{Artifact, ArtifactNamedImport, CompiledFunction},
+    machine::{ExecutionOutcome, Host, NoInterrupt, RunResult, RuntimeError, RuntimeStack, Value},
+    parse::ParseError,
+    types::{FunctionType, Module, Name},
+    validate::{ValidationConfig, ValidationError},
+};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use wast::{parser, AssertExpression, Expression, Span, Wast, WastDirective, WastExecute};
+
+/// A Wasm feature whose support is controlled by the [`ValidationConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Proposal {
+    /// The [sign extension operators](https://github.com/WebAssembly/sign-extension-ops/blob/master/proposals/sign-extension-ops/Overview.md)
+    /// proposal.
+    SignExtensionOps,
+    /// Restriction of `global.get` in constant expressions to imported
+    /// globals only, as required by the current version of the Wasm spec. See [issue](https://github.com/WebAssembly/spec/issues/1522)
+    /// on the Wasm spec repository.
+    ImportedGlobalsInInit,
+}
+
+impl Proposal {
+    /// All the proposals the runner knows about.
+    pub const ALL: [Proposal; 2] = [Proposal::SignExtensionOps, Proposal::ImportedGlobalsInInit];
+
+    /// Whether the proposal is enabled in the given configuration.
+    pub fn is_enabled(self, config: &ValidationConfig) -> bool {
+        match self {
+            Proposal::SignExtensionOps => config.allow_sign_extension_instr,
+            Proposal::ImportedGlobalsInInit => !config.allow_globals_in_init,
+        }
+    }
+
+    /// Opcodes introduced by the proposal. If the proposal is disabled then
+    /// modules using these opcodes are expected to be rejected by the parser.
+    pub fn opcodes(self) -> &'static [u8] {
+        match self {
+            Proposal::SignExtensionOps => &[0xC0, 0xC1, 0xC2, 0xC3, 0xC4],
+            Proposal::ImportedGlobalsInInit => &[],
+        }
+    }
+}
+
+impl std::fmt::Display for Proposal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Proposal::SignExtensionOps => write!(f, "sign-extension-ops"),
+            Proposal::ImportedGlobalsInInit => write!(f, "imported-globals-in-init"),
+        }
+    }
+}
+
+/// Instructions that are not supported by the interpreter regardless of the
+/// configuration, i.e., floating point instructions and the saturating
+/// truncation instructions. Modules containing these are expected to be
+/// rejected by the parser.
+pub const DISALLOWED_INSTRUCTIONS: &[u8] = &[
+    0x43, 0x44, 0x5B, 0x5C, 0x5D, 0x5E, 0x5F, 0x60, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x8B, 0x8C,
+    0x8D, 0x8E, 0x8F, 0x90, 0x91, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0x9B, 0x9C,
+    0x9D, 0x9E, 0x9F, 0xA0, 0xA1, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7, 0xA8, 0xA9, 0xAA, 0xAB, 0xAC,
+    0xAD, 0xAE, 0xAF, 0xB0, 0xB1, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xBB, 0xBC,
+    0xBD, 0xBE, 0xBF, 0xFC, // saturating truncation
+    0x2A, 0x2B,
+];
+
+/// The condition under which a [`Skip`] entry applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipCondition {
+    /// The entry always applies.
+    Always,
+    /// The entry applies if the proposal is enabled.
+    Enabled(Proposal),
+    /// The entry applies if the proposal is disabled.
+    Disabled(Proposal),
+}
+
+/// An entry in a skip list. Directives matching the entry are not executed
+/// and are reported as [`Outcome::Skipped`].
+#[derive(Debug, Clone)]
+pub struct Skip {
+    /// The name of the `.wast` file, e.g., `i32.wast`.
+    pub file:      &'static str,
+    /// The (1-based) line of the directive. If [`None`] the entire file is
+    /// skipped.
+    pub line:      Option<usize>,
+    /// When the entry applies.
+    pub condition: SkipCondition,
+    /// Why the directive is skipped.
+    pub reason:    &'static str,
+}
+
+impl Skip {
+    /// Whether the entry applies to the given configuration.
+    pub fn applies(&self, config: &ValidationConfig) -> bool {
+        match self.condition {
+            SkipCondition::Always => true,
+            SkipCondition::Enabled(p) => p.is_enabled(config),
+            SkipCondition::Disabled(p) => !p.is_enabled(config),
+        }
+    }
+
+    fn matches(&self, file: &str, line: usize) -> bool {
+        self.file == file && (self.line.is_none() || self.line == Some(line))
+    }
+}
+
+const REM_S_OVERFLOW: &str =
+    "The interpreter traps on `rem_s` of the minimum value by -1 instead of returning 0.";
+
+/// Directives of the test suite that are known not to apply to some
+/// configurations.
+pub const SKIP_LIST: &[Skip] = &[
+    // Known deviations of the interpreter from the spec. These are only
+    // observable if sign extension instructions are allowed since otherwise
+    // the modules containing the tests are rejected. The behaviour is part of
+    // the semantics of contracts on chain, so it can only be changed together
+    // with a protocol update.
+    Skip {
+        file:      "i32.wast",
+        line:      Some(109),
+        condition: SkipCondition::Always,
+        reason:    REM_S_OVERFLOW,
+    },
+    Skip {
+        file:      "i64.wast",
+        line:      Some(110),
+        condition: SkipCondition::Always,
+        reason:    REM_S_OVERFLOW,
+    },
+];
+
+/// Configuration of a test suite run.
+#[derive(Debug, Clone)]
+pub struct SpecTestConfig {
+    /// The configuration used to validate modules.
+    pub validation: ValidationConfig,
+    /// Directives that should not be executed.
+    pub skip:       Vec<Skip>,
+    /// If set, all the modules that are encoded from the test scripts are
+    /// written to this directory as `{file}-{line}.wasm`.
+    pub out_dir:    Option<PathBuf>,
+}
+
+impl SpecTestConfig {
+    /// Construct a configuration with the entries of [`SKIP_LIST`] that apply
+    /// to the given validation configuration.
+    pub fn new(validation: ValidationConfig) -> Self {
+        let skip = SKIP_LIST.iter().filter(|s| s.applies(&validation)).cloned().collect();
+        Self {
+            validation,
+            skip,
+            out_dir: None,
+        }
+    }
+
+    /// Whether the opcode is expected to be rejected by the parser in this
+    /// configuration.
+    fn is_disallowed_opcode(&self, opcode: u8) -> bool {
+        DISALLOWED_INSTRUCTIONS.contains(&opcode)
+            || Proposal::ALL
+                .iter()
+                .any(|p| !p.is_enabled(&self.validation) && p.opcodes().contains(&opcode))
+    }
+
+    fn find_skip(&self, file: &str, line: usize) -> Option<&Skip> {
+        self.skip.iter().find(|s| s.matches(file, line))
+    }
+}
+
+/// The kind of directive an [`AssertionResult`] is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssertionKind {
+    Module,
+    QuoteModule,
+    AssertMalformed,
+    AssertInvalid,
+    Register,
+    Invoke,
+    AssertTrap,
+    AssertReturn,
+    AssertExhaustion,
+    AssertUnlinkable,
+}
+
+impl std::fmt::Display for AssertionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssertionKind::Module => write!(f, "module"),
+            AssertionKind::QuoteModule => write!(f, "module quote"),
+            AssertionKind::AssertMalformed => write!(f, "assert_malformed"),
+            AssertionKind::AssertInvalid => write!(f, "assert_invalid"),
+            AssertionKind::Register => write!(f, "register"),
+            AssertionKind::Invoke => write!(f, "invoke"),
+            AssertionKind::AssertTrap => write!(f, "assert_trap"),
+            AssertionKind::AssertReturn => write!(f, "assert_return"),
+            AssertionKind::AssertExhaustion => write!(f, "assert_exhaustion"),
+            AssertionKind::AssertUnlinkable => write!(f, "assert_unlinkable"),
+        }
+    }
+}
+
+/// The outcome of running a single directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    Failed {
+        message: String,
+    },
+    /// The directive was not executed, either because it matched an entry
+    /// of the skip list, or because it uses features the interpreter does not
+    /// support.
+    Skipped {
+        reason: String,
+    },
+}
+
+/// The result of running a single directive of a test script.
+#[derive(Debug, Clone)]
+pub struct AssertionResult {
+    /// The (1-based) line of the directive.
+    pub line:    usize,
+    /// The (1-based) column of the directive.
+    pub column:  usize,
+    pub kind:    AssertionKind,
+    pub outcome: Outcome,
+}
+
+/// Results of running all the directives in a single test script.
+#[derive(Debug, Clone)]
+pub struct FileReport {
+    pub name:    String,
+    pub results: Vec<AssertionResult>,
+}
+
+impl FileReport {
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|r| r.outcome == Outcome::Passed).count()
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.results.iter().filter(|r| matches!(r.outcome, Outcome::Skipped { .. })).count()
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &AssertionResult> {
+        self.results.iter().filter(|r| matches!(r.outcome, Outcome::Failed { .. }))
+    }
+}
+
+/// Results of running a directory of test scripts.
+#[derive(Debug, Clone, Default)]
+pub struct SuiteReport {
+    pub files: Vec<FileReport>,
+}
+
+impl SuiteReport {
+    pub fn passed(&self) -> usize { self.files.iter().map(FileReport::passed).sum() }
+
+    pub fn skipped(&self) -> usize { self.files.iter().map(FileReport::skipped).sum() }
+
+    /// All the failed directives together with the name of the file they are
+    /// in.
+    pub fn failures(&self) -> impl Iterator<Item = (&str, &AssertionResult)> {
+        self.files.iter().flat_map(|f| f.failures().map(move |r| (f.name.as_str(), r)))
+    }
+
+    pub fn is_success(&self) -> bool { self.failures().next().is_none() }
+}
+
+struct TrapHost;
+
+#[derive(Debug)]
+struct HostCallError {
+    name: ArtifactNamedImport,
+}
+
+impl std::fmt::Display for HostCallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Host function called {}", self.name)
+    }
+}
+
+impl Host<ArtifactNamedImport> for TrapHost {
+    type Interrupt = NoInterrupt;
+
+    fn tick_initial_memory(&mut self, _num_pages: u32) -> RunResult<()> { Ok(()) }
+
+    fn call(
+        &mut self,
+        f: &ArtifactNamedImport,
+        _memory: &mut Vec<u8>,
+        _stack: &mut RuntimeStack,
+    ) -> RunResult<Option<NoInterrupt>> {
+        bail!(HostCallError {
+            name: f.clone(),
+        })
+    }
+
+    fn tick_energy(&mut self, _energy: u64) -> RunResult<()> { Ok(()) }
+
+    fn track_call(&mut self) -> RunResult<()> { Ok(()) }
+
+    fn track_return(&mut self) {}
+}
+
+#[derive(Default)]
+struct MeteringHost {
+    call_depth: i64,
+}
+
+impl Host<ArtifactNamedImport> for MeteringHost {
+    type Interrupt = NoInterrupt;
+
+    fn tick_initial_memory(&mut self, _num_pages: u32) -> RunResult<()> { Ok(()) }
+
+    fn call(
+        &mut self,
+        f: &ArtifactNamedImport,
+        _memory: &mut Vec<u8>,
+        _stack: &mut RuntimeStack,
+    ) -> RunResult<Option<NoInterrupt>> {
+        if f.matches("concordium_metering", "account_memory") {
+            // do nothing
+        } else {
+            bail!(HostCallError {
+                name: f.clone(),
+            })
+        }
+        Ok(None)
+    }
+
+    fn tick_energy(&mut self, _energy: u64) -> RunResult<()> { Ok(()) }
+
+    fn track_call(&mut self) -> RunResult<()> {
+        self.call_depth += 1;
+        ensure!(self.call_depth <= 10_000, "Call depth exceeded.");
+        Ok(())
+    }
+
+    fn track_return(&mut self) { self.call_depth -= 1; }
+}
+
+fn validate(config: ValidationConfig, source: &[u8]) -> anyhow::Result<Module> {
+    struct AllowAll;
+
+    impl concordium_wasm::validate::ValidateImportExport for AllowAll {
+        fn validate_import_function(
+            &self,
+            _duplicate: bool,
+            _mod_name: &Name,
+            _item_name: &Name,
+            _ty: &FunctionType,
+        ) -> bool {
+            true
+        }
+
+        fn validate_export_function(&self, _item_name: &Name, _ty: &FunctionType) -> bool { true }
+    }
+
+    let skel = concordium_wasm::parse::parse_skeleton(source)?;
+    concordium_wasm::validate::validate_module(config, &AllowAll, &skel)
+}
+
+fn mk_values(exprs: &[Expression<'_>]) -> anyhow::Result<Vec<Value>> {
+    let mut out = Vec::new();
+    for e in exprs.iter() {
+        if e.instrs.len() == 1 {
+            match e.instrs[0] {
+                wast::Instruction::I32Const(n) => out.push(Value::I32(n)),
+                wast::Instruction::I64Const(n) => out.push(Value::I64(n)),
+                _ => bail!("Unsupported argument instruction {:?}", e.instrs[0]),
+            }
+        } else {
+            bail!("Unsupported length of argument expression.")
+        }
+    }
+    Ok(out)
+}
+
+fn mk_results(exprs: &[AssertExpression<'_>]) -> anyhow::Result<Option<Value>> {
+    if let Some(x) = exprs.first() {
+        if exprs.len() <= 1 {
+            match x {
+                AssertExpression::I32(n) => Ok(Some(Value::I32(*n))),
+                AssertExpression::I64(n) => Ok(Some(Value::I64(*n))),
+                _ => bail!("Unsupported assert expression {:?}", x),
+            }
+        } else {
+            bail!("Too many results.")
+        }
+    } else {
+        Ok(None)
+    }
+}
+
+fn invoke_update(
+    artifact: &Artifact<ArtifactNamedImport, CompiledFunction>,
+    name: &str,
+    args: &[Value],
+) -> anyhow::Result<Option<Value>> {
+    match artifact.run(&mut TrapHost, name, args)? {
+        ExecutionOutcome::Success {
+            result,
+            ..
+        } => Ok(result),
+        ExecutionOutcome::Interrupted {
+            reason,
+            ..
+        } => match reason {}, // impossible case
+    }
+}
+
+fn invoke_update_metering(
+    artifact: &Artifact<ArtifactNamedImport, CompiledFunction>,
+    name: &str,
+    args: &[Value],
+) -> anyhow::Result<Option<Value>> {
+    match artifact.run(&mut MeteringHost::default(), name, args)? {
+        ExecutionOutcome::Success {
+            result,
+            ..
+        } => Ok(result),
+        ExecutionOutcome::Interrupted {
+            reason,
+            ..
+        } => match reason {},
+    }
+}
+
+/// Check whether the module failing validation with the given error is
+/// expected in the configuration, i.e., whether the module uses features
+/// that are not supported. Returns [`None`] if the error is expected.
+fn unexpected_validation_error(config: &SpecTestConfig, e: &anyhow::Error) -> Option<String> {
+    if let Some(pe) = e.downcast_ref::<ParseError>() {
+        let expected = match pe {
+            ParseError::UnsupportedInstruction {
+                opcode,
+            } => config.is_disallowed_opcode(*opcode),
+            ParseError::UnsupportedValueType {
+                byte,
+            } => *byte == 0x7D || *byte == 0x7C,
+            ParseError::UnsupportedImportType {
+                tag,
+            } => *tag == 0x01 || *tag == 0x02 || *tag == 0x03,
+            ParseError::OnlySingleReturn => true,
+            ParseError::OnlyASCIINames => true,
+            ParseError::NameTooLong => true,
+            ParseError::FuncNameTooLong => true,
+            ParseError::StartFunctionsNotSupported => true,
+        };
+        if expected {
+            None
+        } else {
+            Some(pe.to_string())
+        }
+    } else if let Some(ve) = e.downcast_ref::<ValidationError>() {
+        match ve {
+            ValidationError::TooManyLocals {
+                ..
+            } => None,
+        }
+    } else {
+        Some(format!("Module not valid due to {:#}.", e))
+    }
+}
+
+fn directive_span(directive: &WastDirective<'_>) -> Span {
+    match directive {
+        WastDirective::Module(m) => m.span,
+        WastDirective::Invoke(i) => i.span,
+        WastDirective::QuoteModule {
+            span,
+            ..
+        }
+        | WastDirective::AssertMalformed {
+            span,
+            ..
+        }
+        | WastDirective::AssertInvalid {
+            span,
+            ..
+        }
+        | WastDirective::Register {
+            span,
+            ..
+        }
+        | WastDirective::AssertTrap {
+            span,
+            ..
+        }
+        | WastDirective::AssertReturn {
+            span,
+            ..
+        }
+        | WastDirective::AssertExhaustion {
+            span,
+            ..
+        }
+        | WastDirective::AssertUnlinkable {
+            span,
+            ..
+        } => *span,
+    }
+}
+
+fn directive_kind(directive: &WastDirective<'_>) -> AssertionKind {
+    match directive {
+        WastDirective::Module(_) => AssertionKind::Module,
+        WastDirective::QuoteModule {
+            ..
+        } => AssertionKind::QuoteModule,
+        WastDirective::AssertMalformed {
+            ..
+        } => AssertionKind::AssertMalformed,
+        WastDirective::AssertInvalid {
+            ..
+        } => AssertionKind::AssertInvalid,
+        WastDirective::Register {
+            ..
+        } => AssertionKind::Register,
+        WastDirective::Invoke(_) => AssertionKind::Invoke,
+        WastDirective::AssertTrap {
+            ..
+        } => AssertionKind::AssertTrap,
+        WastDirective::AssertReturn {
+            ..
+        } => AssertionKind::AssertReturn,
+        WastDirective::AssertExhaustion {
+            ..
+        } => AssertionKind::AssertExhaustion,
+        WastDirective::AssertUnlinkable {
+            ..
+        } => AssertionKind::AssertUnlinkable,
+    }
+}
+
+fn skipped(reason: impl Into<String>) -> Outcome {
+    Outcome::Skipped {
+        reason: reason.into(),
+    }
+}
+
+fn failed(message: impl Into<String>) -> Outcome {
+    Outcome::Failed {
+        message: message.into(),
+    }
+}
+
+/// Modules defined so far in a script, indexed by their name. The unnamed
+/// entry refers to the most recently defined module. Modules which are
+/// not supported by the interpreter are recorded as [`None`] so that
+/// directives referring to them are skipped.
+type Modules = BTreeMap<Option<String>, Option<Artifact<ArtifactNamedImport, CompiledFunction>>>;
+
+struct ScriptRunner<'a> {
+    config:  &'a SpecTestConfig,
+    /// The stem of the script file name, used to name output modules.
+    stem:    &'a str,
+    modules: Modules,
+}
+
+impl<'a> ScriptRunner<'a> {
+    fn output(&self, line: usize, bytes: &[u8]) -> anyhow::Result<()> {
+        if let Some(dir) = self.config.out_dir.as_ref() {
+            let mut out_path = dir.clone();
+            out_path.push(format!("{}-{}.wasm", self.stem, line));
+            fs::write(out_path, bytes)?;
+        }
+        Ok(())
+    }
+
+    fn get_module(
+        &self,
+        id: Option<wast::Id<'_>>,
+    ) -> Option<&Artifact<ArtifactNamedImport, CompiledFunction>> {
+        self.modules.get(&id.map(|x| x.name().to_string())).and_then(Option::as_ref)
+    }
+
+    fn insert_module(
+        &mut self,
+        id: Option<wast::Id<'_>>,
+        artifact: Option<Artifact<ArtifactNamedImport, CompiledFunction>>,
+    ) {
+        if let Some(id) = id {
+            self.modules.insert(Some(id.name().to_string()), artifact.clone());
+        }
+        self.modules.insert(None, artifact);
+    }
+
+    /// Check that a module that is expected to be rejected is rejected.
+    fn expect_invalid(&self, bytes: &[u8], message: &str) -> Outcome {
+        if validate(self.config.validation, bytes).is_ok() {
+            failed(format!("Module is valid, but expected: {}", message))
+        } else {
+            Outcome::Passed
+        }
+    }
+
+    fn run_directive(
+        &mut self,
+        directive: WastDirective<'_>,
+        line: usize,
+    ) -> anyhow::Result<Outcome> {
+        let outcome = match directive {
+            WastDirective::Module(mut m) => {
+                let encoded = m.encode()?;
+                self.output(line, &encoded)?;
+                match validate(self.config.validation, &encoded) {
+                    Ok(module) => match module.compile::<ArtifactNamedImport>() {
+                        Ok(artifact) => {
+                            self.insert_module(m.id, Some(artifact));
+                            Outcome::Passed
+                        }
+                        Err(e) => {
+                            self.insert_module(m.id, None);
+                            failed(format!("Compilation failed: {}", e))
+                        }
+                    },
+                    Err(e) => {
+                        self.insert_module(m.id, None);
+                        match unexpected_validation_error(self.config, &e) {
+                            None => skipped(format!("Unsupported module: {:#}", e)),
+                            Some(msg) => failed(msg),
+                        }
+                    }
+                }
+            }
+            WastDirective::QuoteModule {
+                ..
+            } => skipped("Quoted modules are not supported."),
+            WastDirective::AssertMalformed {
+                module,
+                message,
+                ..
+            } => match module {
+                wast::QuoteModule::Module(mut m) => {
+                    let bytes = m.encode()?;
+                    self.output(line, &bytes)?;
+                    self.expect_invalid(&bytes, message)
+                }
+                wast::QuoteModule::Quote(mods_bytes) => {
+                    let mut outcome = Outcome::Passed;
+                    for bytes in mods_bytes {
+                        outcome = self.expect_invalid(bytes, message);
+                        if outcome != Outcome::Passed {
+                            break;
+                        }
+                    }
+                    outcome
+                }
+            },
+            WastDirective::AssertInvalid {
+                mut module,
+                message,
+                ..
+            } => {
+                let bytes = module.encode()?;
+                self.output(line, &bytes)?;
+                self.expect_invalid(&bytes, message)
+            }
+            WastDirective::Register {
+                ..
+            } => {
+                // we don't support linking, so registering is not useful.
+                skipped("Linking is not supported.")
+            }
+            WastDirective::Invoke(a) => match self.get_module(a.module) {
+                Some(artifact) => {
+                    if !artifact.imports.is_empty() {
+                        skipped("Module has imports.")
+                    } else if let Ok(values) = mk_values(&a.args) {
+                        match invoke_update(artifact, a.name, &values) {
+                            Ok(_) => Outcome::Passed,
+                            Err(e) => failed(format!("Calling {}: {}", a.name, e)),
+                        }
+                    } else {
+                        skipped("Unsupported input types.")
+                    }
+                }
+                None => skipped("Unsupported module."),
+            },
+            WastDirective::AssertTrap {
+                exec,
+                message,
+                ..
+            } => match exec {
+                WastExecute::Invoke(invoke) => match self.get_module(invoke.module) {
+                    Some(artifact) => {
+                        if let Ok(values) = mk_values(&invoke.args) {
+                            if invoke_update(artifact, invoke.name, &values).is_ok() {
+                                failed(format!("Calling {} did not trap: {}", invoke.name, message))
+                            } else {
+                                Outcome::Passed
+                            }
+                        } else {
+                            skipped("Unsupported input types.")
+                        }
+                    }
+                    None => skipped("Unsupported module."),
+                },
+                // this has to do with linking and start functions, which we do
+                // not support.
+                WastExecute::Module(_) => skipped("Instantiating modules is not supported."),
+                WastExecute::Get {
+                    ..
+                } => skipped("Getting globals is not supported."),
+            },
+            WastDirective::AssertReturn {
+                exec,
+                results,
+                ..
+            } => {
+                if let WastExecute::Invoke(invoke) = exec {
+                    match mk_results(&results) {
+                        Ok(expected) => match self.get_module(invoke.module) {
+                            Some(artifact) => {
+                                if let Ok(values) = mk_values(&invoke.args) {
+                                    match invoke_update(artifact, invoke.name, &values) {
+                                        Ok(v) if v == expected => Outcome::Passed,
+                                        Ok(v) => failed(format!(
+                                            "Calling {}: {:?} != {:?}",
+                                            invoke.name, v, expected
+                                        )),
+                                        Err(e) => {
+                                            if let Some(x) = e.downcast_ref::<RuntimeError>() {
+                                                match x {
+                                                    RuntimeError::DirectlyCallImport => {
+                                                        // OK, this is our own restriction.
+                                                        skipped(x.to_string())
+                                                    }
+                                                }
+                                            } else if e.downcast_ref::<HostCallError>().is_some() {
+                                                // OK, this is our restriction
+                                                skipped(e.to_string())
+                                            } else {
+                                                failed(format!("Calling {}: {}", invoke.name, e))
+                                            }
+                                        }
+                                    }
+                                } else {
+                                    skipped("Unsupported input types.")
+                                }
+                            }
+                            None => skipped("Unsupported module."),
+                        },
+                        Err(_) => skipped("Unsupported types or multiple return values."),
+                    }
+                } else {
+                    skipped("Unsupported module invocation.")
+                }
+            }
+            WastDirective::AssertExhaustion {
+                call,
+                message,
+                ..
+            } => match self.get_module(call.module) {
+                Some(artifact) => {
+                    if let Ok(values) = mk_values(&call.args) {
+                        if invoke_update_metering(artifact, call.name, &values).is_ok() {
+                            failed(format!("Calling {} did not exhaust: {}", call.name, message))
+                        } else {
+                            Outcome::Passed
+                        }
+                    } else {
+                        skipped("Unsupported input types.")
+                    }
+                }
+                None => skipped("Unsupported module."),
+            },
+            WastDirective::AssertUnlinkable {
+                ..
+            } => {
+                // skip these since we do not support dependencies.
+                skipped("Linking is not supported.")
+            }
+        };
+        Ok(outcome)
+    }
+}
+
+/// Run all the directives of the given test script. The `name` is the file
+/// name of the script, and is used to look up entries in the skip list.
+///
+/// An error is returned only if the script cannot be parsed or if the output
+/// modules cannot be written. Failed assertions are reported in the returned
+/// [`FileReport`].
+pub fn run_script(config: &SpecTestConfig, name: &str, input: &str) -> anyhow::Result<FileReport> {
+    let buf = parser::ParseBuffer::new(input)?;
+    let script = parser::parse::<Wast>(&buf)?;
+    let stem = name.strip_suffix(".wast").unwrap_or(name);
+    let mut runner = ScriptRunner {
+        config,
+        stem,
+        modules: BTreeMap::new(),
+    };
+    let mut results = Vec::with_capacity(script.directives.len());
+    for directive in script.directives {
+        let (line, col) = directive_span(&directive).linecol_in(input);
+        // linecol_in is 0-based, but usually in editors it is 1-based
+        let (line, column) = (line + 1, col + 1);
+        let kind = directive_kind(&directive);
+        let outcome = if let Some(skip) = config.find_skip(name, line) {
+            if let WastDirective::Module(m) = &directive {
+                runner.insert_module(m.id, None);
+            }
+            skipped(skip.reason)
+        } else {
+            runner.run_directive(directive, line)?
+        };
+        results.push(AssertionResult {
+            line,
+            column,
+            kind,
+            outcome,
+        });
+    }
+    Ok(FileReport {
+        name: name.into(),
+        results,
+    })
+}
+
+/// Run the test script in the given file. See [`run_script`].
+pub fn run_file(config: &SpecTestConfig, path: &Path) -> anyhow::Result<FileReport> {
+    let name = match path.file_name().and_then(|s| s.to_str()) {
+        Some(name) => name,
+        None => bail!("Invalid file name {}.", path.display()),
+    };
+    let input = fs::read_to_string(path)?;
+    run_script(config, name, &input)
+}
+
+/// Run all the `.wast` files in the given directory, in the order of their
+/// names.
+pub fn run_directory(config: &SpecTestConfig, dir: &Path) -> anyhow::Result<SuiteReport> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)?.filter_map(Result::ok) {
+        let path = entry.path();
+        if entry.metadata()?.is_file() && path.extension().and_then(|s| s.to_str()) == Some("wast")
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    let mut report = SuiteReport::default();
+    for path in paths {
+        report.files.push(run_file(config, &path)?);
+    }
+    Ok(report)
+}
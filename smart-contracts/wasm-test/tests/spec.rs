@@ -0,0 +1,35 @@
+//! Run the Wasm core specification test suite with the validation
+//! configurations used by the different protocol versions.
+use concordium_wasm::validate::ValidationConfig;
+use std::path::PathBuf;
+use wasm_test::{run_directory, Outcome, SpecTestConfig};
+
+fn spec_suite_dir() -> PathBuf {
+    let mut dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    dir.push("../testdata/wasm-spec-test-suite/core");
+    dir
+}
+
+fn run_spec_suite(validation: ValidationConfig) {
+    let config = SpecTestConfig::new(validation);
+    let report = run_directory(&config, &spec_suite_dir()).expect("Test suite should run.");
+    let failures = report
+        .failures()
+        .map(|(file, r)| match &r.outcome {
+            Outcome::Failed {
+                message,
+            } => {
+                format!("{}:{}:{} {}: {}", file, r.line, r.column, r.kind, message)
+            }
+            _ => unreachable!("Only failures are returned."),
+        })
+        .collect::<Vec<_>>();
+    assert!(failures.is_empty(), "Spec tests failed:\n{}", failures.join("\n"));
+    assert!(report.passed() > 0, "No spec tests were run.");
+}
+
+#[test]
+fn spec_suite_v0() { run_spec_suite(ValidationConfig::V0) }
+
+#[test]
+fn spec_suite_v1() { run_spec_suite(ValidationConfig::V1) }